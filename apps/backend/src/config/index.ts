@@ -105,6 +105,7 @@ export const config = {
     EARLY_BIRD_BONUS_BPS: 2_000, // 20%
     EARLY_BIRD_THRESHOLD: 24 * 3600, // 24 hours
     REJECT_ALL_THRESHOLD_BPS: 6_667, // 66.67%
+    VESTING_THRESHOLD: 100_000_000_000, // winnings at or above this vest linearly
   },
 };
 
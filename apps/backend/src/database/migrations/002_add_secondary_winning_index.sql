@@ -0,0 +1,7 @@
+-- Migration: Track the second winning image for MiddleWay settlement
+-- MiddleWay ideas have two winners (most- and least-voted images)
+
+ALTER TABLE ideas
+ADD COLUMN IF NOT EXISTS secondary_winning_image_index SMALLINT;
+
+COMMENT ON COLUMN ideas.secondary_winning_image_index IS 'Least-voted co-winner for MiddleWay ideas. NULL otherwise.';
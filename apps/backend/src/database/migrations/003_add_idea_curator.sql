@@ -0,0 +1,9 @@
+-- Migration: Separate curator role from initiator
+-- The curator fee is paid to the curator designated at idea creation
+
+ALTER TABLE ideas
+ADD COLUMN IF NOT EXISTS curator_pubkey VARCHAR(44);
+
+UPDATE ideas SET curator_pubkey = initiator_pubkey WHERE curator_pubkey IS NULL;
+
+COMMENT ON COLUMN ideas.curator_pubkey IS 'Receives the curator fee. Defaults to the initiator.';
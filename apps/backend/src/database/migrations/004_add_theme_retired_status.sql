@@ -0,0 +1,6 @@
+-- Migration: Allow the Retired theme status
+-- Retired themes have been wound down; remaining curve SOL is claimable by holders
+
+ALTER TABLE themes DROP CONSTRAINT IF EXISTS valid_status;
+ALTER TABLE themes
+ADD CONSTRAINT valid_status CHECK (status IN ('Active', 'Migrated', 'Paused', 'Retired'));
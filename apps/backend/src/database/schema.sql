@@ -33,7 +33,7 @@ CREATE TABLE IF NOT EXISTS themes (
     updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
     
     CONSTRAINT valid_voting_mode CHECK (voting_mode IN ('Classic', 'Reverse', 'MiddleWay')),
-    CONSTRAINT valid_status CHECK (status IN ('Active', 'Migrated', 'Paused')),
+    CONSTRAINT valid_status CHECK (status IN ('Active', 'Migrated', 'Paused', 'Retired')),
     CONSTRAINT unique_theme_id UNIQUE(creator_pubkey, theme_id)
 );
 
@@ -78,6 +78,7 @@ CREATE TABLE IF NOT EXISTS ideas (
     theme_pubkey VARCHAR(44) REFERENCES themes(pubkey) ON DELETE CASCADE, -- NULL for standalone ideas
     initiator_pubkey VARCHAR(44) NOT NULL,
     sponsor_pubkey VARCHAR(44), -- NULL for non-sponsored ideas
+    curator_pubkey VARCHAR(44), -- Receives the curator fee (defaults to initiator)
     prompt TEXT NOT NULL,
     status VARCHAR(20) NOT NULL DEFAULT 'GeneratingImages',
     image_uris TEXT[] DEFAULT '{}', -- Array of image URIs
@@ -96,6 +97,7 @@ CREATE TABLE IF NOT EXISTS ideas (
     reject_all_weight BIGINT NOT NULL DEFAULT 0,
     total_voters INTEGER NOT NULL DEFAULT 0,
     winning_image_index SMALLINT, -- NULL until settled
+    secondary_winning_image_index SMALLINT, -- MiddleWay only: least-voted co-winner
     
     -- Settlement data
     curator_fee_collected BIGINT NOT NULL DEFAULT 0,
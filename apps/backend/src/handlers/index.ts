@@ -5,6 +5,11 @@ import {
   ThemeCreatedEvent,
   TokensSwappedEvent,
   BuybackExecutedEvent,
+  ThemeMigratedEvent,
+  ThemeMetadataUpdatedEvent,
+  ThemePausedEvent,
+  ThemeUnpausedEvent,
+  ThemeRetiredEvent,
   IdeaCreatedEvent,
   SponsoredIdeaCreatedEvent,
   ImagesGeneratedEvent,
@@ -14,6 +19,8 @@ import {
   IdeaCancelledEvent,
   VotingCancelledEvent,
   RefundWithdrawnEvent,
+  LoserRefundWithdrawnEvent,
+  UnclaimedSweptEvent,
   IdeaStatus,
   GenerationStatus,
   VotingMode,
@@ -256,6 +263,9 @@ export async function handleBuybackExecuted(
         theme: event.theme.toString(),
         tokensBurned: event.tokensBurned.toString(),
         solSpent: event.solSpent.toString(),
+        keeper: event.keeper.toString(),
+        keeperReward: event.keeperReward.toString(),
+        policy: event.policy,
       },
     });
 
@@ -365,16 +375,17 @@ export async function handleIdeaCreated(
     // Insert into database (theme_pubkey is NULL for standalone ideas)
     await db.query(
       `INSERT INTO ideas (
-        pubkey, idea_id, theme_pubkey, initiator_pubkey, prompt, status,
+        pubkey, idea_id, theme_pubkey, initiator_pubkey, curator_pubkey, prompt, status,
         generation_status, generation_deadline, depin_provider,
         total_staked, min_stake, curator_fee_bps, votes,
         reject_all_weight, total_voters, created_at
-      ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, NOW())`,
+      ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, NOW())`,
       [
         event.idea.toString(),
         ideaAccountInfo.ideaId,
         null, // theme_pubkey - ideas are standalone
         event.initiator.toString(),
+        event.curator.toString(),
         event.prompt,
         IdeaStatus.GeneratingImages,
         GenerationStatus.Pending,
@@ -674,6 +685,8 @@ export async function handleVotingSettled(
       idea: event.idea.toString(),
       winningImageIndex: event.winningImageIndex,
       totalStaked: event.totalStaked.toString(),
+      settler: event.settler.toString(),
+      settlerReward: event.settlerReward.toString(),
       signature,
     });
 
@@ -683,15 +696,17 @@ export async function handleVotingSettled(
         `UPDATE ideas 
          SET status = $1,
              winning_image_index = $2,
-             curator_fee_collected = $3,
-             platform_fee_collected = $4,
-             penalty_pool_amount = $5,
-             winner_count = $6,
+             secondary_winning_image_index = $3,
+             curator_fee_collected = $4,
+             platform_fee_collected = $5,
+             penalty_pool_amount = $6,
+             winner_count = $7,
              updated_at = NOW()
-         WHERE pubkey = $7`,
+         WHERE pubkey = $8`,
         [
           IdeaStatus.Completed,
           event.winningImageIndex,
+          event.secondaryWinningIndex ?? null,
           event.curatorFee.toString(),
           event.platformFee.toString(),
           event.penaltyPool.toString(),
@@ -700,12 +715,12 @@ export async function handleVotingSettled(
         ]
       );
 
-      // Mark winning votes
+      // Mark winning votes (MiddleWay has two winning images)
       await client.query(
         `UPDATE votes 
          SET is_winner = TRUE
-         WHERE idea_pubkey = $1 AND image_choice = $2`,
-        [event.idea.toString(), event.winningImageIndex]
+         WHERE idea_pubkey = $1 AND (image_choice = $2 OR image_choice = $3)`,
+        [event.idea.toString(), event.winningImageIndex, event.secondaryWinningIndex ?? null]
       );
 
       // Update reviewer stakes
@@ -872,6 +887,275 @@ export async function handleRefundWithdrawn(
   }
 }
 
+/**
+ * Handle LoserRefundWithdrawn event
+ */
+export async function handleLoserRefundWithdrawn(
+  event: LoserRefundWithdrawnEvent,
+  signature: string
+): Promise<void> {
+  try {
+    logger.info('Handling LoserRefundWithdrawn event', {
+      idea: event.idea.toString(),
+      reviewer: event.reviewer.toString(),
+      amount: event.amount.toString(),
+      penalty: event.penalty.toString(),
+      signature,
+    });
+
+    // Mark losing stake as reclaimed (reusing winnings_withdrawn flag)
+    await db.query(
+      `UPDATE votes 
+       SET winnings_withdrawn = TRUE
+       WHERE idea_pubkey = $1 AND voter_pubkey = $2`,
+      [event.idea.toString(), event.reviewer.toString()]
+    );
+
+    logger.info('LoserRefundWithdrawn event handled successfully');
+  } catch (error) {
+    logger.error('Error handling LoserRefundWithdrawn event', { error, signature });
+    throw error;
+  }
+}
+
+/**
+ * Handle UnclaimedSwept event
+ */
+export async function handleUnclaimedSwept(
+  event: UnclaimedSweptEvent,
+  signature: string
+): Promise<void> {
+  try {
+    logger.info('Handling UnclaimedSwept event', {
+      idea: event.idea.toString(),
+      amount: event.amount.toString(),
+      signature,
+    });
+
+    // Claim window expired; only winnings that are still vesting stay in the vault
+    await db.query(
+      `UPDATE votes 
+       SET winnings_withdrawn = TRUE
+       WHERE idea_pubkey = $1 AND winnings_withdrawn = FALSE`,
+      [event.idea.toString()]
+    );
+
+    logger.info('UnclaimedSwept event handled successfully');
+  } catch (error) {
+    logger.error('Error handling UnclaimedSwept event', { error, signature });
+    throw error;
+  }
+}
+
+/**
+ * Handle ThemeMigrated event
+ */
+export async function handleThemeMigrated(
+  event: ThemeMigratedEvent,
+  signature: string
+): Promise<void> {
+  try {
+    logger.info('Handling ThemeMigrated event', {
+      theme: event.theme.toString(),
+      pool: event.pool.toString(),
+      signature,
+    });
+
+    // Curve reserves moved into the AMM pool, bonding-curve trading is closed
+    await db.query(
+      `UPDATE themes 
+       SET status = $1,
+           sol_reserves = 0,
+           token_reserves = 0,
+           updated_at = NOW()
+       WHERE pubkey = $2`,
+      [ThemeStatus.Migrated, event.theme.toString()]
+    );
+
+    WebSocketService.getInstance().broadcast({
+      type: 'token:migrated',
+      data: {
+        theme: event.theme.toString(),
+        pool: event.pool.toString(),
+        lpMint: event.lpMint.toString(),
+        lpLock: event.lpLock.toString(),
+        lpAmount: event.lpAmount,
+        lpUnlockAt: event.lpUnlockAt,
+      },
+    });
+
+    logger.info('ThemeMigrated event handled successfully', {
+      theme: event.theme.toString(),
+    });
+  } catch (error) {
+    logger.error('Error handling ThemeMigrated event', { error, signature });
+    throw error;
+  }
+}
+
+/**
+ * Handle ThemeMetadataUpdated event
+ */
+export async function handleThemeMetadataUpdated(
+  event: ThemeMetadataUpdatedEvent,
+  signature: string
+): Promise<void> {
+  try {
+    logger.info('Handling ThemeMetadataUpdated event', {
+      theme: event.theme.toString(),
+      signature,
+    });
+
+    const name = Buffer.from(event.name).toString('utf8').replace(/\0/g, '');
+    const description = Buffer.from(event.description).toString('utf8').replace(/\0/g, '');
+
+    await db.query(
+      `UPDATE themes 
+       SET name = $1,
+           description = $2,
+           updated_at = NOW()
+       WHERE pubkey = $3`,
+      [name, description, event.theme.toString()]
+    );
+
+    WebSocketService.getInstance().broadcast({
+      type: 'theme:updated',
+      data: {
+        theme: event.theme.toString(),
+        name,
+        description,
+      },
+    });
+
+    logger.info('ThemeMetadataUpdated event handled successfully', {
+      theme: event.theme.toString(),
+    });
+  } catch (error) {
+    logger.error('Error handling ThemeMetadataUpdated event', { error, signature });
+    throw error;
+  }
+}
+
+/**
+ * Handle ThemePaused event
+ */
+export async function handleThemePaused(
+  event: ThemePausedEvent,
+  signature: string
+): Promise<void> {
+  try {
+    logger.info('Handling ThemePaused event', {
+      theme: event.theme.toString(),
+      authority: event.authority.toString(),
+      signature,
+    });
+
+    await db.query(
+      `UPDATE themes 
+       SET status = $1,
+           updated_at = NOW()
+       WHERE pubkey = $2`,
+      [ThemeStatus.Paused, event.theme.toString()]
+    );
+
+    WebSocketService.getInstance().broadcast({
+      type: 'theme:paused',
+      data: {
+        theme: event.theme.toString(),
+        authority: event.authority.toString(),
+      },
+    });
+
+    logger.info('ThemePaused event handled successfully', {
+      theme: event.theme.toString(),
+    });
+  } catch (error) {
+    logger.error('Error handling ThemePaused event', { error, signature });
+    throw error;
+  }
+}
+
+/**
+ * Handle ThemeUnpaused event
+ */
+export async function handleThemeUnpaused(
+  event: ThemeUnpausedEvent,
+  signature: string
+): Promise<void> {
+  try {
+    logger.info('Handling ThemeUnpaused event', {
+      theme: event.theme.toString(),
+      authority: event.authority.toString(),
+      signature,
+    });
+
+    await db.query(
+      `UPDATE themes 
+       SET status = $1,
+           updated_at = NOW()
+       WHERE pubkey = $2`,
+      [ThemeStatus.Active, event.theme.toString()]
+    );
+
+    WebSocketService.getInstance().broadcast({
+      type: 'theme:unpaused',
+      data: {
+        theme: event.theme.toString(),
+        authority: event.authority.toString(),
+      },
+    });
+
+    logger.info('ThemeUnpaused event handled successfully', {
+      theme: event.theme.toString(),
+    });
+  } catch (error) {
+    logger.error('Error handling ThemeUnpaused event', { error, signature });
+    throw error;
+  }
+}
+
+/**
+ * Handle ThemeRetired event
+ */
+export async function handleThemeRetired(
+  event: ThemeRetiredEvent,
+  signature: string
+): Promise<void> {
+  try {
+    logger.info('Handling ThemeRetired event', {
+      theme: event.theme.toString(),
+      totalClaimable: event.totalClaimable.toString(),
+      signature,
+    });
+
+    // Curve SOL moved into the holder payout pool, curve trading is closed
+    await db.query(
+      `UPDATE themes 
+       SET status = $1,
+           sol_reserves = 0,
+           buyback_pool = 0,
+           updated_at = NOW()
+       WHERE pubkey = $2`,
+      [ThemeStatus.Retired, event.theme.toString()]
+    );
+
+    WebSocketService.getInstance().broadcast({
+      type: 'theme:retired',
+      data: {
+        theme: event.theme.toString(),
+        totalClaimable: event.totalClaimable.toString(),
+      },
+    });
+
+    logger.info('ThemeRetired event handled successfully', {
+      theme: event.theme.toString(),
+    });
+  } catch (error) {
+    logger.error('Error handling ThemeRetired event', { error, signature });
+    throw error;
+  }
+}
+
 /**
  * Helper: Fetch idea account data from Solana
  */
@@ -902,6 +1186,11 @@ export default {
   handleThemeCreated,
   handleTokensSwapped,
   handleBuybackExecuted,
+  handleThemeMigrated,
+  handleThemeMetadataUpdated,
+  handleThemePaused,
+  handleThemeUnpaused,
+  handleThemeRetired,
   handleIdeaCreated,
   handleSponsoredIdeaCreated,
   handleImagesGenerated,
@@ -911,4 +1200,6 @@ export default {
   handleIdeaCancelled,
   handleVotingCancelled,
   handleRefundWithdrawn,
+  handleLoserRefundWithdrawn,
+  handleUnclaimedSwept,
 };
@@ -5,6 +5,7 @@ import { config } from '../../config';
 import { logger } from '../../utils/logger';
 import { db } from '../../database';
 import { IPFSService } from '../ipfs';
+import { ClaimsService } from '../claims';
 import {
   IdeaListQuery,
   IdeaResponse,
@@ -22,10 +23,12 @@ import * as fs from 'fs';
 export class APIServer {
   private app: express.Application;
   private ipfsService: IPFSService;
+  private claimsService: ClaimsService;
 
   constructor() {
     this.app = express();
     this.ipfsService = IPFSService.getInstance();
+    this.claimsService = ClaimsService.getInstance();
     this.setupMiddleware();
     this.setupRoutes();
   }
@@ -73,6 +76,7 @@ export class APIServer {
     router.get('/themes/:id/ideas', this.getThemeIdeas.bind(this));
     router.get('/themes/:id/swaps', this.getThemeSwaps.bind(this));
     router.get('/themes/:id/price-history', this.getThemePriceHistory.bind(this));
+    router.get('/themes/:id/claimables/:wallet', this.getClaimables.bind(this));
 
     // Ideas
     router.get('/ideas', this.getIdeas.bind(this));
@@ -565,6 +569,24 @@ export class APIServer {
     }
   }
 
+  /**
+   * GET /api/themes/:id/claimables/:wallet - Get outstanding claims with instruction payloads
+   */
+  private async getClaimables(req: Request, res: Response): Promise<void> {
+    try {
+      const { id, wallet } = req.params;
+      const response = await this.claimsService.getClaimables(id, wallet);
+      res.json(response);
+    } catch (error) {
+      logger.error('Error fetching claimables', {
+        error,
+        theme: req.params.id,
+        wallet: req.params.wallet,
+      });
+      res.status(500).json({ error: 'Failed to fetch claimables' });
+    }
+  }
+
   /**
    * GET /api/users/:pubkey/portfolio - Get user's complete portfolio
    */
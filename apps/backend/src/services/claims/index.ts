@@ -0,0 +1,468 @@
+import { Connection, PublicKey, SystemProgram } from '@solana/web3.js';
+import { BorshAccountsCoder } from '@coral-xyz/anchor';
+import { createHash } from 'crypto';
+import fs from 'fs';
+import path from 'path';
+import { config } from '../../config';
+import { db } from '../../database';
+import { ClaimableItem, ClaimablesResponse, InstructionPayload } from '../../types';
+
+const TOKEN_PROGRAM_ID = new PublicKey('TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA');
+const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey('ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL');
+
+// Payout curves (shared-lib PAYOUT_CURVE_*); any other value pays pro-rata to vote weight^exponent
+const PAYOUT_CURVE_FLAT = 1;
+const PAYOUT_CURVE_STAKE = 2;
+
+/**
+ * Share units of a winning vote under the idea's payout curve (mirrors shared-lib payout_unit)
+ */
+function payoutUnit(curve: number, exponent: number, stake: bigint, voteWeight: bigint): bigint {
+  switch (curve) {
+    case PAYOUT_CURVE_FLAT:
+      return 1n;
+    case PAYOUT_CURVE_STAKE:
+      return stake;
+    default:
+      return voteWeight ** BigInt(Math.max(exponent, 1));
+  }
+}
+
+const big = (value: any): bigint => BigInt(value.toString());
+
+/**
+ * On-chain state needed to price a wallet's claims; the payout curve, per-image
+ * payout weights, reserved fees and discount tiers are not indexed
+ */
+interface ClaimState {
+  protocolConfig: any;
+  reputation: any | null;
+  stakePosition: PublicKey | null;
+  stakedAmount: bigint;
+  ideas: Map<string, { idea: any; vote: any }>;
+}
+
+/**
+ * Accounts of a settlement withdraw instruction that depend on the idea
+ */
+interface WithdrawAccounts {
+  theme: PublicKey;
+  season: number;
+  curator: PublicKey;
+  treasury: PublicKey;
+  stakePosition: PublicKey | null;
+  vests: boolean;
+}
+
+/**
+ * Claims Service - Collects every outstanding settlement claim for a wallet
+ * and precomputes the instruction payloads needed to withdraw them.
+ */
+export class ClaimsService {
+  private static instance: ClaimsService;
+  private coreProgramId: PublicKey;
+  private settlementProgramId: PublicKey;
+  private tokenProgramId: PublicKey;
+  private connection: Connection;
+  private coreCoder: BorshAccountsCoder;
+  private settlementCoder: BorshAccountsCoder;
+  private tokenCoder: BorshAccountsCoder;
+
+  private constructor() {
+    this.coreProgramId = new PublicKey(config.solana.coreProgramId);
+    this.settlementProgramId = new PublicKey(config.solana.settlementProgramId);
+    this.tokenProgramId = new PublicKey(config.solana.tokenProgramId);
+    this.connection = new Connection(config.solana.rpcUrl, 'confirmed');
+
+    const loadCoder = (name: string) =>
+      new BorshAccountsCoder(
+        JSON.parse(fs.readFileSync(path.join(__dirname, `../../../idl/${name}.json`), 'utf-8'))
+      );
+    this.coreCoder = loadCoder('taste_fun_core');
+    this.settlementCoder = loadCoder('taste_fun_settlement');
+    this.tokenCoder = loadCoder('taste_fun_token');
+  }
+
+  public static getInstance(): ClaimsService {
+    if (!ClaimsService.instance) {
+      ClaimsService.instance = new ClaimsService();
+    }
+    return ClaimsService.instance;
+  }
+
+  /**
+   * Return all unclaimed winnings/refunds/loser refunds of `wallet` across ideas of `theme`
+   */
+  public async getClaimables(themePubkey: string, wallet: string): Promise<ClaimablesResponse> {
+    const result = await db.query(
+      `SELECT
+         v.idea_pubkey,
+         v.image_choice,
+         v.is_winner,
+         rs.total_staked,
+         i.status,
+         i.winning_image_index,
+         i.secondary_winning_image_index,
+         t.token_mint
+       FROM votes v
+       JOIN reviewer_stakes rs
+         ON rs.idea_pubkey = v.idea_pubkey AND rs.reviewer_pubkey = v.voter_pubkey
+       JOIN ideas i ON v.idea_pubkey = i.pubkey
+       JOIN themes t ON i.theme_pubkey = t.pubkey
+       WHERE i.theme_pubkey = $1
+         AND v.voter_pubkey = $2
+         AND v.winnings_withdrawn = FALSE
+         AND i.status IN ('Completed', 'Cancelled')
+       ORDER BY v.created_at ASC`,
+      [themePubkey, wallet]
+    );
+
+    const walletKey = new PublicKey(wallet);
+    const claims: ClaimableItem[] = [];
+    const state = await this.fetchClaimState(
+      result.rows.filter((row: any) => row.status === 'Completed').map((row: any) => row.idea_pubkey),
+      new PublicKey(themePubkey),
+      walletKey
+    );
+
+    for (const row of result.rows) {
+      const stake = BigInt(row.total_staked);
+
+      if (row.status === 'Cancelled') {
+        claims.push({
+          idea: row.idea_pubkey,
+          kind: 'refund',
+          amount: stake.toString(),
+          instruction: this.buildWithdrawInstruction(
+            'withdraw_refund',
+            row.idea_pubkey,
+            row.token_mint,
+            walletKey
+          ),
+        });
+        continue;
+      }
+
+      const onchain = state?.ideas.get(row.idea_pubkey);
+      if (!state || !onchain) {
+        continue;
+      }
+      const { idea, vote } = onchain;
+      // Discounted part of the reviewer's reserved curator/platform fees is paid back on claim
+      const rebate = this.claimFeeRebate(state, idea, stake);
+      const accounts: WithdrawAccounts = {
+        theme: idea.theme,
+        season: state.protocolConfig.season,
+        curator: idea.curator,
+        treasury: state.protocolConfig.treasury,
+        stakePosition: state.stakePosition,
+        vests: false,
+      };
+
+      if (
+        row.image_choice === row.winning_image_index ||
+        row.image_choice === row.secondary_winning_image_index
+      ) {
+        const amount = stake + this.winnerShare(idea, vote) + rebate;
+        claims.push({
+          idea: row.idea_pubkey,
+          kind: 'winnings',
+          amount: amount.toString(),
+          instruction: this.buildWithdrawInstruction(
+            'withdraw_winnings',
+            row.idea_pubkey,
+            row.token_mint,
+            walletKey,
+            { ...accounts, vests: amount >= BigInt(config.constants.VESTING_THRESHOLD) }
+          ),
+        });
+      } else {
+        // Losing side: the non-penalized part of the stake is reclaimable (all of it in no-loss mode),
+        // rounded down like settlement loser_refund_amount
+        const refund = idea.no_loss
+          ? stake
+          : (stake * BigInt(config.constants.BPS_DENOMINATOR - config.constants.PENALTY_BPS)) /
+            BigInt(config.constants.BPS_DENOMINATOR);
+        claims.push({
+          idea: row.idea_pubkey,
+          kind: 'loser_refund',
+          amount: (refund + rebate).toString(),
+          instruction: this.buildWithdrawInstruction(
+            'withdraw_loser_refund',
+            row.idea_pubkey,
+            row.token_mint,
+            walletKey,
+            accounts
+          ),
+        });
+      }
+    }
+
+    const total = claims.reduce((sum, c) => sum + BigInt(c.amount), 0n);
+
+    return {
+      theme: themePubkey,
+      wallet,
+      claims,
+      totalAmount: total.toString(),
+    };
+  }
+
+  /**
+   * Fetch the protocol config, the wallet's reputation and theme stake position,
+   * and the idea and vote behind each settled claim in a single RPC round trip
+   */
+  private async fetchClaimState(
+    ideaPubkeys: string[],
+    theme: PublicKey,
+    reviewer: PublicKey
+  ): Promise<ClaimState | null> {
+    if (ideaPubkeys.length === 0) {
+      return null;
+    }
+
+    const [protocolConfig] = this.corePda([Buffer.from('protocol_config')]);
+    const [reputation] = this.settlementPda([Buffer.from('reputation'), reviewer.toBuffer()]);
+    const [stakePosition] = PublicKey.findProgramAddressSync(
+      [Buffer.from('stake_position'), theme.toBuffer(), reviewer.toBuffer()],
+      this.tokenProgramId
+    );
+    const ideas = ideaPubkeys.map((idea) => new PublicKey(idea));
+    const votes = ideas.map(
+      (idea) => this.corePda([Buffer.from('vote'), idea.toBuffer(), reviewer.toBuffer()])[0]
+    );
+
+    const [configInfo, reputationInfo, positionInfo, ...infos] =
+      await this.connection.getMultipleAccountsInfo([
+        protocolConfig,
+        reputation,
+        stakePosition,
+        ...ideas,
+        ...votes,
+      ]);
+    if (!configInfo) {
+      return null;
+    }
+
+    const position = positionInfo ? this.tokenCoder.decode('StakePosition', positionInfo.data) : null;
+    const state: ClaimState = {
+      protocolConfig: this.coreCoder.decode('ProtocolConfig', configInfo.data),
+      reputation: reputationInfo ? this.settlementCoder.decode('Reputation', reputationInfo.data) : null,
+      stakePosition: position ? stakePosition : null,
+      stakedAmount: position ? big(position.amount) : 0n,
+      ideas: new Map(),
+    };
+    ideaPubkeys.forEach((idea, i) => {
+      const ideaInfo = infos[i];
+      const voteInfo = infos[ideaPubkeys.length + i];
+      if (ideaInfo && voteInfo) {
+        state.ideas.set(idea, {
+          idea: this.coreCoder.decode('Idea', ideaInfo.data),
+          vote: this.coreCoder.decode('Vote', voteInfo.data),
+        });
+      }
+    });
+    return state;
+  }
+
+  /**
+   * Winner's cut of the penalty pool under the idea's payout curve (mirrors settlement claimable_winnings)
+   */
+  private winnerShare(idea: any, vote: any): bigint {
+    const unit = payoutUnit(
+      idea.payout_curve,
+      idea.payout_exponent,
+      big(vote.stake_amount),
+      big(vote.vote_weight)
+    );
+    const winningWeight = [idea.winning_image_index, idea.secondary_winning_index]
+      .filter((index) => index !== null)
+      .reduce((sum, index) => sum + big(idea.payout_weights[index]), 0n);
+    if (winningWeight === 0n) {
+      return 0n;
+    }
+    return (big(idea.penalty_pool_amount) * unit) / winningWeight;
+  }
+
+  /**
+   * Fee discount tier of the wallet (mirrors settlement claim_fee_discount_bps)
+   */
+  private claimFeeDiscountBps(state: ClaimState): bigint {
+    const cfg = state.protocolConfig;
+    const wins = BigInt(state.reputation?.wins ?? 0);
+    const settled = wins + BigInt(state.reputation?.losses ?? 0);
+    const reputationTier =
+      cfg.reputation_discount_bps > 0 &&
+      settled > 0n &&
+      wins >= BigInt(cfg.reputation_discount_min_wins) &&
+      wins * BigInt(config.constants.BPS_DENOMINATOR) >=
+        BigInt(cfg.reputation_discount_min_accuracy_bps) * settled;
+    let discount = reputationTier ? BigInt(cfg.reputation_discount_bps) : 0n;
+
+    const stakerTier =
+      cfg.staker_discount_bps > 0 &&
+      state.stakedAmount > 0n &&
+      state.stakedAmount >= big(cfg.staker_discount_min_amount);
+    if (stakerTier && BigInt(cfg.staker_discount_bps) > discount) {
+      discount = BigInt(cfg.staker_discount_bps);
+    }
+    return discount;
+  }
+
+  /**
+   * Discount rebate on the reviewer's stake-weighted share of the idea's reserved fees
+   * (mirrors settlement charge_claim_fees)
+   */
+  private claimFeeRebate(state: ClaimState, idea: any, stake: bigint): bigint {
+    const voterStake = big(idea.total_staked) - big(idea.initial_prize_pool);
+    if (voterStake <= 0n) {
+      return 0n;
+    }
+    const discount = this.claimFeeDiscountBps(state);
+    const rebate = (fee: bigint) =>
+      (((fee * stake) / voterStake) * discount) / BigInt(config.constants.BPS_DENOMINATOR);
+    return rebate(big(idea.curator_fee_collected)) + rebate(big(idea.platform_fee_collected));
+  }
+
+  /**
+   * Build the account metas and data for a no-argument settlement withdraw instruction
+   * (`accounts` is required for every instruction but withdraw_refund)
+   */
+  private buildWithdrawInstruction(
+    name: string,
+    ideaPubkey: string,
+    tokenMint: string,
+    reviewer: PublicKey,
+    accounts?: WithdrawAccounts
+  ): InstructionPayload {
+    const idea = new PublicKey(ideaPubkey);
+    const mint = new PublicKey(tokenMint);
+    const [vote] = this.corePda([Buffer.from('vote'), idea.toBuffer(), reviewer.toBuffer()]);
+    const [reviewerStake] = this.corePda([
+      Buffer.from('reviewer_stake'),
+      idea.toBuffer(),
+      reviewer.toBuffer(),
+    ]);
+    const [vault] = this.corePda([Buffer.from('vault'), idea.toBuffer()]);
+    const vaultTokenAccount = this.associatedTokenAddress(vault, mint);
+    const reviewerTokenAccount = this.associatedTokenAddress(reviewer, mint);
+
+    const keys = [
+      { pubkey: idea, isSigner: false, isWritable: true },
+      { pubkey: vote, isSigner: false, isWritable: false },
+      { pubkey: reviewerStake, isSigner: false, isWritable: true },
+    ];
+
+    if (!accounts) {
+      keys.push(
+        { pubkey: vault, isSigner: false, isWritable: true },
+        { pubkey: vaultTokenAccount, isSigner: false, isWritable: true },
+        { pubkey: reviewerTokenAccount, isSigner: false, isWritable: true },
+        {
+          pubkey: this.settlementPda([Buffer.from('rebate_treasury')])[0],
+          isSigner: false,
+          isWritable: true,
+        }
+      );
+    } else {
+      // Optional accounts are passed as the program id (Anchor's None placeholder)
+      const vestingClaim = accounts.vests
+        ? this.settlementPda([Buffer.from('vesting_claim'), idea.toBuffer(), reviewer.toBuffer()])[0]
+        : this.settlementProgramId;
+      keys.push(
+        // Cross-idea reputation record, created on first claim
+        {
+          pubkey: this.settlementPda([Buffer.from('reputation'), reviewer.toBuffer()])[0],
+          isSigner: false,
+          isWritable: true,
+        },
+        { pubkey: vault, isSigner: false, isWritable: true },
+        { pubkey: vaultTokenAccount, isSigner: false, isWritable: true },
+        {
+          pubkey: this.corePda([Buffer.from('protocol_config')])[0],
+          isSigner: false,
+          isWritable: false,
+        },
+        // Reserved curator/platform fees are settled per claim
+        {
+          pubkey: this.associatedTokenAddress(new PublicKey(accounts.curator), mint),
+          isSigner: false,
+          isWritable: true,
+        },
+        {
+          pubkey: this.associatedTokenAddress(new PublicKey(accounts.treasury), mint),
+          isSigner: false,
+          isWritable: true,
+        },
+        {
+          pubkey: accounts.stakePosition ?? this.settlementProgramId,
+          isSigner: false,
+          isWritable: false,
+        },
+        // Global and theme season leaderboards, ranked on every claim
+        {
+          pubkey: this.leaderboard(PublicKey.default, accounts.season),
+          isSigner: false,
+          isWritable: true,
+        },
+        {
+          pubkey: this.leaderboard(new PublicKey(accounts.theme), accounts.season),
+          isSigner: false,
+          isWritable: true,
+        },
+        { pubkey: reviewerTokenAccount, isSigner: false, isWritable: true },
+        { pubkey: vestingClaim, isSigner: false, isWritable: accounts.vests }
+      );
+    }
+
+    keys.push(
+      { pubkey: reviewer, isSigner: true, isWritable: true },
+      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false }
+    );
+
+    return {
+      programId: this.settlementProgramId.toBase58(),
+      name,
+      keys: keys.map((k) => ({ ...k, pubkey: k.pubkey.toBase58() })),
+      data: this.instructionDiscriminator(name).toString('base64'),
+    };
+  }
+
+  private corePda(seeds: Buffer[]): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(seeds, this.coreProgramId);
+  }
+
+  private settlementPda(seeds: Buffer[]): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(seeds, this.settlementProgramId);
+  }
+
+  /**
+   * Season leaderboard PDA; the global board uses the all-zero scope (GLOBAL_LEADERBOARD_SCOPE)
+   */
+  private leaderboard(scope: PublicKey, season: number): PublicKey {
+    const seasonBytes = Buffer.alloc(4);
+    seasonBytes.writeUInt32LE(season);
+    return this.settlementPda([Buffer.from('leaderboard'), scope.toBuffer(), seasonBytes])[0];
+  }
+
+  private eventAuthority(): PublicKey {
+    return this.settlementPda([Buffer.from('__event_authority')])[0];
+  }
+
+  private associatedTokenAddress(owner: PublicKey, mint: PublicKey): PublicKey {
+    return PublicKey.findProgramAddressSync(
+      [owner.toBuffer(), TOKEN_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+      ASSOCIATED_TOKEN_PROGRAM_ID
+    )[0];
+  }
+
+  /**
+   * Anchor instruction discriminator: sha256("global:<name>")[0..8]
+   */
+  private instructionDiscriminator(name: string): Buffer {
+    return createHash('sha256').update(`global:${name}`).digest().subarray(0, 8);
+  }
+}
+
+export default ClaimsService;
@@ -2,8 +2,10 @@ import {
   Connection,
   PublicKey,
   ParsedTransactionWithMeta,
+  PartiallyDecodedInstruction,
 } from '@solana/web3.js';
 import { Program, BorshCoder, EventParser } from '@coral-xyz/anchor';
+import * as bs58 from 'bs58';
 import { config } from '../../config';
 import { logger } from '../../utils/logger';
 import { db } from '../../database';
@@ -18,11 +20,21 @@ import {
   IdeaCancelledEvent,
   VotingCancelledEvent,
   RefundWithdrawnEvent,
+  LoserRefundWithdrawnEvent,
+  UnclaimedSweptEvent,
+  ThemeMigratedEvent,
+  ThemeMetadataUpdatedEvent,
+  ThemePausedEvent,
+  ThemeUnpausedEvent,
+  ThemeRetiredEvent,
 } from '../../types';
 import EventHandlers from '../../handlers';
 import * as fs from 'fs';
 import * as path from 'path';
 
+// Anchor's event CPI instruction discriminator (EVENT_IX_TAG_LE)
+const EVENT_IX_TAG = Buffer.from('e445a52e51cb9a1d', 'hex');
+
 export class BlockchainIndexer {
   private connection: Connection;
   private coreProgramId: PublicKey;
@@ -34,6 +46,9 @@ export class BlockchainIndexer {
   private coreEventParser: EventParser | null = null;
   private settlementEventParser: EventParser | null = null;
   private tokenEventParser: EventParser | null = null;
+  private coreCoder: BorshCoder | null = null;
+  private settlementCoder: BorshCoder | null = null;
+  private tokenCoder: BorshCoder | null = null;
   private wsConnection: WebSocket | null = null;
   private coreSubscriptionId: number | null = null;
   private settlementSubscriptionId: number | null = null;
@@ -71,6 +86,7 @@ export class BlockchainIndexer {
       // Create core program instance
       this.coreProgram = new Program(coreIdl, this.coreProgramId);
       const coreCoder = new BorshCoder(coreIdl);
+      this.coreCoder = coreCoder;
       this.coreEventParser = new EventParser(this.coreProgramId, coreCoder);
 
       // Load Settlement Program IDL
@@ -84,6 +100,7 @@ export class BlockchainIndexer {
       // Create settlement program instance
       this.settlementProgram = new Program(settlementIdl, this.settlementProgramId);
       const settlementCoder = new BorshCoder(settlementIdl);
+      this.settlementCoder = settlementCoder;
       this.settlementEventParser = new EventParser(this.settlementProgramId, settlementCoder);
 
       // Load Token Program IDL
@@ -97,6 +114,7 @@ export class BlockchainIndexer {
       // Create token program instance
       this.tokenProgram = new Program(tokenIdl, this.tokenProgramId);
       const tokenCoder = new BorshCoder(tokenIdl);
+      this.tokenCoder = tokenCoder;
       this.tokenEventParser = new EventParser(this.tokenProgramId, tokenCoder);
 
       // Load last processed slot from database
@@ -348,8 +366,18 @@ export class BlockchainIndexer {
       : programType === 'settlement'
       ? this.settlementEventParser
       : this.tokenEventParser;
+    const coder = programType === 'core'
+      ? this.coreCoder
+      : programType === 'settlement'
+      ? this.settlementCoder
+      : this.tokenCoder;
+    const programId = programType === 'core'
+      ? this.coreProgramId
+      : programType === 'settlement'
+      ? this.settlementProgramId
+      : this.tokenProgramId;
     
-    if (!eventParser) {
+    if (!eventParser || !coder) {
       logger.error('Event parser not initialized', { programType });
       return;
     }
@@ -365,8 +393,12 @@ export class BlockchainIndexer {
         hasLogs: logMessages.length > 0,
       });
 
-      // Parse events from transaction
-      const events = eventParser.parseLogs(logMessages);
+      // Parse events from transaction: programs emit through event CPI,
+      // logs are still read for transactions from before the upgrade
+      const events = [
+        ...this.parseCpiEvents(tx, programId, coder),
+        ...eventParser.parseLogs(logMessages),
+      ];
 
       logger.debug('Parsed events', {
         signature,
@@ -399,6 +431,38 @@ export class BlockchainIndexer {
     }
   }
 
+  /**
+   * Decode events emitted via emit_cpi! from the transaction's inner instructions.
+   * Unlike program logs these cannot be truncated or spoofed by other programs.
+   */
+  private parseCpiEvents(
+    tx: ParsedTransactionWithMeta,
+    programId: PublicKey,
+    coder: BorshCoder
+  ): { name: string; data: any }[] {
+    const events: { name: string; data: any }[] = [];
+
+    for (const inner of tx.meta?.innerInstructions || []) {
+      for (const ix of inner.instructions) {
+        if (!ix.programId.equals(programId) || !('data' in ix)) {
+          continue;
+        }
+
+        const data = Buffer.from(bs58.decode((ix as PartiallyDecodedInstruction).data));
+        if (data.length < 8 || !data.subarray(0, 8).equals(EVENT_IX_TAG)) {
+          continue;
+        }
+
+        const event = coder.events.decode(data.subarray(8).toString('base64'));
+        if (event) {
+          events.push(event);
+        }
+      }
+    }
+
+    return events;
+  }
+
   /**
    * Dispatch event to appropriate handler
    */
@@ -430,6 +494,41 @@ export class BlockchainIndexer {
           );
           break;
 
+        case 'ThemeMigrated':
+          await EventHandlers.handleThemeMigrated(
+            eventData as ThemeMigratedEvent,
+            signature
+          );
+          break;
+
+        case 'ThemeMetadataUpdated':
+          await EventHandlers.handleThemeMetadataUpdated(
+            eventData as ThemeMetadataUpdatedEvent,
+            signature
+          );
+          break;
+
+        case 'ThemePaused':
+          await EventHandlers.handleThemePaused(
+            eventData as ThemePausedEvent,
+            signature
+          );
+          break;
+
+        case 'ThemeUnpaused':
+          await EventHandlers.handleThemeUnpaused(
+            eventData as ThemeUnpausedEvent,
+            signature
+          );
+          break;
+
+        case 'ThemeRetired':
+          await EventHandlers.handleThemeRetired(
+            eventData as ThemeRetiredEvent,
+            signature
+          );
+          break;
+
         case 'IdeaCreated':
           await EventHandlers.handleIdeaCreated(
             eventData as IdeaCreatedEvent,
@@ -493,6 +592,20 @@ export class BlockchainIndexer {
           );
           break;
 
+        case 'LoserRefundWithdrawn':
+          await EventHandlers.handleLoserRefundWithdrawn(
+            eventData as LoserRefundWithdrawnEvent,
+            signature
+          );
+          break;
+
+        case 'UnclaimedSwept':
+          await EventHandlers.handleUnclaimedSwept(
+            eventData as UnclaimedSweptEvent,
+            signature
+          );
+          break;
+
         default:
           logger.warn('Unknown event type', { eventName });
       }
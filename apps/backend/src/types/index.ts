@@ -14,6 +14,7 @@ export enum ThemeStatus {
   Active = 'Active',
   Migrated = 'Migrated',
   Paused = 'Paused',
+  Retired = 'Retired',
 }
 
 export enum IdeaStatus {
@@ -96,6 +97,7 @@ export interface IdeaRecord {
   reject_all_weight: string;
   total_voters: number;
   winning_image_index?: number;
+  secondary_winning_image_index?: number;
   curator_fee_collected: string;
   platform_fee_collected: string;
   penalty_pool_amount: string;
@@ -162,11 +164,54 @@ export interface BuybackExecutedEvent {
   solSpent: string;
   tokensBurned: string;
   newTokenReserves: string;
+  keeper: PublicKey;
+  keeperReward: string;
+  policy: number;
+}
+
+export interface ThemeMigratedEvent {
+  theme: PublicKey;
+  migrationTarget: number; // 0 = Raydium, 1 = Meteora
+  pool: PublicKey;
+  lpMint: PublicKey;
+  solAmount: string;
+  tokenAmount: string;
+  timestamp: string;
+  lpLock: PublicKey; // LpLock PDA holding the migration LP tokens
+  lpAmount: string;
+  lpUnlockAt: string;
+}
+
+export interface ThemeMetadataUpdatedEvent {
+  theme: PublicKey;
+  name: number[]; // [u8; 12], zero-padded utf8
+  description: number[]; // [u8; 48], zero-padded utf8
+  timestamp: string;
+}
+
+export interface ThemeRetiredEvent {
+  theme: PublicKey;
+  merkleRoot: number[];
+  totalClaimable: string;
+  timestamp: string;
+}
+
+export interface ThemePausedEvent {
+  theme: PublicKey;
+  authority: PublicKey;
+  timestamp: string;
+}
+
+export interface ThemeUnpausedEvent {
+  theme: PublicKey;
+  authority: PublicKey;
+  timestamp: string;
 }
 
 export interface IdeaCreatedEvent {
   idea: PublicKey;
   initiator: PublicKey;
+  curator: PublicKey;
   prompt: string;
   depinProvider: PublicKey;
 }
@@ -195,11 +240,18 @@ export interface VoteCastEvent {
 export interface VotingSettledEvent {
   idea: PublicKey;
   winningImageIndex: number;
+  secondaryWinningIndex: number | null;
   totalStaked: string;
   curatorFee: string;
   platformFee: string;
   penaltyPool: string;
   winnerCount: string;
+  settler: PublicKey;
+  settlerReward: string;
+  insuranceContribution: string;
+  penaltyBurned: string;
+  remixRoyalty: string;
+  collectionContribution: string;
 }
 
 export interface WinningsWithdrawnEvent {
@@ -224,6 +276,18 @@ export interface RefundWithdrawnEvent {
   amount: string;
 }
 
+export interface LoserRefundWithdrawnEvent {
+  idea: PublicKey;
+  reviewer: PublicKey;
+  amount: string;
+  penalty: string;
+}
+
+export interface UnclaimedSweptEvent {
+  idea: PublicKey;
+  amount: string;
+}
+
 // ============================================================================
 // API Request/Response Types
 // ============================================================================
@@ -334,6 +398,31 @@ export interface UploadResponse {
   cids: string[];
 }
 
+export interface InstructionPayload {
+  programId: string;
+  name: string;
+  keys: {
+    pubkey: string;
+    isSigner: boolean;
+    isWritable: boolean;
+  }[];
+  data: string; // base64
+}
+
+export interface ClaimableItem {
+  idea: string;
+  kind: 'winnings' | 'refund' | 'loser_refund';
+  amount: string; // theme token base units
+  instruction: InstructionPayload;
+}
+
+export interface ClaimablesResponse {
+  theme: string;
+  wallet: string;
+  claims: ClaimableItem[];
+  totalAmount: string;
+}
+
 // ============================================================================
 // WebSocket Message Types
 // ============================================================================
@@ -1,7 +1,9 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, Transfer};
+use anchor_lang::Discriminator;
+use anchor_spl::token::{self, Burn, CloseAccount, Mint, Token, TokenAccount, Transfer};
 use anchor_spl::associated_token::AssociatedToken;
 use taste_fun_shared::*;
+use taste_fun_token::program::TasteFunToken;
 
 declare_id!("DiyEKJXPDNJ4Phfe3wVYkgi2NbJQuHtifgDgBYbCRuGe");
 
@@ -10,6 +12,12 @@ pub mod taste_fun_core {
     use super::*;
 
     /// 创建新创意，提交 AI 生图 Prompt
+    #[access_control(
+        protocol_active(ctx.accounts.protocol_config.paused)
+        theme_not_paused(ctx.accounts.theme_account.status)
+        valid_prompt(&prompt)
+        valid_voting_duration(voting_duration_hours)
+    )]
     pub fn create_idea(
         ctx: Context<CreateIdea>,
         idea_id: u64,
@@ -17,17 +25,169 @@ pub mod taste_fun_core {
         theme: Pubkey,
         depin_provider: Pubkey,
         voting_duration_hours: u16,
+        allow_rerun: bool,
+        curator: Option<Pubkey>,
     ) -> Result<()> {
-        require!(
-            prompt.len() > 0 && prompt.len() <= MAX_PROMPT_LEN,
-            ConsensusError::InvalidPrompt
-        );
-        require!(
-            voting_duration_hours >= 24 && voting_duration_hours <= 168,
-            ConsensusError::InvalidVotingDuration
+        let clock = Clock::get()?;
+        let prompt_hash = anchor_lang::solana_program::keccak::hash(prompt.as_bytes()).to_bytes();
+        let idea = &mut ctx.accounts.idea;
+
+        idea.initiator = ctx.accounts.initiator.key();
+        idea.idea_id = idea_id;
+        idea.prompt = prompt.clone();
+        idea.created_at = clock.unix_timestamp;
+        idea.theme = theme;
+        idea.theme_token_mint = ctx.accounts.theme_token_mint.key();
+        idea.image_uris = Vec::new();
+        idea.generation_status = GenerationStatus::Pending;
+        idea.generation_deadline = clock.unix_timestamp + IMAGE_GENERATION_TIMEOUT;
+        idea.total_staked = 0;
+        idea.min_stake = MIN_TOKEN_STAKE.max(ctx.accounts.theme_account.min_stake_tokens); // Now uses token amount
+        idea.curator_fee_bps = CURATOR_FEE_BPS;
+        idea.votes = [0; 4];
+        idea.reject_all_weight = 0;
+        idea.total_voters = 0;
+        idea.voting_deadline = 0;
+        idea.curator_fee_collected = 0;
+        idea.platform_fee_collected = 0;
+        idea.penalty_pool_amount = 0;
+        idea.winner_count = 0;
+        idea.status = IdeaStatus::GeneratingImages;
+        idea.vault_bump = ctx.bumps.vault;
+        idea.idea_bump = ctx.bumps.idea;
+        idea.depin_provider = depin_provider;
+        idea.sponsor = None;
+        idea.initial_prize_pool = 0;
+        idea.allowlist_root = None;
+        idea.gate_mint = None;
+        idea.gate_min_amount = 0;
+        idea.cancel_reason = CANCEL_REASON_NONE;
+        idea.refund_rebates_paid = 0;
+        idea.sponsor_profile = None;
+        idea.no_loss = false;
+        idea.series = None;
+        idea.image_hashes = [[0; 32]; 4];
+        idea.reveal_ts = 0;
+        idea.bonus_judge = None;
+        idea.bonus_pool = 0;
+        idea.prompt_hash = prompt_hash;
+        idea.payout_curve = ctx.accounts.theme_account.payout_curve;
+        idea.payout_exponent = ctx.accounts.theme_account.payout_exponent;
+        idea.payout_weights = [0; 4];
+        idea.voter_counts = [0; 4];
+        idea.stake_totals = [0; 4];
+        idea.voting_mode = VotingMode::from_u8(ctx.accounts.theme_account.voting_mode)?;
+        idea.secondary_winning_index = None;
+        idea.tie_break_rule = ctx.accounts.theme_account.tie_break_rule;
+        idea.last_vote_ts = [0; 4];
+        idea.claim_deadline = 0;
+        idea.dispute_period = ctx.accounts.theme_account.dispute_period;
+        idea.reject_all_threshold_bps = ctx.accounts.theme_account.reject_all_threshold_bps;
+        idea.penalty_burn_bps = ctx.accounts.theme_account.penalty_burn_bps;
+        idea.dispute_deadline = 0;
+        idea.disputed = false;
+        // 策展费接收方，未指定时归发起者
+        idea.curator = curator.unwrap_or(idea.initiator);
+        idea.rollover_bps = 0;
+        idea.insurance_contribution = 0;
+        idea.parent = None;
+        idea.remix_fee_bps = 0;
+        idea.remix_royalty = 0;
+        idea.collection = None;
+        idea.version = IDEA_ACCOUNT_VERSION;
+
+        // 同一发起者在同一主题内的相同 Prompt 仍处于活跃期时拒绝 (allow_rerun 可显式重跑)
+        let registry = &mut ctx.accounts.prompt_registry;
+        registry.theme = idea.theme;
+        registry.bump = ctx.bumps.prompt_registry;
+        register_prompt(registry, idea, idea.key(), clock.unix_timestamp, allow_rerun)?;
+
+        // 主题奖池滚存: 若提供了奖池账户，将累计奖池全部注入本创意作为初始奖金
+        if let (Some(jackpot), Some(jackpot_token_account)) = (
+            ctx.accounts.jackpot.as_mut(),
+            ctx.accounts.jackpot_token_account.as_ref(),
+        ) {
+            let amount = jackpot_token_account.amount;
+            if amount > 0 {
+                let jackpot_seeds: &[&[u8]] = &[b"jackpot", theme.as_ref(), &[jackpot.bump]];
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: jackpot_token_account.to_account_info(),
+                            to: ctx.accounts.vault_token_account.to_account_info(),
+                            authority: jackpot.to_account_info(),
+                        },
+                        &[jackpot_seeds],
+                    ),
+                    amount,
+                )?;
+
+                idea.initial_prize_pool = amount;
+                idea.total_staked = amount;
+                jackpot.total_paid_out = jackpot.total_paid_out
+                    .checked_add(amount)
+                    .ok_or(ConsensusError::Overflow)?;
+                jackpot.last_funded_idea = Some(idea.key());
+
+                emit_cpi!(JackpotFunded {
+                    theme,
+                    idea: idea.key(),
+                    amount,
+                });
+            }
+        }
+
+        // 收取发起费用
+        let ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.initiator.key(),
+            &ctx.accounts.protocol_treasury.key(),
+            ctx.accounts.protocol_config.creation_fee,
         );
+        anchor_lang::solana_program::program::invoke(
+            &ix,
+            &[
+                ctx.accounts.initiator.to_account_info(),
+                ctx.accounts.protocol_treasury.to_account_info(),
+            ],
+        )?;
+
+        emit_cpi!(IdeaCreated {
+            idea: idea.key(),
+            initiator: idea.initiator,
+            curator: idea.curator,
+            prompt: prompt.clone(),
+            depin_provider,
+        });
+        emit_cpi!(PointsAccrued {
+            user: idea.initiator,
+            kind: POINTS_KIND_IDEA,
+            points: POINTS_PER_IDEA,
+        });
 
+        Ok(())
+    }
+
+    /// 创建衍生创意 (remix)：引用同一主题内已结算且有获胜图片的原创意，
+    /// 结算时按 remix_fee_bps 从费用基数中提取版税，归原创意的发起者与获胜评审
+    #[access_control(
+        protocol_active(ctx.accounts.protocol_config.paused)
+        theme_not_paused(ctx.accounts.theme_account.status)
+        valid_prompt(&prompt)
+        valid_voting_duration(voting_duration_hours)
+        valid_remix_parent(&ctx.accounts.parent_idea, &theme, remix_fee_bps)
+    )]
+    pub fn create_remix(
+        ctx: Context<CreateRemix>,
+        idea_id: u64,
+        prompt: String,
+        theme: Pubkey,
+        depin_provider: Pubkey,
+        voting_duration_hours: u16,
+        remix_fee_bps: u16,
+    ) -> Result<()> {
         let clock = Clock::get()?;
+        let prompt_hash = anchor_lang::solana_program::keccak::hash(prompt.as_bytes()).to_bytes();
         let idea = &mut ctx.accounts.idea;
 
         idea.initiator = ctx.accounts.initiator.key();
@@ -40,7 +200,7 @@ pub mod taste_fun_core {
         idea.generation_status = GenerationStatus::Pending;
         idea.generation_deadline = clock.unix_timestamp + IMAGE_GENERATION_TIMEOUT;
         idea.total_staked = 0;
-        idea.min_stake = MIN_TOKEN_STAKE; // Now uses token amount
+        idea.min_stake = MIN_TOKEN_STAKE.max(ctx.accounts.theme_account.min_stake_tokens);
         idea.curator_fee_bps = CURATOR_FEE_BPS;
         idea.votes = [0; 4];
         idea.reject_all_weight = 0;
@@ -56,12 +216,54 @@ pub mod taste_fun_core {
         idea.depin_provider = depin_provider;
         idea.sponsor = None;
         idea.initial_prize_pool = 0;
+        idea.allowlist_root = None;
+        idea.gate_mint = None;
+        idea.gate_min_amount = 0;
+        idea.cancel_reason = CANCEL_REASON_NONE;
+        idea.refund_rebates_paid = 0;
+        idea.sponsor_profile = None;
+        idea.no_loss = false;
+        idea.series = None;
+        idea.image_hashes = [[0; 32]; 4];
+        idea.reveal_ts = 0;
+        idea.bonus_judge = None;
+        idea.bonus_pool = 0;
+        idea.prompt_hash = prompt_hash;
+        idea.payout_curve = ctx.accounts.theme_account.payout_curve;
+        idea.payout_exponent = ctx.accounts.theme_account.payout_exponent;
+        idea.payout_weights = [0; 4];
+        idea.voter_counts = [0; 4];
+        idea.stake_totals = [0; 4];
+        idea.voting_mode = VotingMode::from_u8(ctx.accounts.theme_account.voting_mode)?;
+        idea.secondary_winning_index = None;
+        idea.tie_break_rule = ctx.accounts.theme_account.tie_break_rule;
+        idea.last_vote_ts = [0; 4];
+        idea.claim_deadline = 0;
+        idea.dispute_period = ctx.accounts.theme_account.dispute_period;
+        idea.reject_all_threshold_bps = ctx.accounts.theme_account.reject_all_threshold_bps;
+        idea.penalty_burn_bps = ctx.accounts.theme_account.penalty_burn_bps;
+        idea.dispute_deadline = 0;
+        idea.disputed = false;
+        idea.curator = idea.initiator;
+        idea.rollover_bps = 0;
+        idea.insurance_contribution = 0;
+        idea.parent = Some(ctx.accounts.parent_idea.key());
+        idea.remix_fee_bps = remix_fee_bps;
+        idea.remix_royalty = 0;
+        idea.collection = None;
+        idea.version = IDEA_ACCOUNT_VERSION;
+
+        // 衍生创意的 Prompt 通常与原创意相近，仍按发起者去重但不允许重跑
+        let registry = &mut ctx.accounts.prompt_registry;
+        registry.theme = idea.theme;
+        registry.bump = ctx.bumps.prompt_registry;
+        register_prompt(registry, idea, idea.key(), clock.unix_timestamp, false)?;
 
         // 收取发起费用
         let ix = anchor_lang::solana_program::system_instruction::transfer(
             &ctx.accounts.initiator.key(),
             &ctx.accounts.protocol_treasury.key(),
-            CREATION_FEE,
+            ctx.accounts.protocol_config.creation_fee,
         );
         anchor_lang::solana_program::program::invoke(
             &ix,
@@ -71,17 +273,35 @@ pub mod taste_fun_core {
             ],
         )?;
 
-        emit!(IdeaCreated {
+        emit_cpi!(IdeaCreated {
             idea: idea.key(),
             initiator: idea.initiator,
-            prompt: prompt.clone(),
+            curator: idea.curator,
+            prompt,
             depin_provider,
         });
+        emit_cpi!(RemixCreated {
+            idea: idea.key(),
+            parent: ctx.accounts.parent_idea.key(),
+            remix_fee_bps,
+        });
+        emit_cpi!(PointsAccrued {
+            user: idea.initiator,
+            kind: POINTS_KIND_IDEA,
+            points: POINTS_PER_IDEA,
+        });
 
         Ok(())
     }
 
     /// 创建赞助竞赛 (赞助商注入初始奖池)
+    /// no_loss: 无损模式，评审本金全额返还，仅赞助奖池分配给获胜者
+    #[access_control(
+        protocol_active(ctx.accounts.protocol_config.paused)
+        theme_not_paused(ctx.accounts.theme_account.status)
+        valid_prompt(&prompt)
+        valid_voting_duration(voting_duration_hours)
+    )]
     pub fn create_sponsored_idea(
         ctx: Context<CreateSponsoredIdea>,
         idea_id: u64,
@@ -90,19 +310,17 @@ pub mod taste_fun_core {
         depin_provider: Pubkey,
         voting_duration_hours: u16,
         initial_prize_pool: u64,
+        no_loss: bool,
+        rollover_bps: u16,
     ) -> Result<()> {
-        require!(
-            prompt.len() > 0 && prompt.len() <= MAX_PROMPT_LEN,
-            ConsensusError::InvalidPrompt
-        );
-        require!(
-            voting_duration_hours >= 24 && voting_duration_hours <= 168,
-            ConsensusError::InvalidVotingDuration
-        );
         require!(
             initial_prize_pool >= MIN_TOKEN_STAKE,
             ConsensusError::StakeTooLow
         );
+        require!(
+            rollover_bps <= BPS_DENOMINATOR,
+            ConsensusError::InvalidRolloverBps
+        );
 
         let clock = Clock::get()?;
         let idea = &mut ctx.accounts.idea;
@@ -117,7 +335,7 @@ pub mod taste_fun_core {
         idea.generation_status = GenerationStatus::Pending;
         idea.generation_deadline = clock.unix_timestamp + IMAGE_GENERATION_TIMEOUT;
         idea.total_staked = initial_prize_pool;
-        idea.min_stake = MIN_TOKEN_STAKE;
+        idea.min_stake = MIN_TOKEN_STAKE.max(ctx.accounts.theme_account.min_stake_tokens);
         idea.curator_fee_bps = CURATOR_FEE_BPS;
         idea.votes = [0; 4];
         idea.reject_all_weight = 0;
@@ -133,12 +351,55 @@ pub mod taste_fun_core {
         idea.depin_provider = depin_provider;
         idea.sponsor = Some(ctx.accounts.sponsor.key());
         idea.initial_prize_pool = initial_prize_pool;
+        idea.allowlist_root = None;
+        idea.gate_mint = None;
+        idea.gate_min_amount = 0;
+        idea.cancel_reason = CANCEL_REASON_NONE;
+        idea.refund_rebates_paid = 0;
+        idea.sponsor_profile = ctx.accounts.sponsor_profile.as_ref().map(|p| p.key());
+        idea.no_loss = no_loss;
+        idea.series = None;
+        idea.image_hashes = [[0; 32]; 4];
+        idea.reveal_ts = 0;
+        idea.bonus_judge = None;
+        idea.bonus_pool = 0;
+        idea.prompt_hash = anchor_lang::solana_program::keccak::hash(prompt.as_bytes()).to_bytes();
+        idea.payout_curve = ctx.accounts.theme_account.payout_curve;
+        idea.payout_exponent = ctx.accounts.theme_account.payout_exponent;
+        idea.payout_weights = [0; 4];
+        idea.voter_counts = [0; 4];
+        idea.stake_totals = [0; 4];
+        idea.voting_mode = VotingMode::from_u8(ctx.accounts.theme_account.voting_mode)?;
+        idea.secondary_winning_index = None;
+        idea.tie_break_rule = ctx.accounts.theme_account.tie_break_rule;
+        idea.last_vote_ts = [0; 4];
+        idea.claim_deadline = 0;
+        idea.dispute_period = ctx.accounts.theme_account.dispute_period;
+        idea.reject_all_threshold_bps = ctx.accounts.theme_account.reject_all_threshold_bps;
+        idea.penalty_burn_bps = ctx.accounts.theme_account.penalty_burn_bps;
+        idea.dispute_deadline = 0;
+        idea.disputed = false;
+        idea.curator = idea.initiator;
+        // RejectAll 取消时滚入主题奖池的赞助奖池比例
+        idea.rollover_bps = rollover_bps;
+        idea.insurance_contribution = 0;
+        idea.parent = None;
+        idea.remix_fee_bps = 0;
+        idea.remix_royalty = 0;
+        idea.collection = None;
+        idea.version = IDEA_ACCOUNT_VERSION;
+
+        // 赞助竞赛同样按发起者去重并占用登记表容量
+        let registry = &mut ctx.accounts.prompt_registry;
+        registry.theme = idea.theme;
+        registry.bump = ctx.bumps.prompt_registry;
+        register_prompt(registry, idea, idea.key(), clock.unix_timestamp, false)?;
 
         // 收取发起费用
         let ix = anchor_lang::solana_program::system_instruction::transfer(
             &ctx.accounts.initiator.key(),
             &ctx.accounts.protocol_treasury.key(),
-            CREATION_FEE,
+            ctx.accounts.protocol_config.creation_fee,
         );
         anchor_lang::solana_program::program::invoke(
             &ix,
@@ -161,316 +422,4014 @@ pub mod taste_fun_core {
             initial_prize_pool,
         )?;
 
-        emit!(SponsoredIdeaCreated {
+        emit_cpi!(SponsoredIdeaCreated {
             idea: idea.key(),
             initiator: idea.initiator,
             sponsor: ctx.accounts.sponsor.key(),
             prompt: prompt.clone(),
             initial_prize_pool,
             depin_provider,
+            sponsor_profile: idea.sponsor_profile,
+            no_loss,
+        });
+        emit_cpi!(PointsAccrued {
+            user: idea.initiator,
+            kind: POINTS_KIND_IDEA,
+            points: POINTS_PER_IDEA,
         });
 
         Ok(())
     }
 
-    /// 确认图片生成完成 (由授权的链下服务调用)
-    pub fn confirm_images(
-        ctx: Context<ConfirmImages>,
-        image_uris: Vec<String>,
+    /// 初始化主题奖池 (任何人都可以调用)，接收 RejectAll 取消的赞助奖池滚存
+    #[access_control(theme_not_paused(ctx.accounts.theme_account.status))]
+    pub fn init_jackpot(ctx: Context<InitJackpot>) -> Result<()> {
+        let jackpot = &mut ctx.accounts.jackpot;
+        jackpot.theme = ctx.accounts.theme_account.key();
+        jackpot.theme_token_mint = ctx.accounts.theme_token_mint.key();
+        jackpot.total_paid_out = 0;
+        jackpot.last_funded_idea = None;
+        jackpot.bump = ctx.bumps.jackpot;
+        Ok(())
+    }
+
+    /// 创建赞助系列赛：预存预算，每 interval_days 天自动开启一期赞助竞赛
+    #[access_control(
+        theme_not_paused(ctx.accounts.theme_account.status)
+        valid_prompt(&prompt)
+    )]
+    pub fn create_series(
+        ctx: Context<CreateSeries>,
+        series_id: u64,
+        prompt: String,
+        theme: Pubkey,
+        depin_provider: Pubkey,
+        interval_days: u16,
+        prize_per_episode: u64,
+        max_episodes: u64,
+        no_loss: bool,
     ) -> Result<()> {
-        let idea = &mut ctx.accounts.idea;
         require!(
-            idea.status == IdeaStatus::GeneratingImages,
-            ConsensusError::InvalidState
+            (MIN_SERIES_INTERVAL_DAYS..=MAX_SERIES_INTERVAL_DAYS).contains(&interval_days)
+                && (1..=MAX_SERIES_EPISODES).contains(&max_episodes),
+            ConsensusError::InvalidSeriesConfig
         );
-        require!(image_uris.len() == 4, ConsensusError::InvalidImageCount);
-
-        // 验证调用者是授权的 DePIN 服务
         require!(
-            ctx.accounts.depin_authority.key() == AUTHORIZED_DEPIN_PUBKEY,
-            ConsensusError::UnauthorizedDePIN
+            prize_per_episode >= MIN_TOKEN_STAKE,
+            ConsensusError::StakeTooLow
         );
 
-        // 验证 URI 长度
-        for uri in &image_uris {
-            require!(
-                uri.len() > 0 && uri.len() <= MAX_IMAGE_URI_LEN,
-                ConsensusError::InvalidImageUri
-            );
-        }
+        let budget = prize_per_episode
+            .checked_mul(max_episodes)
+            .ok_or(ConsensusError::Overflow)?;
 
-        idea.image_uris = image_uris.clone();
-        idea.generation_status = GenerationStatus::Completed;
-        idea.status = IdeaStatus::Voting;
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.sponsor_token_account.to_account_info(),
+                    to: ctx.accounts.series_token_account.to_account_info(),
+                    authority: ctx.accounts.sponsor.to_account_info(),
+                },
+            ),
+            budget,
+        )?;
 
-        let clock = Clock::get()?;
-        idea.voting_deadline = clock.unix_timestamp + DEFAULT_VOTING_DURATION;
+        let series = &mut ctx.accounts.series;
+        series.sponsor = ctx.accounts.sponsor.key();
+        series.series_id = series_id;
+        series.theme = theme;
+        series.theme_token_mint = ctx.accounts.theme_token_mint.key();
+        series.prompt = prompt;
+        series.depin_provider = depin_provider;
+        series.interval_secs = interval_days as i64 * 24 * 3600;
+        series.prize_per_episode = prize_per_episode;
+        series.max_episodes = max_episodes;
+        series.episodes_created = 0;
+        series.next_episode_at = Clock::get()?.unix_timestamp;
+        series.no_loss = no_loss;
+        series.active = true;
+        series.bump = ctx.bumps.series;
 
-        emit!(ImagesGenerated {
-            idea: idea.key(),
-            image_uris,
+        emit_cpi!(SeriesCreated {
+            series: series.key(),
+            sponsor: series.sponsor,
+            theme,
+            interval_secs: series.interval_secs,
+            prize_per_episode,
+            max_episodes,
         });
 
         Ok(())
     }
 
-    /// 质押并投票选择图片 (使用主题代币质押)
-    pub fn vote_for_image(
-        ctx: Context<VoteForImage>,
-        image_index: u8,
-        token_amount: u64,
-    ) -> Result<()> {
-        let idea = &ctx.accounts.idea;
-        require!(idea.status == IdeaStatus::Voting, ConsensusError::InvalidState);
+    /// 推进系列赛：到期后开启下一期赞助竞赛 (任何人可调用，调用者支付账户租金与发起费用)
+    #[access_control(
+        protocol_active(ctx.accounts.protocol_config.paused)
+        theme_not_paused(ctx.accounts.theme_account.status)
+    )]
+    pub fn roll_series(ctx: Context<RollSeries>) -> Result<()> {
+        let clock = Clock::get()?;
+        let series = &ctx.accounts.series;
         require!(
-            image_index < 4 || image_index == 255,
-            ConsensusError::InvalidImageIndex
+            series.active && series.episodes_created < series.max_episodes,
+            ConsensusError::InvalidState
         );
-        require!(token_amount >= idea.min_stake, ConsensusError::StakeTooLow);
-
-        let clock = Clock::get()?;
         require!(
-            clock.unix_timestamp < idea.voting_deadline,
-            ConsensusError::VotingEnded
+            clock.unix_timestamp >= series.next_episode_at,
+            ConsensusError::SeriesEpisodeNotDue
         );
 
-        // 转移代币质押到 vault（使用 SPL Token）
+        // 从系列预算中划拨本期奖池
+        let sponsor_key = series.sponsor;
+        let series_id_bytes = series.series_id.to_le_bytes();
+        let series_seeds: &[&[u8]] = &[
+            b"series",
+            sponsor_key.as_ref(),
+            series_id_bytes.as_ref(),
+            &[series.bump],
+        ];
         token::transfer(
-            CpiContext::new(
+            CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
-                    from: ctx.accounts.voter_token_account.to_account_info(),
+                    from: ctx.accounts.series_token_account.to_account_info(),
                     to: ctx.accounts.vault_token_account.to_account_info(),
-                    authority: ctx.accounts.voter.to_account_info(),
+                    authority: ctx.accounts.series.to_account_info(),
                 },
+                &[series_seeds],
             ),
-            token_amount,
+            series.prize_per_episode,
         )?;
 
-        // 计算二次方投票权重: vote_weight = sqrt(token_amount)
-        let vote_weight = integer_sqrt(token_amount);
+        // 收取发起费用 (与 create_idea 一致)
+        let ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.cranker.key(),
+            &ctx.accounts.protocol_treasury.key(),
+            ctx.accounts.protocol_config.creation_fee,
+        );
+        anchor_lang::solana_program::program::invoke(
+            &ix,
+            &[
+                ctx.accounts.cranker.to_account_info(),
+                ctx.accounts.protocol_treasury.to_account_info(),
+            ],
+        )?;
 
-        // 更新 idea 统计
+        let series_key = series.key();
+        let episode = series.episodes_created;
+        let prize = series.prize_per_episode;
         let idea = &mut ctx.accounts.idea;
-        if image_index < 4 {
-            idea.votes[image_index as usize] = idea.votes[image_index as usize]
-                .checked_add(vote_weight)
-                .ok_or(ConsensusError::Overflow)?;
-        } else {
-            // RejectAll 投票权重
-            idea.reject_all_weight = idea.reject_all_weight
-                .checked_add(vote_weight)
-                .ok_or(ConsensusError::Overflow)?;
-        }
-        idea.total_staked = idea.total_staked.checked_add(token_amount)
-            .ok_or(ConsensusError::Overflow)?;
-        idea.total_voters += 1;
-
-        // 创建投票记录（首次投票）
-        let vote = &mut ctx.accounts.vote;
-        vote.idea = idea.key();
-        vote.voter = ctx.accounts.voter.key();
-        vote.image_choice = image_index;
-        vote.stake_amount = token_amount;
-        vote.ts = clock.unix_timestamp;
-        vote.vote_weight = vote_weight;
-
-        // 创建质押记录（首次投票）
-        let reviewer_stake = &mut ctx.accounts.reviewer_stake;
-        reviewer_stake.idea = idea.key();
-        reviewer_stake.reviewer = ctx.accounts.voter.key();
-        reviewer_stake.total_staked = token_amount; // 首次投票，直接设置
-        reviewer_stake.is_winner = false;
-        reviewer_stake.winnings = 0;
-        reviewer_stake.bump = ctx.bumps.reviewer_stake;
-
-        emit!(VoteCast {
+
+        idea.initiator = series_key;
+        idea.idea_id = episode;
+        idea.prompt = series.prompt.clone();
+        idea.created_at = clock.unix_timestamp;
+        idea.theme = series.theme;
+        idea.theme_token_mint = series.theme_token_mint;
+        idea.image_uris = Vec::new();
+        idea.generation_status = GenerationStatus::Pending;
+        idea.generation_deadline = clock.unix_timestamp + IMAGE_GENERATION_TIMEOUT;
+        idea.total_staked = prize;
+        idea.min_stake = MIN_TOKEN_STAKE.max(ctx.accounts.theme_account.min_stake_tokens);
+        idea.curator_fee_bps = CURATOR_FEE_BPS;
+        idea.votes = [0; 4];
+        idea.reject_all_weight = 0;
+        idea.total_voters = 0;
+        idea.voting_deadline = 0;
+        idea.curator_fee_collected = 0;
+        idea.platform_fee_collected = 0;
+        idea.penalty_pool_amount = 0;
+        idea.winner_count = 0;
+        idea.status = IdeaStatus::GeneratingImages;
+        idea.vault_bump = ctx.bumps.vault;
+        idea.idea_bump = ctx.bumps.idea;
+        idea.depin_provider = series.depin_provider;
+        idea.sponsor = Some(sponsor_key);
+        idea.initial_prize_pool = prize;
+        idea.allowlist_root = None;
+        idea.gate_mint = None;
+        idea.gate_min_amount = 0;
+        idea.cancel_reason = CANCEL_REASON_NONE;
+        idea.refund_rebates_paid = 0;
+        idea.sponsor_profile = None;
+        idea.no_loss = series.no_loss;
+        idea.series = Some(series_key);
+        idea.image_hashes = [[0; 32]; 4];
+        idea.reveal_ts = 0;
+        idea.bonus_judge = None;
+        idea.bonus_pool = 0;
+        idea.prompt_hash = anchor_lang::solana_program::keccak::hash(series.prompt.as_bytes()).to_bytes();
+        idea.payout_curve = ctx.accounts.theme_account.payout_curve;
+        idea.payout_exponent = ctx.accounts.theme_account.payout_exponent;
+        idea.payout_weights = [0; 4];
+        idea.voter_counts = [0; 4];
+        idea.stake_totals = [0; 4];
+        idea.voting_mode = VotingMode::from_u8(ctx.accounts.theme_account.voting_mode)?;
+        idea.secondary_winning_index = None;
+        idea.tie_break_rule = ctx.accounts.theme_account.tie_break_rule;
+        idea.last_vote_ts = [0; 4];
+        idea.claim_deadline = 0;
+        idea.dispute_period = ctx.accounts.theme_account.dispute_period;
+        idea.reject_all_threshold_bps = ctx.accounts.theme_account.reject_all_threshold_bps;
+        idea.penalty_burn_bps = ctx.accounts.theme_account.penalty_burn_bps;
+        idea.dispute_deadline = 0;
+        idea.disputed = false;
+        // 系列赛的策展费归赞助商
+        idea.curator = series.sponsor;
+        idea.rollover_bps = 0;
+        idea.insurance_contribution = 0;
+        idea.parent = None;
+        idea.remix_fee_bps = 0;
+        idea.remix_royalty = 0;
+        idea.collection = None;
+        idea.version = IDEA_ACCOUNT_VERSION;
+
+        // 系列赛每期按设计重跑同一 Prompt，仅登记以占用容量
+        let registry = &mut ctx.accounts.prompt_registry;
+        registry.theme = idea.theme;
+        registry.bump = ctx.bumps.prompt_registry;
+        register_prompt(registry, idea, idea.key(), clock.unix_timestamp, true)?;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.idea = idea.key();
+        vault.bump = ctx.bumps.vault;
+
+        let series = &mut ctx.accounts.series;
+        series.episodes_created += 1;
+        series.next_episode_at = clock.unix_timestamp + series.interval_secs;
+
+        emit_cpi!(SeriesEpisodeCreated {
+            series: series_key,
             idea: idea.key(),
-            voter: ctx.accounts.voter.key(),
-            image_choice: image_index,
-            stake_amount: token_amount,
+            episode,
+            initial_prize_pool: prize,
         });
 
         Ok(())
     }
 
-    /// 取消创意 (参与者不足或超时)
-    pub fn cancel_idea(ctx: Context<CancelIdea>) -> Result<()> {
-        let idea = &mut ctx.accounts.idea;
-        let clock = Clock::get()?;
+    /// 关闭系列赛并退还剩余预算 (仅赞助商)
+    #[access_control(is_account_owner(&ctx.accounts.series.sponsor, ctx.accounts.sponsor.key))]
+    pub fn close_series(ctx: Context<CloseSeries>) -> Result<()> {
+        let series = &ctx.accounts.series;
+        require!(series.active, ConsensusError::InvalidState);
+
+        let remaining = ctx.accounts.series_token_account.amount;
+        let sponsor_key = series.sponsor;
+        let series_id_bytes = series.series_id.to_le_bytes();
+        let series_seeds: &[&[u8]] = &[
+            b"series",
+            sponsor_key.as_ref(),
+            series_id_bytes.as_ref(),
+            &[series.bump],
+        ];
+
+        if remaining > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.series_token_account.to_account_info(),
+                        to: ctx.accounts.sponsor_token_account.to_account_info(),
+                        authority: ctx.accounts.series.to_account_info(),
+                    },
+                    &[series_seeds],
+                ),
+                remaining,
+            )?;
+        }
+
+        let series = &mut ctx.accounts.series;
+        series.active = false;
+
+        emit_cpi!(SeriesClosed {
+            series: series.key(),
+            episodes_created: series.episodes_created,
+            refunded: remaining,
+        });
+
+        Ok(())
+    }
+
+    /// 创建创意合集：发起者可将同一主题下的多个创意归入合集，共享元数据，
+    /// 结算时按 grand_prize_bps 注入合集大奖，合集结束后统一结算
+    #[access_control(valid_collection(&name, &metadata_uri, grand_prize_bps, duration_days))]
+    pub fn create_collection(
+        ctx: Context<CreateCollection>,
+        collection_id: u64,
+        theme: Pubkey,
+        name: String,
+        metadata_uri: String,
+        grand_prize_bps: u16,
+        duration_days: u16,
+    ) -> Result<()> {
+        let collection = &mut ctx.accounts.collection;
+        collection.initiator = ctx.accounts.initiator.key();
+        collection.collection_id = collection_id;
+        collection.theme = theme;
+        collection.theme_token_mint = ctx.accounts.theme_token_mint.key();
+        collection.name = name.clone();
+        collection.metadata_uri = metadata_uri;
+        collection.grand_prize_bps = grand_prize_bps;
+        collection.ends_at = Clock::get()?.unix_timestamp + duration_days as i64 * 24 * 3600;
+        collection.idea_count = 0;
+        collection.bump = ctx.bumps.collection;
 
-        // 只能由发起者取消，或者超时后任何人都可以取消
-        let can_cancel = ctx.accounts.authority.key() == idea.initiator
-            || clock.unix_timestamp > idea.generation_deadline + DEFAULT_VOTING_DURATION;
+        emit_cpi!(CollectionCreated {
+            collection: collection.key(),
+            initiator: collection.initiator,
+            theme,
+            name,
+            grand_prize_bps,
+            ends_at: collection.ends_at,
+        });
 
-        require!(can_cancel, ConsensusError::Unauthorized);
+        Ok(())
+    }
 
+    /// 将自己发起、尚未结算的创意归入合集 (每个创意只能属于一个合集)
+    #[access_control(is_account_owner(&ctx.accounts.collection.initiator, ctx.accounts.initiator.key))]
+    pub fn add_idea_to_collection(ctx: Context<AddIdeaToCollection>) -> Result<()> {
+        let idea = &ctx.accounts.idea;
+        let collection = &ctx.accounts.collection;
         require!(
-            idea.status == IdeaStatus::GeneratingImages || idea.status == IdeaStatus::Voting,
-            ConsensusError::InvalidState
+            idea.collection.is_none()
+                && idea.theme == collection.theme
+                && !matches!(idea.status, IdeaStatus::Completed | IdeaStatus::Cancelled),
+            ConsensusError::InvalidCollection
+        );
+        require!(
+            Clock::get()?.unix_timestamp < collection.ends_at,
+            ConsensusError::CollectionClosed
         );
 
-        idea.status = IdeaStatus::Cancelled;
+        let collection_key = collection.key();
+        ctx.accounts.idea.collection = Some(collection_key);
+        let collection = &mut ctx.accounts.collection;
+        collection.idea_count = collection.idea_count
+            .checked_add(1)
+            .ok_or(ConsensusError::Overflow)?;
 
-        emit!(IdeaCancelled {
-            idea: idea.key(),
-            reason: "Cancelled by initiator or timeout".to_string(),
+        emit_cpi!(IdeaAddedToCollection {
+            collection: collection_key,
+            idea: ctx.accounts.idea.key(),
+            idea_count: collection.idea_count,
         });
 
         Ok(())
     }
-}
 
-// -----------------------------------------------------------------------------
-// Contexts
-// -----------------------------------------------------------------------------
+    /// 创建赞助商品牌资料 (名称、Logo、链接)，供赞助竞赛引用
+    #[access_control(validate_sponsor_profile(&logo_uri, &link))]
+    pub fn create_sponsor_profile(
+        ctx: Context<CreateSponsorProfile>,
+        name: [u8; 32],
+        logo_uri: String,
+        link: String,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let profile = &mut ctx.accounts.sponsor_profile;
+        profile.sponsor = ctx.accounts.sponsor.key();
+        profile.name = name;
+        profile.logo_uri = logo_uri;
+        profile.link = link;
+        profile.created_at = clock.unix_timestamp;
+        profile.updated_at = clock.unix_timestamp;
+        profile.bump = ctx.bumps.sponsor_profile;
 
-#[derive(Accounts)]
-#[instruction(idea_id: u64, prompt: String, theme: Pubkey)]
-pub struct CreateIdea<'info> {
-    #[account(
-        init,
-        payer = initiator,
-        space = 8 + Idea::SPACE,
-        seeds = [b"idea", initiator.key().as_ref(), &idea_id.to_le_bytes()],
-        bump
-    )]
-    pub idea: Box<Account<'info, Idea>>,
+        emit_cpi!(SponsorProfileUpdated {
+            sponsor_profile: profile.key(),
+            sponsor: profile.sponsor,
+            name: profile.name,
+            logo_uri: profile.logo_uri.clone(),
+            link: profile.link.clone(),
+        });
 
-    #[account(
-        init,
-        payer = initiator,
-        space = 8 + Vault::SPACE,
-        seeds = [b"vault", idea.key().as_ref()],
-        bump
-    )]
-    pub vault: Box<Account<'info, Vault>>,
+        Ok(())
+    }
 
-    /// CHECK: Theme token mint - validated by constraint
-    #[account(
-        constraint = theme_token_mint.key() != Pubkey::default() @ ConsensusError::InvalidTheme
+    /// 更新赞助商品牌资料 (仅赞助商本人)
+    #[access_control(
+        is_account_owner(&ctx.accounts.sponsor_profile.sponsor, ctx.accounts.sponsor.key)
+        validate_sponsor_profile(&logo_uri, &link)
     )]
-    pub theme_token_mint: UncheckedAccount<'info>,
+    pub fn update_sponsor_profile(
+        ctx: Context<UpdateSponsorProfile>,
+        name: [u8; 32],
+        logo_uri: String,
+        link: String,
+    ) -> Result<()> {
+        let profile = &mut ctx.accounts.sponsor_profile;
+        profile.name = name;
+        profile.logo_uri = logo_uri;
+        profile.link = link;
+        profile.updated_at = Clock::get()?.unix_timestamp;
 
-    #[account(mut)]
-    pub initiator: Signer<'info>,
+        emit_cpi!(SponsorProfileUpdated {
+            sponsor_profile: profile.key(),
+            sponsor: profile.sponsor,
+            name: profile.name,
+            logo_uri: profile.logo_uri.clone(),
+            link: profile.link.clone(),
+        });
 
-    /// CHECK: Protocol treasury account
-    #[account(mut)]
-    pub protocol_treasury: UncheckedAccount<'info>,
+        Ok(())
+    }
 
-    pub system_program: Program<'info, System>,
-}
+    /// 赞助商托管分批奖池：图片确认、评审人数达标、结算时依次解锁
+    #[access_control(idea_in_status(ctx.accounts.idea.status, IdeaStatus::GeneratingImages))]
+    pub fn fund_sponsor_escrow(
+        ctx: Context<FundSponsorEscrow>,
+        tranches: [u64; ESCROW_MILESTONE_COUNT],
+    ) -> Result<()> {
+        let idea = &ctx.accounts.idea;
+        let total = tranches
+            .iter()
+            .try_fold(0u64, |acc, &t| acc.checked_add(t))
+            .ok_or(ConsensusError::Overflow)?;
+        require!(total >= MIN_TOKEN_STAKE, ConsensusError::StakeTooLow);
 
-#[derive(Accounts)]
-#[instruction(idea_id: u64, prompt: String, theme: Pubkey)]
-pub struct CreateSponsoredIdea<'info> {
-    #[account(
-        init,
-        payer = initiator,
-        space = 8 + Idea::SPACE,
-        seeds = [b"idea", initiator.key().as_ref(), &idea_id.to_le_bytes()],
-        bump
-    )]
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.sponsor_token_account.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.sponsor.to_account_info(),
+                },
+            ),
+            total,
+        )?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.idea = idea.key();
+        escrow.sponsor = ctx.accounts.sponsor.key();
+        escrow.token_mint = idea.theme_token_mint;
+        escrow.tranches = tranches;
+        escrow.released_mask = 0;
+        escrow.refunded = false;
+        escrow.bump = ctx.bumps.escrow;
+
+        emit_cpi!(EscrowFunded {
+            idea: escrow.idea,
+            sponsor: escrow.sponsor,
+            tranches,
+        });
+
+        Ok(())
+    }
+
+    /// 里程碑达成后释放对应分批到奖池 (任何人可调用)
+    /// 奖池只能在结算前增加，结算里程碑在投票截止后、结算前解锁
+    #[access_control(idea_in_status(ctx.accounts.idea.status, IdeaStatus::Voting))]
+    pub fn release_escrow_tranche(ctx: Context<ReleaseEscrowTranche>, milestone: u8) -> Result<()> {
+        require!(
+            (milestone as usize) < ESCROW_MILESTONE_COUNT,
+            ConsensusError::InvalidAmount
+        );
+
+        let escrow = &ctx.accounts.escrow;
+        require!(!escrow.refunded, ConsensusError::InvalidState);
+        require!(
+            escrow.released_mask & (1 << milestone) == 0,
+            ConsensusError::MilestoneAlreadyReleased
+        );
+
+        let idea = &ctx.accounts.idea;
+        let reached = match milestone {
+            ESCROW_MILESTONE_IMAGES_CONFIRMED => idea.generation_status == GenerationStatus::Completed,
+            ESCROW_MILESTONE_MIN_REVIEWERS => idea.total_voters >= MIN_REVIEWERS,
+            _ => Clock::get()?.unix_timestamp >= idea.voting_deadline,
+        };
+        require!(reached, ConsensusError::MilestoneNotReached);
+
+        let amount = escrow.tranches[milestone as usize];
+        let idea_key = idea.key();
+        let escrow_seeds: &[&[u8]] = &[b"sponsor_escrow", idea_key.as_ref(), &[escrow.bump]];
+
+        if amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.escrow_token_account.to_account_info(),
+                        to: ctx.accounts.vault_token_account.to_account_info(),
+                        authority: ctx.accounts.escrow.to_account_info(),
+                    },
+                    &[escrow_seeds],
+                ),
+                amount,
+            )?;
+        }
+
+        let idea = &mut ctx.accounts.idea;
+        idea.total_staked = idea.total_staked
+            .checked_add(amount)
+            .ok_or(ConsensusError::Overflow)?;
+        idea.initial_prize_pool = idea.initial_prize_pool
+            .checked_add(amount)
+            .ok_or(ConsensusError::Overflow)?;
+        ctx.accounts.escrow.released_mask |= 1 << milestone;
+
+        emit_cpi!(EscrowTrancheReleased {
+            idea: idea_key,
+            milestone,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// 创意结束后将未达成里程碑的托管资金退还赞助商 (任何人可调用)
+    #[access_control(idea_closed(ctx.accounts.idea.status))]
+    pub fn refund_sponsor_escrow(ctx: Context<RefundSponsorEscrow>) -> Result<()> {
+        let idea = &ctx.accounts.idea;
+        let escrow = &ctx.accounts.escrow;
+        require!(!escrow.refunded, ConsensusError::AlreadyWithdrawn);
+
+        let amount = ctx.accounts.escrow_token_account.amount;
+        let idea_key = idea.key();
+        let escrow_seeds: &[&[u8]] = &[b"sponsor_escrow", idea_key.as_ref(), &[escrow.bump]];
+
+        if amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.escrow_token_account.to_account_info(),
+                        to: ctx.accounts.sponsor_token_account.to_account_info(),
+                        authority: ctx.accounts.escrow.to_account_info(),
+                    },
+                    &[escrow_seeds],
+                ),
+                amount,
+            )?;
+        }
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.refunded = true;
+
+        emit_cpi!(EscrowRefunded {
+            idea: idea_key,
+            sponsor: escrow.sponsor,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// 设置赞助竞赛的投票门槛 (仅赞助商，图片生成阶段可修改)
+    /// allowlist_root: 评审白名单 Merkle 根；gate_mint: 要求持有的代币/NFT
+    #[access_control(idea_in_status(ctx.accounts.idea.status, IdeaStatus::GeneratingImages))]
+    pub fn set_vote_gate(
+        ctx: Context<SetVoteGate>,
+        allowlist_root: Option<[u8; 32]>,
+        gate_mint: Option<Pubkey>,
+        gate_min_amount: u64,
+    ) -> Result<()> {
+        let idea = &mut ctx.accounts.idea;
+        idea.allowlist_root = allowlist_root;
+        idea.gate_mint = gate_mint;
+        idea.gate_min_amount = gate_min_amount;
+
+        emit_cpi!(VoteGateUpdated {
+            idea: idea.key(),
+            allowlist_root,
+            gate_mint,
+            gate_min_amount,
+        });
+
+        Ok(())
+    }
+
+    /// 赞助商指定加奖评委并注入加奖资金 (结算前，仅一次)
+    /// 结算后评委可将加奖授予任一图片的投票者，与投票结果无关
+    #[access_control(idea_open(ctx.accounts.idea.status))]
+    pub fn set_bonus_judge(
+        ctx: Context<SetBonusJudge>,
+        judge: Pubkey,
+        bonus_amount: u64,
+    ) -> Result<()> {
+        let idea = &ctx.accounts.idea;
+        require!(idea.bonus_judge.is_none(), ConsensusError::InvalidState);
+        require!(bonus_amount > 0, ConsensusError::InvalidAmount);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.sponsor_token_account.to_account_info(),
+                    to: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: ctx.accounts.sponsor.to_account_info(),
+                },
+            ),
+            bonus_amount,
+        )?;
+
+        let idea = &mut ctx.accounts.idea;
+        idea.bonus_judge = Some(judge);
+        idea.bonus_pool = bonus_amount;
+
+        emit_cpi!(BonusJudgeSet {
+            idea: idea.key(),
+            judge,
+            bonus_amount,
+        });
+
+        Ok(())
+    }
+
+    /// 确认图片生成完成 (由授权的链下服务调用)
+    #[access_control(
+        is_assigned_provider(&ctx.accounts.roles.depin_admin, ctx.accounts.depin_authority.key)
+        idea_in_status(ctx.accounts.idea.status, IdeaStatus::GeneratingImages)
+    )]
+    pub fn confirm_images(
+        ctx: Context<ConfirmImages>,
+        image_uris: Vec<String>,
+    ) -> Result<()> {
+        let idea = &mut ctx.accounts.idea;
+        require!(image_uris.len() == 4, ConsensusError::InvalidImageCount);
+
+        // 验证 URI 长度与存储前缀
+        let allowed_prefixes = &ctx.accounts.image_storage_config.allowed_prefixes;
+        for uri in &image_uris {
+            require!(
+                uri.len() > 0 && uri.len() <= MAX_IMAGE_URI_LEN,
+                ConsensusError::InvalidImageUri
+            );
+            image_uri_allowed(uri, allowed_prefixes)?;
+        }
+
+        // 与两阶段揭晓一致记录 URI 哈希，作为获胜图片 NFT 的来源凭证
+        for (hash, uri) in idea.image_hashes.iter_mut().zip(image_uris.iter()) {
+            *hash = anchor_lang::solana_program::keccak::hash(uri.as_bytes()).to_bytes();
+        }
+        idea.image_uris = image_uris.clone();
+        idea.generation_status = GenerationStatus::Completed;
+        idea.status = IdeaStatus::Voting;
+
+        let clock = Clock::get()?;
+        idea.voting_deadline = clock.unix_timestamp + DEFAULT_VOTING_DURATION;
+        sync_prompt_deadline(&mut ctx.accounts.prompt_registry, idea, idea.key());
+
+        emit_cpi!(ImagesGenerated {
+            idea: idea.key(),
+            image_uris,
+        });
+
+        Ok(())
+    }
+
+    /// 两阶段揭晓 (第一步)：DePIN 仅提交图片 URI 的哈希，并约定统一揭晓时间
+    #[access_control(
+        is_assigned_provider(&ctx.accounts.roles.depin_admin, ctx.accounts.depin_authority.key)
+        idea_in_status(ctx.accounts.idea.status, IdeaStatus::GeneratingImages)
+    )]
+    pub fn commit_images(
+        ctx: Context<ConfirmImages>,
+        image_hashes: [[u8; 32]; 4],
+        reveal_ts: i64,
+    ) -> Result<()> {
+        let idea = &mut ctx.accounts.idea;
+
+        let clock = Clock::get()?;
+        require!(
+            reveal_ts > clock.unix_timestamp
+                && reveal_ts <= clock.unix_timestamp + MAX_REVEAL_DELAY,
+            ConsensusError::InvalidRevealTime
+        );
+
+        idea.image_hashes = image_hashes;
+        idea.reveal_ts = reveal_ts;
+        idea.generation_status = GenerationStatus::Completed;
+        idea.status = IdeaStatus::AwaitingReveal;
+
+        emit_cpi!(ImagesCommitted {
+            idea: idea.key(),
+            image_hashes,
+            reveal_ts,
+        });
+
+        Ok(())
+    }
+
+    /// 两阶段揭晓 (第二步)：到达 reveal_ts 后公布 URI，投票同时开启
+    /// URI 由哈希约束，DePIN 或任意 keeper 均可调用
+    #[access_control(idea_in_status(ctx.accounts.idea.status, IdeaStatus::AwaitingReveal))]
+    pub fn reveal_images(ctx: Context<RevealImages>, image_uris: Vec<String>) -> Result<()> {
+        let idea = &mut ctx.accounts.idea;
+        require!(image_uris.len() == 4, ConsensusError::InvalidImageCount);
+
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= idea.reveal_ts,
+            ConsensusError::RevealNotReady
+        );
+
+        for (uri, hash) in image_uris.iter().zip(idea.image_hashes.iter()) {
+            require!(
+                !uri.is_empty() && uri.len() <= MAX_IMAGE_URI_LEN,
+                ConsensusError::InvalidImageUri
+            );
+            image_uri_allowed(uri, &ctx.accounts.image_storage_config.allowed_prefixes)?;
+            require!(
+                anchor_lang::solana_program::keccak::hash(uri.as_bytes()).to_bytes() == *hash,
+                ConsensusError::RevealHashMismatch
+            );
+        }
+
+        idea.image_uris = image_uris.clone();
+        idea.status = IdeaStatus::Voting;
+        idea.voting_deadline = clock.unix_timestamp + DEFAULT_VOTING_DURATION;
+        sync_prompt_deadline(&mut ctx.accounts.prompt_registry, idea, idea.key());
+
+        emit_cpi!(ImagesGenerated {
+            idea: idea.key(),
+            image_uris,
+        });
+
+        Ok(())
+    }
+
+    /// 质押并投票选择图片 (使用主题代币质押)
+    /// 门槛竞赛中：allowlist_proof 为白名单证明，持币凭证通过 remaining_accounts[0] 传入
+    #[access_control(
+        protocol_active(ctx.accounts.protocol_config.paused)
+        voting_open(ctx.accounts.idea.status, ctx.accounts.idea.voting_deadline)
+    )]
+    pub fn vote_for_image(
+        ctx: Context<VoteForImage>,
+        image_index: u8,
+        token_amount: u64,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        // 先做不依赖账户数据的校验，再对 Idea 只取一次可变借用贯穿整个投票路径
+        require!(
+            image_index < 4 || image_index == 255,
+            ConsensusError::InvalidImageIndex
+        );
+        let voter = ctx.accounts.voter.key();
+        let emitter = event_emitter!(ctx);
+        let idea = &mut ctx.accounts.idea;
+        require!(token_amount >= idea.min_stake, ConsensusError::StakeTooLow);
+
+        check_vote_gate(idea, &voter, &allowlist_proof, ctx.remaining_accounts)?;
+        let weight_multiplier_bps = voter_multiplier_bps(ctx.accounts.reputation.as_ref(), &voter)?;
+
+        // 转移代币质押到 vault（使用 SPL Token）
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.voter_token_account.to_account_info(),
+                    to: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: ctx.accounts.voter.to_account_info(),
+                },
+            ),
+            token_amount,
+        )?;
+
+        record_vote(
+            &emitter,
+            idea,
+            &mut ctx.accounts.vote,
+            &mut ctx.accounts.reviewer_stake,
+            voter,
+            image_index,
+            token_amount,
+            weight_multiplier_bps,
+            ctx.bumps.reviewer_stake,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    /// 一笔交易内用 SOL 买入主题代币并全部质押投票
+    /// 买入通过 CPI 调用主题代币程序完成，质押数量为投票者代币账户的余额增量
+    #[access_control(
+        protocol_active(ctx.accounts.protocol_config.paused)
+        voting_open(ctx.accounts.idea.status, ctx.accounts.idea.voting_deadline)
+    )]
+    pub fn buy_and_vote(
+        ctx: Context<BuyAndVote>,
+        image_index: u8,
+        sol_amount: u64,
+        min_tokens_out: u64,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(
+            image_index < 4 || image_index == 255,
+            ConsensusError::InvalidImageIndex
+        );
+
+        check_vote_gate(
+            &ctx.accounts.idea,
+            &ctx.accounts.voter.key(),
+            &allowlist_proof,
+            ctx.remaining_accounts,
+        )?;
+
+        let tokens_before = read_token_account(&ctx.accounts.voter_token_account)?
+            .map_or(0, |account| account.amount);
+
+        taste_fun_token::cpi::swap_sol_for_tokens(
+            CpiContext::new(
+                ctx.accounts.theme_program.to_account_info(),
+                taste_fun_token::cpi::accounts::SwapSolForTokens {
+                    theme: ctx.accounts.theme.to_account_info(),
+                    vault: ctx.accounts.theme_vault.to_account_info(),
+                    token_mint: ctx.accounts.token_mint.to_account_info(),
+                    vault_token_account: ctx.accounts.theme_vault_token_account.to_account_info(),
+                    user_token_account: ctx.accounts.voter_token_account.to_account_info(),
+                    trading_config: ctx.accounts.trading_config.to_account_info(),
+                    protocol_config: ctx.accounts.protocol_config.to_account_info(),
+                    price_oracle: ctx.accounts.price_oracle.to_account_info(),
+                    referral: None,
+                    launch_limit: ctx.accounts.launch_limit.as_ref().map(|a| a.to_account_info()),
+                    trader_state: ctx.accounts.trader_state.as_ref().map(|a| a.to_account_info()),
+                    user_wsol_account: None,
+                    wsol_mint: None,
+                    vault_wsol_account: None,
+                    user: ctx.accounts.voter.to_account_info(),
+                    vault_sol_account: ctx.accounts.vault_sol_account.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                    associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    event_authority: ctx.accounts.theme_event_authority.to_account_info(),
+                    program: ctx.accounts.theme_program.to_account_info(),
+                },
+            ),
+            sol_amount,
+            min_tokens_out,
+            None,
+        )?;
+
+        // 买入后校验投票者 ATA 并以余额增量作为质押数量
+        let voter_token_account = read_token_account(&ctx.accounts.voter_token_account)?
+            .ok_or(ConsensusError::InvalidMint)?;
+        require_keys_eq!(voter_token_account.mint, ctx.accounts.idea.theme_token_mint, ConsensusError::InvalidMint);
+        require_keys_eq!(voter_token_account.owner, ctx.accounts.voter.key(), ConsensusError::Unauthorized);
+        let token_amount = voter_token_account.amount
+            .checked_sub(tokens_before)
+            .ok_or(ConsensusError::Overflow)?;
+        require!(token_amount >= ctx.accounts.idea.min_stake, ConsensusError::StakeTooLow);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.voter_token_account.to_account_info(),
+                    to: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: ctx.accounts.voter.to_account_info(),
+                },
+            ),
+            token_amount,
+        )?;
+
+        record_vote(
+            &event_emitter!(ctx),
+            &mut ctx.accounts.idea,
+            &mut ctx.accounts.vote,
+            &mut ctx.accounts.reviewer_stake,
+            ctx.accounts.voter.key(),
+            image_index,
+            token_amount,
+            voter_multiplier_bps(ctx.accounts.reputation.as_ref(), &ctx.accounts.voter.key())?,
+            ctx.bumps.reviewer_stake,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    /// 结算程序 CPI: 奖金已由结算程序直接转入本创意金库，此处仅记录投票
+    #[access_control(voting_open(ctx.accounts.idea.status, ctx.accounts.idea.voting_deadline))]
+    pub fn restake_vote(
+        ctx: Context<RestakeVote>,
+        image_index: u8,
+        token_amount: u64,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let idea = &ctx.accounts.idea;
+        require!(
+            image_index < 4 || image_index == 255,
+            ConsensusError::InvalidImageIndex
+        );
+        require!(token_amount >= idea.min_stake, ConsensusError::StakeTooLow);
+
+        check_vote_gate(
+            idea,
+            &ctx.accounts.voter.key(),
+            &allowlist_proof,
+            ctx.remaining_accounts,
+        )?;
+
+        record_vote(
+            &event_emitter!(ctx),
+            &mut ctx.accounts.idea,
+            &mut ctx.accounts.vote,
+            &mut ctx.accounts.reviewer_stake,
+            ctx.accounts.voter.key(),
+            image_index,
+            token_amount,
+            BPS_DENOMINATOR,
+            ctx.bumps.reviewer_stake,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    /// 结算程序 CPI: 从创意金库转出代币 (金库 PDA 归本程序所有，结算程序无法直接签名)
+    pub fn vault_transfer(ctx: Context<VaultTransfer>, amount: u64) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let vault_seeds: &[&[u8]] = &[b"vault", vault.idea.as_ref(), &[vault.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            amount,
+        )
+    }
+
+    /// 结算程序 CPI: 销毁金库中的代币 (惩罚池销毁份额)
+    pub fn vault_burn(ctx: Context<VaultBurn>, amount: u64) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let vault_seeds: &[&[u8]] = &[b"vault", vault.idea.as_ref(), &[vault.bump]];
+        token::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.token_mint.to_account_info(),
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            amount,
+        )
+    }
+
+    /// 结算程序 CPI: 关闭已清空的金库代币账户与金库，租金退回接收方
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let vault_seeds: &[&[u8]] = &[b"vault", vault.idea.as_ref(), &[vault.bump]];
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.vault_token_account.to_account_info(),
+                destination: ctx.accounts.receiver.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ))
+    }
+
+    /// 结算程序 CPI: 写回结算程序计算出的 Idea 结算字段
+    pub fn apply_settlement(ctx: Context<ApplySettlement>, update: IdeaSettlement) -> Result<()> {
+        update.apply(&mut ctx.accounts.idea);
+        Ok(())
+    }
+
+    /// 结算程序 CPI: 记录评审的领取结果 (is_winner 兼作已领取标记)
+    pub fn settle_reviewer_stake(
+        ctx: Context<SettleReviewerStake>,
+        is_winner: bool,
+        winnings: u64,
+    ) -> Result<()> {
+        let reviewer_stake = &mut ctx.accounts.reviewer_stake;
+        reviewer_stake.is_winner = is_winner;
+        reviewer_stake.winnings = winnings;
+        Ok(())
+    }
+
+    /// 将主题代币托管给策展人代为投票 (首次委托时设定策展人分成，追加时不可修改)
+    #[access_control(protocol_active(ctx.accounts.protocol_config.paused))]
+    pub fn delegate_stake(
+        ctx: Context<DelegateStake>,
+        amount: u64,
+        curator_cut_bps: u16,
+    ) -> Result<()> {
+        require!(amount > 0, ConsensusError::InvalidAmount);
+        require!(
+            curator_cut_bps <= MAX_DELEGATION_CUT_BPS,
+            ConsensusError::DelegationCutTooHigh
+        );
+        require_keys_neq!(
+            ctx.accounts.delegator.key(),
+            ctx.accounts.curator.key(),
+            ConsensusError::InvalidDelegation
+        );
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.delegator_token_account.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.delegator.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let delegation = &mut ctx.accounts.delegation;
+        if delegation.delegator == Pubkey::default() {
+            delegation.delegator = ctx.accounts.delegator.key();
+            delegation.curator = ctx.accounts.curator.key();
+            delegation.theme_token_mint = ctx.accounts.theme_token_mint.key();
+            delegation.curator_cut_bps = curator_cut_bps;
+            delegation.bump = ctx.bumps.delegation;
+        } else {
+            require!(
+                delegation.curator_cut_bps == curator_cut_bps,
+                ConsensusError::InvalidDelegation
+            );
+        }
+        delegation.amount = delegation.amount
+            .checked_add(amount)
+            .ok_or(ConsensusError::Overflow)?;
+
+        emit_cpi!(StakeDelegated {
+            delegation: delegation.key(),
+            delegator: delegation.delegator,
+            curator: delegation.curator,
+            amount,
+            curator_cut_bps,
+        });
+
+        Ok(())
+    }
+
+    /// 委托人取回尚未被策展人用于投票的托管代币
+    #[access_control(
+        is_account_owner(&ctx.accounts.delegation.delegator, ctx.accounts.delegator.key)
+        amount_within(amount, ctx.accounts.delegation.amount)
+    )]
+    pub fn undelegate_stake(ctx: Context<UndelegateStake>, amount: u64) -> Result<()> {
+        let delegation = &ctx.accounts.delegation;
+        let delegation_seeds: &[&[u8]] = &[
+            b"delegation",
+            delegation.delegator.as_ref(),
+            delegation.curator.as_ref(),
+            delegation.theme_token_mint.as_ref(),
+            &[delegation.bump],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.delegator_token_account.to_account_info(),
+                    authority: ctx.accounts.delegation.to_account_info(),
+                },
+                &[delegation_seeds],
+            ),
+            amount,
+        )?;
+
+        let delegation = &mut ctx.accounts.delegation;
+        delegation.amount -= amount;
+
+        emit_cpi!(StakeUndelegated {
+            delegation: delegation.key(),
+            delegator: delegation.delegator,
+            amount,
+            remaining: delegation.amount,
+        });
+
+        Ok(())
+    }
+
+    /// 策展人使用委托托管的代币投票，投票与质押记录以委托 PDA 为投票者
+    /// 门槛与声誉加成按策展人本人计算，收益在结算程序中按分成返还委托人
+    #[access_control(
+        protocol_active(ctx.accounts.protocol_config.paused)
+        voting_open(ctx.accounts.idea.status, ctx.accounts.idea.voting_deadline)
+    )]
+    pub fn vote_as_delegate(
+        ctx: Context<VoteAsDelegate>,
+        image_index: u8,
+        token_amount: u64,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let idea = &ctx.accounts.idea;
+        require!(
+            image_index < 4 || image_index == 255,
+            ConsensusError::InvalidImageIndex
+        );
+        require!(token_amount >= idea.min_stake, ConsensusError::StakeTooLow);
+
+        let delegation = &ctx.accounts.delegation;
+        require!(token_amount <= delegation.amount, ConsensusError::InvalidAmount);
+
+        check_vote_gate(
+            idea,
+            &ctx.accounts.curator.key(),
+            &allowlist_proof,
+            ctx.remaining_accounts,
+        )?;
+
+        let delegation_seeds: &[&[u8]] = &[
+            b"delegation",
+            delegation.delegator.as_ref(),
+            delegation.curator.as_ref(),
+            delegation.theme_token_mint.as_ref(),
+            &[delegation.bump],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: ctx.accounts.delegation.to_account_info(),
+                },
+                &[delegation_seeds],
+            ),
+            token_amount,
+        )?;
+
+        let delegation = &mut ctx.accounts.delegation;
+        delegation.amount -= token_amount;
+        let delegation_key = delegation.key();
+
+        record_vote(
+            &event_emitter!(ctx),
+            &mut ctx.accounts.idea,
+            &mut ctx.accounts.vote,
+            &mut ctx.accounts.reviewer_stake,
+            delegation_key,
+            image_index,
+            token_amount,
+            voter_multiplier_bps(ctx.accounts.reputation.as_ref(), &ctx.accounts.curator.key())?,
+            ctx.bumps.reviewer_stake,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        emit_cpi!(DelegatedVoteCast {
+            idea: ctx.accounts.idea.key(),
+            delegation: delegation_key,
+            curator: ctx.accounts.curator.key(),
+            stake_amount: token_amount,
+        });
+
+        Ok(())
+    }
+
+    /// 创建战队：成员存入主题代币换取份额，由队长以战队身份投票
+    #[access_control(protocol_active(ctx.accounts.protocol_config.paused))]
+    pub fn create_squad(ctx: Context<CreateSquad>, squad_id: u64) -> Result<()> {
+        let squad = &mut ctx.accounts.squad;
+        squad.captain = ctx.accounts.captain.key();
+        squad.squad_id = squad_id;
+        squad.theme_token_mint = ctx.accounts.theme_token_mint.key();
+        squad.total_shares = 0;
+        squad.active_stake = 0;
+        squad.member_count = 0;
+        squad.bump = ctx.bumps.squad;
+
+        emit_cpi!(SquadCreated {
+            squad: squad.key(),
+            captain: squad.captain,
+            theme_token_mint: squad.theme_token_mint,
+        });
+
+        Ok(())
+    }
+
+    /// 成员存入主题代币，按战队净资产 (托管余额 + 投票中的本金) 折算份额
+    #[access_control(protocol_active(ctx.accounts.protocol_config.paused))]
+    pub fn deposit_to_squad(ctx: Context<DepositToSquad>, amount: u64) -> Result<()> {
+        require!(amount > 0, ConsensusError::InvalidAmount);
+
+        let squad = &ctx.accounts.squad;
+        let assets = ctx.accounts.escrow_token_account.amount
+            .checked_add(squad.active_stake)
+            .ok_or(ConsensusError::Overflow)?;
+        let shares = if squad.total_shares == 0 || assets == 0 {
+            amount
+        } else {
+            (amount as u128)
+                .checked_mul(squad.total_shares as u128)
+                .and_then(|x| x.checked_div(assets as u128))
+                .and_then(|x| u64::try_from(x).ok())
+                .ok_or(ConsensusError::Overflow)?
+        };
+        require!(shares > 0, ConsensusError::InvalidAmount);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.member_token_account.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.member.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let squad_key = ctx.accounts.squad.key();
+        let member = &mut ctx.accounts.squad_member;
+        if member.squad == Pubkey::default() {
+            member.squad = squad_key;
+            member.member = ctx.accounts.member.key();
+            member.bump = ctx.bumps.squad_member;
+            ctx.accounts.squad.member_count = ctx.accounts.squad.member_count
+                .checked_add(1)
+                .ok_or(ConsensusError::Overflow)?;
+        }
+        member.shares = member.shares
+            .checked_add(shares)
+            .ok_or(ConsensusError::Overflow)?;
+        let squad = &mut ctx.accounts.squad;
+        squad.total_shares = squad.total_shares
+            .checked_add(shares)
+            .ok_or(ConsensusError::Overflow)?;
+
+        emit_cpi!(SquadDeposited {
+            squad: squad_key,
+            member: member.member,
+            amount,
+            shares,
+        });
+
+        Ok(())
+    }
+
+    /// 成员赎回份额，按比例取回战队净资产 (只能动用未投出的托管余额)
+    #[access_control(
+        is_account_owner(&ctx.accounts.squad_member.member, ctx.accounts.member.key)
+        amount_within(shares, ctx.accounts.squad_member.shares)
+    )]
+    pub fn withdraw_from_squad(ctx: Context<WithdrawFromSquad>, shares: u64) -> Result<()> {
+        let squad = &ctx.accounts.squad;
+        let idle = ctx.accounts.escrow_token_account.amount;
+        let amount = (shares as u128)
+            .checked_mul(idle.checked_add(squad.active_stake).ok_or(ConsensusError::Overflow)? as u128)
+            .and_then(|x| x.checked_div(squad.total_shares as u128))
+            .and_then(|x| u64::try_from(x).ok())
+            .ok_or(ConsensusError::Overflow)?;
+        require!(amount <= idle, ConsensusError::InsufficientReserves);
+
+        let squad_id = squad.squad_id.to_le_bytes();
+        let squad_seeds: &[&[u8]] = &[
+            b"squad",
+            squad.captain.as_ref(),
+            squad_id.as_ref(),
+            &[squad.bump],
+        ];
+        if amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.escrow_token_account.to_account_info(),
+                        to: ctx.accounts.member_token_account.to_account_info(),
+                        authority: ctx.accounts.squad.to_account_info(),
+                    },
+                    &[squad_seeds],
+                ),
+                amount,
+            )?;
+        }
+
+        ctx.accounts.squad_member.shares -= shares;
+        let squad = &mut ctx.accounts.squad;
+        squad.total_shares -= shares;
+
+        emit_cpi!(SquadWithdrawn {
+            squad: squad.key(),
+            member: ctx.accounts.member.key(),
+            amount,
+            shares,
+        });
+
+        Ok(())
+    }
+
+    /// 队长以战队身份投票，投票与质押记录以战队 PDA 为投票者，声誉加成按战队记录计算
+    #[access_control(
+        protocol_active(ctx.accounts.protocol_config.paused)
+        voting_open(ctx.accounts.idea.status, ctx.accounts.idea.voting_deadline)
+    )]
+    pub fn squad_vote(
+        ctx: Context<SquadVote>,
+        image_index: u8,
+        token_amount: u64,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let idea = &ctx.accounts.idea;
+        require!(
+            image_index < 4 || image_index == 255,
+            ConsensusError::InvalidImageIndex
+        );
+        require!(token_amount >= idea.min_stake, ConsensusError::StakeTooLow);
+
+        check_vote_gate(
+            idea,
+            &ctx.accounts.captain.key(),
+            &allowlist_proof,
+            ctx.remaining_accounts,
+        )?;
+
+        let squad = &ctx.accounts.squad;
+        let squad_id = squad.squad_id.to_le_bytes();
+        let squad_seeds: &[&[u8]] = &[
+            b"squad",
+            squad.captain.as_ref(),
+            squad_id.as_ref(),
+            &[squad.bump],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: ctx.accounts.squad.to_account_info(),
+                },
+                &[squad_seeds],
+            ),
+            token_amount,
+        )?;
+
+        let squad = &mut ctx.accounts.squad;
+        squad.active_stake = squad.active_stake
+            .checked_add(token_amount)
+            .ok_or(ConsensusError::Overflow)?;
+        let squad_key = squad.key();
+
+        record_vote(
+            &event_emitter!(ctx),
+            &mut ctx.accounts.idea,
+            &mut ctx.accounts.vote,
+            &mut ctx.accounts.reviewer_stake,
+            squad_key,
+            image_index,
+            token_amount,
+            voter_multiplier_bps(ctx.accounts.reputation.as_ref(), &squad_key)?,
+            ctx.bumps.reviewer_stake,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    /// 结算程序 CPI: 战队投票结算后，所得已转回战队托管账户，此处释放投票中的本金记录
+    #[access_control(amount_within(amount, ctx.accounts.squad.active_stake))]
+    pub fn release_squad_stake(ctx: Context<ReleaseSquadStake>, amount: u64) -> Result<()> {
+        let squad = &mut ctx.accounts.squad;
+        squad.active_stake = squad.active_stake
+            .checked_sub(amount)
+            .ok_or(ConsensusError::Overflow)?;
+        Ok(())
+    }
+
+    /// 关注策展人：设定获胜小费与每次跟投数量，跟投余额预存在关注 PDA 的托管账户中
+    #[access_control(valid_follow(tip_bps, ctx.accounts.follower.key, ctx.accounts.curator.key))]
+    pub fn follow_curator(
+        ctx: Context<FollowCurator>,
+        tip_bps: u16,
+        mirror_amount: u64,
+        auto_mirror: bool,
+    ) -> Result<()> {
+
+        let follow = &mut ctx.accounts.follow;
+        follow.follower = ctx.accounts.follower.key();
+        follow.curator = ctx.accounts.curator.key();
+        follow.theme_token_mint = ctx.accounts.theme_token_mint.key();
+        follow.mirror_amount = mirror_amount;
+        follow.tip_bps = tip_bps;
+        follow.auto_mirror = auto_mirror;
+        follow.bump = ctx.bumps.follow;
+
+        emit_cpi!(CuratorFollowed {
+            follow: follow.key(),
+            follower: follow.follower,
+            curator: follow.curator,
+            tip_bps,
+        });
+
+        Ok(())
+    }
+
+    /// 关注者调整跟投数量与自动跟投开关 (小费比例在关注时确定，不可修改)
+    #[access_control(is_account_owner(&ctx.accounts.follow.follower, ctx.accounts.follower.key))]
+    pub fn set_follow_mirror(
+        ctx: Context<UpdateFollow>,
+        mirror_amount: u64,
+        auto_mirror: bool,
+    ) -> Result<()> {
+        let follow = &mut ctx.accounts.follow;
+        follow.mirror_amount = mirror_amount;
+        follow.auto_mirror = auto_mirror;
+
+        emit_cpi!(FollowMirrorUpdated {
+            follow: follow.key(),
+            mirror_amount,
+            auto_mirror,
+        });
+
+        Ok(())
+    }
+
+    /// 关注者存入跟投余额
+    #[access_control(is_account_owner(&ctx.accounts.follow.follower, ctx.accounts.follower.key))]
+    pub fn fund_follow(ctx: Context<FundFollow>, amount: u64) -> Result<()> {
+        require!(amount > 0, ConsensusError::InvalidAmount);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.follower_token_account.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.follower.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        emit_cpi!(FollowBalanceChanged {
+            follow: ctx.accounts.follow.key(),
+            deposit: true,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// 关注者取回未用于跟投的余额
+    #[access_control(
+        is_account_owner(&ctx.accounts.follow.follower, ctx.accounts.follower.key)
+        amount_within(amount, ctx.accounts.escrow_token_account.amount)
+    )]
+    pub fn withdraw_follow(ctx: Context<FundFollow>, amount: u64) -> Result<()> {
+
+        let follow = &ctx.accounts.follow;
+        let follow_seeds: &[&[u8]] = &[
+            b"follow",
+            follow.follower.as_ref(),
+            follow.curator.as_ref(),
+            follow.theme_token_mint.as_ref(),
+            &[follow.bump],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.follower_token_account.to_account_info(),
+                    authority: ctx.accounts.follow.to_account_info(),
+                },
+                &[follow_seeds],
+            ),
+            amount,
+        )?;
+
+        emit_cpi!(FollowBalanceChanged {
+            follow: follow.key(),
+            deposit: false,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// keeper 为开启自动跟投的关注者复制策展人在该创意上的投票 (任何人可调用)
+    /// 投票与质押记录以关注 PDA 为投票者，准入条件按关注者本人校验
+    #[access_control(
+        protocol_active(ctx.accounts.protocol_config.paused)
+        voting_open(ctx.accounts.idea.status, ctx.accounts.idea.voting_deadline)
+    )]
+    pub fn mirror_vote(ctx: Context<MirrorVote>, allowlist_proof: Vec<[u8; 32]>) -> Result<()> {
+        let idea = &ctx.accounts.idea;
+        let follow = &ctx.accounts.follow;
+        require!(follow.auto_mirror, ConsensusError::AutoMirrorDisabled);
+
+        let token_amount = follow.mirror_amount;
+        require!(token_amount >= idea.min_stake, ConsensusError::StakeTooLow);
+        require!(
+            token_amount <= ctx.accounts.escrow_token_account.amount,
+            ConsensusError::InvalidAmount
+        );
+
+        check_vote_gate(
+            idea,
+            &follow.follower,
+            &allowlist_proof,
+            ctx.remaining_accounts,
+        )?;
+
+        let follow_seeds: &[&[u8]] = &[
+            b"follow",
+            follow.follower.as_ref(),
+            follow.curator.as_ref(),
+            follow.theme_token_mint.as_ref(),
+            &[follow.bump],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: ctx.accounts.follow.to_account_info(),
+                },
+                &[follow_seeds],
+            ),
+            token_amount,
+        )?;
+
+        record_vote(
+            &event_emitter!(ctx),
+            &mut ctx.accounts.idea,
+            &mut ctx.accounts.vote,
+            &mut ctx.accounts.reviewer_stake,
+            ctx.accounts.follow.key(),
+            ctx.accounts.curator_vote.image_choice,
+            token_amount,
+            BPS_DENOMINATOR,
+            ctx.bumps.reviewer_stake,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    /// 发表带押金的评论 (正文存链下，链上记录内容哈希与 URI)，结算前均可发表
+    #[access_control(idea_open(ctx.accounts.idea.status))]
+    pub fn post_comment(
+        ctx: Context<PostComment>,
+        comment_id: u32,
+        content_hash: [u8; 32],
+        uri: String,
+        deposit: u64,
+    ) -> Result<()> {
+        let idea = &ctx.accounts.idea;
+        require!(
+            uri.len() <= MAX_IMAGE_URI_LEN && content_hash != [0; 32],
+            ConsensusError::InvalidComment
+        );
+        require!(deposit >= MIN_COMMENT_DEPOSIT, ConsensusError::StakeTooLow);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.author_token_account.to_account_info(),
+                    to: ctx.accounts.deposit_token_account.to_account_info(),
+                    authority: ctx.accounts.author.to_account_info(),
+                },
+            ),
+            deposit,
+        )?;
+
+        let comment = &mut ctx.accounts.comment;
+        comment.idea = idea.key();
+        comment.author = ctx.accounts.author.key();
+        comment.comment_id = comment_id;
+        comment.content_hash = content_hash;
+        comment.uri = uri.clone();
+        comment.deposit = deposit;
+        comment.created_at = Clock::get()?.unix_timestamp;
+        comment.bump = ctx.bumps.comment;
+
+        emit_cpi!(CommentPosted {
+            idea: comment.idea,
+            comment: comment.key(),
+            author: comment.author,
+            content_hash,
+            uri,
+            deposit,
+        });
+
+        Ok(())
+    }
+
+    /// 创意结算或取消后作者关闭评论，取回押金与租金
+    #[access_control(
+        is_account_owner(&ctx.accounts.comment.author, ctx.accounts.author.key)
+        idea_closed(ctx.accounts.idea.status)
+    )]
+    pub fn close_comment(ctx: Context<CloseComment>) -> Result<()> {
+        let comment = &ctx.accounts.comment;
+        release_comment_deposit(
+            comment,
+            &ctx.accounts.comment.to_account_info(),
+            &ctx.accounts.deposit_token_account.to_account_info(),
+            &ctx.accounts.author_token_account.to_account_info(),
+            &ctx.accounts.author.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+        )?;
+
+        emit_cpi!(CommentClosed {
+            comment: comment.key(),
+            author: comment.author,
+            refunded: comment.deposit,
+            slashed: false,
+        });
+
+        Ok(())
+    }
+
+    /// 版主标记滥用评论：押金罚没至协议财库，评论账户租金退还作者
+    #[access_control(has_role(&ctx.accounts.roles.moderator, ctx.accounts.moderator.key))]
+    pub fn flag_comment(ctx: Context<FlagComment>) -> Result<()> {
+        let comment = &ctx.accounts.comment;
+        release_comment_deposit(
+            comment,
+            &ctx.accounts.comment.to_account_info(),
+            &ctx.accounts.deposit_token_account.to_account_info(),
+            &ctx.accounts.protocol_treasury_token_account.to_account_info(),
+            &ctx.accounts.author.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+        )?;
+
+        emit_cpi!(CommentClosed {
+            comment: comment.key(),
+            author: comment.author,
+            refunded: 0,
+            slashed: true,
+        });
+
+        Ok(())
+    }
+
+    /// 取消创意 (参与者不足或超时)
+    #[access_control(
+        idea_open(ctx.accounts.idea.status)
+        can_cancel_idea(&ctx.accounts.idea, ctx.accounts.authority.key)
+    )]
+    pub fn cancel_idea(ctx: Context<CancelIdea>) -> Result<()> {
+        let idea = &mut ctx.accounts.idea;
+        let timed_out = cancel_timed_out(idea, Clock::get()?.unix_timestamp);
+
+        // 揭晓未完成视同生成失败
+        idea.cancel_reason = if !timed_out {
+            CANCEL_REASON_INITIATOR
+        } else if idea.status != IdeaStatus::Voting {
+            CANCEL_REASON_GENERATION_FAILED
+        } else {
+            CANCEL_REASON_TIMEOUT
+        };
+        idea.status = IdeaStatus::Cancelled;
+
+        emit_cpi!(IdeaCancelled {
+            idea: idea.key(),
+            reason: "Cancelled by initiator or timeout".to_string(),
+        });
+
+        Ok(())
+    }
+
+    /// 初始化全局协议配置 (国库、发起费、平台费) 与角色表，只能执行一次
+    /// 由程序升级权限执行，之后的管理操作以配置中记录的 authority 为准
+    #[access_control(is_upgrade_authority(
+        ctx.accounts.program_data.upgrade_authority_address,
+        ctx.accounts.authority.key
+    ))]
+    pub fn initialize_protocol_config(
+        ctx: Context<InitializeProtocolConfig>,
+        treasury: Pubkey,
+        creation_fee: u64,
+        platform_fee_bps: u16,
+    ) -> Result<()> {
+        validate_protocol_fees(creation_fee, platform_fee_bps)?;
+
+        let config = &mut ctx.accounts.protocol_config;
+        config.authority = ctx.accounts.authority.key();
+        config.treasury = treasury;
+        config.creation_fee = creation_fee;
+        config.platform_fee_bps = platform_fee_bps;
+        // 折扣档位默认关闭，由 fee-setter 通过 set_fee_discount_tiers 开启
+        config.reputation_discount_min_wins = 0;
+        config.reputation_discount_min_accuracy_bps = 0;
+        config.reputation_discount_bps = 0;
+        config.staker_discount_min_amount = 0;
+        config.staker_discount_bps = 0;
+        config.season = 0;
+        config.season_started_at = Clock::get()?.unix_timestamp;
+        config.bump = ctx.bumps.protocol_config;
+        config.paused = false;
+        config.version = PROTOCOL_CONFIG_VERSION;
+
+        // 角色初始均未授予，由管理员逐一 grant_role
+        let roles = &mut ctx.accounts.roles;
+        roles.guardian = Pubkey::default();
+        roles.fee_setter = Pubkey::default();
+        roles.depin_admin = Pubkey::default();
+        roles.treasurer = Pubkey::default();
+        roles.moderator = Pubkey::default();
+        roles.bump = ctx.bumps.roles;
+
+        emit_cpi!(ProtocolConfigUpdated {
+            treasury,
+            creation_fee,
+            platform_fee_bps,
+        });
+
+        Ok(())
+    }
+
+    /// 调整发起费与平台费 (仅 fee-setter)
+    #[access_control(has_role(&ctx.accounts.roles.fee_setter, ctx.accounts.signer.key))]
+    pub fn set_protocol_fees(
+        ctx: Context<UpdateProtocolConfig>,
+        creation_fee: u64,
+        platform_fee_bps: u16,
+    ) -> Result<()> {
+        validate_protocol_fees(creation_fee, platform_fee_bps)?;
+
+        let config = &mut ctx.accounts.protocol_config;
+        config.creation_fee = creation_fee;
+        config.platform_fee_bps = platform_fee_bps;
+
+        emit_cpi!(ProtocolConfigUpdated {
+            treasury: config.treasury,
+            creation_fee,
+            platform_fee_bps,
+        });
+
+        Ok(())
+    }
+
+    /// 设置领取费用折扣档位 (仅 fee-setter)，折扣为 0 的档位不生效
+    #[access_control(has_role(&ctx.accounts.roles.fee_setter, ctx.accounts.signer.key))]
+    pub fn set_fee_discount_tiers(
+        ctx: Context<UpdateProtocolConfig>,
+        reputation_min_wins: u32,
+        reputation_min_accuracy_bps: u16,
+        reputation_discount_bps: u16,
+        staker_min_amount: u64,
+        staker_discount_bps: u16,
+    ) -> Result<()> {
+        require!(
+            reputation_min_accuracy_bps <= BPS_DENOMINATOR,
+            ConsensusError::InvalidAmount
+        );
+        require!(
+            reputation_discount_bps <= MAX_FEE_DISCOUNT_BPS
+                && staker_discount_bps <= MAX_FEE_DISCOUNT_BPS,
+            ConsensusError::ProtocolFeeTooHigh
+        );
+
+        let config = &mut ctx.accounts.protocol_config;
+        config.reputation_discount_min_wins = reputation_min_wins;
+        config.reputation_discount_min_accuracy_bps = reputation_min_accuracy_bps;
+        config.reputation_discount_bps = reputation_discount_bps;
+        config.staker_discount_min_amount = staker_min_amount;
+        config.staker_discount_bps = staker_discount_bps;
+
+        emit_cpi!(FeeDiscountTiersUpdated {
+            reputation_min_wins,
+            reputation_min_accuracy_bps,
+            reputation_discount_bps,
+            staker_min_amount,
+            staker_discount_bps,
+        });
+
+        Ok(())
+    }
+
+    /// 更换协议国库 (仅 treasurer)
+    #[access_control(has_role(&ctx.accounts.roles.treasurer, ctx.accounts.signer.key))]
+    pub fn set_protocol_treasury(ctx: Context<UpdateProtocolConfig>, treasury: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.protocol_config;
+        config.treasury = treasury;
+
+        emit_cpi!(ProtocolConfigUpdated {
+            treasury,
+            creation_fee: config.creation_fee,
+            platform_fee_bps: config.platform_fee_bps,
+        });
+
+        Ok(())
+    }
+
+    /// 开启新赛季：排行榜与评审的赛季战绩按新赛季编号重新累计 (仅协议管理员)
+    #[access_control(has_role(&ctx.accounts.protocol_config.authority, ctx.accounts.signer.key))]
+    pub fn start_season(ctx: Context<UpdateProtocolConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.protocol_config;
+        config.season = config.season.checked_add(1).ok_or(ConsensusError::Overflow)?;
+        config.season_started_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(SeasonStarted {
+            season: config.season,
+            started_at: config.season_started_at,
+        });
+
+        Ok(())
+    }
+
+    /// 紧急暂停/恢复：暂停期间创建创意、投票、结算、交易与回购全部拒绝 (仅 guardian)
+    #[access_control(has_role(&ctx.accounts.roles.guardian, ctx.accounts.signer.key))]
+    pub fn set_protocol_paused(ctx: Context<UpdateProtocolConfig>, paused: bool) -> Result<()> {
+        ctx.accounts.protocol_config.paused = paused;
+
+        emit_cpi!(ProtocolPauseToggled {
+            guardian: ctx.accounts.signer.key(),
+            paused,
+        });
+
+        Ok(())
+    }
+
+    /// 设置图片存储配置 (仅协议管理员)：DePIN 提交的 URI 须匹配允许的前缀，
+    /// 前端通过 gateway_base 解析内容；前缀列表为空时不限制
+    #[access_control(is_protocol_authority(
+        &ctx.accounts.protocol_config.authority,
+        ctx.accounts.authority.key
+    ))]
+    pub fn set_image_storage_config(
+        ctx: Context<SetImageStorageConfig>,
+        allowed_prefixes: Vec<String>,
+        gateway_base: String,
+    ) -> Result<()> {
+        require!(
+            allowed_prefixes.len() <= MAX_STORAGE_PREFIXES
+                && allowed_prefixes
+                    .iter()
+                    .all(|p| !p.is_empty() && p.len() <= MAX_STORAGE_PREFIX_LEN),
+            ConsensusError::InvalidStorageConfig
+        );
+        require!(
+            gateway_base.len() <= MAX_STORAGE_PREFIX_LEN,
+            ConsensusError::InvalidStorageConfig
+        );
+
+        let config = &mut ctx.accounts.image_storage_config;
+        config.allowed_prefixes = allowed_prefixes.clone();
+        config.gateway_base = gateway_base.clone();
+        config.bump = ctx.bumps.image_storage_config;
+
+        emit_cpi!(ImageStorageConfigUpdated {
+            allowed_prefixes,
+            gateway_base,
+        });
+
+        Ok(())
+    }
+
+    /// 授予或轮换角色 (仅协议管理员)
+    #[access_control(is_protocol_authority(
+        &ctx.accounts.protocol_config.authority,
+        ctx.accounts.authority.key
+    ))]
+    pub fn grant_role(ctx: Context<ManageRoles>, role: u8, member: Pubkey) -> Result<()> {
+        require!(member != Pubkey::default(), ConsensusError::InvalidRole);
+        *ctx.accounts.roles.holder_mut(role)? = member;
+
+        emit_cpi!(RoleGranted { role, member });
+
+        Ok(())
+    }
+
+    /// 撤销角色，撤销后该角色的操作在重新授予前不可执行 (仅协议管理员)
+    #[access_control(is_protocol_authority(
+        &ctx.accounts.protocol_config.authority,
+        ctx.accounts.authority.key
+    ))]
+    pub fn revoke_role(ctx: Context<ManageRoles>, role: u8) -> Result<()> {
+        let holder = ctx.accounts.roles.holder_mut(role)?;
+        let member = *holder;
+        *holder = Pubkey::default();
+
+        emit_cpi!(RoleRevoked { role, member });
+
+        Ok(())
+    }
+
+    /// 账户结构升级：按 discriminator 识别 Idea / ProtocolConfig，realloc 到当前大小并写入当前版本号
+    /// 由协议管理员执行，旧布局的配置账户可能已无法按新结构反序列化，authority 按固定偏移读取
+    #[access_control(is_protocol_config_authority(
+        &ctx.accounts.protocol_config,
+        ctx.accounts.authority.key
+    ))]
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        let info = ctx.accounts.account.to_account_info();
+        let payer = ctx.accounts.authority.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let discriminator: [u8; 8] = info.try_borrow_data()?[..8]
+            .try_into()
+            .map_err(|_| ConsensusError::UnsupportedMigration)?;
+
+        let (kind, from_version, to_version) = if discriminator == Idea::DISCRIMINATOR {
+            // 创意只升级到布局版本，每图统计由 migrate_idea_stats 重算后才到当前版本
+            realloc_account(&info, &payer, &system_program, 8 + Idea::SPACE)?;
+            let mut idea = Idea::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            let from_version = idea.version;
+            require!(from_version < IDEA_LAYOUT_VERSION, ConsensusError::AccountUpToDate);
+            idea.version = IDEA_LAYOUT_VERSION;
+            idea.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+            (ACCOUNT_KIND_IDEA, from_version, IDEA_LAYOUT_VERSION)
+        } else if discriminator == ProtocolConfig::DISCRIMINATOR {
+            realloc_account(&info, &payer, &system_program, 8 + ProtocolConfig::SPACE)?;
+            let mut config = ProtocolConfig::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            let from_version = config.version;
+            require!(from_version < PROTOCOL_CONFIG_VERSION, ConsensusError::AccountUpToDate);
+            config.version = PROTOCOL_CONFIG_VERSION;
+            config.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+            (ACCOUNT_KIND_PROTOCOL_CONFIG, from_version, PROTOCOL_CONFIG_VERSION)
+        } else {
+            return err!(ConsensusError::UnsupportedMigration);
+        };
+
+        emit_cpi!(AccountMigrated {
+            account: info.key(),
+            kind,
+            from_version,
+            to_version,
+            size: info.data_len() as u32,
+        });
+
+        Ok(())
+    }
+
+    /// Idea v1→v2：按投票记录重算每图质押、人数、赔付权重与最后投票时间
+    /// remaining_accounts 为该创意的投票记录，按投票者公钥升序分批传入，进度保存在迁移账户中
+    /// 处理完 total_voters 条记录后写回创意并升级版本，迁移账户关闭退还租金
+    #[access_control(is_protocol_authority(
+        &ctx.accounts.protocol_config.authority,
+        ctx.accounts.authority.key
+    ))]
+    pub fn migrate_idea_stats<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateIdeaStats<'info>>,
+    ) -> Result<()> {
+        let idea = &ctx.accounts.idea;
+        require!(idea.version >= IDEA_LAYOUT_VERSION, ConsensusError::AccountMigrationRequired);
+        require!(idea.version < IDEA_ACCOUNT_VERSION, ConsensusError::AccountUpToDate);
+        let idea_key = idea.key();
+
+        let migration = &mut ctx.accounts.idea_migration;
+        if migration.idea == Pubkey::default() {
+            migration.idea = idea_key;
+            migration.bump = ctx.bumps.idea_migration;
+        }
+
+        for info in ctx.remaining_accounts {
+            let vote = Account::<Vote>::try_from(info)?;
+            require_keys_eq!(vote.idea, idea_key, ConsensusError::InvalidState);
+            require!(vote.voter > migration.cursor, ConsensusError::MigrationOrderViolation);
+            migration.cursor = vote.voter;
+            migration.processed = migration.processed
+                .checked_add(1)
+                .ok_or(ConsensusError::Overflow)?;
+
+            // RejectAll 投票不计入每图统计
+            if vote.image_choice < 4 {
+                let i = vote.image_choice as usize;
+                migration.stake_totals[i] = migration.stake_totals[i]
+                    .checked_add(vote.stake_amount)
+                    .ok_or(ConsensusError::Overflow)?;
+                migration.voter_counts[i] = migration.voter_counts[i]
+                    .checked_add(1)
+                    .ok_or(ConsensusError::Overflow)?;
+                let unit = payout_unit(idea.payout_curve, idea.payout_exponent, vote.stake_amount, vote.vote_weight);
+                migration.payout_weights[i] = migration.payout_weights[i]
+                    .checked_add(unit)
+                    .ok_or(ConsensusError::Overflow)?;
+                migration.last_vote_ts[i] = migration.last_vote_ts[i].max(vote.ts);
+            }
+        }
+        require!(migration.processed <= idea.total_voters, ConsensusError::InvalidState);
+
+        let processed = migration.processed;
+        let completed = processed == idea.total_voters;
+        if completed {
+            let idea = &mut ctx.accounts.idea;
+            idea.stake_totals = migration.stake_totals;
+            idea.voter_counts = migration.voter_counts;
+            idea.payout_weights = migration.payout_weights;
+            idea.last_vote_ts = migration.last_vote_ts;
+            // 领取期上线前已结算的创意没有截止时间，从迁移时起补足一个完整领取期
+            if idea.status == IdeaStatus::Completed && idea.claim_deadline == 0 {
+                idea.claim_deadline = Clock::get()?
+                    .unix_timestamp
+                    .checked_add(CLAIM_WINDOW)
+                    .ok_or(ConsensusError::Overflow)?;
+            }
+            idea.version = IDEA_ACCOUNT_VERSION;
+            migration.close(ctx.accounts.authority.to_account_info())?;
+        }
+
+        emit_cpi!(IdeaStatsMigrated {
+            idea: idea_key,
+            processed,
+            total_voters: ctx.accounts.idea.total_voters,
+            completed,
+        });
+
+        Ok(())
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+
+/// 校验协议费用参数 (初始化与更新共用)
+fn validate_protocol_fees(creation_fee: u64, platform_fee_bps: u16) -> Result<()> {
+    require!(creation_fee <= MAX_CREATION_FEE, ConsensusError::ProtocolFeeTooHigh);
+    require!(platform_fee_bps <= MAX_PLATFORM_FEE_BPS, ConsensusError::ProtocolFeeTooHigh);
+    Ok(())
+}
+
+/// 登记 Prompt 哈希：同一发起者的相同 Prompt 在活跃期内不可重复发起
+/// 投票开启前活跃期取可能的最晚投票截止时间，开启后由 sync_prompt_deadline 改为实际截止时间
+/// 只复用已过期的记录，登记表中全部为活跃记录时拒绝创建
+fn register_prompt(
+    registry: &mut PromptRegistry,
+    idea: &Idea,
+    idea_key: Pubkey,
+    now: i64,
+    allow_rerun: bool,
+) -> Result<()> {
+    if !allow_rerun {
+        let duplicate = registry.entries.iter().any(|e| {
+            e.active_until > now && e.initiator == idea.initiator && e.prompt_hash == idea.prompt_hash
+        });
+        require!(!duplicate, ConsensusError::DuplicatePrompt);
+    }
+
+    let slot = registry
+        .entries
+        .iter_mut()
+        .find(|e| e.active_until <= now)
+        .ok_or(ConsensusError::PromptRegistryFull)?;
+    slot.idea = idea_key;
+    slot.initiator = idea.initiator;
+    slot.prompt_hash = idea.prompt_hash;
+    slot.active_until = idea.generation_deadline + MAX_REVEAL_DELAY + DEFAULT_VOTING_DURATION;
+
+    Ok(())
+}
+
+/// 投票开启后将登记记录的活跃期改为创意实际的投票截止时间
+fn sync_prompt_deadline(registry: &mut PromptRegistry, idea: &Idea, idea_key: Pubkey) {
+    if let Some(entry) = registry.entries.iter_mut().find(|e| e.idea == idea_key) {
+        entry.active_until = idea.voting_deadline;
+    }
+}
+
+/// 记录投票并更新创意统计 (质押代币已转入金库)
+fn record_vote(
+    emitter: &EventEmitter,
+    idea: &mut Account<Idea>,
+    vote: &mut Account<Vote>,
+    reviewer_stake: &mut Account<ReviewerStake>,
+    voter: Pubkey,
+    image_index: u8,
+    token_amount: u64,
+    weight_multiplier_bps: u16,
+    reviewer_stake_bump: u8,
+    now: i64,
+) -> Result<()> {
+    // 旧创意的每图统计须先重算，否则新投票会叠加在错误的基数上
+    idea_migrated(idea.version)?;
+
+    // 计算二次方投票权重: vote_weight = sqrt(token_amount)，再乘以声誉加成
+    let vote_weight = (integer_sqrt(token_amount) as u128)
+        .checked_mul(weight_multiplier_bps as u128)
+        .and_then(|x| x.checked_div(BPS_DENOMINATOR as u128))
+        .and_then(|x| u64::try_from(x).ok())
+        .ok_or(ConsensusError::Overflow)?;
+
+    // 更新 idea 统计
+    if image_index < 4 {
+        idea.votes[image_index as usize] = idea.votes[image_index as usize]
+            .checked_add(vote_weight)
+            .ok_or(ConsensusError::Overflow)?;
+        // 按分配曲线累加该图片的份额总量
+        let unit = payout_unit(idea.payout_curve, idea.payout_exponent, token_amount, vote_weight);
+        idea.payout_weights[image_index as usize] = idea.payout_weights[image_index as usize]
+            .checked_add(unit)
+            .ok_or(ConsensusError::Overflow)?;
+        idea.voter_counts[image_index as usize] = idea.voter_counts[image_index as usize]
+            .checked_add(1)
+            .ok_or(ConsensusError::Overflow)?;
+        idea.stake_totals[image_index as usize] = idea.stake_totals[image_index as usize]
+            .checked_add(token_amount)
+            .ok_or(ConsensusError::Overflow)?;
+        idea.last_vote_ts[image_index as usize] = now;
+    } else {
+        // RejectAll 投票权重
+        idea.reject_all_weight = idea.reject_all_weight
+            .checked_add(vote_weight)
+            .ok_or(ConsensusError::Overflow)?;
+    }
+    idea.total_staked = idea.total_staked.checked_add(token_amount)
+        .ok_or(ConsensusError::Overflow)?;
+    idea.total_voters += 1;
+
+    // 创建投票记录（首次投票）
+    vote.idea = idea.key();
+    vote.voter = voter;
+    vote.image_choice = image_index;
+    vote.stake_amount = token_amount;
+    vote.ts = now;
+    vote.vote_weight = vote_weight;
+
+    // 创建质押记录（首次投票）
+    reviewer_stake.idea = idea.key();
+    reviewer_stake.reviewer = voter;
+    reviewer_stake.total_staked = token_amount; // 首次投票，直接设置
+    reviewer_stake.is_winner = false;
+    reviewer_stake.winnings = 0;
+    reviewer_stake.bump = reviewer_stake_bump;
+
+    emitter.emit(VoteCast {
+        idea: idea.key(),
+        voter,
+        image_choice: image_index,
+        stake_amount: token_amount,
+    })?;
+    emitter.emit(PointsAccrued {
+        user: voter,
+        kind: POINTS_KIND_VOTE,
+        points: POINTS_PER_VOTE,
+    })
+}
+
+/// 将评论押金转给接收方并关闭押金代币账户 (租金退还作者)
+fn release_comment_deposit<'info>(
+    comment: &Comment,
+    comment_info: &AccountInfo<'info>,
+    deposit_token_account: &AccountInfo<'info>,
+    recipient: &AccountInfo<'info>,
+    author: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let comment_id = comment.comment_id.to_le_bytes();
+    let comment_seeds: &[&[u8]] = &[
+        b"comment",
+        comment.idea.as_ref(),
+        comment.author.as_ref(),
+        comment_id.as_ref(),
+        &[comment.bump],
+    ];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            Transfer {
+                from: deposit_token_account.clone(),
+                to: recipient.clone(),
+                authority: comment_info.clone(),
+            },
+            &[comment_seeds],
+        ),
+        comment.deposit,
+    )?;
+    token::close_account(CpiContext::new_with_signer(
+        token_program.clone(),
+        CloseAccount {
+            account: deposit_token_account.clone(),
+            destination: author.clone(),
+            authority: comment_info.clone(),
+        },
+        &[comment_seeds],
+    ))
+}
+
+/// 投票者的声誉权重倍数，未提供声誉账户时为 1x
+fn voter_multiplier_bps(reputation: Option<&UncheckedAccount>, voter: &Pubkey) -> Result<u16> {
+    match reputation {
+        Some(reputation) => reputation_multiplier_from_account(reputation, voter),
+        None => Ok(BPS_DENOMINATOR),
+    }
+}
+
+/// 读取可能尚未创建的代币账户 (未创建返回 None)
+fn read_token_account(info: &AccountInfo) -> Result<Option<TokenAccount>> {
+    if info.data_is_empty() {
+        return Ok(None);
+    }
+    require_keys_eq!(*info.owner, token::ID, ConsensusError::InvalidMint);
+    Ok(Some(TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?))
+}
+
+/// 校验赞助商资料字段长度
+fn validate_sponsor_profile(logo_uri: &str, link: &str) -> Result<()> {
+    require!(
+        logo_uri.len() <= MAX_IMAGE_URI_LEN && link.len() <= MAX_SPONSOR_LINK_LEN,
+        ConsensusError::InvalidSponsorProfile
+    );
+    Ok(())
+}
+
+/// Prompt 非空且不超过长度上限
+fn valid_prompt(prompt: &str) -> Result<()> {
+    require!(
+        !prompt.is_empty() && prompt.len() <= MAX_PROMPT_LEN,
+        ConsensusError::InvalidPrompt
+    );
+    Ok(())
+}
+
+/// 衍生创意的原创意须已在同一主题内结算出获胜图片，版税比例不超过上限
+fn valid_remix_parent(parent: &Idea, theme: &Pubkey, remix_fee_bps: u16) -> Result<()> {
+    require!(
+        parent.status == IdeaStatus::Completed
+            && parent.winning_image_index.is_some()
+            && parent.theme == *theme,
+        ConsensusError::InvalidRemixParent
+    );
+    require!(remix_fee_bps <= MAX_REMIX_FEE_BPS, ConsensusError::InvalidRemixFee);
+    Ok(())
+}
+
+/// 合集名称与元数据 URI 长度受限，大奖比例与持续时间不超过上限
+fn valid_collection(
+    name: &str,
+    metadata_uri: &str,
+    grand_prize_bps: u16,
+    duration_days: u16,
+) -> Result<()> {
+    require!(
+        !name.is_empty()
+            && name.len() <= MAX_COLLECTION_NAME_LEN
+            && metadata_uri.len() <= MAX_IMAGE_URI_LEN
+            && grand_prize_bps <= MAX_COLLECTION_PRIZE_BPS
+            && (1..=MAX_COLLECTION_DURATION_DAYS).contains(&duration_days),
+        ConsensusError::InvalidCollection
+    );
+    Ok(())
+}
+
+/// 投票时长 24~168 小时
+fn valid_voting_duration(voting_duration_hours: u16) -> Result<()> {
+    require!(
+        (24..=168).contains(&voting_duration_hours),
+        ConsensusError::InvalidVotingDuration
+    );
+    Ok(())
+}
+
+/// 关注小费不超过上限，且不能关注自己
+fn valid_follow(tip_bps: u16, follower: &Pubkey, curator: &Pubkey) -> Result<()> {
+    require!(tip_bps <= MAX_FOLLOW_TIP_BPS, ConsensusError::FollowTipTooHigh);
+    require_keys_neq!(*follower, *curator, ConsensusError::Unauthorized);
+    Ok(())
+}
+
+/// 超过生成截止时间 + 默认投票期仍未结束的创意视为超时
+fn cancel_timed_out(idea: &Idea, now: i64) -> bool {
+    now > idea.generation_deadline + DEFAULT_VOTING_DURATION
+}
+
+/// 只能由发起者取消，或者超时后任何人都可以取消
+fn can_cancel_idea(idea: &Idea, signer: &Pubkey) -> Result<()> {
+    require!(
+        *signer == idea.initiator || cancel_timed_out(idea, Clock::get()?.unix_timestamp),
+        ConsensusError::Unauthorized
+    );
+    Ok(())
+}
+
+/// 校验投票者是否满足赞助竞赛的准入条件
+fn check_vote_gate(
+    idea: &Idea,
+    voter: &Pubkey,
+    allowlist_proof: &[[u8; 32]],
+    remaining_accounts: &[AccountInfo],
+) -> Result<()> {
+    if let Some(root) = idea.allowlist_root {
+        let leaf = anchor_lang::solana_program::keccak::hash(voter.as_ref()).to_bytes();
+        require!(
+            verify_merkle_proof(allowlist_proof, root, leaf),
+            ConsensusError::VoterNotEligible
+        );
+    }
+
+    if let Some(gate_mint) = idea.gate_mint {
+        let holding_info = remaining_accounts
+            .first()
+            .ok_or(ConsensusError::VoterNotEligible)?;
+        require!(
+            holding_info.owner == &token::ID,
+            ConsensusError::VoterNotEligible
+        );
+        let holding = TokenAccount::try_deserialize(&mut &holding_info.try_borrow_data()?[..])?;
+        require!(
+            holding.owner == *voter
+                && holding.mint == gate_mint
+                && holding.amount >= idea.gate_min_amount.max(1),
+            ConsensusError::VoterNotEligible
+        );
+    }
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Contexts
+// -----------------------------------------------------------------------------
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(idea_id: u64, prompt: String, theme: Pubkey)]
+pub struct CreateIdea<'info> {
+    #[account(
+        init,
+        payer = initiator,
+        space = 8 + Idea::SPACE,
+        seeds = [b"idea", initiator.key().as_ref(), &idea_id.to_le_bytes()],
+        bump
+    )]
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        init,
+        payer = initiator,
+        space = 8 + Vault::SPACE,
+        seeds = [b"vault", idea.key().as_ref()],
+        bump
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// 主题内最近 Prompt 哈希登记表
+    #[account(
+        init_if_needed,
+        payer = initiator,
+        space = 8 + PromptRegistry::SPACE,
+        seeds = [b"prompt_registry", theme.as_ref()],
+        bump
+    )]
+    pub prompt_registry: Box<Account<'info, PromptRegistry>>,
+
+    /// 主题账户 (读取投票模式、分配曲线等主题配置)
+    #[account(constraint = theme_account.key() == theme @ ConsensusError::InvalidTheme)]
+    pub theme_account: Box<Account<'info, taste_fun_token::Theme>>,
+
+    #[account(address = theme_account.token_mint @ ConsensusError::InvalidMint)]
+    pub theme_token_mint: Box<Account<'info, Mint>>,
+
+    /// 创意金库的主题代币 ATA，创建时确定，后续指令据此约束资金去向
+    #[account(
+        init,
+        payer = initiator,
+        associated_token::mint = theme_token_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub initiator: Signer<'info>,
+
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    /// CHECK: Protocol treasury account, must match the protocol config
+    #[account(mut, address = protocol_config.treasury @ ConsensusError::Unauthorized)]
+    pub protocol_treasury: UncheckedAccount<'info>,
+
+    /// 主题奖池 (可选，存在滚存时注入本创意)
+    #[account(mut, seeds = [b"jackpot", theme.as_ref()], bump = jackpot.bump)]
+    pub jackpot: Option<Box<Account<'info, Jackpot>>>,
+
+    /// Jackpot token account
+    #[account(mut, token::authority = jackpot, token::mint = theme_token_mint)]
+    pub jackpot_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(idea_id: u64, prompt: String, theme: Pubkey)]
+pub struct CreateRemix<'info> {
+    #[account(
+        init,
+        payer = initiator,
+        space = 8 + Idea::SPACE,
+        seeds = [b"idea", initiator.key().as_ref(), &idea_id.to_le_bytes()],
+        bump
+    )]
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        init,
+        payer = initiator,
+        space = 8 + Vault::SPACE,
+        seeds = [b"vault", idea.key().as_ref()],
+        bump
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// 被衍生的原创意
+    pub parent_idea: Box<Account<'info, Idea>>,
+
+    /// 主题内最近 Prompt 哈希登记表
+    #[account(
+        init_if_needed,
+        payer = initiator,
+        space = 8 + PromptRegistry::SPACE,
+        seeds = [b"prompt_registry", theme.as_ref()],
+        bump
+    )]
+    pub prompt_registry: Box<Account<'info, PromptRegistry>>,
+
+    /// 主题账户 (读取投票模式、分配曲线等主题配置)
+    #[account(constraint = theme_account.key() == theme @ ConsensusError::InvalidTheme)]
+    pub theme_account: Box<Account<'info, taste_fun_token::Theme>>,
+
+    #[account(address = theme_account.token_mint @ ConsensusError::InvalidMint)]
+    pub theme_token_mint: Box<Account<'info, Mint>>,
+
+    /// 创意金库的主题代币 ATA
+    #[account(
+        init,
+        payer = initiator,
+        associated_token::mint = theme_token_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub initiator: Signer<'info>,
+
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    /// CHECK: Protocol treasury account, must match the protocol config
+    #[account(mut, address = protocol_config.treasury @ ConsensusError::Unauthorized)]
+    pub protocol_treasury: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitJackpot<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Jackpot::SPACE,
+        seeds = [b"jackpot", theme_account.key().as_ref()],
+        bump
+    )]
+    pub jackpot: Account<'info, Jackpot>,
+
+    pub theme_account: Box<Account<'info, taste_fun_token::Theme>>,
+
+    #[account(address = theme_account.token_mint @ ConsensusError::InvalidMint)]
+    pub theme_token_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = theme_token_mint,
+        associated_token::authority = jackpot
+    )]
+    pub jackpot_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(idea_id: u64, prompt: String, theme: Pubkey)]
+pub struct CreateSponsoredIdea<'info> {
+    #[account(
+        init,
+        payer = initiator,
+        space = 8 + Idea::SPACE,
+        seeds = [b"idea", initiator.key().as_ref(), &idea_id.to_le_bytes()],
+        bump
+    )]
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        init,
+        payer = initiator,
+        space = 8 + Vault::SPACE,
+        seeds = [b"vault", idea.key().as_ref()],
+        bump
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// 主题内最近 Prompt 哈希登记表
+    #[account(
+        init_if_needed,
+        payer = initiator,
+        space = 8 + PromptRegistry::SPACE,
+        seeds = [b"prompt_registry", theme.as_ref()],
+        bump
+    )]
+    pub prompt_registry: Box<Account<'info, PromptRegistry>>,
+
+    /// 主题账户 (读取投票模式、分配曲线等主题配置)
+    #[account(constraint = theme_account.key() == theme @ ConsensusError::InvalidTheme)]
+    pub theme_account: Box<Account<'info, taste_fun_token::Theme>>,
+
+    #[account(address = theme_account.token_mint @ ConsensusError::InvalidMint)]
+    pub theme_token_mint: Box<Account<'info, Mint>>,
+
+    /// 创意金库的主题代币 ATA，创建时确定，后续指令据此约束资金去向
+    #[account(
+        init,
+        payer = initiator,
+        associated_token::mint = theme_token_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = theme_token_mint,
+        token::authority = sponsor,
+    )]
+    pub sponsor_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub initiator: Signer<'info>,
+
+    /// CHECK: Sponsor account providing initial prize pool
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    /// 可选：赞助商品牌资料
+    #[account(
+        seeds = [b"sponsor_profile", sponsor.key().as_ref()],
+        bump = sponsor_profile.bump
+    )]
+    pub sponsor_profile: Option<Account<'info, SponsorProfile>>,
+
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    /// CHECK: Protocol treasury account, must match the protocol config
+    #[account(mut, address = protocol_config.treasury @ ConsensusError::Unauthorized)]
+    pub protocol_treasury: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(series_id: u64, prompt: String, theme: Pubkey)]
+pub struct CreateSeries<'info> {
+    #[account(
+        init,
+        payer = sponsor,
+        space = 8 + Series::SPACE,
+        seeds = [b"series", sponsor.key().as_ref(), &series_id.to_le_bytes()],
+        bump
+    )]
+    pub series: Box<Account<'info, Series>>,
+
+    #[account(constraint = theme_account.key() == theme @ ConsensusError::InvalidTheme)]
+    pub theme_account: Box<Account<'info, taste_fun_token::Theme>>,
+
+    #[account(
+        constraint = theme_token_mint.key() == theme_account.token_mint @ ConsensusError::InvalidMint
+    )]
+    pub theme_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = sponsor,
+        associated_token::mint = theme_token_mint,
+        associated_token::authority = series,
+    )]
+    pub series_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated by token program via transfer
+    #[account(mut)]
+    pub sponsor_token_account: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RollSeries<'info> {
+    #[account(
+        mut,
+        seeds = [b"series", series.sponsor.as_ref(), &series.series_id.to_le_bytes()],
+        bump = series.bump
+    )]
+    pub series: Box<Account<'info, Series>>,
+
+    #[account(
+        init,
+        payer = cranker,
+        space = 8 + Idea::SPACE,
+        seeds = [b"idea", series.key().as_ref(), &series.episodes_created.to_le_bytes()],
+        bump
+    )]
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        init,
+        payer = cranker,
+        space = 8 + Vault::SPACE,
+        seeds = [b"vault", idea.key().as_ref()],
+        bump
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// 主题内最近 Prompt 哈希登记表
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        space = 8 + PromptRegistry::SPACE,
+        seeds = [b"prompt_registry", series.theme.as_ref()],
+        bump
+    )]
+    pub prompt_registry: Box<Account<'info, PromptRegistry>>,
+
+    /// 主题账户 (读取投票模式、分配曲线等主题配置)
+    #[account(address = series.theme @ ConsensusError::InvalidTheme)]
+    pub theme_account: Box<Account<'info, taste_fun_token::Theme>>,
+
+    #[account(address = series.theme_token_mint @ ConsensusError::InvalidMint)]
+    pub theme_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = theme_token_mint,
+        associated_token::authority = series,
+    )]
+    pub series_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = cranker,
+        associated_token::mint = theme_token_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    /// CHECK: Protocol treasury account, must match the protocol config
+    #[account(mut, address = protocol_config.treasury @ ConsensusError::Unauthorized)]
+    pub protocol_treasury: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseSeries<'info> {
+    #[account(
+        mut,
+        seeds = [b"series", sponsor.key().as_ref(), &series.series_id.to_le_bytes()],
+        bump = series.bump,
+        has_one = sponsor @ ConsensusError::Unauthorized
+    )]
+    pub series: Box<Account<'info, Series>>,
+
+    #[account(
+        mut,
+        associated_token::mint = series.theme_token_mint,
+        associated_token::authority = series,
+    )]
+    pub series_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = series.theme_token_mint,
+        token::authority = sponsor,
+    )]
+    pub sponsor_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub sponsor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(collection_id: u64)]
+pub struct CreateCollection<'info> {
+    #[account(
+        init,
+        payer = initiator,
+        space = 8 + Collection::SPACE,
+        seeds = [b"collection", initiator.key().as_ref(), &collection_id.to_le_bytes()],
+        bump
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+
+    #[account(
+        constraint = theme_token_mint.key() != Pubkey::default() @ ConsensusError::InvalidTheme
+    )]
+    pub theme_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub initiator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AddIdeaToCollection<'info> {
+    #[account(
+        mut,
+        seeds = [b"collection", initiator.key().as_ref(), &collection.collection_id.to_le_bytes()],
+        bump = collection.bump
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+
+    #[account(mut, has_one = initiator @ ConsensusError::Unauthorized)]
+    pub idea: Box<Account<'info, Idea>>,
+
+    pub initiator: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateSponsorProfile<'info> {
+    #[account(
+        init,
+        payer = sponsor,
+        space = 8 + SponsorProfile::SPACE,
+        seeds = [b"sponsor_profile", sponsor.key().as_ref()],
+        bump
+    )]
+    pub sponsor_profile: Account<'info, SponsorProfile>,
+
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateSponsorProfile<'info> {
+    #[account(
+        mut,
+        seeds = [b"sponsor_profile", sponsor.key().as_ref()],
+        bump = sponsor_profile.bump,
+        has_one = sponsor @ ConsensusError::Unauthorized
+    )]
+    pub sponsor_profile: Account<'info, SponsorProfile>,
+
+    pub sponsor: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FundSponsorEscrow<'info> {
+    #[account(
+        constraint = idea.sponsor == Some(sponsor.key()) @ ConsensusError::Unauthorized
+    )]
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        init,
+        payer = sponsor,
+        space = 8 + SponsorEscrow::SPACE,
+        seeds = [b"sponsor_escrow", idea.key().as_ref()],
+        bump
+    )]
+    pub escrow: Box<Account<'info, SponsorEscrow>>,
+
+    #[account(address = idea.theme_token_mint @ ConsensusError::InvalidMint)]
+    pub theme_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = sponsor,
+        associated_token::mint = theme_token_mint,
+        associated_token::authority = escrow,
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated by token program via transfer
+    #[account(mut)]
+    pub sponsor_token_account: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReleaseEscrowTranche<'info> {
+    #[account(mut)]
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        mut,
+        seeds = [b"sponsor_escrow", idea.key().as_ref()],
+        bump = escrow.bump,
+        has_one = idea
+    )]
+    pub escrow: Box<Account<'info, SponsorEscrow>>,
+
+    #[account(
+        mut,
+        associated_token::mint = escrow.token_mint,
+        associated_token::authority = escrow,
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(seeds = [b"vault", idea.key().as_ref()], bump = idea.vault_bump)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        token::mint = escrow.token_mint,
+        token::authority = vault,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RefundSponsorEscrow<'info> {
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        mut,
+        seeds = [b"sponsor_escrow", idea.key().as_ref()],
+        bump = escrow.bump,
+        has_one = idea
+    )]
+    pub escrow: Box<Account<'info, SponsorEscrow>>,
+
+    #[account(
+        mut,
+        associated_token::mint = escrow.token_mint,
+        associated_token::authority = escrow,
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = escrow.token_mint,
+        token::authority = escrow.sponsor,
+    )]
+    pub sponsor_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetVoteGate<'info> {
+    #[account(
+        mut,
+        constraint = idea.sponsor == Some(sponsor.key()) @ ConsensusError::Unauthorized
+    )]
+    pub idea: Account<'info, Idea>,
+
+    pub sponsor: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetBonusJudge<'info> {
+    #[account(
+        mut,
+        constraint = idea.sponsor == Some(sponsor.key()) @ ConsensusError::Unauthorized
+    )]
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(seeds = [b"vault", idea.key().as_ref()], bump = idea.vault_bump)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        associated_token::mint = idea.theme_token_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = idea.theme_token_mint,
+        token::authority = sponsor,
+    )]
+    pub sponsor_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub sponsor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfirmImages<'info> {
+    #[account(mut)]
+    pub idea: Account<'info, Idea>,
+
+    /// 创意所在主题的 Prompt 登记表，投票开启时写入实际截止时间
+    #[account(
+        mut,
+        seeds = [b"prompt_registry", idea.theme.as_ref()],
+        bump = prompt_registry.bump
+    )]
+    pub prompt_registry: Box<Account<'info, PromptRegistry>>,
+
+    #[account(seeds = [b"roles"], bump = roles.bump)]
+    pub roles: Account<'info, Roles>,
+
+    #[account(seeds = [b"image_storage_config"], bump = image_storage_config.bump)]
+    pub image_storage_config: Account<'info, ImageStorageConfig>,
+
+    /// CHECK: 授权的 DePIN 服务账户
+    pub depin_authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevealImages<'info> {
+    #[account(mut)]
+    pub idea: Account<'info, Idea>,
+
+    /// 创意所在主题的 Prompt 登记表，投票开启时写入实际截止时间
+    #[account(
+        mut,
+        seeds = [b"prompt_registry", idea.theme.as_ref()],
+        bump = prompt_registry.bump
+    )]
+    pub prompt_registry: Box<Account<'info, PromptRegistry>>,
+
+    #[account(seeds = [b"image_storage_config"], bump = image_storage_config.bump)]
+    pub image_storage_config: Account<'info, ImageStorageConfig>,
+
+    pub revealer: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(image_index: u8, token_amount: u64)]
+pub struct VoteForImage<'info> {
+    #[account(mut)]
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + Vote::SPACE,
+        seeds = [b"vote", idea.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote: Box<Account<'info, Vote>>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + ReviewerStake::SPACE,
+        seeds = [b"reviewer_stake", idea.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub reviewer_stake: Box<Account<'info, ReviewerStake>>,
+
+    #[account(mut, seeds = [b"vault", idea.key().as_ref()], bump = idea.vault_bump)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// 投票者的主题代币账户，只能质押创意所属主题的代币
+    #[account(
+        mut,
+        token::mint = idea.theme_token_mint,
+        token::authority = voter,
+    )]
+    pub voter_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 创意金库的主题代币账户
+    #[account(
+        mut,
+        token::mint = idea.theme_token_mint,
+        token::authority = vault,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: 可选，投票者在结算程序中的声誉记录，按布局校验后计算权重加成
+    pub reputation: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BuyAndVote<'info> {
+    #[account(mut)]
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + Vote::SPACE,
+        seeds = [b"vote", idea.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote: Box<Account<'info, Vote>>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + ReviewerStake::SPACE,
+        seeds = [b"reviewer_stake", idea.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub reviewer_stake: Box<Account<'info, ReviewerStake>>,
+
+    #[account(mut, seeds = [b"vault", idea.key().as_ref()], bump = idea.vault_bump)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        token::mint = idea.theme_token_mint,
+        token::authority = vault,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: 投票者的主题代币 ATA，可由代币程序在买入时创建，买入后在指令内校验
+    #[account(mut)]
+    pub voter_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Theme - must match the idea, validated by token program
+    #[account(mut, address = idea.theme)]
+    pub theme: UncheckedAccount<'info>,
+
+    /// CHECK: Theme vault - validated by token program
+    #[account(mut)]
+    pub theme_vault: UncheckedAccount<'info>,
+
+    #[account(mut, address = idea.theme_token_mint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Theme vault token account - validated by token program
+    #[account(mut)]
+    pub theme_vault_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Trading config - validated by token program
+    pub trading_config: UncheckedAccount<'info>,
+
+    /// CHECK: Theme price oracle - validated by token program
+    #[account(mut)]
+    pub price_oracle: UncheckedAccount<'info>,
+
+    /// CHECK: Voter launch limit (required during the launch window) - validated by token program
+    #[account(mut)]
+    pub launch_limit: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Voter trader state (required when the theme has a swap cooldown) - validated by token program
+    #[account(mut)]
+    pub trader_state: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Theme vault SOL account - validated by token program
+    #[account(mut)]
+    pub vault_sol_account: UncheckedAccount<'info>,
+
+    /// CHECK: 可选，投票者在结算程序中的声誉记录，按布局校验后计算权重加成
+    pub reputation: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    /// CHECK: 主题代币程序的事件 authority - validated by token program
+    pub theme_event_authority: UncheckedAccount<'info>,
+
+    pub theme_program: Program<'info, TasteFunToken>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RestakeVote<'info> {
+    #[account(mut)]
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + Vote::SPACE,
+        seeds = [b"vote", idea.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote: Box<Account<'info, Vote>>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + ReviewerStake::SPACE,
+        seeds = [b"reviewer_stake", idea.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub reviewer_stake: Box<Account<'info, ReviewerStake>>,
+
+    /// 结算程序的复投授权 PDA，仅结算程序可以签名
+    #[account(seeds = [b"restake_authority"], bump, seeds::program = SETTLEMENT_PROGRAM_ID)]
+    pub restake_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VaultTransfer<'info> {
+    #[account(seeds = [b"vault", vault.idea.as_ref()], bump = vault.bump)]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut, token::authority = vault)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: 接收方代币账户，由 token 程序校验
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    /// 结算程序的授权 PDA，仅结算程序可以签名
+    #[account(seeds = [b"settlement_authority"], bump, seeds::program = SETTLEMENT_PROGRAM_ID)]
+    pub settlement_authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct VaultBurn<'info> {
+    #[account(seeds = [b"vault", vault.idea.as_ref()], bump = vault.bump)]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut, token::authority = vault, token::mint = token_mint)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub token_mint: Account<'info, Mint>,
+
+    /// 结算程序的授权 PDA，仅结算程序可以签名
+    #[account(seeds = [b"settlement_authority"], bump, seeds::program = SETTLEMENT_PROGRAM_ID)]
+    pub settlement_authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.idea.as_ref()],
+        bump = vault.bump,
+        close = receiver
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut, token::authority = vault)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: 租金接收方，由结算程序校验
+    #[account(mut)]
+    pub receiver: UncheckedAccount<'info>,
+
+    /// 结算程序的授权 PDA，仅结算程序可以签名
+    #[account(seeds = [b"settlement_authority"], bump, seeds::program = SETTLEMENT_PROGRAM_ID)]
+    pub settlement_authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ApplySettlement<'info> {
+    #[account(mut)]
+    pub idea: Box<Account<'info, Idea>>,
+
+    /// 结算程序的授权 PDA，仅结算程序可以签名
+    #[account(seeds = [b"settlement_authority"], bump, seeds::program = SETTLEMENT_PROGRAM_ID)]
+    pub settlement_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleReviewerStake<'info> {
+    #[account(mut)]
+    pub reviewer_stake: Account<'info, ReviewerStake>,
+
+    /// 结算程序的授权 PDA，仅结算程序可以签名
+    #[account(seeds = [b"settlement_authority"], bump, seeds::program = SETTLEMENT_PROGRAM_ID)]
+    pub settlement_authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DelegateStake<'info> {
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        init_if_needed,
+        payer = delegator,
+        space = 8 + Delegation::SPACE,
+        seeds = [
+            b"delegation",
+            delegator.key().as_ref(),
+            curator.key().as_ref(),
+            theme_token_mint.key().as_ref()
+        ],
+        bump
+    )]
+    pub delegation: Box<Account<'info, Delegation>>,
+
+    pub theme_token_mint: Box<Account<'info, Mint>>,
+
+    /// 委托托管账户 (委托 PDA 的 ATA)
+    #[account(
+        init_if_needed,
+        payer = delegator,
+        associated_token::mint = theme_token_mint,
+        associated_token::authority = delegation,
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = theme_token_mint,
+        token::authority = delegator,
+    )]
+    pub delegator_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: 受托策展人钱包，仅作为 PDA 种子与记录
+    pub curator: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub delegator: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UndelegateStake<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"delegation",
+            delegator.key().as_ref(),
+            delegation.curator.as_ref(),
+            delegation.theme_token_mint.as_ref()
+        ],
+        bump = delegation.bump,
+        has_one = delegator
+    )]
+    pub delegation: Box<Account<'info, Delegation>>,
+
+    #[account(
+        mut,
+        associated_token::mint = delegation.theme_token_mint,
+        associated_token::authority = delegation,
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = delegation.theme_token_mint,
+        token::authority = delegator,
+    )]
+    pub delegator_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub delegator: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct VoteAsDelegate<'info> {
+    #[account(mut)]
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"delegation",
+            delegation.delegator.as_ref(),
+            curator.key().as_ref(),
+            idea.theme_token_mint.as_ref()
+        ],
+        bump = delegation.bump,
+        has_one = curator
+    )]
+    pub delegation: Box<Account<'info, Delegation>>,
+
+    #[account(
+        mut,
+        associated_token::mint = idea.theme_token_mint,
+        associated_token::authority = delegation,
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = curator,
+        space = 8 + Vote::SPACE,
+        seeds = [b"vote", idea.key().as_ref(), delegation.key().as_ref()],
+        bump
+    )]
+    pub vote: Box<Account<'info, Vote>>,
+
+    #[account(
+        init,
+        payer = curator,
+        space = 8 + ReviewerStake::SPACE,
+        seeds = [b"reviewer_stake", idea.key().as_ref(), delegation.key().as_ref()],
+        bump
+    )]
+    pub reviewer_stake: Box<Account<'info, ReviewerStake>>,
+
+    #[account(seeds = [b"vault", idea.key().as_ref()], bump = idea.vault_bump)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        token::mint = idea.theme_token_mint,
+        token::authority = vault,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: 可选，策展人在结算程序中的声誉记录，按布局校验后计算权重加成
+    pub reputation: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub curator: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(squad_id: u64)]
+pub struct CreateSquad<'info> {
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        init,
+        payer = captain,
+        space = 8 + Squad::SPACE,
+        seeds = [b"squad", captain.key().as_ref(), squad_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub squad: Box<Account<'info, Squad>>,
+
+    pub theme_token_mint: Box<Account<'info, Mint>>,
+
+    /// 战队托管账户 (战队 PDA 的 ATA)
+    #[account(
+        init,
+        payer = captain,
+        associated_token::mint = theme_token_mint,
+        associated_token::authority = squad,
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub captain: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DepositToSquad<'info> {
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"squad", squad.captain.as_ref(), squad.squad_id.to_le_bytes().as_ref()],
+        bump = squad.bump
+    )]
+    pub squad: Box<Account<'info, Squad>>,
+
+    #[account(
+        init_if_needed,
+        payer = member,
+        space = 8 + SquadMember::SPACE,
+        seeds = [b"squad_member", squad.key().as_ref(), member.key().as_ref()],
+        bump
+    )]
+    pub squad_member: Box<Account<'info, SquadMember>>,
+
+    #[account(
+        mut,
+        associated_token::mint = squad.theme_token_mint,
+        associated_token::authority = squad,
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = squad.theme_token_mint,
+        token::authority = member,
+    )]
+    pub member_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub member: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawFromSquad<'info> {
+    #[account(
+        mut,
+        seeds = [b"squad", squad.captain.as_ref(), squad.squad_id.to_le_bytes().as_ref()],
+        bump = squad.bump
+    )]
+    pub squad: Box<Account<'info, Squad>>,
+
+    #[account(
+        mut,
+        seeds = [b"squad_member", squad.key().as_ref(), member.key().as_ref()],
+        bump = squad_member.bump,
+        has_one = squad,
+        has_one = member
+    )]
+    pub squad_member: Box<Account<'info, SquadMember>>,
+
+    #[account(
+        mut,
+        associated_token::mint = squad.theme_token_mint,
+        associated_token::authority = squad,
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = squad.theme_token_mint,
+        token::authority = member,
+    )]
+    pub member_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub member: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SquadVote<'info> {
+    #[account(mut)]
     pub idea: Box<Account<'info, Idea>>,
 
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"squad", captain.key().as_ref(), squad.squad_id.to_le_bytes().as_ref()],
+        bump = squad.bump,
+        has_one = captain,
+        constraint = squad.theme_token_mint == idea.theme_token_mint @ ConsensusError::InvalidMint
+    )]
+    pub squad: Box<Account<'info, Squad>>,
+
+    #[account(
+        mut,
+        associated_token::mint = idea.theme_token_mint,
+        associated_token::authority = squad,
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
     #[account(
         init,
-        payer = initiator,
-        space = 8 + Vault::SPACE,
-        seeds = [b"vault", idea.key().as_ref()],
+        payer = captain,
+        space = 8 + Vote::SPACE,
+        seeds = [b"vote", idea.key().as_ref(), squad.key().as_ref()],
+        bump
+    )]
+    pub vote: Box<Account<'info, Vote>>,
+
+    #[account(
+        init,
+        payer = captain,
+        space = 8 + ReviewerStake::SPACE,
+        seeds = [b"reviewer_stake", idea.key().as_ref(), squad.key().as_ref()],
         bump
     )]
+    pub reviewer_stake: Box<Account<'info, ReviewerStake>>,
+
+    #[account(seeds = [b"vault", idea.key().as_ref()], bump = idea.vault_bump)]
     pub vault: Box<Account<'info, Vault>>,
 
-    /// CHECK: Theme token mint - validated by constraint
     #[account(
-        constraint = theme_token_mint.key() != Pubkey::default() @ ConsensusError::InvalidTheme
+        mut,
+        token::mint = idea.theme_token_mint,
+        token::authority = vault,
     )]
-    pub theme_token_mint: UncheckedAccount<'info>,
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
 
-    /// CHECK: Validated by token program via transfer
-    #[account(mut)]
-    pub sponsor_token_account: AccountInfo<'info>,
+    /// CHECK: 可选，战队在结算程序中的声誉记录，按布局校验后计算权重加成
+    pub reputation: Option<UncheckedAccount<'info>>,
 
-    /// CHECK: Validated by token program via transfer
     #[account(mut)]
-    pub vault_token_account: AccountInfo<'info>,
+    pub captain: Signer<'info>,
 
-    #[account(mut)]
-    pub initiator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
 
-    /// CHECK: Sponsor account providing initial prize pool
-    #[account(mut)]
-    pub sponsor: Signer<'info>,
+#[derive(Accounts)]
+pub struct ReleaseSquadStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"squad", squad.captain.as_ref(), squad.squad_id.to_le_bytes().as_ref()],
+        bump = squad.bump
+    )]
+    pub squad: Box<Account<'info, Squad>>,
+
+    /// 结算程序的战队授权 PDA，仅结算程序可以签名
+    #[account(seeds = [b"squad_authority"], bump, seeds::program = SETTLEMENT_PROGRAM_ID)]
+    pub squad_authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FollowCurator<'info> {
+    #[account(
+        init,
+        payer = follower,
+        space = 8 + Follow::SPACE,
+        seeds = [
+            b"follow",
+            follower.key().as_ref(),
+            curator.key().as_ref(),
+            theme_token_mint.key().as_ref()
+        ],
+        bump
+    )]
+    pub follow: Box<Account<'info, Follow>>,
+
+    pub theme_token_mint: Box<Account<'info, Mint>>,
+
+    /// 跟投余额托管账户 (关注 PDA 的 ATA)
+    #[account(
+        init,
+        payer = follower,
+        associated_token::mint = theme_token_mint,
+        associated_token::authority = follow,
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: 被关注的策展人钱包，仅作为 PDA 种子与记录
+    pub curator: UncheckedAccount<'info>,
 
-    /// CHECK: Protocol treasury account
     #[account(mut)]
-    pub protocol_treasury: UncheckedAccount<'info>,
+    pub follower: Signer<'info>,
 
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct ConfirmImages<'info> {
-    #[account(mut)]
-    pub idea: Account<'info, Idea>,
+pub struct UpdateFollow<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"follow",
+            follower.key().as_ref(),
+            follow.curator.as_ref(),
+            follow.theme_token_mint.as_ref()
+        ],
+        bump = follow.bump,
+        has_one = follower
+    )]
+    pub follow: Box<Account<'info, Follow>>,
 
-    /// CHECK: 授权的 DePIN 服务账户
-    pub depin_authority: Signer<'info>,
+    pub follower: Signer<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(image_index: u8, token_amount: u64)]
-pub struct VoteForImage<'info> {
+pub struct FundFollow<'info> {
+    #[account(
+        seeds = [
+            b"follow",
+            follower.key().as_ref(),
+            follow.curator.as_ref(),
+            follow.theme_token_mint.as_ref()
+        ],
+        bump = follow.bump,
+        has_one = follower
+    )]
+    pub follow: Box<Account<'info, Follow>>,
+
+    #[account(
+        mut,
+        associated_token::mint = follow.theme_token_mint,
+        associated_token::authority = follow,
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = follow.theme_token_mint,
+        token::authority = follower,
+    )]
+    pub follower_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub follower: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MirrorVote<'info> {
     #[account(mut)]
     pub idea: Box<Account<'info, Idea>>,
 
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        seeds = [
+            b"follow",
+            follow.follower.as_ref(),
+            follow.curator.as_ref(),
+            idea.theme_token_mint.as_ref()
+        ],
+        bump = follow.bump
+    )]
+    pub follow: Box<Account<'info, Follow>>,
+
+    /// 被关注策展人在该创意上的投票
+    #[account(
+        seeds = [b"vote", idea.key().as_ref(), follow.curator.as_ref()],
+        bump
+    )]
+    pub curator_vote: Box<Account<'info, Vote>>,
+
+    #[account(
+        mut,
+        associated_token::mint = idea.theme_token_mint,
+        associated_token::authority = follow,
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
     #[account(
         init,
-        payer = voter,
+        payer = keeper,
         space = 8 + Vote::SPACE,
-        seeds = [b"vote", idea.key().as_ref(), voter.key().as_ref()],
+        seeds = [b"vote", idea.key().as_ref(), follow.key().as_ref()],
         bump
     )]
     pub vote: Box<Account<'info, Vote>>,
 
     #[account(
         init,
-        payer = voter,
+        payer = keeper,
         space = 8 + ReviewerStake::SPACE,
-        seeds = [b"reviewer_stake", idea.key().as_ref(), voter.key().as_ref()],
+        seeds = [b"reviewer_stake", idea.key().as_ref(), follow.key().as_ref()],
         bump
     )]
     pub reviewer_stake: Box<Account<'info, ReviewerStake>>,
 
-    #[account(mut, seeds = [b"vault", idea.key().as_ref()], bump = idea.vault_bump)]
+    #[account(seeds = [b"vault", idea.key().as_ref()], bump = idea.vault_bump)]
     pub vault: Box<Account<'info, Vault>>,
 
-    /// CHECK: Validated by token program via transfer
+    #[account(
+        mut,
+        token::mint = idea.theme_token_mint,
+        token::authority = vault,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
     #[account(mut)]
-    pub voter_token_account: AccountInfo<'info>,
+    pub keeper: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(comment_id: u32)]
+pub struct PostComment<'info> {
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        init,
+        payer = author,
+        space = 8 + Comment::SPACE,
+        seeds = [
+            b"comment",
+            idea.key().as_ref(),
+            author.key().as_ref(),
+            comment_id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub comment: Box<Account<'info, Comment>>,
+
+    #[account(address = idea.theme_token_mint @ ConsensusError::InvalidMint)]
+    pub theme_token_mint: Box<Account<'info, Mint>>,
+
+    /// 评论押金托管账户 (评论 PDA 的 ATA)
+    #[account(
+        init,
+        payer = author,
+        associated_token::mint = theme_token_mint,
+        associated_token::authority = comment,
+    )]
+    pub deposit_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = theme_token_mint,
+        token::authority = author,
+    )]
+    pub author_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub author: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseComment<'info> {
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"comment",
+            idea.key().as_ref(),
+            author.key().as_ref(),
+            comment.comment_id.to_le_bytes().as_ref()
+        ],
+        bump = comment.bump,
+        has_one = idea,
+        has_one = author,
+        close = author
+    )]
+    pub comment: Box<Account<'info, Comment>>,
+
+    #[account(
+        mut,
+        associated_token::mint = idea.theme_token_mint,
+        associated_token::authority = comment,
+    )]
+    pub deposit_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = idea.theme_token_mint,
+        token::authority = author,
+    )]
+    pub author_token_account: Box<Account<'info, TokenAccount>>,
 
-    /// CHECK: Validated by token program via transfer
     #[account(mut)]
-    pub vault_token_account: AccountInfo<'info>,
+    pub author: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FlagComment<'info> {
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"comment",
+            idea.key().as_ref(),
+            author.key().as_ref(),
+            comment.comment_id.to_le_bytes().as_ref()
+        ],
+        bump = comment.bump,
+        has_one = idea,
+        has_one = author,
+        close = author
+    )]
+    pub comment: Box<Account<'info, Comment>>,
+
+    #[account(
+        mut,
+        associated_token::mint = idea.theme_token_mint,
+        associated_token::authority = comment,
+    )]
+    pub deposit_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(seeds = [b"roles"], bump = roles.bump)]
+    pub roles: Box<Account<'info, Roles>>,
 
+    /// 协议财库的主题代币账户，接收罚没的押金
+    #[account(
+        mut,
+        token::mint = idea.theme_token_mint,
+        token::authority = protocol_config.treasury,
+    )]
+    pub protocol_treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: 评论作者，接收评论与押金账户的租金
     #[account(mut)]
-    pub voter: Signer<'info>,
+    pub author: UncheckedAccount<'info>,
+
+    pub moderator: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelIdea<'info> {
+    #[account(mut)]
+    pub idea: Account<'info, Idea>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeProtocolConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ProtocolConfig::SPACE,
+        seeds = [b"protocol_config"],
+        bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Roles::SPACE,
+        seeds = [b"roles"],
+        bump
+    )]
+    pub roles: Account<'info, Roles>,
+
+    /// 本程序的 ProgramData，其升级权限即初始化者
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = anchor_lang::solana_program::bpf_loader_upgradeable::ID
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateProtocolConfig<'info> {
+    #[account(mut, seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(seeds = [b"roles"], bump = roles.bump)]
+    pub roles: Account<'info, Roles>,
+
+    /// 持有对应角色的签名者，由各指令的 access_control 校验
+    pub signer: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetImageStorageConfig<'info> {
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        has_one = authority @ ConsensusError::Unauthorized
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ImageStorageConfig::SPACE,
+        seeds = [b"image_storage_config"],
+        bump
+    )]
+    pub image_storage_config: Account<'info, ImageStorageConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ManageRoles<'info> {
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        has_one = authority @ ConsensusError::Unauthorized
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(mut, seeds = [b"roles"], bump = roles.bump)]
+    pub roles: Account<'info, Roles>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: 待升级的本程序账户，按 discriminator 识别类型后再反序列化
+    #[account(mut, owner = crate::ID @ ConsensusError::UnsupportedMigration)]
+    pub account: UncheckedAccount<'info>,
+
+    /// CHECK: 全局协议配置，由 is_protocol_config_authority 按固定偏移读取管理员
+    #[account(seeds = [b"protocol_config"], bump)]
+    pub protocol_config: UncheckedAccount<'info>,
+
+    /// 协议管理员，补足 realloc 所需租金
+    #[account(mut)]
+    pub authority: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct CancelIdea<'info> {
+pub struct MigrateIdeaStats<'info> {
     #[account(mut)]
-    pub idea: Account<'info, Idea>,
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + IdeaMigration::SPACE,
+        seeds = [b"idea_migration", idea.key().as_ref()],
+        bump
+    )]
+    pub idea_migration: Box<Account<'info, IdeaMigration>>,
 
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(mut)]
     pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 // -----------------------------------------------------------------------------
@@ -481,10 +4440,18 @@ pub struct CancelIdea<'info> {
 pub struct IdeaCreated {
     pub idea: Pubkey,
     pub initiator: Pubkey,
+    pub curator: Pubkey,
     pub prompt: String,
     pub depin_provider: Pubkey,
 }
 
+#[event]
+pub struct RemixCreated {
+    pub idea: Pubkey,
+    pub parent: Pubkey,
+    pub remix_fee_bps: u16,
+}
+
 #[event]
 pub struct SponsoredIdeaCreated {
     pub idea: Pubkey,
@@ -493,6 +4460,102 @@ pub struct SponsoredIdeaCreated {
     pub prompt: String,
     pub initial_prize_pool: u64,
     pub depin_provider: Pubkey,
+    pub sponsor_profile: Option<Pubkey>,
+    pub no_loss: bool,
+}
+
+#[event]
+pub struct SeriesCreated {
+    pub series: Pubkey,
+    pub sponsor: Pubkey,
+    pub theme: Pubkey,
+    pub interval_secs: i64,
+    pub prize_per_episode: u64,
+    pub max_episodes: u64,
+}
+
+#[event]
+pub struct SeriesEpisodeCreated {
+    pub series: Pubkey,
+    pub idea: Pubkey,
+    pub episode: u64,
+    pub initial_prize_pool: u64,
+}
+
+#[event]
+pub struct SeriesClosed {
+    pub series: Pubkey,
+    pub episodes_created: u64,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct CollectionCreated {
+    pub collection: Pubkey,
+    pub initiator: Pubkey,
+    pub theme: Pubkey,
+    pub name: String,
+    pub grand_prize_bps: u16,
+    pub ends_at: i64,
+}
+
+#[event]
+pub struct IdeaAddedToCollection {
+    pub collection: Pubkey,
+    pub idea: Pubkey,
+    pub idea_count: u64,
+}
+
+#[event]
+pub struct SponsorProfileUpdated {
+    pub sponsor_profile: Pubkey,
+    pub sponsor: Pubkey,
+    pub name: [u8; 32],
+    pub logo_uri: String,
+    pub link: String,
+}
+
+#[event]
+pub struct EscrowFunded {
+    pub idea: Pubkey,
+    pub sponsor: Pubkey,
+    pub tranches: [u64; ESCROW_MILESTONE_COUNT],
+}
+
+#[event]
+pub struct EscrowTrancheReleased {
+    pub idea: Pubkey,
+    pub milestone: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct EscrowRefunded {
+    pub idea: Pubkey,
+    pub sponsor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct VoteGateUpdated {
+    pub idea: Pubkey,
+    pub allowlist_root: Option<[u8; 32]>,
+    pub gate_mint: Option<Pubkey>,
+    pub gate_min_amount: u64,
+}
+
+#[event]
+pub struct BonusJudgeSet {
+    pub idea: Pubkey,
+    pub judge: Pubkey,
+    pub bonus_amount: u64,
+}
+
+#[event]
+pub struct ImagesCommitted {
+    pub idea: Pubkey,
+    pub image_hashes: [[u8; 32]; 4],
+    pub reveal_ts: i64,
 }
 
 #[event]
@@ -509,102 +4572,504 @@ pub struct VoteCast {
     pub stake_amount: u64,
 }
 
+#[event]
+pub struct StakeDelegated {
+    pub delegation: Pubkey,
+    pub delegator: Pubkey,
+    pub curator: Pubkey,
+    pub amount: u64,
+    pub curator_cut_bps: u16,
+}
+
+#[event]
+pub struct StakeUndelegated {
+    pub delegation: Pubkey,
+    pub delegator: Pubkey,
+    pub amount: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct DelegatedVoteCast {
+    pub idea: Pubkey,
+    pub delegation: Pubkey,
+    pub curator: Pubkey,
+    pub stake_amount: u64,
+}
+
+#[event]
+pub struct SquadCreated {
+    pub squad: Pubkey,
+    pub captain: Pubkey,
+    pub theme_token_mint: Pubkey,
+}
+
+#[event]
+pub struct SquadDeposited {
+    pub squad: Pubkey,
+    pub member: Pubkey,
+    pub amount: u64,
+    pub shares: u64,
+}
+
+#[event]
+pub struct SquadWithdrawn {
+    pub squad: Pubkey,
+    pub member: Pubkey,
+    pub amount: u64,
+    pub shares: u64,
+}
+
+#[event]
+pub struct CuratorFollowed {
+    pub follow: Pubkey,
+    pub follower: Pubkey,
+    pub curator: Pubkey,
+    pub tip_bps: u16,
+}
+
+#[event]
+pub struct FollowMirrorUpdated {
+    pub follow: Pubkey,
+    pub mirror_amount: u64,
+    pub auto_mirror: bool,
+}
+
+#[event]
+pub struct FollowBalanceChanged {
+    pub follow: Pubkey,
+    pub deposit: bool,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CommentPosted {
+    pub idea: Pubkey,
+    pub comment: Pubkey,
+    pub author: Pubkey,
+    pub content_hash: [u8; 32],
+    pub uri: String,
+    pub deposit: u64,
+}
+
+#[event]
+pub struct CommentClosed {
+    pub comment: Pubkey,
+    pub author: Pubkey,
+    pub refunded: u64,
+    pub slashed: bool,
+}
+
 #[event]
 pub struct IdeaCancelled {
     pub idea: Pubkey,
     pub reason: String,
 }
 
+#[event]
+pub struct JackpotFunded {
+    pub theme: Pubkey,
+    pub idea: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ProtocolConfigUpdated {
+    pub treasury: Pubkey,
+    pub creation_fee: u64,
+    pub platform_fee_bps: u16,
+}
+
+#[event]
+pub struct FeeDiscountTiersUpdated {
+    pub reputation_min_wins: u32,
+    pub reputation_min_accuracy_bps: u16,
+    pub reputation_discount_bps: u16,
+    pub staker_min_amount: u64,
+    pub staker_discount_bps: u16,
+}
+
+/// 赛季积分累计 (链下按赛季汇总，见 POINTS_KIND_*)
+#[event]
+pub struct PointsAccrued {
+    pub user: Pubkey,
+    pub kind: u8,
+    pub points: u64,
+}
+
+#[event]
+pub struct SeasonStarted {
+    pub season: u32,
+    pub started_at: i64,
+}
+
+#[event]
+pub struct ProtocolPauseToggled {
+    pub guardian: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct ImageStorageConfigUpdated {
+    pub allowed_prefixes: Vec<String>,
+    pub gateway_base: String,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub role: u8,
+    pub member: Pubkey,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub role: u8,
+    pub member: Pubkey,
+}
+
+#[event]
+pub struct IdeaStatsMigrated {
+    pub idea: Pubkey,
+    pub processed: u64,
+    pub total_voters: u64,
+    pub completed: bool,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub kind: u8,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub size: u32,
+}
+
 // -----------------------------------------------------------------------------
 // Account Structures
 // -----------------------------------------------------------------------------
 
+idea_accounts!();
+
+/// 结算程序经 CPI 写回的 Idea 字段 (Idea 归本程序所有，结算程序不能直接写入)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct IdeaSettlement {
+    pub status: IdeaStatus,
+    pub cancel_reason: u8,
+    pub winning_image_index: Option<u8>,
+    pub secondary_winning_index: Option<u8>,
+    pub curator_fee_collected: u64,
+    pub platform_fee_collected: u64,
+    pub insurance_contribution: u64,
+    pub penalty_pool_amount: u64,
+    pub winner_count: u64,
+    pub dispute_deadline: i64,
+    pub claim_deadline: i64,
+    pub disputed: bool,
+    pub refund_rebates_paid: u64,
+    pub bonus_pool: u64,
+    pub remix_royalty: u64,
+    pub outstanding_vesting: u64,
+}
+
+impl IdeaSettlement {
+    pub fn from_idea(idea: &Idea) -> Self {
+        Self {
+            status: idea.status,
+            cancel_reason: idea.cancel_reason,
+            winning_image_index: idea.winning_image_index,
+            secondary_winning_index: idea.secondary_winning_index,
+            curator_fee_collected: idea.curator_fee_collected,
+            platform_fee_collected: idea.platform_fee_collected,
+            insurance_contribution: idea.insurance_contribution,
+            penalty_pool_amount: idea.penalty_pool_amount,
+            winner_count: idea.winner_count,
+            dispute_deadline: idea.dispute_deadline,
+            claim_deadline: idea.claim_deadline,
+            disputed: idea.disputed,
+            refund_rebates_paid: idea.refund_rebates_paid,
+            bonus_pool: idea.bonus_pool,
+            remix_royalty: idea.remix_royalty,
+            outstanding_vesting: idea.outstanding_vesting,
+        }
+    }
+
+    fn apply(&self, idea: &mut Idea) {
+        idea.status = self.status;
+        idea.cancel_reason = self.cancel_reason;
+        idea.winning_image_index = self.winning_image_index;
+        idea.secondary_winning_index = self.secondary_winning_index;
+        idea.curator_fee_collected = self.curator_fee_collected;
+        idea.platform_fee_collected = self.platform_fee_collected;
+        idea.insurance_contribution = self.insurance_contribution;
+        idea.penalty_pool_amount = self.penalty_pool_amount;
+        idea.winner_count = self.winner_count;
+        idea.dispute_deadline = self.dispute_deadline;
+        idea.claim_deadline = self.claim_deadline;
+        idea.disputed = self.disputed;
+        idea.refund_rebates_paid = self.refund_rebates_paid;
+        idea.bonus_pool = self.bonus_pool;
+        idea.remix_royalty = self.remix_royalty;
+        idea.outstanding_vesting = self.outstanding_vesting;
+    }
+}
+
 #[account]
-pub struct Idea {
-    // 核心字段
-    pub initiator: Pubkey,
-    pub idea_id: u64,
-    pub prompt: String,
+pub struct SponsorProfile {
+    pub sponsor: Pubkey,
+    pub name: [u8; 32],
+    pub logo_uri: String,
+    pub link: String,
     pub created_at: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+}
 
-    // 主题关联（新增）
+impl SponsorProfile {
+    pub const SPACE: usize = SPONSOR_PROFILE_SPACE;
+}
+
+#[account]
+pub struct Jackpot {
     pub theme: Pubkey,
     pub theme_token_mint: Pubkey,
+    pub total_paid_out: u64,
+    pub last_funded_idea: Option<Pubkey>,
+    pub bump: u8,
+}
 
-    // DePIN 相关
-    pub image_uris: Vec<String>,
-    pub generation_status: GenerationStatus,
-    pub generation_deadline: i64,
-    pub depin_provider: Pubkey,
+impl Jackpot {
+    pub const SPACE: usize = JACKPOT_SPACE;
+}
 
-    // 赞助竞赛相关
-    pub sponsor: Option<Pubkey>,
-    pub initial_prize_pool: u64,
+/// Idea v1→v2 迁移进度：已按升序处理到 cursor 的投票记录及其累计的每图统计
+#[account]
+pub struct IdeaMigration {
+    pub idea: Pubkey,
+    pub cursor: Pubkey,
+    pub processed: u64,
+    pub stake_totals: [u64; 4],
+    pub voter_counts: [u32; 4],
+    pub payout_weights: [u128; 4],
+    pub last_vote_ts: [i64; 4],
+    pub bump: u8,
+}
 
-    // 质押池参数
-    pub total_staked: u64,
-    pub min_stake: u64,
-    pub curator_fee_bps: u16,
+impl IdeaMigration {
+    pub const SPACE: usize = IDEA_MIGRATION_SPACE;
+}
 
-    // 投票统计 (存储投票权重，非票数)
-    pub votes: [u64; 4],
-    pub reject_all_weight: u64,
-    pub total_voters: u64,
-    pub winning_image_index: Option<u8>,
+/// 全局协议配置 PDA，core 与 settlement 据此约束国库与费用
+#[account]
+pub struct ProtocolConfig {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub creation_fee: u64,
+    pub platform_fee_bps: u16,
+    /// 声誉档位：已领取结果中胜场与胜率均达标的评审享受折扣
+    pub reputation_discount_min_wins: u32,
+    pub reputation_discount_min_accuracy_bps: u16,
+    pub reputation_discount_bps: u16,
+    /// 质押档位：在创意所属主题质押代币达标的评审享受折扣
+    pub staker_discount_min_amount: u64,
+    pub staker_discount_bps: u16,
+    /// 当前赛季编号，递增后排行榜按新赛季重新累计
+    pub season: u32,
+    pub season_started_at: i64,
+    pub bump: u8,
+    /// token 程序按 PROTOCOL_CONFIG_PAUSED_OFFSET 读取，之后只能追加字段
+    pub paused: bool,
+    /// 账户结构版本 (PROTOCOL_CONFIG_VERSION)
+    pub version: u8,
+}
 
-    // 结算数据
-    pub curator_fee_collected: u64,
-    pub platform_fee_collected: u64,
-    pub penalty_pool_amount: u64,
-    pub winner_count: u64,
+impl ProtocolConfig {
+    pub const SPACE: usize = PROTOCOL_CONFIG_SPACE;
+}
 
-    // 时间控制
-    pub voting_deadline: i64,
+/// 协议角色表 PDA：各角色独立授予与轮换，未授予时为默认公钥
+#[account]
+pub struct Roles {
+    pub guardian: Pubkey,
+    pub fee_setter: Pubkey,
+    pub depin_admin: Pubkey,
+    pub treasurer: Pubkey,
+    pub moderator: Pubkey,
+    pub bump: u8,
+}
 
-    // 状态与 bumps
-    pub status: IdeaStatus,
-    pub vault_bump: u8,
-    pub idea_bump: u8,
+impl Roles {
+    pub const SPACE: usize = ROLES_SPACE;
+
+    pub fn holder_mut(&mut self, role: u8) -> Result<&mut Pubkey> {
+        match role {
+            ROLE_GUARDIAN => Ok(&mut self.guardian),
+            ROLE_FEE_SETTER => Ok(&mut self.fee_setter),
+            ROLE_DEPIN_ADMIN => Ok(&mut self.depin_admin),
+            ROLE_TREASURER => Ok(&mut self.treasurer),
+            ROLE_MODERATOR => Ok(&mut self.moderator),
+            _ => err!(ConsensusError::InvalidRole),
+        }
+    }
+}
+
+/// 图片存储配置 PDA：允许的 URI 前缀 (存储后端) 与前端使用的网关地址
+#[account]
+pub struct ImageStorageConfig {
+    pub allowed_prefixes: Vec<String>,
+    pub gateway_base: String,
+    pub bump: u8,
 }
 
-impl Idea {
-    pub const SPACE: usize = IDEA_SPACE + 64; // Added theme + theme_token_mint
+impl ImageStorageConfig {
+    pub const SPACE: usize = IMAGE_STORAGE_CONFIG_SPACE;
 }
 
 #[account]
-pub struct Vault {
+pub struct PromptRegistry {
+    pub theme: Pubkey,
+    pub entries: [PromptRecord; RECENT_PROMPT_SLOTS],
+    pub bump: u8,
+}
+
+impl PromptRegistry {
+    pub const SPACE: usize = PROMPT_REGISTRY_SPACE;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct PromptRecord {
     pub idea: Pubkey,
+    pub initiator: Pubkey,
+    pub prompt_hash: [u8; 32],
+    pub active_until: i64,
+}
+
+#[account]
+pub struct Series {
+    pub sponsor: Pubkey,
+    pub series_id: u64,
+    pub theme: Pubkey,
+    pub theme_token_mint: Pubkey,
+    pub prompt: String,
+    pub depin_provider: Pubkey,
+    pub interval_secs: i64,
+    pub prize_per_episode: u64,
+    pub max_episodes: u64,
+    pub episodes_created: u64,
+    pub next_episode_at: i64,
+    pub no_loss: bool,
+    pub active: bool,
+    pub bump: u8,
+}
+
+impl Series {
+    pub const SPACE: usize = SERIES_SPACE;
+}
+
+#[account]
+pub struct Collection {
+    pub initiator: Pubkey,
+    pub collection_id: u64,
+    pub theme: Pubkey,
+    pub theme_token_mint: Pubkey,
+    pub name: String,
+    pub metadata_uri: String,
+    pub grand_prize_bps: u16,
+    pub ends_at: i64,
+    pub idea_count: u64,
     pub bump: u8,
 }
 
-impl Vault {
-    pub const SPACE: usize = VAULT_SPACE;
+impl Collection {
+    pub const SPACE: usize = COLLECTION_SPACE;
 }
 
 #[account]
-pub struct Vote {
+pub struct SponsorEscrow {
     pub idea: Pubkey,
-    pub voter: Pubkey,
-    pub image_choice: u8,
-    pub stake_amount: u64,
-    pub vote_weight: u64,
-    pub ts: i64,
+    pub sponsor: Pubkey,
+    pub token_mint: Pubkey,
+    pub tranches: [u64; ESCROW_MILESTONE_COUNT],
+    pub released_mask: u8,
+    pub refunded: bool,
+    pub bump: u8,
+}
+
+impl SponsorEscrow {
+    pub const SPACE: usize = SPONSOR_ESCROW_SPACE;
+}
+
+/// 质押委托 PDA：托管代币由策展人代为投票，amount 为尚未投出的余额
+#[account]
+pub struct Delegation {
+    pub delegator: Pubkey,
+    pub curator: Pubkey,
+    pub theme_token_mint: Pubkey,
+    pub amount: u64,
+    pub curator_cut_bps: u16,
+    pub bump: u8,
+}
+
+impl Delegation {
+    pub const SPACE: usize = DELEGATION_SPACE;
+}
+
+/// 战队 PDA：托管成员存入的主题代币，active_stake 为投票中尚未结算的本金
+#[account]
+pub struct Squad {
+    pub captain: Pubkey,
+    pub squad_id: u64,
+    pub theme_token_mint: Pubkey,
+    pub total_shares: u64,
+    pub active_stake: u64,
+    pub member_count: u32,
+    pub bump: u8,
+}
+
+impl Squad {
+    pub const SPACE: usize = SQUAD_SPACE;
+}
+
+#[account]
+pub struct SquadMember {
+    pub squad: Pubkey,
+    pub member: Pubkey,
+    pub shares: u64,
+    pub bump: u8,
 }
 
-impl Vote {
-    pub const SPACE: usize = VOTE_SPACE;
+impl SquadMember {
+    pub const SPACE: usize = SQUAD_MEMBER_SPACE;
 }
 
+/// 关注 PDA：follower → curator，托管账户余额用于自动跟投
 #[account]
-pub struct ReviewerStake {
+pub struct Follow {
+    pub follower: Pubkey,
+    pub curator: Pubkey,
+    pub theme_token_mint: Pubkey,
+    pub mirror_amount: u64,
+    pub tip_bps: u16,
+    pub auto_mirror: bool,
+    pub bump: u8,
+}
+
+impl Follow {
+    pub const SPACE: usize = FOLLOW_SPACE;
+}
+
+/// 创意评论 PDA：正文存链下，押金托管在评论 PDA 的 ATA 中
+#[account]
+pub struct Comment {
     pub idea: Pubkey,
-    pub reviewer: Pubkey,
-    pub total_staked: u64,
-    pub is_winner: bool,
-    pub winnings: u64,
+    pub author: Pubkey,
+    pub comment_id: u32,
+    pub content_hash: [u8; 32],
+    pub uri: String,
+    pub deposit: u64,
+    pub created_at: i64,
     pub bump: u8,
 }
 
-impl ReviewerStake {
-    pub const SPACE: usize = REVIEWER_STAKE_SPACE;
+impl Comment {
+    pub const SPACE: usize = COMMENT_SPACE;
 }
@@ -1,552 +1,6768 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::{get_associated_token_address, AssociatedToken};
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer};
+use taste_fun_core::program::TasteFunCore;
+// Idea/Vault/Vote/ReviewerStake 由 core 创建并持有，直接使用 core 的声明 (owner 为 core)，写入经 core CPI
+use taste_fun_core::{Idea, IdeaSettlement, ReviewerStake, Vault, Vote};
 use taste_fun_shared::*;
+use taste_fun_token::program::TasteFunToken;
 
 declare_id!("EeHN1oagPFzfyaye9FPyUjNx4nbnsFy2z3xhWPetVRxH");
 
+/// 由指令上下文构建 core CPI 签名账户 (要求 settlement_authority 与 core_program 字段)
+macro_rules! core_cpi {
+    ($ctx:expr) => {
+        CoreCpi {
+            core_program: $ctx.accounts.core_program.to_account_info(),
+            settlement_authority: $ctx.accounts.settlement_authority.to_account_info(),
+            authority_bump: $ctx.bumps.settlement_authority,
+        }
+    };
+}
+
+/// 由指令上下文构建创意金库的 core CPI 账户 (另需 vault、vault_token_account 与 token_program 字段)
+macro_rules! core_vault {
+    ($ctx:expr) => {
+        CoreVault {
+            core: core_cpi!($ctx),
+            vault: $ctx.accounts.vault.to_account_info(),
+            vault_token_account: $ctx.accounts.vault_token_account.to_account_info(),
+            token_program: $ctx.accounts.token_program.to_account_info(),
+        }
+    };
+}
+
+/// 领取时按折扣档位结清评审的费用份额，返回应并入领取金额的返还额
+/// (要求领取上下文带有 protocol_config、reputation、stake_position 与费用接收账户)
+/// 领取后更新已提供的全局与主题赛季排行榜 (在 record_reputation 之后调用)
+macro_rules! rank_reviewer {
+    ($ctx:ident, $theme:expr) => {{
+        let season = $ctx.accounts.protocol_config.season;
+        if let Some(board) = $ctx.accounts.leaderboard.as_mut() {
+            rank_reviewer(
+                board,
+                GLOBAL_LEADERBOARD_SCOPE,
+                season,
+                $ctx.bumps.leaderboard.ok_or(ConsensusError::InvalidState)?,
+                &$ctx.accounts.reputation,
+            )?;
+        }
+        if let Some(board) = $ctx.accounts.theme_leaderboard.as_mut() {
+            rank_reviewer(
+                board,
+                $theme,
+                season,
+                $ctx.bumps.theme_leaderboard.ok_or(ConsensusError::InvalidState)?,
+                &$ctx.accounts.reputation,
+            )?;
+        }
+    }};
+}
+
+macro_rules! claim_fees {
+    ($ctx:ident, $idea:expr) => {
+        charge_claim_fees(
+            &event_emitter!($ctx),
+            $idea,
+            $ctx.accounts.reviewer.key(),
+            $ctx.accounts.reviewer_stake.total_staked,
+            claim_fee_discount_bps(
+                &$ctx.accounts.protocol_config,
+                $idea,
+                $ctx.accounts.reviewer.key,
+                &$ctx.accounts.reputation,
+                $ctx.accounts.stake_position.as_deref(),
+            )?,
+            ClaimFeeAccounts {
+                vault: &core_vault!($ctx),
+                curator_token_account: $ctx.accounts.curator_token_account.to_account_info(),
+                protocol_treasury_token_account: $ctx
+                    .accounts
+                    .protocol_treasury_token_account
+                    .to_account_info(),
+            },
+        )
+    };
+}
+
 #[program]
 pub mod taste_fun_settlement {
     use super::*;
 
     /// 结算投票，分配奖金 (含时间加权、平台费用、RejectAll逻辑)
-    pub fn settle_voting(ctx: Context<SettleVoting>, voting_mode: VotingMode) -> Result<()> {
-        let idea = &mut ctx.accounts.idea;
-        require!(idea.status == IdeaStatus::Voting, ConsensusError::InvalidState);
+    #[access_control(
+        protocol_active(ctx.accounts.protocol_config.paused)
+        voting_closed(ctx.accounts.idea.status, ctx.accounts.idea.voting_deadline)
+        idea_migrated(ctx.accounts.idea.version)
+    )]
+    pub fn settle_voting(ctx: Context<SettleVoting>) -> Result<()> {
+        let vault = core_vault!(ctx);
+        let emitter = event_emitter!(ctx);
+        let accounts = ctx.accounts;
+        let jackpot = match (&accounts.jackpot, &accounts.jackpot_token_account) {
+            (Some(jackpot), Some(token_account)) => Some(JackpotAccounts {
+                key: jackpot.key(),
+                theme_token_mint: jackpot.theme_token_mint,
+                token_account: token_account.to_account_info(),
+            }),
+            _ => None,
+        };
+        let remix_pool = match (accounts.remix_pool.as_mut(), &accounts.remix_pool_token_account) {
+            (Some(pool), Some(token_account)) => Some(RemixPoolAccounts {
+                pool,
+                token_account: token_account.to_account_info(),
+            }),
+            _ => None,
+        };
+        let collection_prize = match (
+            accounts.collection_prize.as_mut(),
+            &accounts.collection_prize_token_account,
+        ) {
+            (Some(prize), Some(token_account)) => Some(CollectionPrizeAccounts {
+                prize,
+                token_account: token_account.to_account_info(),
+            }),
+            _ => None,
+        };
+        let gallery = match (accounts.gallery.as_mut(), accounts.gallery_page.as_mut()) {
+            (Some(gallery), Some(page)) => Some(GalleryAccounts { gallery, page }),
+            _ => None,
+        };
 
-        let clock = Clock::get()?;
+        settle_idea(SettleAccounts {
+            vault: &vault,
+            token_mint: accounts.token_mint.to_account_info(),
+            vault_mint: accounts.vault_token_account.mint,
+            theme_buyback_token_account: accounts.theme_buyback_token_account.to_account_info(),
+            settler_token_account: accounts.settler_token_account.to_account_info(),
+            settler: accounts.settler.key(),
+            insurance_token_account: accounts.insurance_token_account.to_account_info(),
+            jackpot,
+            remix_pool,
+            collection_prize,
+            gallery,
+            recent_slothashes: accounts.recent_slothashes.to_account_info(),
+            platform_fee_bps: accounts.protocol_config.platform_fee_bps,
+            emitter,
+            idea: &mut accounts.idea,
+            insurance: &mut accounts.insurance,
+        })?;
+        vault.core.sync_idea(&accounts.idea)
+    }
+
+    /// 批量结算: remaining_accounts 按每组 8 个账户排列
+    /// (idea, vault, token_mint, vault_token_account, theme_buyback_token_account,
+    ///  settler_token_account, insurance_token_account, theme)
+    /// 未到期、非投票中、需要奖池滚存、为衍生创意、属于合集或每图统计未迁移的创意会被跳过而不是让整笔交易失败
+    #[access_control(protocol_active(ctx.accounts.protocol_config.paused))]
+    pub fn settle_many<'info>(ctx: Context<'_, '_, 'info, 'info, SettleMany<'info>>) -> Result<()> {
+        let remaining = ctx.remaining_accounts;
         require!(
-            clock.unix_timestamp >= idea.voting_deadline,
-            ConsensusError::VotingNotEnded
+            !remaining.is_empty() && remaining.len() % SETTLE_MANY_ACCOUNTS_PER_IDEA == 0,
+            ConsensusError::InvalidAmount
         );
 
-        // 检查最小参与者数量
-        if idea.total_voters < MIN_REVIEWERS {
-            idea.status = IdeaStatus::Cancelled;
-            emit!(VotingCancelled {
-                idea: idea.key(),
-                reason: "Insufficient participation".to_string(),
-            });
-            return Ok(());
+        let now = Clock::get()?.unix_timestamp;
+        let settler = ctx.accounts.settler.key();
+        let insurance_key = ctx.accounts.insurance.key();
+        let mut settled: u32 = 0;
+        let mut skipped: u32 = 0;
+
+        for group in remaining.chunks(SETTLE_MANY_ACCOUNTS_PER_IDEA) {
+            let mut idea = Box::new(Account::<Idea>::try_from(&group[0])?);
+
+            // 奖池滚存、衍生创意版税与合集大奖需要额外账户，须单独调用 settle_voting
+            let ready = idea.status == IdeaStatus::Voting
+                && now > idea.voting_deadline
+                && idea.rollover_bps == 0
+                && idea.parent.is_none()
+                && idea.collection.is_none()
+                && idea.version >= IDEA_ACCOUNT_VERSION;
+            if !ready {
+                skipped += 1;
+                continue;
+            }
+
+            let vault_key = Pubkey::create_program_address(
+                &[b"vault", idea.key().as_ref(), &[idea.vault_bump]],
+                &taste_fun_core::ID,
+            )
+            .map_err(|_| ConsensusError::InvalidState)?;
+            require_keys_eq!(group[1].key(), vault_key, ConsensusError::InvalidState);
+
+            let vault_token_account = Account::<TokenAccount>::try_from(&group[3])?;
+            require_keys_eq!(group[2].key(), vault_token_account.mint, ConsensusError::InvalidMint);
+            verify_theme_buyback_account(&idea, &group[7], vault_token_account.mint, group[4].key())?;
+
+            let settler_token_account = Account::<TokenAccount>::try_from(&group[5])?;
+            require_keys_eq!(settler_token_account.owner, settler, ConsensusError::Unauthorized);
+            let insurance_token_account = Account::<TokenAccount>::try_from(&group[6])?;
+            require_keys_eq!(
+                insurance_token_account.owner,
+                insurance_key,
+                ConsensusError::Unauthorized
+            );
+
+            let vault = CoreVault {
+                core: core_cpi!(ctx),
+                vault: group[1].clone(),
+                vault_token_account: group[3].clone(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            };
+            settle_idea(SettleAccounts {
+                idea: &mut idea,
+                vault: &vault,
+                token_mint: group[2].clone(),
+                vault_mint: vault_token_account.mint,
+                theme_buyback_token_account: group[4].clone(),
+                settler_token_account: group[5].clone(),
+                settler,
+                insurance: &mut ctx.accounts.insurance,
+                insurance_token_account: group[6].clone(),
+                jackpot: None,
+                remix_pool: None,
+                collection_prize: None,
+                gallery: None,
+                recent_slothashes: ctx.accounts.recent_slothashes.to_account_info(),
+                platform_fee_bps: ctx.accounts.protocol_config.platform_fee_bps,
+                emitter: event_emitter!(ctx),
+            })?;
+            vault.core.sync_idea(&idea)?;
+            settled += 1;
         }
 
-        // 计算总投票权重 (包括 RejectAll)
-        let total_weight = idea.votes.iter().sum::<u64>() + idea.reject_all_weight;
+        emit_cpi!(BatchSettled {
+            settler,
+            settled,
+            skipped,
+        });
+
+        Ok(())
+    }
 
-        // 检查 RejectAll 是否达到 2/3 阈值
-        if total_weight > 0 {
-            let reject_ratio_bps = (idea.reject_all_weight as u128)
-                .checked_mul(BPS_DENOMINATOR as u128)
-                .and_then(|x| x.checked_div(total_weight as u128))
-                .and_then(|x| u16::try_from(x).ok())
+    /// 提取奖金
+    #[access_control(
+        claimable(&ctx.accounts.idea)
+        not_withdrawn(ctx.accounts.reviewer_stake.is_winner)
+    )]
+    pub fn withdraw_winnings(ctx: Context<WithdrawWinnings>) -> Result<()> {
+        let vault = core_vault!(ctx);
+        let idea = &ctx.accounts.idea;
+        let total_winnings =
+            claimable_winnings(idea, &ctx.accounts.vote, &ctx.accounts.reviewer_stake)?
+                .checked_add(claim_fees!(ctx, idea)?)
                 .ok_or(ConsensusError::Overflow)?;
 
-            if reject_ratio_bps >= REJECT_ALL_THRESHOLD_BPS {
-                // RejectAll 胜出，全员退款
-                idea.status = IdeaStatus::Cancelled;
-                emit!(VotingCancelled {
-                    idea: idea.key(),
-                    reason: "Rejected by supermajority (2/3+ RejectAll votes)".to_string(),
-                });
-                return Ok(());
-            }
+        // 大额奖金改为线性释放，由 claim_vested 分期领取；否则直接转账（使用 SPL Token）
+        if !vest_winnings(
+            &event_emitter!(ctx),
+            &vault.core,
+            &mut ctx.accounts.idea,
+            ctx.accounts.vesting_claim.as_deref_mut(),
+            ctx.bumps.vesting_claim,
+            ctx.accounts.reviewer.key(),
+            total_winnings,
+        )? {
+            vault.transfer(ctx.accounts.reviewer_token_account.to_account_info(), total_winnings)?;
         }
 
-        // 根据投票模式决定获胜者
-        let winning_index = match voting_mode {
-            VotingMode::Classic => {
-                // 经典模式：最多票获胜
-                let max_votes = *idea.votes.iter().max().unwrap();
-                let winning_indices: Vec<usize> = idea.votes
-                    .iter()
-                    .enumerate()
-                    .filter(|(_, &v)| v == max_votes)
-                    .map(|(i, _)| i)
-                    .collect();
-
-                // 如果有平局，取消投票
-                if winning_indices.len() > 1 {
-                    idea.status = IdeaStatus::Cancelled;
-                    emit!(VotingCancelled {
-                        idea: idea.key(),
-                        reason: "Vote tied".to_string(),
-                    });
-                    return Ok(());
-                }
-                winning_indices[0] as u8
-            }
-            VotingMode::Reverse => {
-                // 反向模式：最少票获胜
-                let min_votes = *idea.votes.iter().min().unwrap();
-                let winning_indices: Vec<usize> = idea.votes
-                    .iter()
-                    .enumerate()
-                    .filter(|(_, &v)| v == min_votes)
-                    .map(|(i, _)| i)
-                    .collect();
-
-                // 如果有平局，取消投票
-                if winning_indices.len() > 1 {
-                    idea.status = IdeaStatus::Cancelled;
-                    emit!(VotingCancelled {
-                        idea: idea.key(),
-                        reason: "Vote tied (reverse mode)".to_string(),
-                    });
-                    return Ok(());
-                }
-                winning_indices[0] as u8
-            }
-            VotingMode::MiddleWay => {
-                // 中间派模式：最多和最少都赢
-                // 这种模式下，我们将最多和最少视为"联合获胜"
-                // 简化处理：选择最多票的作为主获胜者
-                let max_votes = *idea.votes.iter().max().unwrap();
-                let winning_indices: Vec<usize> = idea.votes
-                    .iter()
-                    .enumerate()
-                    .filter(|(_, &v)| v == max_votes)
-                    .map(|(i, _)| i)
-                    .collect();
-
-                if winning_indices.len() > 1 {
-                    idea.status = IdeaStatus::Cancelled;
-                    emit!(VotingCancelled {
-                        idea: idea.key(),
-                        reason: "Vote tied (middle way mode)".to_string(),
-                    });
-                    return Ok(());
-                }
-                winning_indices[0] as u8
-            }
-        };
+        let idea = &ctx.accounts.idea;
+        let reviewer_stake = &mut ctx.accounts.reviewer_stake;
+
+        reviewer_stake.is_winner = true;
+        reviewer_stake.winnings = total_winnings;
+        vault.core.sync_stake(reviewer_stake)?;
+        record_reputation(
+            &event_emitter!(ctx),
+            &mut ctx.accounts.reputation,
+            ctx.accounts.reviewer.key(),
+            ctx.bumps.reputation,
+            is_winning_vote(idea, &ctx.accounts.vote),
+            reviewer_stake.total_staked,
+            total_winnings,
+            ctx.accounts.protocol_config.season,
+        )?;
+        rank_reviewer!(ctx, idea.theme);
 
-        idea.winning_image_index = Some(winning_index);
+        emit_cpi!(WinningsWithdrawn {
+            idea: idea.key(),
+            reviewer: ctx.accounts.reviewer.key(),
+            amount: total_winnings,
+        });
 
-        // 计算费用分配
-        let curator_fee = (idea.total_staked as u128)
-            .checked_mul(idea.curator_fee_bps as u128)
-            .and_then(|x| x.checked_div(BPS_DENOMINATOR as u128))
-            .and_then(|x| u64::try_from(x).ok())
-            .ok_or(ConsensusError::Overflow)?;
+        Ok(())
+    }
 
-        let platform_fee = (idea.total_staked as u128)
-            .checked_mul(PLATFORM_FEE_BPS as u128)
-            .and_then(|x| x.checked_div(BPS_DENOMINATOR as u128))
+    /// 领取已线性释放的奖金 (任何人可调用，只能转入受益人的代币账户)
+    /// 释放期可能超过领取期，未释放部分在清扫时保留，故不受领取截止时间限制
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        let vault = core_vault!(ctx);
+        let idea = &mut ctx.accounts.idea;
+        let now = Clock::get()?.unix_timestamp;
+
+        let vesting = &mut ctx.accounts.vesting_claim;
+        let elapsed = now.saturating_sub(vesting.start_ts).clamp(0, vesting.duration);
+        let vested = (vesting.total_amount as u128)
+            .checked_mul(elapsed as u128)
+            .and_then(|x| x.checked_div(vesting.duration as u128))
             .and_then(|x| u64::try_from(x).ok())
             .ok_or(ConsensusError::Overflow)?;
-
-        let remaining_pool = idea.total_staked
-            .checked_sub(curator_fee)
-            .and_then(|x| x.checked_sub(platform_fee))
+        let amount = vested
+            .checked_sub(vesting.claimed_amount)
             .ok_or(ConsensusError::Overflow)?;
+        require!(amount > 0, ConsensusError::NothingVested);
 
-        // 5% 进入主题回购池
-        let buyback_contribution = (remaining_pool as u128)
-            .checked_mul(SETTLEMENT_BUYBACK_BPS as u128)
-            .and_then(|x| x.checked_div(BPS_DENOMINATOR as u128))
-            .and_then(|x| u64::try_from(x).ok())
-            .ok_or(ConsensusError::Overflow)?;
+        let idea_key = idea.key();
 
-        // 50% 惩罚比例（从剩余池中扣除回购贡献后计算）
-        let penalty_pool = (remaining_pool as u128)
-            .checked_sub(buyback_contribution as u128)
-            .ok_or(ConsensusError::Overflow)?
-            .checked_mul(PENALTY_BPS as u128)
-            .and_then(|x| x.checked_div(BPS_DENOMINATOR as u128))
-            .and_then(|x| u64::try_from(x).ok())
-            .ok_or(ConsensusError::Overflow)?;
+        vault.transfer(ctx.accounts.reviewer_token_account.to_account_info(), amount)?;
 
-        let winner_count = idea.votes[winning_index as usize];
+        vesting.claimed_amount = vested;
 
-        idea.curator_fee_collected = curator_fee;
-        idea.platform_fee_collected = platform_fee;
-        idea.penalty_pool_amount = penalty_pool;
-        idea.winner_count = winner_count;
-        idea.status = IdeaStatus::Completed;
+        // 升级前创建的释放记录未计入 outstanding_vesting
+        idea.outstanding_vesting = idea.outstanding_vesting.saturating_sub(amount);
+        vault.core.sync_idea(idea)?;
 
-        // 转移费用（使用 SPL Token）
-        let idea_key = idea.key();
-        let vault_seeds = &[
-            b"vault",
-            idea_key.as_ref(),
-            &[idea.vault_bump],
-        ];
-        let signer = &[&vault_seeds[..]];
-
-        // 转策展费给发起者（代币）
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.vault_token_account.to_account_info(),
-                    to: ctx.accounts.initiator_token_account.to_account_info(),
-                    authority: ctx.accounts.vault.to_account_info(),
-                },
-                signer,
-            ),
-            curator_fee,
+        emit_cpi!(VestedClaimed {
+            idea: idea_key,
+            reviewer: vesting.reviewer,
+            amount,
+            remaining: vesting.total_amount - vested,
+        });
+
+        Ok(())
+    }
+
+    /// 提取奖金并在同一交易内通过联合曲线卖出为 SOL (按正常交易费率)
+    #[access_control(
+        claimable(&ctx.accounts.idea)
+        not_withdrawn(ctx.accounts.reviewer_stake.is_winner)
+    )]
+    pub fn withdraw_winnings_as_sol(
+        ctx: Context<WithdrawWinningsAsSol>,
+        min_sol_out: u64,
+    ) -> Result<()> {
+        let vault = core_vault!(ctx);
+        let idea = &ctx.accounts.idea;
+        let total_winnings =
+            claimable_winnings(idea, &ctx.accounts.vote, &ctx.accounts.reviewer_stake)?
+                .checked_add(claim_fees!(ctx, idea)?)
+                .ok_or(ConsensusError::Overflow)?;
+        // 大额奖金须走线性释放，不可一次性卖出
+        vest_winnings(
+            &event_emitter!(ctx),
+            &vault.core,
+            &mut ctx.accounts.idea,
+            None,
+            None,
+            ctx.accounts.reviewer.key(),
+            total_winnings,
         )?;
 
-        // 转平台费给协议财库（代币）
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.vault_token_account.to_account_info(),
-                    to: ctx.accounts.protocol_treasury_token_account.to_account_info(),
-                    authority: ctx.accounts.vault.to_account_info(),
-                },
-                signer,
-            ),
-            platform_fee,
+        let idea = &ctx.accounts.idea;
+        let idea_key = idea.key();
+
+        vault.transfer(ctx.accounts.reviewer_token_account.to_account_info(), total_winnings)?;
+
+        let reviewer_stake = &mut ctx.accounts.reviewer_stake;
+        reviewer_stake.is_winner = true;
+        reviewer_stake.winnings = total_winnings;
+        vault.core.sync_stake(reviewer_stake)?;
+        record_reputation(
+            &event_emitter!(ctx),
+            &mut ctx.accounts.reputation,
+            ctx.accounts.reviewer.key(),
+            ctx.bumps.reputation,
+            is_winning_vote(idea, &ctx.accounts.vote),
+            reviewer_stake.total_staked,
+            total_winnings,
+            ctx.accounts.protocol_config.season,
         )?;
+        rank_reviewer!(ctx, idea.theme);
 
-        // 转回购贡献到主题回购池（代币）
-        // 注意：这里先转到主题vault，后续由theme程序管理回购
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.vault_token_account.to_account_info(),
-                    to: ctx.accounts.theme_buyback_token_account.to_account_info(),
-                    authority: ctx.accounts.vault.to_account_info(),
+        // CPI 到主题代币程序的卖出流程
+        taste_fun_token::cpi::swap_tokens_for_sol(
+            CpiContext::new(
+                ctx.accounts.theme_program.to_account_info(),
+                taste_fun_token::cpi::accounts::SwapTokensForSol {
+                    theme: ctx.accounts.theme.to_account_info(),
+                    vault: ctx.accounts.theme_vault.to_account_info(),
+                    token_mint: ctx.accounts.token_mint.to_account_info(),
+                    vault_token_account: ctx.accounts.theme_vault_token_account.to_account_info(),
+                    user_token_account: ctx.accounts.reviewer_token_account.to_account_info(),
+                    trading_config: ctx.accounts.trading_config.to_account_info(),
+                    protocol_config: ctx.accounts.protocol_config.to_account_info(),
+                    price_oracle: ctx.accounts.price_oracle.to_account_info(),
+                    referral: None,
+                    trader_state: ctx.accounts.trader_state.as_ref().map(|a| a.to_account_info()),
+                    user_wsol_account: None,
+                    user: ctx.accounts.reviewer.to_account_info(),
+                    vault_sol_account: ctx.accounts.vault_sol_account.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    event_authority: ctx.accounts.theme_event_authority.to_account_info(),
+                    program: ctx.accounts.theme_program.to_account_info(),
                 },
-                signer,
             ),
-            buyback_contribution,
+            total_winnings,
+            min_sol_out,
+            None,
         )?;
 
-        emit!(VotingSettled {
-            idea: idea.key(),
-            winning_image_index: winning_index,
-            total_staked: idea.total_staked,
-            curator_fee,
-            platform_fee,
-            penalty_pool,
-            winner_count,
+        emit_cpi!(WinningsWithdrawn {
+            idea: idea_key,
+            reviewer: ctx.accounts.reviewer.key(),
+            amount: total_winnings,
         });
 
         Ok(())
     }
 
-    /// 提取奖金
-    pub fn withdraw_winnings(ctx: Context<WithdrawWinnings>) -> Result<()> {
+    /// 领取奖金并直接复投到同一主题的另一个创意 (金库间直接转账，不经过评审 ATA)
+    #[access_control(
+        claimable(&ctx.accounts.idea)
+        not_withdrawn(ctx.accounts.reviewer_stake.is_winner)
+    )]
+    pub fn claim_and_vote<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimAndVote<'info>>,
+        image_index: u8,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let vault = core_vault!(ctx);
         let idea = &ctx.accounts.idea;
-        require!(
-            idea.status == IdeaStatus::Completed,
+        require_keys_neq!(
+            idea.key(),
+            ctx.accounts.target_idea.key(),
             ConsensusError::InvalidState
         );
+        let total_winnings =
+            claimable_winnings(idea, &ctx.accounts.vote, &ctx.accounts.reviewer_stake)?
+                .checked_add(claim_fees!(ctx, idea)?)
+                .ok_or(ConsensusError::Overflow)?;
 
-        let vote = &ctx.accounts.vote;
-        let reviewer_stake = &mut ctx.accounts.reviewer_stake;
-
-        // 检查是否已经提取过
-        require!(!reviewer_stake.is_winner, ConsensusError::AlreadyWithdrawn);
-
-        // 检查是否是获胜方
-        let winning_index = idea.winning_image_index.ok_or(ConsensusError::NoWinner)?;
-        require!(
-            vote.image_choice == winning_index,
-            ConsensusError::NotWinner
-        );
+        let idea_key = idea.key();
 
-        // 计算应得奖金
-        let per_winner_share = idea.penalty_pool_amount
-            .checked_div(idea.winner_count)
-            .ok_or(ConsensusError::DivisionByZero)?;
+        // 大额奖金须走线性释放，不可直接复投
+        vest_winnings(
+            &event_emitter!(ctx),
+            &vault.core,
+            &mut ctx.accounts.idea,
+            None,
+            None,
+            ctx.accounts.reviewer.key(),
+            total_winnings,
+        )?;
+        let idea = &ctx.accounts.idea;
 
-        let total_winnings = reviewer_stake.total_staked
-            .checked_add(per_winner_share)
-            .ok_or(ConsensusError::Overflow)?;
+        // 奖金从本创意金库直接转入目标创意金库
+        vault.transfer(ctx.accounts.target_vault_token_account.to_account_info(), total_winnings)?;
 
-        // 转账（使用 SPL Token）
-        let idea_key = idea.key();
-        let vault_seeds = &[
-            b"vault",
-            idea_key.as_ref(),
-            &[idea.vault_bump],
-        ];
-        let signer = &[&vault_seeds[..]];
+        let reviewer_stake = &mut ctx.accounts.reviewer_stake;
+        reviewer_stake.is_winner = true;
+        reviewer_stake.winnings = total_winnings;
+        vault.core.sync_stake(reviewer_stake)?;
+        record_reputation(
+            &event_emitter!(ctx),
+            &mut ctx.accounts.reputation,
+            ctx.accounts.reviewer.key(),
+            ctx.bumps.reputation,
+            is_winning_vote(idea, &ctx.accounts.vote),
+            reviewer_stake.total_staked,
+            total_winnings,
+            ctx.accounts.protocol_config.season,
+        )?;
+        rank_reviewer!(ctx, idea.theme);
 
-        token::transfer(
+        // 由复投授权 PDA 签名，CPI 到 core 记录目标创意上的投票
+        let authority_seeds: &[&[u8]] = &[b"restake_authority", &[ctx.bumps.restake_authority]];
+        taste_fun_core::cpi::restake_vote(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.vault_token_account.to_account_info(),
-                    to: ctx.accounts.reviewer_token_account.to_account_info(),
-                    authority: ctx.accounts.vault.to_account_info(),
+                ctx.accounts.core_program.to_account_info(),
+                taste_fun_core::cpi::accounts::RestakeVote {
+                    idea: ctx.accounts.target_idea.to_account_info(),
+                    vote: ctx.accounts.target_vote.to_account_info(),
+                    reviewer_stake: ctx.accounts.target_reviewer_stake.to_account_info(),
+                    restake_authority: ctx.accounts.restake_authority.to_account_info(),
+                    voter: ctx.accounts.reviewer.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    event_authority: ctx.accounts.core_event_authority.to_account_info(),
+                    program: ctx.accounts.core_program.to_account_info(),
                 },
-                signer,
-            ),
+                &[authority_seeds],
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            image_index,
             total_winnings,
+            allowlist_proof,
         )?;
 
-        reviewer_stake.is_winner = true;
-        reviewer_stake.winnings = total_winnings;
+        emit_cpi!(WinningsWithdrawn {
+            idea: idea_key,
+            reviewer: ctx.accounts.reviewer.key(),
+            amount: total_winnings,
+        });
 
-        emit!(WinningsWithdrawn {
-            idea: idea.key(),
+        emit_cpi!(WinningsRestaked {
+            idea: idea_key,
+            target_idea: ctx.accounts.target_idea.key(),
             reviewer: ctx.accounts.reviewer.key(),
+            image_index,
             amount: total_winnings,
         });
 
         Ok(())
     }
 
-    /// 提取退款 (仅在取消时可用)
-    pub fn withdraw_refund(ctx: Context<WithdrawRefund>) -> Result<()> {
+    /// 未获胜评审取回未被罚没的本金 (质押扣除惩罚比例后的部分)
+    #[access_control(
+        claimable(&ctx.accounts.idea)
+        not_withdrawn(ctx.accounts.reviewer_stake.is_winner)
+    )]
+    pub fn withdraw_loser_refund(ctx: Context<WithdrawWinnings>) -> Result<()> {
+        let vault = core_vault!(ctx);
         let idea = &ctx.accounts.idea;
-        require!(
-            idea.status == IdeaStatus::Cancelled,
-            ConsensusError::InvalidState
-        );
+        let (refund, penalty_share) =
+            loser_refund(idea, &ctx.accounts.vote, &ctx.accounts.reviewer_stake)?;
 
-        let _vote = &ctx.accounts.vote;
+        let rebate = claim_fees!(ctx, idea)?;
         let reviewer_stake = &mut ctx.accounts.reviewer_stake;
+        let refund_amount = refund
+            .checked_add(rebate)
+            .ok_or(ConsensusError::Overflow)?;
 
-        require!(!reviewer_stake.is_winner, ConsensusError::AlreadyWithdrawn);
-
-        let refund_amount = reviewer_stake.total_staked;
-
-        // 转账退款（使用 SPL Token）
         let idea_key = idea.key();
-        let vault_seeds = &[
-            b"vault",
-            idea_key.as_ref(),
-            &[idea.vault_bump],
-        ];
-        let signer = &[&vault_seeds[..]];
 
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.vault_token_account.to_account_info(),
-                    to: ctx.accounts.reviewer_token_account.to_account_info(),
-                    authority: ctx.accounts.vault.to_account_info(),
-                },
-                signer,
-            ),
-            refund_amount,
-        )?;
+        vault.transfer(ctx.accounts.reviewer_token_account.to_account_info(), refund_amount)?;
 
         reviewer_stake.is_winner = true; // 标记为已处理
+        vault.core.sync_stake(reviewer_stake)?;
+        record_reputation(
+            &event_emitter!(ctx),
+            &mut ctx.accounts.reputation,
+            ctx.accounts.reviewer.key(),
+            ctx.bumps.reputation,
+            false,
+            reviewer_stake.total_staked,
+            refund_amount,
+            ctx.accounts.protocol_config.season,
+        )?;
+        rank_reviewer!(ctx, idea.theme);
 
-        emit!(RefundWithdrawn {
-            idea: idea.key(),
+        emit_cpi!(LoserRefundWithdrawn {
+            idea: idea_key,
             reviewer: ctx.accounts.reviewer.key(),
             amount: refund_amount,
+            penalty: penalty_share,
         });
 
         Ok(())
     }
-}
 
-// -----------------------------------------------------------------------------
-// Contexts
-// -----------------------------------------------------------------------------
+    /// 提取退款 (仅在取消时可用)
+    #[access_control(
+        idea_in_status(ctx.accounts.idea.status, IdeaStatus::Cancelled)
+        not_withdrawn(ctx.accounts.reviewer_stake.is_winner)
+    )]
+    pub fn withdraw_refund(ctx: Context<WithdrawRefund>) -> Result<()> {
+        let vault = core_vault!(ctx);
+        let idea = &ctx.accounts.idea;
+        let reviewer_stake = &mut ctx.accounts.reviewer_stake;
+        let refund_amount = reviewer_stake.total_staked;
 
-#[derive(Accounts)]
-pub struct SettleVoting<'info> {
-    #[account(mut)]
-    pub idea: Account<'info, Idea>,
+        // 转账退款（使用 SPL Token）
+        vault.transfer(ctx.accounts.reviewer_token_account.to_account_info(), refund_amount)?;
 
-    #[account(mut, seeds = [b"vault", idea.key().as_ref()], bump = idea.vault_bump)]
-    pub vault: Account<'info, Vault>,
+        reviewer_stake.is_winner = true; // 标记为已处理
+        vault.core.sync_stake(reviewer_stake)?;
 
-    /// CHECK: Theme token mint - validated through token program operations
-    #[account(mut)]
-    pub token_mint: AccountInfo<'info>,
+        // 非评审过错的取消：从财库补贴领取交易费（每个创意有上限，财库不足时跳过）
+        let mut gas_rebate = 0;
+        let treasury = &ctx.accounts.rebate_treasury;
+        let treasury_floor = Rent::get()?.minimum_balance(0)
+            .checked_add(REFUND_GAS_REBATE)
+            .ok_or(ConsensusError::Overflow)?;
+        let rebates_after = idea.refund_rebates_paid
+            .checked_add(REFUND_GAS_REBATE)
+            .ok_or(ConsensusError::Overflow)?;
 
-    /// Vault token account holding staked tokens
-    #[account(mut)]
-    pub vault_token_account: Account<'info, TokenAccount>,
+        if is_rebate_eligible(idea.cancel_reason)
+            && rebates_after <= MAX_REFUND_REBATE_PER_IDEA
+            && treasury.lamports() >= treasury_floor
+        {
+            let treasury_seeds: &[&[u8]] = &[b"rebate_treasury", &[ctx.bumps.rebate_treasury]];
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: treasury.to_account_info(),
+                        to: ctx.accounts.reviewer.to_account_info(),
+                    },
+                    &[treasury_seeds],
+                ),
+                REFUND_GAS_REBATE,
+            )?;
+            ctx.accounts.idea.refund_rebates_paid = rebates_after;
+            vault.core.sync_idea(&ctx.accounts.idea)?;
+            gas_rebate = REFUND_GAS_REBATE;
+        }
 
-    /// Initiator's token account to receive curator fee
-    #[account(mut)]
-    pub initiator_token_account: Account<'info, TokenAccount>,
+        emit_cpi!(RefundWithdrawn {
+            idea: ctx.accounts.idea.key(),
+            reviewer: ctx.accounts.reviewer.key(),
+            amount: refund_amount,
+            gas_rebate,
+        });
 
-    /// Protocol treasury token account to receive platform fee
-    #[account(mut)]
-    pub protocol_treasury_token_account: Account<'info, TokenAccount>,
+        Ok(())
+    }
 
-    /// Theme buyback token account to receive buyback contribution
-    #[account(mut)]
-    pub theme_buyback_token_account: Account<'info, TokenAccount>,
+    /// 结算委托投票：本金与收益返还委托人，策展人按委托分成取得净收益的一部分
+    /// 取消的创意全额退还本金；大额所得同样线性释放，由委托人通过 claim_vested 领取
+    #[access_control(
+        idea_closed(ctx.accounts.idea.status)
+        not_withdrawn(ctx.accounts.reviewer_stake.is_winner)
+    )]
+    pub fn withdraw_delegated_winnings(ctx: Context<WithdrawDelegatedWinnings>) -> Result<()> {
+        let vault = core_vault!(ctx);
+        let idea = &ctx.accounts.idea;
+        let principal = ctx.accounts.reviewer_stake.total_staked;
+        let total = pooled_vote_payout(
+            &event_emitter!(ctx),
+            idea,
+            &ctx.accounts.vote,
+            &ctx.accounts.reviewer_stake,
+            ctx.accounts.delegation.key(),
+            ClaimFeeAccounts {
+                vault: &vault,
+                curator_token_account: ctx.accounts.curator_token_account.to_account_info(),
+                protocol_treasury_token_account: ctx
+                    .accounts
+                    .protocol_treasury_token_account
+                    .to_account_info(),
+            },
+        )?;
 
-    /// CHECK: Initiator to receive curator fee
-    #[account(mut)]
-    pub initiator: UncheckedAccount<'info>,
+        // 策展人分成只从净收益中扣除，亏损时不收取
+        let curator_cut = (total.saturating_sub(principal) as u128)
+            .checked_mul(ctx.accounts.delegation.curator_cut_bps as u128)
+            .and_then(|x| x.checked_div(BPS_DENOMINATOR as u128))
+            .and_then(|x| u64::try_from(x).ok())
+            .ok_or(ConsensusError::Overflow)?;
+        let delegator_amount = total - curator_cut;
 
-    /// CHECK: Protocol treasury to receive platform fee
-    #[account(mut)]
-    pub protocol_treasury: UncheckedAccount<'info>,
+        let idea_key = idea.key();
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-}
+        let delegator = ctx.accounts.delegation.delegator;
+        let vested = vest_winnings(
+            &event_emitter!(ctx),
+            &vault.core,
+            &mut ctx.accounts.idea,
+            ctx.accounts.vesting_claim.as_deref_mut(),
+            ctx.bumps.vesting_claim,
+            delegator,
+            delegator_amount,
+        )?;
+        for (to, amount) in [
+            (ctx.accounts.delegation_curator_token_account.to_account_info(), curator_cut),
+            (
+                ctx.accounts.delegator_token_account.to_account_info(),
+                if vested { 0 } else { delegator_amount },
+            ),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            vault.transfer(to, amount)?;
+        }
 
-#[derive(Accounts)]
-pub struct WithdrawWinnings<'info> {
-    #[account(mut)]
-    pub idea: Account<'info, Idea>,
+        let reviewer_stake = &mut ctx.accounts.reviewer_stake;
+        reviewer_stake.is_winner = true; // 标记为已处理
+        reviewer_stake.winnings = total;
+        vault.core.sync_stake(reviewer_stake)?;
 
-    #[account(
-        seeds = [b"vote", idea.key().as_ref(), reviewer.key().as_ref()],
-        bump
-    )]
-    pub vote: Account<'info, Vote>,
+        emit_cpi!(DelegatedWinningsWithdrawn {
+            idea: idea_key,
+            delegation: ctx.accounts.delegation.key(),
+            delegator,
+            curator: ctx.accounts.delegation.curator,
+            amount: delegator_amount,
+            curator_cut,
+        });
 
-    #[account(
-        mut,
-        seeds = [b"reviewer_stake", idea.key().as_ref(), reviewer.key().as_ref()],
-        bump = reviewer_stake.bump
-    )]
-    pub reviewer_stake: Account<'info, ReviewerStake>,
+        Ok(())
+    }
 
-    #[account(mut, seeds = [b"vault", idea.key().as_ref()], bump = idea.vault_bump)]
+    /// 结算战队投票：所得转回战队托管账户 (成员按份额分享)，并以战队身份更新声誉与排行榜
+    /// 大额所得线性释放至战队托管账户，由任何人通过 claim_vested 领取
+    #[access_control(
+        idea_closed(ctx.accounts.idea.status)
+        not_withdrawn(ctx.accounts.reviewer_stake.is_winner)
+    )]
+    pub fn withdraw_squad_winnings(ctx: Context<WithdrawSquadWinnings>) -> Result<()> {
+        let vault = core_vault!(ctx);
+        let idea = &ctx.accounts.idea;
+        let squad_key = ctx.accounts.squad.key();
+        let principal = ctx.accounts.reviewer_stake.total_staked;
+        let total = pooled_vote_payout(
+            &event_emitter!(ctx),
+            idea,
+            &ctx.accounts.vote,
+            &ctx.accounts.reviewer_stake,
+            squad_key,
+            ClaimFeeAccounts {
+                vault: &vault,
+                curator_token_account: ctx.accounts.curator_token_account.to_account_info(),
+                protocol_treasury_token_account: ctx
+                    .accounts
+                    .protocol_treasury_token_account
+                    .to_account_info(),
+            },
+        )?;
+
+        let idea_key = idea.key();
+
+        if !vest_winnings(
+            &event_emitter!(ctx),
+            &vault.core,
+            &mut ctx.accounts.idea,
+            ctx.accounts.vesting_claim.as_deref_mut(),
+            ctx.bumps.vesting_claim,
+            squad_key,
+            total,
+        )? {
+            vault.transfer(ctx.accounts.squad_token_account.to_account_info(), total)?;
+        }
+        let idea = &ctx.accounts.idea;
+
+        // 由战队授权 PDA 签名，CPI 到 core 释放投票中的本金记录
+        let authority_seeds: &[&[u8]] = &[b"squad_authority", &[ctx.bumps.squad_authority]];
+        taste_fun_core::cpi::release_squad_stake(
+            CpiContext::new_with_signer(
+                ctx.accounts.core_program.to_account_info(),
+                taste_fun_core::cpi::accounts::ReleaseSquadStake {
+                    squad: ctx.accounts.squad.to_account_info(),
+                    squad_authority: ctx.accounts.squad_authority.to_account_info(),
+                },
+                &[authority_seeds],
+            ),
+            principal,
+        )?;
+
+        let reviewer_stake = &mut ctx.accounts.reviewer_stake;
+        reviewer_stake.is_winner = true; // 标记为已处理
+        reviewer_stake.winnings = total;
+        vault.core.sync_stake(reviewer_stake)?;
+
+        // 取消的创意不计入战队战绩
+        if idea.status == IdeaStatus::Completed {
+            record_reputation(
+                &event_emitter!(ctx),
+                &mut ctx.accounts.reputation,
+                squad_key,
+                ctx.bumps.reputation,
+                is_winning_vote(idea, &ctx.accounts.vote),
+                principal,
+                total,
+                ctx.accounts.protocol_config.season,
+            )?;
+            rank_reviewer!(ctx, idea.theme);
+        }
+
+        emit_cpi!(SquadWinningsReturned {
+            idea: idea_key,
+            squad: squad_key,
+            amount: total,
+        });
+
+        Ok(())
+    }
+
+    /// 结算跟投投票：所得转回关注者的跟投托管账户，获胜时按关注设定的小费比例从净收益中付给策展人
+    /// (任何人可调用；大额所得线性释放至跟投托管账户，由 claim_vested 领取)
+    #[access_control(
+        idea_closed(ctx.accounts.idea.status)
+        not_withdrawn(ctx.accounts.reviewer_stake.is_winner)
+    )]
+    pub fn withdraw_mirrored_winnings(ctx: Context<WithdrawMirroredWinnings>) -> Result<()> {
+        let vault = core_vault!(ctx);
+        let idea = &ctx.accounts.idea;
+        let principal = ctx.accounts.reviewer_stake.total_staked;
+        let total = pooled_vote_payout(
+            &event_emitter!(ctx),
+            idea,
+            &ctx.accounts.vote,
+            &ctx.accounts.reviewer_stake,
+            ctx.accounts.follow.key(),
+            ClaimFeeAccounts {
+                vault: &vault,
+                curator_token_account: ctx.accounts.curator_token_account.to_account_info(),
+                protocol_treasury_token_account: ctx
+                    .accounts
+                    .protocol_treasury_token_account
+                    .to_account_info(),
+            },
+        )?;
+
+        let tip = (total.saturating_sub(principal) as u128)
+            .checked_mul(ctx.accounts.follow.tip_bps as u128)
+            .and_then(|x| x.checked_div(BPS_DENOMINATOR as u128))
+            .and_then(|x| u64::try_from(x).ok())
+            .ok_or(ConsensusError::Overflow)?;
+
+        let idea_key = idea.key();
+
+        let vested = vest_winnings(
+            &event_emitter!(ctx),
+            &vault.core,
+            &mut ctx.accounts.idea,
+            ctx.accounts.vesting_claim.as_deref_mut(),
+            ctx.bumps.vesting_claim,
+            ctx.accounts.follow.key(),
+            total - tip,
+        )?;
+        for (to, amount) in [
+            (ctx.accounts.followed_curator_token_account.to_account_info(), tip),
+            (
+                ctx.accounts.follow_escrow_token_account.to_account_info(),
+                if vested { 0 } else { total - tip },
+            ),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            vault.transfer(to, amount)?;
+        }
+
+        let reviewer_stake = &mut ctx.accounts.reviewer_stake;
+        reviewer_stake.is_winner = true; // 标记为已处理
+        reviewer_stake.winnings = total;
+        vault.core.sync_stake(reviewer_stake)?;
+
+        emit_cpi!(MirroredWinningsWithdrawn {
+            idea: idea_key,
+            follow: ctx.accounts.follow.key(),
+            follower: ctx.accounts.follow.follower,
+            curator: ctx.accounts.follow.curator,
+            amount: total - tip,
+            tip,
+        });
+
+        Ok(())
+    }
+
+    /// 发布结算状态根 (已结算竞赛与评审余额的 Merkle 根，供其他链轻量验证)
+    /// 叶子编码见 taste_fun_shared::contest_leaf / balance_leaf
+    #[access_control(
+        is_protocol_authority(&ctx.accounts.protocol_config.authority, ctx.accounts.keeper.key)
+    )]
+    pub fn post_state_root(
+        ctx: Context<PostStateRoot>,
+        epoch: u64,
+        root: [u8; 32],
+        leaf_count: u64,
+    ) -> Result<()> {
+        let state_root = &mut ctx.accounts.state_root;
+        require!(
+            epoch == state_root.epoch + 1,
+            ConsensusError::InvalidStateRootEpoch
+        );
+
+        let clock = Clock::get()?;
+        state_root.root = root;
+        state_root.epoch = epoch;
+        state_root.leaf_count = leaf_count;
+        state_root.slot = clock.slot;
+        state_root.posted_at = clock.unix_timestamp;
+        state_root.bump = ctx.bumps.state_root;
+
+        emit_cpi!(StateRootPosted {
+            epoch,
+            root,
+            leaf_count,
+            slot: clock.slot,
+        });
+
+        Ok(())
+    }
+
+    /// 赞助评委授予加奖 (结算后窗口期内，与投票结果无关)
+    #[access_control(
+        idea_in_status(ctx.accounts.idea.status, IdeaStatus::Completed)
+        bonus_window_open(&ctx.accounts.idea)
+    )]
+    pub fn award_bonus(ctx: Context<AwardBonus>, image_index: u8) -> Result<()> {
+        let core = core_cpi!(ctx);
+        let idea = &mut ctx.accounts.idea;
+        require!(idea.bonus_pool > 0, ConsensusError::InvalidState);
+        require!(image_index < 4, ConsensusError::InvalidImageIndex);
+
+        let total_weight = idea.votes[image_index as usize];
+        require!(total_weight > 0, ConsensusError::NoWinner);
+
+        let award = &mut ctx.accounts.bonus_award;
+        award.idea = idea.key();
+        award.judge = ctx.accounts.judge.key();
+        award.image_index = image_index;
+        award.amount = idea.bonus_pool;
+        award.total_weight = total_weight;
+        award.awarded_at = Clock::get()?.unix_timestamp;
+        award.bump = ctx.bumps.bonus_award;
+
+        idea.bonus_pool = 0;
+        core.sync_idea(idea)?;
+
+        emit_cpi!(BonusAwarded {
+            idea: idea.key(),
+            judge: award.judge,
+            image_index,
+            amount: award.amount,
+        });
+
+        Ok(())
+    }
+
+    /// 领取评委加奖 (按投票权重分配给所选图片的投票者)
+    #[access_control(
+        voted_for(ctx.accounts.vote.image_choice, ctx.accounts.bonus_award.image_index)
+        claimable(&ctx.accounts.idea)
+    )]
+    pub fn claim_bonus(ctx: Context<ClaimBonus>) -> Result<()> {
+        let vault = core_vault!(ctx);
+        let award = &ctx.accounts.bonus_award;
+        let vote = &ctx.accounts.vote;
+        let amount = (award.amount as u128)
+            .checked_mul(vote.vote_weight as u128)
+            .and_then(|x| x.checked_div(award.total_weight as u128))
+            .and_then(|x| u64::try_from(x).ok())
+            .ok_or(ConsensusError::Overflow)?;
+
+        let idea = &ctx.accounts.idea;
+        let idea_key = idea.key();
+
+        vault.transfer(ctx.accounts.reviewer_token_account.to_account_info(), amount)?;
+
+        let claim = &mut ctx.accounts.bonus_claim;
+        claim.idea = idea_key;
+        claim.reviewer = ctx.accounts.reviewer.key();
+        claim.amount = amount;
+        claim.bump = ctx.bumps.bonus_claim;
+
+        emit_cpi!(BonusClaimed {
+            idea: idea_key,
+            reviewer: claim.reviewer,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// 赞助商收回未授予的加奖 (创意取消，或评委逾期未授予)
+    #[access_control(idea_closed(ctx.accounts.idea.status))]
+    pub fn reclaim_bonus(ctx: Context<ReclaimBonus>) -> Result<()> {
+        let vault = core_vault!(ctx);
+        let idea = &ctx.accounts.idea;
+        require!(idea.bonus_pool > 0, ConsensusError::InvalidState);
+
+        let expired = idea.status == IdeaStatus::Completed
+            && Clock::get()?.unix_timestamp > idea.voting_deadline + BONUS_AWARD_WINDOW;
+        require!(
+            idea.status == IdeaStatus::Cancelled || expired,
+            ConsensusError::BonusWindowOpen
+        );
+
+        let amount = idea.bonus_pool;
+        let idea_key = idea.key();
+
+        vault.transfer(ctx.accounts.sponsor_token_account.to_account_info(), amount)?;
+
+        ctx.accounts.idea.bonus_pool = 0;
+        vault.core.sync_idea(&ctx.accounts.idea)?;
+
+        emit_cpi!(BonusReclaimed {
+            idea: idea_key,
+            sponsor: ctx.accounts.sponsor.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// 领取期结束后，将金库剩余代币清扫至协议财库并关闭金库 (任何人都可以调用)
+    /// 线性释放中的奖金保留在金库，全部领取完毕后方可再次清扫并关闭金库
+    #[access_control(
+        idea_in_status(ctx.accounts.idea.status, IdeaStatus::Completed)
+        claim_window_closed(&ctx.accounts.idea)
+    )]
+    pub fn sweep_unclaimed(ctx: Context<SweepUnclaimed>) -> Result<()> {
+        let vault = core_vault!(ctx);
+        let idea = &ctx.accounts.idea;
+
+        // 含未领取评审对应的预留策展费与平台费
+        let outstanding = idea.outstanding_vesting;
+        let amount = ctx.accounts.vault_token_account.amount.saturating_sub(outstanding);
+        require!(amount > 0 || outstanding == 0, ConsensusError::VestingOutstanding);
+        let idea_key = idea.key();
+
+        if amount > 0 {
+            vault.transfer(ctx.accounts.protocol_treasury_token_account.to_account_info(), amount)?;
+        }
+
+        // 关闭金库代币账户与金库，租金退回协议财库
+        if outstanding == 0 {
+            vault.close(ctx.accounts.protocol_treasury.to_account_info())?;
+        }
+
+        ctx.accounts.idea.bonus_pool = 0;
+        vault.core.sync_idea(&ctx.accounts.idea)?;
+
+        emit_cpi!(UnclaimedSwept {
+            idea: idea_key,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// 争议期内质押保证金对结算结果提出异议 (如确认了错误的图片)，冻结领取直至仲裁
+    #[access_control(
+        idea_in_status(ctx.accounts.idea.status, IdeaStatus::Completed)
+        dispute_window_open(&ctx.accounts.idea)
+    )]
+    pub fn open_dispute(ctx: Context<OpenDispute>, evidence_hash: [u8; 32]) -> Result<()> {
+        let core = core_cpi!(ctx);
+        let idea = &mut ctx.accounts.idea;
+        let bond = (idea.total_staked as u128)
+            .checked_mul(DISPUTE_BOND_BPS as u128)
+            .and_then(|x| x.checked_div(BPS_DENOMINATOR as u128))
+            .and_then(|x| u64::try_from(x).ok())
+            .ok_or(ConsensusError::Overflow)?
+            .max(MIN_STAKE);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.challenger_token_account.to_account_info(),
+                    to: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: ctx.accounts.challenger.to_account_info(),
+                },
+            ),
+            bond,
+        )?;
+
+        idea.disputed = true;
+        core.sync_idea(idea)?;
+
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.idea = idea.key();
+        dispute.challenger = ctx.accounts.challenger.key();
+        dispute.bond = bond;
+        dispute.evidence_hash = evidence_hash;
+        dispute.opened_at = Clock::get()?.unix_timestamp;
+        dispute.bump = ctx.bumps.dispute;
+
+        emit_cpi!(DisputeOpened {
+            idea: dispute.idea,
+            challenger: dispute.challenger,
+            bond,
+            evidence_hash,
+        });
+
+        Ok(())
+    }
+
+    /// 仲裁裁决争议: 支持则更正获胜图片、按新获胜质押重新分账并退还保证金，驳回则保证金归协议财库
+    #[access_control(
+        is_protocol_authority(&ctx.accounts.protocol_config.authority, ctx.accounts.arbiter.key)
+    )]
+    pub fn resolve_dispute(
+        ctx: Context<ResolveDispute>,
+        upheld: bool,
+        corrected_winning_index: u8,
+    ) -> Result<()> {
+        let vault = core_vault!(ctx);
+        let idea = &mut ctx.accounts.idea;
+        require!(idea.disputed, ConsensusError::InvalidState);
+
+        if upheld {
+            // 中间派模式有两张获胜图片，无法通过单一索引更正
+            require!(
+                idea.secondary_winning_index.is_none(),
+                ConsensusError::InvalidState
+            );
+            require!(
+                (corrected_winning_index as usize) < idea.voter_counts.len(),
+                ConsensusError::InvalidImageIndex
+            );
+            let overturned = idea.winning_image_index.ok_or(ConsensusError::NoWinner)?;
+
+            // 预留费用与惩罚池按更正后的获胜质押重新分账，份额总量随获胜图片切换
+            let resettled = resettlement_split(
+                idea.total_staked,
+                idea.initial_prize_pool,
+                idea.no_loss,
+                &SettlementSplit {
+                    fees: SettlementFees {
+                        curator_fee: idea.curator_fee_collected,
+                        platform_fee: idea.platform_fee_collected,
+                        ..Default::default()
+                    },
+                    penalty_pool: idea.penalty_pool_amount,
+                    ..Default::default()
+                },
+                idea.stake_totals[overturned as usize],
+                idea.stake_totals[corrected_winning_index as usize],
+            )?;
+            idea.curator_fee_collected = resettled.fees.curator_fee;
+            idea.platform_fee_collected = resettled.fees.platform_fee;
+            idea.penalty_pool_amount = resettled.penalty_pool;
+            idea.winning_image_index = Some(corrected_winning_index);
+            idea.winner_count = idea.voter_counts[corrected_winning_index as usize] as u64;
+        }
+
+        idea.disputed = false;
+        vault.core.sync_idea(idea)?;
+
+        let bond = ctx.accounts.dispute.bond;
+        let idea_key = idea.key();
+
+        let recipient = if upheld {
+            ctx.accounts.challenger_token_account.to_account_info()
+        } else {
+            ctx.accounts.protocol_treasury_token_account.to_account_info()
+        };
+
+        vault.transfer(recipient, bond)?;
+
+        emit_cpi!(DisputeResolved {
+            idea: idea_key,
+            upheld,
+            winning_image_index: idea.winning_image_index,
+            bond,
+            curator_fee: idea.curator_fee_collected,
+            platform_fee: idea.platform_fee_collected,
+            penalty_pool: idea.penalty_pool_amount,
+            winner_count: idea.winner_count,
+        });
+
+        Ok(())
+    }
+
+    /// 初始化协议保险基金
+    #[access_control(
+        is_protocol_authority(&ctx.accounts.protocol_config.authority, ctx.accounts.authority.key)
+    )]
+    pub fn initialize_insurance(ctx: Context<InitializeInsurance>) -> Result<()> {
+        let insurance = &mut ctx.accounts.insurance;
+        insurance.total_contributed = 0;
+        insurance.total_paid = 0;
+        insurance.claim_count = 0;
+        insurance.bump = ctx.bumps.insurance;
+        Ok(())
+    }
+
+    /// 保险理赔: 对可证明的程序错误 (如结算错误的创意) 补偿受损用户
+    #[access_control(
+        is_protocol_authority(&ctx.accounts.protocol_config.authority, ctx.accounts.authority.key)
+    )]
+    pub fn pay_insurance_claim(
+        ctx: Context<PayInsuranceClaim>,
+        idea: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, ConsensusError::InvalidAmount);
+
+        let insurance_seeds: &[&[u8]] = &[b"insurance", &[ctx.accounts.insurance.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.insurance_token_account.to_account_info(),
+                    to: ctx.accounts.recipient_token_account.to_account_info(),
+                    authority: ctx.accounts.insurance.to_account_info(),
+                },
+                &[insurance_seeds],
+            ),
+            amount,
+        )?;
+
+        let insurance = &mut ctx.accounts.insurance;
+        insurance.total_paid = insurance.total_paid
+            .checked_add(amount)
+            .ok_or(ConsensusError::Overflow)?;
+        insurance.claim_count += 1;
+
+        emit_cpi!(InsuranceClaimPaid {
+            idea,
+            recipient: ctx.accounts.recipient_token_account.owner,
+            mint: ctx.accounts.insurance_token_account.mint,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// 发布派奖 Merkle 根 (叶子为 balance_leaf(idea, reviewer, payout))，挑战期后可凭证明领取
+    #[access_control(
+        is_protocol_authority(&ctx.accounts.protocol_config.authority, ctx.accounts.settler.key)
+        idea_in_status(ctx.accounts.idea.status, IdeaStatus::Completed)
+    )]
+    pub fn post_payout_root(
+        ctx: Context<PostPayoutRoot>,
+        root: [u8; 32],
+        total_amount: u64,
+    ) -> Result<()> {
+        require!(
+            total_amount <= ctx.accounts.vault_token_account.amount,
+            ConsensusError::PayoutExceedsTotal
+        );
+
+        // 已有领取后不可替换 (被挑战的根在挑战裁决后、领取前可重新发布)
+        require!(
+            ctx.accounts.payout_challenge.data_is_empty(),
+            ConsensusError::PayoutChallengeOpen
+        );
+        let payout_root = &mut ctx.accounts.payout_root;
+        require!(payout_root.claimed_amount == 0, ConsensusError::PayoutRootLocked);
+
+        let posted_at = Clock::get()?.unix_timestamp;
+        payout_root.idea = ctx.accounts.idea.key();
+        payout_root.root = root;
+        payout_root.total_amount = total_amount;
+        payout_root.posted_at = posted_at;
+        payout_root.challenged = false;
+        payout_root.bump = ctx.bumps.payout_root;
+
+        emit_cpi!(PayoutRootPosted {
+            idea: payout_root.idea,
+            root,
+            total_amount,
+            challenge_deadline: posted_at + PAYOUT_CHALLENGE_WINDOW,
+        });
+
+        Ok(())
+    }
+
+    /// 评审在挑战期内缴纳保证金对错误的派奖根提出异议，冻结证明领取直至仲裁
+    /// 同一派奖根同时只能有一个未决挑战，保证金存放在挑战记录中
+    #[access_control(payout_root_challengeable(&ctx.accounts.payout_root))]
+    pub fn challenge_payout_root(ctx: Context<ChallengePayoutRoot>) -> Result<()> {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.challenger.to_account_info(),
+                    to: ctx.accounts.payout_challenge.to_account_info(),
+                },
+            ),
+            PAYOUT_CHALLENGE_BOND,
+        )?;
+
+        let payout_root = &mut ctx.accounts.payout_root;
+        payout_root.challenged = true;
+
+        let challenge = &mut ctx.accounts.payout_challenge;
+        challenge.payout_root = payout_root.key();
+        challenge.challenger = ctx.accounts.challenger.key();
+        challenge.bond = PAYOUT_CHALLENGE_BOND;
+        challenge.opened_at = Clock::get()?.unix_timestamp;
+        challenge.bump = ctx.bumps.payout_challenge;
+
+        emit_cpi!(PayoutRootChallenged {
+            idea: payout_root.idea,
+            challenger: challenge.challenger,
+            bond: challenge.bond,
+        });
+
+        Ok(())
+    }
+
+    /// 仲裁裁决派奖根挑战: 支持则退还保证金，派奖根保持冻结直至重新发布；
+    /// 驳回则保证金归协议财库并恢复证明领取
+    #[access_control(
+        is_protocol_authority(&ctx.accounts.protocol_config.authority, ctx.accounts.arbiter.key)
+    )]
+    pub fn resolve_payout_challenge(
+        ctx: Context<ResolvePayoutChallenge>,
+        upheld: bool,
+    ) -> Result<()> {
+        let bond = ctx.accounts.payout_challenge.bond;
+
+        if !upheld {
+            move_lamports(
+                &ctx.accounts.payout_challenge.to_account_info(),
+                &ctx.accounts.protocol_treasury.to_account_info(),
+                bond,
+            )?;
+            ctx.accounts.payout_root.challenged = false;
+        }
+
+        emit_cpi!(PayoutChallengeResolved {
+            idea: ctx.accounts.payout_root.idea,
+            challenger: ctx.accounts.challenger.key(),
+            upheld,
+            bond,
+        });
+
+        Ok(())
+    }
+
+    /// 凭 Merkle 证明领取派奖
+    #[access_control(
+        claimable(&ctx.accounts.idea)
+        not_withdrawn(ctx.accounts.reviewer_stake.is_winner)
+        payout_root_final(&ctx.accounts.payout_root)
+    )]
+    pub fn claim_with_proof(
+        ctx: Context<ClaimWithProof>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let vault = core_vault!(ctx);
+        let idea = &ctx.accounts.idea;
+        let payout_root = &mut ctx.accounts.payout_root;
+        let leaf = balance_leaf(&idea.key(), &ctx.accounts.reviewer.key(), amount);
+        require!(
+            verify_merkle_proof(&proof, payout_root.root, leaf),
+            ConsensusError::InvalidMerkleProof
+        );
+
+        payout_root.claimed_amount = payout_root
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(ConsensusError::Overflow)?;
+        require!(
+            payout_root.claimed_amount <= payout_root.total_amount,
+            ConsensusError::PayoutExceedsTotal
+        );
+
+        // 派奖叶子不含费用折扣返还，返还在领取时按链上档位结算
+        let payout = amount
+            .checked_add(claim_fees!(ctx, idea)?)
+            .ok_or(ConsensusError::Overflow)?;
+        let idea_key = idea.key();
+
+        if !vest_winnings(
+            &event_emitter!(ctx),
+            &vault.core,
+            &mut ctx.accounts.idea,
+            ctx.accounts.vesting_claim.as_deref_mut(),
+            ctx.bumps.vesting_claim,
+            ctx.accounts.reviewer.key(),
+            payout,
+        )? {
+            vault.transfer(ctx.accounts.reviewer_token_account.to_account_info(), payout)?;
+        }
+        let idea = &ctx.accounts.idea;
+
+        let reviewer_stake = &mut ctx.accounts.reviewer_stake;
+        reviewer_stake.is_winner = true;
+        reviewer_stake.winnings = payout;
+        vault.core.sync_stake(reviewer_stake)?;
+        // 派奖金额超过本金即为获胜 (无损模式的未获胜者恰好取回本金)
+        record_reputation(
+            &event_emitter!(ctx),
+            &mut ctx.accounts.reputation,
+            ctx.accounts.reviewer.key(),
+            ctx.bumps.reputation,
+            amount > reviewer_stake.total_staked,
+            reviewer_stake.total_staked,
+            payout,
+            ctx.accounts.protocol_config.season,
+        )?;
+        rank_reviewer!(ctx, idea.theme);
+
+        emit_cpi!(WinningsWithdrawn {
+            idea: idea_key,
+            reviewer: ctx.accounts.reviewer.key(),
+            amount: payout,
+        });
+
+        Ok(())
+    }
+
+    /// 财库为已结束的赛季注资 SOL 奖励池并发布积分奖励 Merkle 根
+    /// (叶子为 points_reward_leaf(rewards_season, user, lamports)，由链下按 PointsAccrued 汇总)
+    #[access_control(season_ended(season, ctx.accounts.protocol_config.season))]
+    pub fn fund_season_rewards(
+        ctx: Context<FundSeasonRewards>,
+        season: u32,
+        merkle_root: [u8; 32],
+        total_amount: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(total_amount > 0, ConsensusError::InvalidAmount);
+        require!(
+            expires_at >= Clock::get()?.unix_timestamp + MIN_REWARDS_CLAIM_WINDOW,
+            ConsensusError::RewardsExpired
+        );
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.treasury.to_account_info(),
+                    to: ctx.accounts.rewards_season.to_account_info(),
+                },
+            ),
+            total_amount,
+        )?;
+
+        let rewards_season = &mut ctx.accounts.rewards_season;
+        rewards_season.season = season;
+        rewards_season.merkle_root = merkle_root;
+        rewards_season.total_amount = total_amount;
+        rewards_season.claimed_amount = 0;
+        rewards_season.expires_at = expires_at;
+        rewards_season.reclaimed = false;
+        rewards_season.bump = ctx.bumps.rewards_season;
+
+        emit_cpi!(SeasonRewardsFunded {
+            rewards_season: rewards_season.key(),
+            season,
+            merkle_root,
+            total_amount,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// 到期前凭 Merkle 证明领取赛季积分奖励
+    #[access_control(rewards_open(ctx.accounts.rewards_season.expires_at))]
+    pub fn claim_season_rewards(
+        ctx: Context<ClaimSeasonRewards>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(amount > 0, ConsensusError::InvalidAmount);
+
+        let rewards_season = &mut ctx.accounts.rewards_season;
+        let rewards_season_key = rewards_season.key();
+        let claimant = ctx.accounts.claimant.key();
+        require!(
+            verify_merkle_proof(
+                &proof,
+                rewards_season.merkle_root,
+                points_reward_leaf(&rewards_season_key, &claimant, amount)
+            ),
+            ConsensusError::InvalidMerkleProof
+        );
+
+        rewards_season.claimed_amount = rewards_season
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(ConsensusError::Overflow)?;
+        require!(
+            rewards_season.claimed_amount <= rewards_season.total_amount,
+            ConsensusError::PayoutExceedsTotal
+        );
+
+        move_lamports(
+            &rewards_season.to_account_info(),
+            &ctx.accounts.claimant.to_account_info(),
+            amount,
+        )?;
+
+        let claim = &mut ctx.accounts.rewards_claim;
+        claim.rewards_season = rewards_season_key;
+        claim.claimant = claimant;
+        claim.amount = amount;
+
+        emit_cpi!(SeasonRewardsClaimed {
+            rewards_season: rewards_season_key,
+            season: rewards_season.season,
+            claimant,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// 领取期结束后财库收回未领取的赛季奖励
+    #[access_control(rewards_reclaimable(&ctx.accounts.rewards_season))]
+    pub fn reclaim_season_rewards(ctx: Context<ReclaimSeasonRewards>) -> Result<()> {
+        let rewards_season = &mut ctx.accounts.rewards_season;
+
+        let amount = rewards_season.total_amount - rewards_season.claimed_amount;
+        if amount > 0 {
+            move_lamports(
+                &rewards_season.to_account_info(),
+                &ctx.accounts.treasury.to_account_info(),
+                amount,
+            )?;
+        }
+        rewards_season.reclaimed = true;
+
+        emit_cpi!(SeasonRewardsReclaimed {
+            rewards_season: rewards_season.key(),
+            season: rewards_season.season,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// 铸造获胜图片的 NFT (Token Metadata 1/1 主版本)，每个创意仅一枚
+    /// 结算终局后发起者独占优先期，逾期未铸造则任一获胜评审可铸造
+    /// 唯一创作者为创意的版税金库 PDA (二级版税由获胜评审分领)，Idea 上的图片哈希作为来源凭证
+    #[access_control(
+        idea_in_status(ctx.accounts.idea.status, IdeaStatus::Completed)
+        require_claims_open(&ctx.accounts.idea)
+        can_mint_winner_nft(&ctx.accounts.idea, ctx.accounts.minter.key, &ctx.accounts.vote)
+    )]
+    pub fn mint_winner_nft(ctx: Context<MintWinnerNft>) -> Result<()> {
+        let idea = &ctx.accounts.idea;
+        let image_index = idea.winning_image_index.ok_or(ConsensusError::NoWinner)?;
+        let uri = idea
+            .image_uris
+            .get(image_index as usize)
+            .ok_or(ConsensusError::InvalidImageIndex)?
+            .clone();
+        // 早于哈希记录的创意按 URI 重新计算
+        let image_hash = match idea.image_hashes[image_index as usize] {
+            hash if hash != [0; 32] => hash,
+            _ => anchor_lang::solana_program::keccak::hash(uri.as_bytes()).to_bytes(),
+        };
+
+        let authority_seeds: &[&[u8]] = &[b"nft_authority", &[ctx.bumps.nft_authority]];
+
+        let royalty_vault = &mut ctx.accounts.royalty_vault;
+        royalty_vault.idea = idea.key();
+        royalty_vault.total_claimed = 0;
+        royalty_vault.bump = ctx.bumps.royalty_vault;
+
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.nft_mint.to_account_info(),
+                    to: ctx.accounts.minter_nft_account.to_account_info(),
+                    authority: ctx.accounts.nft_authority.to_account_info(),
+                },
+                &[authority_seeds],
+            ),
+            1,
+        )?;
+        let a = &ctx.accounts;
+        create_winner_nft_metadata(
+            &WinnerNftMetadataAccounts {
+                metadata: a.metadata.to_account_info(),
+                master_edition: a.master_edition.to_account_info(),
+                nft_mint: a.nft_mint.to_account_info(),
+                nft_authority: a.nft_authority.to_account_info(),
+                payer: a.minter.to_account_info(),
+                royalty_vault: a.royalty_vault.key(),
+                token_program: a.token_program.to_account_info(),
+                system_program: a.system_program.to_account_info(),
+                rent: a.rent.to_account_info(),
+                token_metadata_program: a.token_metadata_program.to_account_info(),
+            },
+            format!("taste.fun #{}", idea.idea_id),
+            uri,
+            authority_seeds,
+        )?;
+
+        let winner_nft = &mut ctx.accounts.winner_nft;
+        winner_nft.idea = idea.key();
+        winner_nft.mint = ctx.accounts.nft_mint.key();
+        winner_nft.minter = ctx.accounts.minter.key();
+        winner_nft.image_index = image_index;
+        winner_nft.image_hash = image_hash;
+        winner_nft.minted_at = Clock::get()?.unix_timestamp;
+        winner_nft.bump = ctx.bumps.winner_nft;
+
+        emit_cpi!(WinnerNftMinted {
+            idea: winner_nft.idea,
+            mint: winner_nft.mint,
+            minter: winner_nft.minter,
+            image_index,
+            image_hash,
+        });
+
+        Ok(())
+    }
+
+    /// 发布获胜评审 NFT 空投 Merkle 根 (叶子为 winner_nft_leaf(idea, winner))
+    /// 大型竞赛不在结算时逐个铸造，由获胜评审凭证明自行领取；首次领取前可替换
+    #[access_control(
+        is_protocol_authority(&ctx.accounts.protocol_config.authority, ctx.accounts.settler.key)
+        idea_in_status(ctx.accounts.idea.status, IdeaStatus::Completed)
+    )]
+    pub fn post_winner_nft_root(
+        ctx: Context<PostWinnerNftRoot>,
+        root: [u8; 32],
+        winner_count: u64,
+    ) -> Result<()> {
+        require!(
+            winner_count > 0 && winner_count <= ctx.accounts.idea.winner_count,
+            ConsensusError::InvalidAmount
+        );
+
+        let drop = &mut ctx.accounts.winner_nft_drop;
+        require!(drop.claimed_count == 0, ConsensusError::WinnerNftRootLocked);
+
+        drop.idea = ctx.accounts.idea.key();
+        drop.root = root;
+        drop.winner_count = winner_count;
+        drop.posted_at = Clock::get()?.unix_timestamp;
+        drop.bump = ctx.bumps.winner_nft_drop;
+
+        emit_cpi!(WinnerNftRootPosted {
+            idea: drop.idea,
+            root,
+            winner_count,
+        });
+
+        Ok(())
+    }
+
+    /// 获胜评审凭 Merkle 证明领取获胜图片的纪念 NFT (每人一枚，版税同样归创意的版税金库)
+    #[access_control(
+        idea_in_status(ctx.accounts.idea.status, IdeaStatus::Completed)
+        require_claims_open(&ctx.accounts.idea)
+    )]
+    pub fn claim_winner_nft(ctx: Context<ClaimWinnerNft>, proof: Vec<[u8; 32]>) -> Result<()> {
+        let idea = &ctx.accounts.idea;
+        let winner = ctx.accounts.winner.key();
+        let drop = &mut ctx.accounts.winner_nft_drop;
+        require!(
+            verify_merkle_proof(&proof, drop.root, winner_nft_leaf(&idea.key(), &winner)),
+            ConsensusError::InvalidMerkleProof
+        );
+        drop.claimed_count = drop.claimed_count
+            .checked_add(1)
+            .ok_or(ConsensusError::Overflow)?;
+        require!(
+            drop.claimed_count <= drop.winner_count,
+            ConsensusError::WinnerNftDropExhausted
+        );
+
+        let image_index = idea.winning_image_index.ok_or(ConsensusError::NoWinner)?;
+        let uri = idea
+            .image_uris
+            .get(image_index as usize)
+            .ok_or(ConsensusError::InvalidImageIndex)?
+            .clone();
+
+        let authority_seeds: &[&[u8]] = &[b"nft_authority", &[ctx.bumps.nft_authority]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.nft_mint.to_account_info(),
+                    to: ctx.accounts.winner_nft_account.to_account_info(),
+                    authority: ctx.accounts.nft_authority.to_account_info(),
+                },
+                &[authority_seeds],
+            ),
+            1,
+        )?;
+        let a = &ctx.accounts;
+        create_winner_nft_metadata(
+            &WinnerNftMetadataAccounts {
+                metadata: a.metadata.to_account_info(),
+                master_edition: a.master_edition.to_account_info(),
+                nft_mint: a.nft_mint.to_account_info(),
+                nft_authority: a.nft_authority.to_account_info(),
+                payer: a.winner.to_account_info(),
+                royalty_vault: a.royalty_vault.key(),
+                token_program: a.token_program.to_account_info(),
+                system_program: a.system_program.to_account_info(),
+                rent: a.rent.to_account_info(),
+                token_metadata_program: a.token_metadata_program.to_account_info(),
+            },
+            format!("taste.fun #{} Winner", idea.idea_id),
+            uri,
+            authority_seeds,
+        )?;
+
+        let claim = &mut ctx.accounts.winner_nft_claim;
+        claim.idea = idea.key();
+        claim.winner = winner;
+        claim.mint = ctx.accounts.nft_mint.key();
+        claim.claimed_at = Clock::get()?.unix_timestamp;
+        claim.bump = ctx.bumps.winner_nft_claim;
+
+        emit_cpi!(WinnerNftClaimed {
+            idea: claim.idea,
+            winner,
+            mint: claim.mint,
+        });
+
+        Ok(())
+    }
+
+    /// 主题创建者为主题配置参与徽章 merkle 树 (树账户由客户端按深度预先分配给压缩程序)
+    /// 徽章树 PDA 作为 Bubblegum 树的创建者，铸造时由本程序签名
+    #[access_control(is_theme_admin(&ctx.accounts.theme.creator, ctx.accounts.creator.key))]
+    pub fn init_badge_tree(
+        ctx: Context<InitBadgeTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+        uri: String,
+    ) -> Result<()> {
+        require!(
+            max_depth > 0 && max_depth <= MAX_BADGE_TREE_DEPTH,
+            ConsensusError::InvalidBadgeTree
+        );
+        require!(
+            !uri.is_empty() && uri.len() <= MAX_IMAGE_URI_LEN,
+            ConsensusError::InvalidImageUri
+        );
+
+        let theme_key = ctx.accounts.theme.key();
+        let tree_seeds: &[&[u8]] = &[b"badge_tree", theme_key.as_ref(), &[ctx.bumps.badge_tree]];
+
+        let mut data = BUBBLEGUM_CREATE_TREE_DISCRIMINATOR.to_vec();
+        (max_depth, max_buffer_size, Some(false)).serialize(&mut data)?; // public = false
+        let a = &ctx.accounts;
+        let ix = Instruction {
+            program_id: BUBBLEGUM_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(a.tree_config.key(), false),
+                AccountMeta::new(a.merkle_tree.key(), false),
+                AccountMeta::new(a.creator.key(), true),
+                AccountMeta::new_readonly(a.badge_tree.key(), true),
+                AccountMeta::new_readonly(a.log_wrapper.key(), false),
+                AccountMeta::new_readonly(a.compression_program.key(), false),
+                AccountMeta::new_readonly(a.system_program.key(), false),
+            ],
+            data,
+        };
+        invoke_signed(
+            &ix,
+            &[
+                a.tree_config.to_account_info(),
+                a.merkle_tree.to_account_info(),
+                a.creator.to_account_info(),
+                a.badge_tree.to_account_info(),
+                a.log_wrapper.to_account_info(),
+                a.compression_program.to_account_info(),
+                a.system_program.to_account_info(),
+                a.bubblegum_program.to_account_info(),
+            ],
+            &[tree_seeds],
+        )?;
+
+        let badge_tree = &mut ctx.accounts.badge_tree;
+        badge_tree.theme = theme_key;
+        badge_tree.merkle_tree = ctx.accounts.merkle_tree.key();
+        badge_tree.enabled = true;
+        badge_tree.uri = uri.clone();
+        badge_tree.minted = 0;
+        badge_tree.bump = ctx.bumps.badge_tree;
+
+        emit_cpi!(BadgeTreeInitialized {
+            theme: theme_key,
+            merkle_tree: badge_tree.merkle_tree,
+            max_depth,
+            max_buffer_size,
+        });
+        emit_cpi!(BadgeConfigUpdated {
+            theme: theme_key,
+            enabled: true,
+            uri,
+        });
+
+        Ok(())
+    }
+
+    /// 主题创建者开关参与徽章或更换徽章元数据 URI
+    #[access_control(is_theme_admin(&ctx.accounts.theme.creator, ctx.accounts.creator.key))]
+    pub fn set_badge_config(ctx: Context<SetBadgeConfig>, enabled: bool, uri: String) -> Result<()> {
+        require!(
+            !uri.is_empty() && uri.len() <= MAX_IMAGE_URI_LEN,
+            ConsensusError::InvalidImageUri
+        );
+
+        let badge_tree = &mut ctx.accounts.badge_tree;
+        badge_tree.enabled = enabled;
+        badge_tree.uri = uri.clone();
+
+        emit_cpi!(BadgeConfigUpdated {
+            theme: badge_tree.theme,
+            enabled,
+            uri,
+        });
+
+        Ok(())
+    }
+
+    /// 结算后为投票者批量铸造参与徽章 (任何 keeper 可调用)
+    /// remaining_accounts 按每组 2 个账户排列 (vote, voter)，投票者公钥须严格升序，
+    /// 且大于本创意上一批的最后一个投票者，据此保证每人只铸造一次
+    #[access_control(idea_in_status(ctx.accounts.idea.status, IdeaStatus::Completed))]
+    pub fn mint_participation_badges<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MintParticipationBadges<'info>>,
+    ) -> Result<()> {
+        let remaining = ctx.remaining_accounts;
+        require!(
+            !remaining.is_empty() && remaining.len() % 2 == 0,
+            ConsensusError::InvalidAmount
+        );
+
+        let idea_key = ctx.accounts.idea.key();
+        let badge_drop = &mut ctx.accounts.badge_drop;
+        if badge_drop.idea == Pubkey::default() {
+            badge_drop.idea = idea_key;
+            badge_drop.bump = ctx.bumps.badge_drop;
+        }
+
+        let badge_tree = &ctx.accounts.badge_tree;
+        let tree_seeds: &[&[u8]] = &[b"badge_tree", badge_tree.theme.as_ref(), &[badge_tree.bump]];
+        let name = format!("Voter #{}", ctx.accounts.idea.idea_id);
+        let mut cursor = badge_drop.cursor;
+        let mut minted: u64 = 0;
+
+        for group in remaining.chunks(2) {
+            let vote = Account::<Vote>::try_from(&group[0])?;
+            require_keys_eq!(vote.idea, idea_key, ConsensusError::InvalidState);
+            require_keys_eq!(group[1].key(), vote.voter, ConsensusError::Unauthorized);
+            require!(vote.voter > cursor, ConsensusError::BadgeOrderViolation);
+            cursor = vote.voter;
+
+            mint_badge(
+                ctx.accounts,
+                &group[1],
+                bubblegum_mint_v1_data(name.clone(), NFT_SYMBOL.to_string(), badge_tree.uri.clone())?,
+                tree_seeds,
+            )?;
+            minted += 1;
+        }
+
+        let badge_drop = &mut ctx.accounts.badge_drop;
+        badge_drop.cursor = cursor;
+        badge_drop.minted += minted;
+        let total_minted = badge_drop.minted;
+        ctx.accounts.badge_tree.minted += minted;
+
+        emit_cpi!(ParticipationBadgesMinted {
+            idea: idea_key,
+            keeper: ctx.accounts.keeper.key(),
+            minted,
+            total_minted,
+        });
+
+        Ok(())
+    }
+
+    /// 发起者在获胜图片 NFT 铸造后开启限时版本销售 (SOL 计价，可设上限，0 为不限量)
+    /// 收入按此处记录的 bps 分给获胜评审 (销售结束后按赔付权重领取)、发起者与主题回购池
+    #[access_control(
+        idea_in_status(ctx.accounts.idea.status, IdeaStatus::Completed)
+        valid_edition_sale(price, duration, voter_share_bps, initiator_share_bps, buyback_share_bps)
+    )]
+    pub fn open_edition_sale(
+        ctx: Context<OpenEditionSale>,
+        price: u64,
+        max_supply: u64,
+        duration: i64,
+        voter_share_bps: u16,
+        initiator_share_bps: u16,
+        buyback_share_bps: u16,
+    ) -> Result<()> {
+        let idea = &ctx.accounts.idea;
+        let uri = idea
+            .image_uris
+            .get(ctx.accounts.winner_nft.image_index as usize)
+            .ok_or(ConsensusError::InvalidImageIndex)?
+            .clone();
+        let authority_seeds: &[&[u8]] = &[b"nft_authority", &[ctx.bumps.nft_authority]];
+        create_edition_metadata(
+            &ctx,
+            format!("taste.fun #{} Edition", idea.idea_id),
+            uri,
+            authority_seeds,
+        )?;
+
+        let end_ts = Clock::get()?
+            .unix_timestamp
+            .checked_add(duration)
+            .ok_or(ConsensusError::Overflow)?;
+        let sale = &mut ctx.accounts.edition_sale;
+        sale.idea = idea.key();
+        sale.mint = ctx.accounts.edition_mint.key();
+        sale.price = price;
+        sale.max_supply = max_supply;
+        sale.sold = 0;
+        sale.end_ts = end_ts;
+        sale.voter_share_bps = voter_share_bps;
+        sale.initiator_share_bps = initiator_share_bps;
+        sale.buyback_share_bps = buyback_share_bps;
+        sale.voter_revenue = 0;
+        sale.voter_claimed = 0;
+        sale.bump = ctx.bumps.edition_sale;
+
+        emit_cpi!(EditionSaleOpened {
+            idea: sale.idea,
+            mint: sale.mint,
+            price,
+            max_supply,
+            end_ts,
+            voter_share_bps,
+            initiator_share_bps,
+            buyback_share_bps,
+        });
+
+        Ok(())
+    }
+
+    /// 购买获胜图片版本：评审份额留存在销售账户，发起者份额直接转出，其余注入主题回购池
+    #[access_control(edition_sale_open(&ctx.accounts.edition_sale, quantity))]
+    pub fn buy_edition(ctx: Context<BuyEdition>, quantity: u64) -> Result<()> {
+        let sale = &ctx.accounts.edition_sale;
+        let cost = sale
+            .price
+            .checked_mul(quantity)
+            .ok_or(ConsensusError::Overflow)?;
+        let voter_amount = bps_of(cost, sale.voter_share_bps)?;
+        let initiator_amount = bps_of(cost, sale.initiator_share_bps)?;
+        let buyback_amount = cost
+            .checked_sub(voter_amount)
+            .and_then(|x| x.checked_sub(initiator_amount))
+            .ok_or(ConsensusError::Overflow)?;
+
+        for (to, amount) in [
+            (ctx.accounts.edition_sale.to_account_info(), voter_amount),
+            (ctx.accounts.initiator.to_account_info(), initiator_amount),
+        ] {
+            if amount > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.buyer.to_account_info(),
+                            to,
+                        },
+                    ),
+                    amount,
+                )?;
+            }
+        }
+        if buyback_amount > 0 {
+            taste_fun_token::cpi::fund_buyback_pool(
+                CpiContext::new(
+                    ctx.accounts.theme_program.to_account_info(),
+                    taste_fun_token::cpi::accounts::FundBuybackPool {
+                        theme: ctx.accounts.theme.to_account_info(),
+                        vault: ctx.accounts.theme_vault.to_account_info(),
+                        funder: ctx.accounts.buyer.to_account_info(),
+                        system_program: ctx.accounts.system_program.to_account_info(),
+                        event_authority: ctx.accounts.theme_event_authority.to_account_info(),
+                        program: ctx.accounts.theme_program.to_account_info(),
+                    },
+                ),
+                buyback_amount,
+            )?;
+        }
+
+        let authority_seeds: &[&[u8]] = &[b"nft_authority", &[ctx.bumps.nft_authority]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.edition_mint.to_account_info(),
+                    to: ctx.accounts.buyer_edition_account.to_account_info(),
+                    authority: ctx.accounts.nft_authority.to_account_info(),
+                },
+                &[authority_seeds],
+            ),
+            quantity,
+        )?;
+
+        let sale = &mut ctx.accounts.edition_sale;
+        sale.sold = sale.sold.checked_add(quantity).ok_or(ConsensusError::Overflow)?;
+        sale.voter_revenue = sale
+            .voter_revenue
+            .checked_add(voter_amount)
+            .ok_or(ConsensusError::Overflow)?;
+
+        emit_cpi!(EditionPurchased {
+            idea: sale.idea,
+            buyer: ctx.accounts.buyer.key(),
+            quantity,
+            voter_amount,
+            initiator_amount,
+            buyback_amount,
+        });
+
+        Ok(())
+    }
+
+    /// 销售结束后获胜评审按赔付权重领取版本收入分成 (每人一次)
+    #[access_control(
+        idea_in_status(ctx.accounts.idea.status, IdeaStatus::Completed)
+        edition_sale_ended(&ctx.accounts.edition_sale)
+    )]
+    pub fn claim_edition_revenue(ctx: Context<ClaimEditionRevenue>) -> Result<()> {
+        let idea = &ctx.accounts.idea;
+        let vote = &ctx.accounts.vote;
+        require!(is_winning_vote(idea, vote), ConsensusError::NotWinner);
+
+        let unit = payout_unit(
+            idea.payout_curve,
+            idea.payout_exponent,
+            vote.stake_amount,
+            vote.vote_weight,
+        );
+        let sale = &mut ctx.accounts.edition_sale;
+        let amount = (sale.voter_revenue as u128)
+            .checked_mul(unit)
+            .and_then(|x| x.checked_div(winning_payout_weight(idea)))
+            .and_then(|x| u64::try_from(x).ok())
+            .ok_or(ConsensusError::DivisionByZero)?;
+        require!(amount > 0, ConsensusError::InvalidAmount);
+
+        move_lamports(
+            &sale.to_account_info(),
+            &ctx.accounts.voter.to_account_info(),
+            amount,
+        )?;
+        sale.voter_claimed = sale
+            .voter_claimed
+            .checked_add(amount)
+            .ok_or(ConsensusError::Overflow)?;
+
+        let claim = &mut ctx.accounts.edition_claim;
+        claim.idea = idea.key();
+        claim.voter = ctx.accounts.voter.key();
+        claim.amount = amount;
+        claim.bump = ctx.bumps.edition_claim;
+
+        emit_cpi!(EditionRevenueClaimed {
+            idea: claim.idea,
+            voter: claim.voter,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// 获胜评审领取版税金库中累计的二级市场版税 (按赔付权重，可随版税到账多次领取)
+    #[access_control(idea_in_status(ctx.accounts.idea.status, IdeaStatus::Completed))]
+    pub fn claim_royalties(ctx: Context<ClaimRoyalties>) -> Result<()> {
+        let idea = &ctx.accounts.idea;
+        let vote = &ctx.accounts.vote;
+        require!(is_winning_vote(idea, vote), ConsensusError::NotWinner);
+
+        // 累计收到的版税 = 当前可用余额 + 已领取总额
+        let royalty_vault = &ctx.accounts.royalty_vault;
+        let vault_info = royalty_vault.to_account_info();
+        let rent_floor = Rent::get()?.minimum_balance(vault_info.data_len());
+        let total_received = vault_info
+            .lamports()
+            .saturating_sub(rent_floor)
+            .checked_add(royalty_vault.total_claimed)
+            .ok_or(ConsensusError::Overflow)?;
+
+        let unit = payout_unit(
+            idea.payout_curve,
+            idea.payout_exponent,
+            vote.stake_amount,
+            vote.vote_weight,
+        );
+        let entitled = (total_received as u128)
+            .checked_mul(unit)
+            .and_then(|x| x.checked_div(winning_payout_weight(idea)))
+            .and_then(|x| u64::try_from(x).ok())
+            .ok_or(ConsensusError::DivisionByZero)?;
+
+        let claim = &mut ctx.accounts.royalty_claim;
+        if claim.voter == Pubkey::default() {
+            claim.idea = idea.key();
+            claim.voter = ctx.accounts.voter.key();
+            claim.bump = ctx.bumps.royalty_claim;
+        }
+        let amount = entitled.saturating_sub(claim.claimed);
+        require!(amount > 0, ConsensusError::InvalidAmount);
+
+        move_lamports(&vault_info, &ctx.accounts.voter.to_account_info(), amount)?;
+        claim.claimed = entitled;
+        ctx.accounts.royalty_vault.total_claimed = ctx
+            .accounts
+            .royalty_vault
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(ConsensusError::Overflow)?;
+
+        emit_cpi!(RoyaltiesClaimed {
+            idea: claim.idea,
+            voter: claim.voter,
+            amount,
+            total_claimed: ctx.accounts.royalty_vault.total_claimed,
+        });
+
+        Ok(())
+    }
+
+    /// 为原创意创建衍生版税池 (任何人可调用，衍生创意结算前须存在)
+    #[access_control(idea_in_status(ctx.accounts.parent_idea.status, IdeaStatus::Completed))]
+    pub fn init_remix_pool(ctx: Context<InitRemixPool>) -> Result<()> {
+        let pool = &mut ctx.accounts.remix_pool;
+        pool.parent = ctx.accounts.parent_idea.key();
+        pool.token_mint = ctx.accounts.theme_token_mint.key();
+        pool.winner_revenue = 0;
+        pool.initiator_revenue = 0;
+        pool.initiator_claimed = 0;
+        pool.bump = ctx.bumps.remix_pool;
+
+        emit_cpi!(RemixPoolInitialized {
+            parent: pool.parent,
+            token_mint: pool.token_mint,
+        });
+
+        Ok(())
+    }
+
+    /// 原创意的发起者与获胜评审领取衍生版税 (按累计应得额差值，可多次领取)
+    /// 获胜评审按原创意的赔付权重分配，发起者领取其固定份额
+    #[access_control(idea_in_status(ctx.accounts.parent_idea.status, IdeaStatus::Completed))]
+    pub fn claim_remix_royalties(ctx: Context<ClaimRemixRoyalties>) -> Result<()> {
+        let parent = &ctx.accounts.parent_idea;
+        let claimer = ctx.accounts.claimer.key();
+        let pool = &mut ctx.accounts.remix_pool;
+        let mut amount: u64 = 0;
+
+        if claimer == parent.initiator {
+            amount = pool.initiator_revenue - pool.initiator_claimed;
+            pool.initiator_claimed = pool.initiator_revenue;
+        }
+
+        let claim = &mut ctx.accounts.remix_claim;
+        if claim.claimer == Pubkey::default() {
+            claim.parent = parent.key();
+            claim.claimer = claimer;
+            claim.bump = ctx.bumps.remix_claim;
+        }
+        if let Some(vote) = ctx.accounts.vote.as_ref().filter(|v| is_winning_vote(parent, v)) {
+            let unit = payout_unit(
+                parent.payout_curve,
+                parent.payout_exponent,
+                vote.stake_amount,
+                vote.vote_weight,
+            );
+            let entitled = (pool.winner_revenue as u128)
+                .checked_mul(unit)
+                .and_then(|x| x.checked_div(winning_payout_weight(parent)))
+                .and_then(|x| u64::try_from(x).ok())
+                .ok_or(ConsensusError::DivisionByZero)?;
+            amount = amount
+                .checked_add(entitled.saturating_sub(claim.claimed))
+                .ok_or(ConsensusError::Overflow)?;
+            claim.claimed = entitled;
+        }
+        require!(amount > 0, ConsensusError::InvalidAmount);
+
+        let parent_key = parent.key();
+        let pool_seeds: &[&[u8]] = &[b"remix_pool", parent_key.as_ref(), &[pool.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.remix_pool_token_account.to_account_info(),
+                    to: ctx.accounts.claimer_token_account.to_account_info(),
+                    authority: ctx.accounts.remix_pool.to_account_info(),
+                },
+                &[pool_seeds],
+            ),
+            amount,
+        )?;
+
+        emit_cpi!(RemixRoyaltiesClaimed {
+            parent: parent_key,
+            claimer,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// 为创意合集创建大奖池 (任何人可调用，合集内创意结算前须存在)
+    pub fn init_collection_prize(ctx: Context<InitCollectionPrize>) -> Result<()> {
+        let collection = &ctx.accounts.collection;
+        let prize = &mut ctx.accounts.collection_prize;
+        prize.collection = collection.key();
+        prize.token_mint = collection.theme_token_mint;
+        prize.grand_prize_bps = collection.grand_prize_bps;
+        prize.ends_at = collection.ends_at;
+        prize.grand_prize = 0;
+        prize.total_prize = 0;
+        prize.settled_count = 0;
+        prize.top_idea = Pubkey::default();
+        prize.top_stake = 0;
+        prize.finalized = false;
+        prize.bump = ctx.bumps.collection_prize;
+
+        emit_cpi!(CollectionPrizeInitialized {
+            collection: prize.collection,
+            token_mint: prize.token_mint,
+            grand_prize_bps: prize.grand_prize_bps,
+        });
+
+        Ok(())
+    }
+
+    /// 合集结束后定格大奖 (任何人可调用)，此后结算的合集创意不再注入大奖
+    pub fn finalize_collection(ctx: Context<FinalizeCollection>) -> Result<()> {
+        let prize = &mut ctx.accounts.collection_prize;
+        require!(!prize.finalized, ConsensusError::InvalidState);
+        require!(
+            Clock::get()?.unix_timestamp >= prize.ends_at,
+            ConsensusError::CollectionNotEnded
+        );
+        prize.finalized = true;
+
+        emit_cpi!(CollectionFinalized {
+            collection: prize.collection,
+            top_idea: prize.top_idea,
+            grand_prize: prize.grand_prize,
+            total_prize: prize.total_prize,
+            settled_count: prize.settled_count,
+        });
+
+        Ok(())
+    }
+
+    /// 合集最佳创意 (总质押最高) 的获胜评审按赔付权重瓜分合集大奖
+    #[access_control(
+        idea_in_status(ctx.accounts.top_idea.status, IdeaStatus::Completed)
+        require_claims_open(&ctx.accounts.top_idea)
+    )]
+    pub fn claim_collection_prize(ctx: Context<ClaimCollectionPrize>) -> Result<()> {
+        let idea = &ctx.accounts.top_idea;
+        let vote = &ctx.accounts.vote;
+        let prize = &ctx.accounts.collection_prize;
+        require!(prize.finalized, ConsensusError::CollectionNotEnded);
+        require!(is_winning_vote(idea, vote), ConsensusError::NotWinner);
+
+        let unit = payout_unit(idea.payout_curve, idea.payout_exponent, vote.stake_amount, vote.vote_weight);
+        let amount = (prize.grand_prize as u128)
+            .checked_mul(unit)
+            .and_then(|x| x.checked_div(winning_payout_weight(idea)))
+            .and_then(|x| u64::try_from(x).ok())
+            .ok_or(ConsensusError::DivisionByZero)?;
+        require!(amount > 0, ConsensusError::InvalidAmount);
+
+        let collection_key = prize.collection;
+        let prize_seeds: &[&[u8]] = &[b"collection_prize", collection_key.as_ref(), &[prize.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.collection_prize_token_account.to_account_info(),
+                    to: ctx.accounts.voter_token_account.to_account_info(),
+                    authority: ctx.accounts.collection_prize.to_account_info(),
+                },
+                &[prize_seeds],
+            ),
+            amount,
+        )?;
+
+        let claim = &mut ctx.accounts.collection_prize_claim;
+        claim.collection = collection_key;
+        claim.voter = ctx.accounts.voter.key();
+        claim.amount = amount;
+        claim.bump = ctx.bumps.collection_prize_claim;
+
+        emit_cpi!(CollectionPrizeClaimed {
+            collection: collection_key,
+            voter: claim.voter,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// 为主题创建画廊及第一页 (任何人可调用)，此后 settle_voting 提供画廊账户时追加获胜图片
+    pub fn init_gallery(ctx: Context<InitGallery>) -> Result<()> {
+        let theme = ctx.accounts.theme.key();
+        let gallery = &mut ctx.accounts.gallery;
+        gallery.theme = theme;
+        gallery.entry_count = 0;
+        gallery.page_count = 1;
+        gallery.bump = ctx.bumps.gallery;
+
+        let page = &mut ctx.accounts.gallery_page;
+        page.theme = theme;
+        page.page = 0;
+        page.entries = Vec::new();
+        page.bump = ctx.bumps.gallery_page;
+
+        emit_cpi!(GalleryPageCreated { theme, page: 0 });
+
+        Ok(())
+    }
+
+    /// 预先创建画廊的下一页 (任何人可调用，当前页写满前后均可)
+    pub fn init_gallery_page(ctx: Context<InitGalleryPage>) -> Result<()> {
+        let gallery = &mut ctx.accounts.gallery;
+        let page = &mut ctx.accounts.gallery_page;
+        page.theme = gallery.theme;
+        page.page = gallery.page_count;
+        page.entries = Vec::new();
+        page.bump = ctx.bumps.gallery_page;
+        gallery.page_count = gallery.page_count
+            .checked_add(1)
+            .ok_or(ConsensusError::Overflow)?;
+
+        emit_cpi!(GalleryPageCreated {
+            theme: page.theme,
+            page: page.page,
+        });
+
+        Ok(())
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+
+/// settle_voting 与 settle_many 共用的结算账户
+struct SettleAccounts<'a, 'info> {
+    idea: &'a mut Account<'info, Idea>,
+    vault: &'a CoreVault<'info>,
+    token_mint: AccountInfo<'info>,
+    vault_mint: Pubkey,
+    theme_buyback_token_account: AccountInfo<'info>,
+    settler_token_account: AccountInfo<'info>,
+    settler: Pubkey,
+    insurance: &'a mut Account<'info, Insurance>,
+    insurance_token_account: AccountInfo<'info>,
+    jackpot: Option<JackpotAccounts<'info>>,
+    remix_pool: Option<RemixPoolAccounts<'a, 'info>>,
+    collection_prize: Option<CollectionPrizeAccounts<'a, 'info>>,
+    gallery: Option<GalleryAccounts<'a>>,
+    recent_slothashes: AccountInfo<'info>,
+    platform_fee_bps: u16,
+    emitter: EventEmitter<'info>,
+}
+
+/// 经 core CPI 写回 core 持有的账户 (Idea、ReviewerStake 与金库的 owner 为 core)
+/// 本程序对这些账户的修改只作用于内存副本，退出时不会落盘，须由 core 写回
+struct CoreCpi<'info> {
+    core_program: AccountInfo<'info>,
+    settlement_authority: AccountInfo<'info>,
+    authority_bump: u8,
+}
+
+impl<'info> CoreCpi<'info> {
+    /// 将内存副本中的结算字段写回 Idea
+    fn sync_idea(&self, idea: &Account<'info, Idea>) -> Result<()> {
+        let seeds: &[&[u8]] = &[b"settlement_authority", &[self.authority_bump]];
+        taste_fun_core::cpi::apply_settlement(
+            CpiContext::new_with_signer(
+                self.core_program.clone(),
+                taste_fun_core::cpi::accounts::ApplySettlement {
+                    idea: idea.to_account_info(),
+                    settlement_authority: self.settlement_authority.clone(),
+                },
+                &[seeds],
+            ),
+            IdeaSettlement::from_idea(idea),
+        )
+    }
+
+    /// 将内存副本中的领取结果写回 ReviewerStake
+    fn sync_stake(&self, reviewer_stake: &Account<'info, ReviewerStake>) -> Result<()> {
+        let seeds: &[&[u8]] = &[b"settlement_authority", &[self.authority_bump]];
+        taste_fun_core::cpi::settle_reviewer_stake(
+            CpiContext::new_with_signer(
+                self.core_program.clone(),
+                taste_fun_core::cpi::accounts::SettleReviewerStake {
+                    reviewer_stake: reviewer_stake.to_account_info(),
+                    settlement_authority: self.settlement_authority.clone(),
+                },
+                &[seeds],
+            ),
+            reviewer_stake.is_winner,
+            reviewer_stake.winnings,
+        )
+    }
+}
+
+/// core 持有的创意金库：金库 PDA 只能由 core 签名，转出、销毁与关闭均经 core CPI
+struct CoreVault<'info> {
+    core: CoreCpi<'info>,
+    vault: AccountInfo<'info>,
+    vault_token_account: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+}
+
+impl<'info> CoreVault<'info> {
+    fn transfer(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let seeds: &[&[u8]] = &[b"settlement_authority", &[self.core.authority_bump]];
+        taste_fun_core::cpi::vault_transfer(
+            CpiContext::new_with_signer(
+                self.core.core_program.clone(),
+                taste_fun_core::cpi::accounts::VaultTransfer {
+                    vault: self.vault.clone(),
+                    vault_token_account: self.vault_token_account.clone(),
+                    destination: to,
+                    settlement_authority: self.core.settlement_authority.clone(),
+                    token_program: self.token_program.clone(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )
+    }
+
+    fn burn(&self, token_mint: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let seeds: &[&[u8]] = &[b"settlement_authority", &[self.core.authority_bump]];
+        taste_fun_core::cpi::vault_burn(
+            CpiContext::new_with_signer(
+                self.core.core_program.clone(),
+                taste_fun_core::cpi::accounts::VaultBurn {
+                    vault: self.vault.clone(),
+                    vault_token_account: self.vault_token_account.clone(),
+                    token_mint,
+                    settlement_authority: self.core.settlement_authority.clone(),
+                    token_program: self.token_program.clone(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )
+    }
+
+    /// 关闭金库代币账户与金库，租金退回接收方
+    fn close(&self, receiver: AccountInfo<'info>) -> Result<()> {
+        let seeds: &[&[u8]] = &[b"settlement_authority", &[self.core.authority_bump]];
+        taste_fun_core::cpi::close_vault(CpiContext::new_with_signer(
+            self.core.core_program.clone(),
+            taste_fun_core::cpi::accounts::CloseVault {
+                vault: self.vault.clone(),
+                vault_token_account: self.vault_token_account.clone(),
+                receiver,
+                settlement_authority: self.core.settlement_authority.clone(),
+                token_program: self.token_program.clone(),
+            },
+            &[seeds],
+        ))
+    }
+}
+
+/// 主题奖池账户 (赞助创意约定滚存时需要)
+struct JackpotAccounts<'info> {
+    key: Pubkey,
+    theme_token_mint: Pubkey,
+    token_account: AccountInfo<'info>,
+}
+
+/// 原创意的衍生版税池 (结算衍生创意时需要)
+struct RemixPoolAccounts<'a, 'info> {
+    pool: &'a mut Box<Account<'info, RemixPool>>,
+    token_account: AccountInfo<'info>,
+}
+
+/// 主题画廊及当前写入页 (提供时结算会追加获胜图片)
+struct GalleryAccounts<'a> {
+    gallery: &'a mut Gallery,
+    page: &'a mut GalleryPage,
+}
+
+/// 创意所属合集的大奖池 (结算合集创意时需要)
+struct CollectionPrizeAccounts<'a, 'info> {
+    prize: &'a mut Box<Account<'info, CollectionPrize>>,
+    token_account: AccountInfo<'info>,
+}
+
+/// 校验回购贡献的去向：主题须为创意所属主题，接收账户须为该主题金库的代币 ATA
+fn verify_theme_buyback_account(
+    idea: &Idea,
+    theme_info: &AccountInfo,
+    vault_mint: Pubkey,
+    theme_buyback_token_account: Pubkey,
+) -> Result<()> {
+    require_keys_eq!(theme_info.key(), idea.theme, ConsensusError::InvalidState);
+    require_keys_eq!(*theme_info.owner, taste_fun_token::ID, ConsensusError::InvalidState);
+    let theme = taste_fun_token::Theme::try_deserialize(&mut &theme_info.try_borrow_data()?[..])?;
+    require_keys_eq!(theme.token_mint, vault_mint, ConsensusError::InvalidMint);
+    require_keys_eq!(idea.theme_token_mint, vault_mint, ConsensusError::InvalidMint);
+
+    let theme_vault = Pubkey::create_program_address(
+        &[
+            b"theme_vault",
+            theme.creator.as_ref(),
+            theme.theme_id.to_le_bytes().as_ref(),
+            &[theme.vault_bump],
+        ],
+        &taste_fun_token::ID,
+    )
+    .map_err(|_| ConsensusError::InvalidState)?;
+    require_keys_eq!(
+        theme_buyback_token_account,
+        get_associated_token_address(&theme_vault, &vault_mint),
+        ConsensusError::InvalidState
+    );
+
+    Ok(())
+}
+
+/// 结算单个创意：判定获胜图片、分配费用并转账
+/// Idea 的修改只作用于内存副本，由调用方经 core CPI 写回
+fn settle_idea(a: SettleAccounts) -> Result<()> {
+    let idea = &mut *a.idea;
+    let vault = a.vault;
+
+    // 检查最小参与者数量
+    if idea.total_voters < MIN_REVIEWERS {
+        idea.cancel_reason = CANCEL_REASON_INSUFFICIENT_VOTERS;
+        idea.status = IdeaStatus::Cancelled;
+        a.emitter.emit(VotingCancelled {
+            idea: idea.key(),
+            reason: "Insufficient participation".to_string(),
+        })?;
+        return Ok(());
+    }
+
+    // 计算总投票权重 (包括 RejectAll)
+    let total_weight = idea.votes.iter().sum::<u64>() + idea.reject_all_weight;
+
+    // 检查 RejectAll 是否达到主题配置的否决阈值
+    if total_weight > 0 {
+        let reject_ratio_bps = (idea.reject_all_weight as u128)
+            .checked_mul(BPS_DENOMINATOR as u128)
+            .and_then(|x| x.checked_div(total_weight as u128))
+            .and_then(|x| u16::try_from(x).ok())
+            .ok_or(ConsensusError::Overflow)?;
+
+        if reject_ratio_bps >= idea.reject_all_threshold_bps {
+            // RejectAll 胜出，全员退款；赞助奖池按约定比例滚入主题奖池
+            let rollover = (idea.initial_prize_pool as u128)
+                .checked_mul(idea.rollover_bps as u128)
+                .and_then(|x| x.checked_div(BPS_DENOMINATOR as u128))
+                .and_then(|x| u64::try_from(x).ok())
+                .ok_or(ConsensusError::Overflow)?;
+
+            if rollover > 0 {
+                let (Some(jackpot), Some(jackpot_token_account)) = (
+                    a.jackpot.as_ref(),
+                    a.jackpot.as_ref().map(|j| &j.token_account),
+                ) else {
+                    return err!(ConsensusError::JackpotAccountMissing);
+                };
+                require!(
+                    jackpot.theme_token_mint == a.vault_mint,
+                    ConsensusError::InvalidTheme
+                );
+
+                let idea_key = idea.key();
+                vault.transfer(jackpot_token_account.to_account_info(), rollover)?;
+
+                a.emitter.emit(JackpotRolledOver {
+                    idea: idea_key,
+                    jackpot: jackpot.key,
+                    amount: rollover,
+                })?;
+            }
+
+            idea.cancel_reason = CANCEL_REASON_REJECTED;
+            idea.status = IdeaStatus::Cancelled;
+            a.emitter.emit(VotingCancelled {
+                idea: idea.key(),
+                reason: "Rejected by supermajority RejectAll votes".to_string(),
+            })?;
+            return Ok(());
+        }
+    }
+
+    // 根据投票模式决定获胜者 (模式在创建时已从主题固化到 Idea)
+    let (winning_index, secondary_index) = match idea.voting_mode {
+        VotingMode::Classic => {
+            // 经典模式：最多票获胜
+            let max_votes = *idea.votes.iter().max().unwrap();
+            let winning_indices: Vec<usize> = idea.votes
+                .iter()
+                .enumerate()
+                .filter(|(_, &v)| v == max_votes)
+                .map(|(i, _)| i)
+                .collect();
+
+            // 如果有平局，按主题规则打破平局；无法打破则取消投票
+            match break_tie(idea, &winning_indices, &a.recent_slothashes)? {
+                Some(winner) => (winner as u8, None),
+                None => {
+                    idea.cancel_reason = CANCEL_REASON_TIE;
+                    idea.status = IdeaStatus::Cancelled;
+                    a.emitter.emit(VotingCancelled {
+                        idea: idea.key(),
+                        reason: "Vote tied".to_string(),
+                    })?;
+                    return Ok(());
+                }
+            }
+        }
+        VotingMode::Reverse => {
+            // 反向模式：最少票获胜
+            let min_votes = *idea.votes.iter().min().unwrap();
+            let winning_indices: Vec<usize> = idea.votes
+                .iter()
+                .enumerate()
+                .filter(|(_, &v)| v == min_votes)
+                .map(|(i, _)| i)
+                .collect();
+
+            // 如果有平局，按主题规则打破平局；无法打破则取消投票
+            match break_tie(idea, &winning_indices, &a.recent_slothashes)? {
+                Some(winner) => (winner as u8, None),
+                None => {
+                    idea.cancel_reason = CANCEL_REASON_TIE;
+                    idea.status = IdeaStatus::Cancelled;
+                    a.emitter.emit(VotingCancelled {
+                        idea: idea.key(),
+                        reason: "Vote tied (reverse mode)".to_string(),
+                    })?;
+                    return Ok(());
+                }
+            }
+        }
+        VotingMode::MiddleWay => {
+            // 中间派模式：最多票和最少票的图片同时获胜，瓜分中间两张图片的惩罚池
+            let max_votes = *idea.votes.iter().max().unwrap();
+            let min_votes = *idea.votes.iter().min().unwrap();
+            let max_indices: Vec<usize> = idea.votes
+                .iter()
+                .enumerate()
+                .filter(|(_, &v)| v == max_votes)
+                .map(|(i, _)| i)
+                .collect();
+            let min_indices: Vec<usize> = idea.votes
+                .iter()
+                .enumerate()
+                .filter(|(_, &v)| v == min_votes)
+                .map(|(i, _)| i)
+                .collect();
+
+            // 最多或最少出现平局，按主题规则打破平局；无法打破则取消投票
+            let slot_hashes = &a.recent_slothashes;
+            let most = break_tie(idea, &max_indices, slot_hashes)?;
+            let least = break_tie(idea, &min_indices, slot_hashes)?;
+            match (most, least) {
+                (Some(most), Some(least)) if most != least => (most as u8, Some(least as u8)),
+                _ => {
+                    idea.cancel_reason = CANCEL_REASON_TIE;
+                    idea.status = IdeaStatus::Cancelled;
+                    a.emitter.emit(VotingCancelled {
+                        idea: idea.key(),
+                        reason: "Vote tied (middle way mode)".to_string(),
+                    })?;
+                    return Ok(());
+                }
+            }
+        }
+    };
+
+    idea.winning_image_index = Some(winning_index);
+    idea.secondary_winning_index = secondary_index;
+
+    // 计算费用分配 (无损模式下仅赞助奖池参与分配，评审本金不计费)
+    let fee_base = if idea.no_loss {
+        idea.initial_prize_pool
+    } else {
+        idea.total_staked
+    };
+
+    // 衍生创意按创建时约定的比例向原创意支付版税
+    let remix_fee_bps = if idea.parent.is_some() { idea.remix_fee_bps } else { 0 };
+
+    // 合集创意按合集约定的比例注入合集大奖 (合集定格后不再注入)
+    let collection_bps = match (idea.collection, a.collection_prize.as_ref()) {
+        (None, _) => 0,
+        (Some(_), None) => return err!(ConsensusError::CollectionPrizeMissing),
+        (Some(_), Some(c)) if c.prize.finalized => 0,
+        (Some(_), Some(c)) => c.prize.grand_prize_bps,
+    };
+
+    // 策展费与平台费仅在此计提并预留在金库中，由各评审领取时按份额结清 (可享受折扣)
+    // 结算者奖励激励 keeper 及时结算，保险基金用于事后补偿结算错误等可证明的损失，5% 进入主题回购池
+    let fees = SettlementFees::from_base(
+        fee_base,
+        idea.curator_fee_bps,
+        a.platform_fee_bps,
+        remix_fee_bps,
+        collection_bps,
+    )?;
+
+    // 获胜者取回全部本金、未获胜者取回未罚没部分，费用与惩罚池只能来自罚没部分
+    // 按主题配置销毁部分惩罚池，剩余部分分给获胜者
+    let winning_stake = secondary_index
+        .map_or(0, |second| idea.stake_totals[second as usize])
+        .checked_add(idea.stake_totals[winning_index as usize])
+        .ok_or(ConsensusError::Overflow)?;
+    let forfeited = forfeited_stake(
+        idea.total_staked,
+        idea.initial_prize_pool,
+        winning_stake,
+        idea.no_loss,
+    )?;
+    let SettlementSplit {
+        fees:
+            SettlementFees {
+                curator_fee,
+                platform_fee,
+                settler_reward,
+                insurance_contribution,
+                remix_royalty,
+                collection_contribution,
+                buyback_contribution,
+            },
+        penalty_burned,
+        penalty_pool,
+    } = settlement_split(fees, forfeited, idea.penalty_burn_bps)?;
+
+    // 获胜人数取投票人数 (votes 记录的是权重)
+    let winner_count = secondary_index
+        .map_or(0, |second| idea.voter_counts[second as usize] as u64)
+        .checked_add(idea.voter_counts[winning_index as usize] as u64)
+        .ok_or(ConsensusError::Overflow)?;
+
+    idea.curator_fee_collected = curator_fee;
+    idea.platform_fee_collected = platform_fee;
+    idea.insurance_contribution = insurance_contribution;
+    idea.remix_royalty = remix_royalty;
+    idea.penalty_pool_amount = penalty_pool;
+    idea.winner_count = winner_count;
+    // 争议期结束后结算终局，领取期从争议期结束起算
+    idea.dispute_deadline = Clock::get()?
+        .unix_timestamp
+        .checked_add(idea.dispute_period)
+        .ok_or(ConsensusError::Overflow)?;
+    idea.claim_deadline = idea
+        .dispute_deadline
+        .checked_add(CLAIM_WINDOW)
+        .ok_or(ConsensusError::Overflow)?;
+    idea.status = IdeaStatus::Completed;
+
+
+    // 转回购贡献到主题回购池（代币）
+    // 注意：这里先转到主题vault，后续由theme程序管理回购
+    vault.transfer(a.theme_buyback_token_account.clone(), buyback_contribution)?;
+
+    // 转结算者奖励给交易签名者（代币）
+    vault.transfer(a.settler_token_account.clone(), settler_reward)?;
+
+    // 转保险基金提取（代币）
+    vault.transfer(a.insurance_token_account.clone(), insurance_contribution)?;
+
+    let insurance = &mut *a.insurance;
+    insurance.total_contributed = insurance.total_contributed
+        .checked_add(insurance_contribution)
+        .ok_or(ConsensusError::Overflow)?;
+
+    // 转衍生版税到原创意的版税池（代币），按比例记入原发起者与原获胜评审
+    if remix_royalty > 0 {
+        let remix_pool = a.remix_pool.ok_or(ConsensusError::RemixPoolMissing)?;
+        let pool = &mut **remix_pool.pool;
+        require!(
+            idea.parent == Some(pool.parent) && pool.token_mint == a.vault_mint,
+            ConsensusError::InvalidRemixParent
+        );
+        require_keys_eq!(
+            remix_pool.token_account.key(),
+            get_associated_token_address(&pool.key(), &a.vault_mint),
+            ConsensusError::InvalidState
+        );
+        vault.transfer(remix_pool.token_account.clone(), remix_royalty)?;
+
+        let initiator_share = bps_of(remix_royalty, REMIX_INITIATOR_SHARE_BPS)?;
+        pool.initiator_revenue = pool
+            .initiator_revenue
+            .checked_add(initiator_share)
+            .ok_or(ConsensusError::Overflow)?;
+        pool.winner_revenue = pool
+            .winner_revenue
+            .checked_add(remix_royalty - initiator_share)
+            .ok_or(ConsensusError::Overflow)?;
+    }
+
+    // 转合集大奖注入（代币），并累计合集奖金统计与最佳创意
+    if let Some(collection_prize) = a.collection_prize {
+        let prize = &mut **collection_prize.prize;
+        require!(
+            idea.collection == Some(prize.collection) && prize.token_mint == a.vault_mint,
+            ConsensusError::InvalidCollection
+        );
+        require_keys_eq!(
+            collection_prize.token_account.key(),
+            get_associated_token_address(&prize.key(), &a.vault_mint),
+            ConsensusError::InvalidState
+        );
+        vault.transfer(collection_prize.token_account.clone(), collection_contribution)?;
+
+        if !prize.finalized {
+            prize.grand_prize = prize
+                .grand_prize
+                .checked_add(collection_contribution)
+                .ok_or(ConsensusError::Overflow)?;
+            prize.total_prize = prize
+                .total_prize
+                .checked_add(penalty_pool)
+                .ok_or(ConsensusError::Overflow)?;
+            prize.settled_count = prize
+                .settled_count
+                .checked_add(1)
+                .ok_or(ConsensusError::Overflow)?;
+            if winner_count > 0 && idea.total_staked > prize.top_stake {
+                prize.top_idea = idea.key();
+                prize.top_stake = idea.total_staked;
+            }
+        }
+    }
+
+    // 获胜图片写入主题画廊，按写入顺序落在第 entry_count / GALLERY_PAGE_CAPACITY 页
+    if let Some(GalleryAccounts { gallery, page }) = a.gallery {
+        require_keys_eq!(gallery.theme, idea.theme, ConsensusError::InvalidTheme);
+        require!(
+            page.theme == gallery.theme
+                && page.page as u64 == gallery.entry_count / GALLERY_PAGE_CAPACITY as u64
+                && page.entries.len() < GALLERY_PAGE_CAPACITY,
+            ConsensusError::InvalidGalleryPage
+        );
+        let entry = GalleryEntry {
+            idea: idea.key(),
+            image_hash: idea.image_hashes[winning_index as usize],
+            image_uri: idea
+                .image_uris
+                .get(winning_index as usize)
+                .cloned()
+                .unwrap_or_default(),
+            settled_at: Clock::get()?.unix_timestamp,
+        };
+        page.entries.push(entry.clone());
+        gallery.entry_count = gallery.entry_count
+            .checked_add(1)
+            .ok_or(ConsensusError::Overflow)?;
+
+        a.emitter.emit(GalleryEntryAdded {
+            theme: gallery.theme,
+            page: page.page,
+            entry,
+        })?;
+    }
+
+    // 销毁惩罚池份额（代币）
+    vault.burn(a.token_mint.clone(), penalty_burned)?;
+
+    a.emitter.emit(VotingSettled {
+        idea: idea.key(),
+        winning_image_index: winning_index,
+        secondary_winning_index: secondary_index,
+        total_staked: idea.total_staked,
+        curator_fee,
+        platform_fee,
+        penalty_pool,
+        winner_count,
+        settler: a.settler,
+        settler_reward,
+        insurance_contribution,
+        penalty_burned,
+        remix_royalty,
+        collection_contribution,
+    })
+}
+
+/// 争议期已过且无未决争议时结算才算终局，此后开放领取
+fn require_claims_open(idea: &Idea) -> Result<()> {
+    require!(!idea.disputed, ConsensusError::SettlementDisputed);
+    require!(
+        Clock::get()?.unix_timestamp >= idea.dispute_deadline,
+        ConsensusError::DisputeWindowOpen
+    );
+    Ok(())
+}
+
+/// 只能为已结束的赛季注资
+fn season_ended(season: u32, current_season: u32) -> Result<()> {
+    require!(season < current_season, ConsensusError::SeasonNotEnded);
+    Ok(())
+}
+
+/// 赛季奖励尚未到期
+fn rewards_open(expires_at: i64) -> Result<()> {
+    require!(
+        Clock::get()?.unix_timestamp < expires_at,
+        ConsensusError::RewardsExpired
+    );
+    Ok(())
+}
+
+/// 赛季奖励已到期且尚未收回
+fn rewards_reclaimable(rewards_season: &RewardsSeason) -> Result<()> {
+    require!(
+        Clock::get()?.unix_timestamp >= rewards_season.expires_at,
+        ConsensusError::RewardsNotExpired
+    );
+    require!(!rewards_season.reclaimed, ConsensusError::AlreadyWithdrawn);
+    Ok(())
+}
+
+/// 按创意的平局规则从并列图片中选出唯一获胜者，无法打破时返回 None
+fn break_tie(idea: &Idea, candidates: &[usize], slot_hashes: &AccountInfo) -> Result<Option<usize>> {
+    if candidates.len() == 1 {
+        return Ok(Some(candidates[0]));
+    }
+
+    let winner = match idea.tie_break_rule {
+        // 最后一票时间最早者，即最早达到最终权重的图片
+        TIE_BREAK_EARLIEST => unique_best(candidates, |i| -idea.last_vote_ts[i]),
+        TIE_BREAK_STAKE => unique_best(candidates, |i| idea.stake_totals[i] as i64),
+        TIE_BREAK_RANDOM => {
+            // SlotHashes 布局: len(u64) 后接 (slot u64, hash [u8; 32])，首项为最近的 slot
+            let data = slot_hashes.try_borrow_data()?;
+            require!(data.len() >= 48, ConsensusError::InvalidState);
+            let seed = u64::from_le_bytes(data[16..24].try_into().unwrap());
+            Some(candidates[(seed % candidates.len() as u64) as usize])
+        }
+        _ => None,
+    };
+
+    Ok(winner)
+}
+
+/// 取得分最高且唯一的候选项
+fn unique_best(candidates: &[usize], score: impl Fn(usize) -> i64) -> Option<usize> {
+    let best = candidates.iter().map(|&i| score(i)).max()?;
+    let mut leaders = candidates.iter().filter(|&&i| score(i) == best);
+    match (leaders.next(), leaders.next()) {
+        (Some(&winner), None) => Some(winner),
+        _ => None,
+    }
+}
+
+/// 投票是否命中获胜图片 (中间派模式下含第二获胜图片)
+fn is_winning_vote(idea: &Idea, vote: &Vote) -> bool {
+    idea.winning_image_index == Some(vote.image_choice)
+        || idea.secondary_winning_index == Some(vote.image_choice)
+}
+
+/// 领取时更新评审声誉：胜负场次、连胜、累计质押与取回金额
+fn record_reputation(
+    emitter: &EventEmitter,
+    reputation: &mut Account<Reputation>,
+    reviewer: Pubkey,
+    bump: u8,
+    won: bool,
+    staked: u64,
+    returned: u64,
+    season: u32,
+) -> Result<()> {
+    if reputation.reviewer == Pubkey::default() {
+        reputation.reviewer = reviewer;
+        reputation.bump = bump;
+    }
+    if reputation.season != season {
+        reputation.season = season;
+        reputation.season_wins = 0;
+        reputation.season_losses = 0;
+        reputation.season_winnings = 0;
+    }
+
+    if won {
+        reputation.wins = reputation.wins.saturating_add(1);
+        reputation.current_streak = reputation.current_streak.saturating_add(1);
+        reputation.best_streak = reputation.best_streak.max(reputation.current_streak);
+        reputation.season_wins = reputation.season_wins.saturating_add(1);
+        reputation.season_winnings = reputation
+            .season_winnings
+            .saturating_add(returned.saturating_sub(staked));
+    } else {
+        reputation.losses = reputation.losses.saturating_add(1);
+        reputation.current_streak = 0;
+        reputation.season_losses = reputation.season_losses.saturating_add(1);
+    }
+    reputation.total_staked = reputation.total_staked.saturating_add(staked);
+    reputation.total_returned = reputation.total_returned.saturating_add(returned);
+    reputation.last_updated = Clock::get()?.unix_timestamp;
+
+    if won {
+        emitter.emit(PointsAccrued {
+            user: reviewer,
+            kind: POINTS_KIND_WIN,
+            points: POINTS_PER_WIN,
+        })?;
+    }
+    emitter.emit(ReputationUpdated {
+        reviewer,
+        won,
+        wins: reputation.wins,
+        losses: reputation.losses,
+        current_streak: reputation.current_streak,
+        best_streak: reputation.best_streak,
+    })
+}
+
+/// 按评审的赛季战绩更新排行榜的两个榜单
+fn rank_reviewer(
+    board: &mut Leaderboard,
+    scope: Pubkey,
+    season: u32,
+    bump: u8,
+    reputation: &Reputation,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    if board.updated_at == 0 {
+        board.scope = scope;
+        board.season = season;
+        board.bump = bump;
+    }
+    board.updated_at = now;
+
+    let entry = LeaderboardEntry {
+        reviewer: reputation.reviewer,
+        winnings: reputation.season_winnings,
+        wins: reputation.season_wins,
+        losses: reputation.season_losses,
+    };
+    upsert_entry(&mut board.by_winnings, entry, |e| e.winnings);
+
+    let sample = entry.wins.saturating_add(entry.losses);
+    if sample >= LEADERBOARD_MIN_SAMPLE {
+        // 胜率 (bps) 占高位，胜场数打破同胜率的平局
+        upsert_entry(&mut board.by_accuracy, entry, |e| {
+            let total = e.wins.saturating_add(e.losses).max(1) as u64;
+            ((e.wins as u64 * BPS_DENOMINATOR as u64 / total) << 32) | e.wins as u64
+        });
+    }
+
+    Ok(())
+}
+
+/// 将评审的最新战绩写入榜单并按得分降序保留前 N 名，同分时在榜者优先
+fn upsert_entry(
+    entries: &mut [LeaderboardEntry; LEADERBOARD_SIZE],
+    entry: LeaderboardEntry,
+    score: impl Fn(&LeaderboardEntry) -> u64,
+) {
+    let mut ranked: Vec<LeaderboardEntry> = entries
+        .iter()
+        .filter(|e| e.reviewer != Pubkey::default() && e.reviewer != entry.reviewer)
+        .copied()
+        .collect();
+    ranked.push(entry);
+    ranked.sort_by_key(|e| std::cmp::Reverse(score(e)));
+    ranked.resize(LEADERBOARD_SIZE, LeaderboardEntry::default());
+    entries.copy_from_slice(&ranked);
+}
+
+/// 从本程序拥有的 PDA 直接划转 lamports
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    **from.try_borrow_mut_lamports()? = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    Ok(())
+}
+
+/// 领取时结清预留费用所需的账户
+struct ClaimFeeAccounts<'a, 'info> {
+    vault: &'a CoreVault<'info>,
+    curator_token_account: AccountInfo<'info>,
+    protocol_treasury_token_account: AccountInfo<'info>,
+}
+
+/// 评审可享受的费用折扣 (bps)：声誉档位与质押档位取较高者，声誉按本次领取前的记录判定
+fn claim_fee_discount_bps(
+    config: &taste_fun_core::ProtocolConfig,
+    idea: &Idea,
+    reviewer: &Pubkey,
+    reputation: &Reputation,
+    stake_position: Option<&Account<taste_fun_token::StakePosition>>,
+) -> Result<u16> {
+    let settled = reputation.wins.saturating_add(reputation.losses) as u64;
+    let reputation_tier = config.reputation_discount_bps > 0
+        && settled > 0
+        && reputation.wins >= config.reputation_discount_min_wins
+        && reputation.wins as u64 * BPS_DENOMINATOR as u64
+            >= config.reputation_discount_min_accuracy_bps as u64 * settled;
+    let mut discount_bps = if reputation_tier { config.reputation_discount_bps } else { 0 };
+
+    if let Some(position) = stake_position {
+        require_keys_eq!(position.owner, *reviewer, ConsensusError::Unauthorized);
+        require_keys_eq!(position.theme, idea.theme, ConsensusError::InvalidTheme);
+        if config.staker_discount_bps > 0
+            && position.amount > 0
+            && position.amount >= config.staker_discount_min_amount
+        {
+            discount_bps = discount_bps.max(config.staker_discount_bps);
+        }
+    }
+
+    Ok(discount_bps)
+}
+
+/// 按评审质押占评审总质押的比例结清预留的策展费与平台费
+/// 折扣部分留在金库并入评审的领取金额，返回该返还额
+fn charge_claim_fees(
+    emitter: &EventEmitter,
+    idea: &Account<Idea>,
+    reviewer: Pubkey,
+    staked: u64,
+    discount_bps: u16,
+    a: ClaimFeeAccounts,
+) -> Result<u64> {
+    let voter_stake = idea
+        .total_staked
+        .checked_sub(idea.initial_prize_pool)
+        .ok_or(ConsensusError::Overflow)?;
+    // 返回 (应付费用, 折扣返还)
+    let split = |fee: u64| -> Result<(u64, u64)> {
+        let share = claim_fee_share(fee, staked, voter_stake)?;
+        let rebate = (share as u128)
+            .checked_mul(discount_bps as u128)
+            .and_then(|x| x.checked_div(BPS_DENOMINATOR as u128))
+            .and_then(|x| u64::try_from(x).ok())
+            .ok_or(ConsensusError::Overflow)?;
+        Ok((share - rebate, rebate))
+    };
+    let (curator_fee, curator_rebate) = split(idea.curator_fee_collected)?;
+    let (platform_fee, platform_rebate) = split(idea.platform_fee_collected)?;
+
+    for (to, amount) in [
+        (&a.curator_token_account, curator_fee),
+        (&a.protocol_treasury_token_account, platform_fee),
+    ] {
+        if amount == 0 {
+            continue;
+        }
+        a.vault.transfer(to.clone(), amount)?;
+    }
+
+    let rebate = curator_rebate
+        .checked_add(platform_rebate)
+        .ok_or(ConsensusError::Overflow)?;
+    emitter.emit(ClaimFeesCharged {
+        idea: idea.key(),
+        reviewer,
+        curator_fee,
+        platform_fee,
+        discount_bps,
+        rebate,
+    })?;
+
+    Ok(rebate)
+}
+
+/// 计算评审应得金额 (本金 + 按分配曲线的惩罚池份额)，领取期与重复领取由调用方校验
+fn claimable_winnings(idea: &Idea, vote: &Vote, reviewer_stake: &ReviewerStake) -> Result<u64> {
+    // 检查是否是获胜方 (无损模式下未获胜者可取回本金)
+    require!(idea.winning_image_index.is_some(), ConsensusError::NoWinner);
+    let is_winner = is_winning_vote(idea, vote);
+    require!(is_winner || idea.no_loss, ConsensusError::NotWinner);
+
+    // 计算应得奖金 (惩罚池按创意的分配曲线分给获胜者)
+    if is_winner {
+        let unit = payout_unit(
+            idea.payout_curve,
+            idea.payout_exponent,
+            vote.stake_amount,
+            vote.vote_weight,
+        );
+        let winner_share = (idea.penalty_pool_amount as u128)
+            .checked_mul(unit)
+            .and_then(|x| x.checked_div(winning_payout_weight(idea)))
+            .and_then(|x| u64::try_from(x).ok())
+            .ok_or(ConsensusError::DivisionByZero)?;
+
+        reviewer_stake
+            .total_staked
+            .checked_add(winner_share)
+            .ok_or(ConsensusError::Overflow.into())
+    } else {
+        Ok(reviewer_stake.total_staked)
+    }
+}
+
+/// 以 PDA 身份投出的质押 (委托、战队、跟投) 的结算所得：取消时为全额本金，
+/// 否则按胜负计算并以无折扣结清费用 (PDA 没有质押档位)
+/// 所有奖金发放路径共用的线性释放判定：达到阈值时写入受益人的释放记录并返回 true，由 claim_vested 分期领取
+/// 未达阈值返回 false 由调用方直接转账；不支持线性释放的路径传 None，大额奖金将被拒绝
+/// 释放中的金额计入创意的 outstanding_vesting，清扫时保留在金库
+fn vest_winnings<'info>(
+    emitter: &EventEmitter,
+    core: &CoreCpi<'info>,
+    idea: &mut Account<'info, Idea>,
+    vesting_claim: Option<&mut Account<VestingClaim>>,
+    bump: Option<u8>,
+    beneficiary: Pubkey,
+    amount: u64,
+) -> Result<bool> {
+    if amount < VESTING_THRESHOLD {
+        return Ok(false);
+    }
+    let vesting = vesting_claim.ok_or(ConsensusError::WinningsMustVest)?;
+    require!(vesting.total_amount == 0, ConsensusError::AlreadyWithdrawn);
+
+    idea.outstanding_vesting = idea
+        .outstanding_vesting
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    core.sync_idea(idea)?;
+
+    let idea = idea.key();
+    let start_ts = Clock::get()?.unix_timestamp;
+    vesting.idea = idea;
+    vesting.reviewer = beneficiary;
+    vesting.total_amount = amount;
+    vesting.claimed_amount = 0;
+    vesting.start_ts = start_ts;
+    vesting.duration = VESTING_DURATION;
+    vesting.bump = bump.ok_or(ConsensusError::InvalidState)?;
+
+    emitter.emit(VestingStarted {
+        idea,
+        reviewer: beneficiary,
+        total_amount: amount,
+        end_ts: start_ts + VESTING_DURATION,
+    })?;
+    Ok(true)
+}
+
+fn pooled_vote_payout(
+    emitter: &EventEmitter,
+    idea: &Account<Idea>,
+    vote: &Vote,
+    reviewer_stake: &ReviewerStake,
+    voter: Pubkey,
+    fee_accounts: ClaimFeeAccounts,
+) -> Result<u64> {
+    if idea.status == IdeaStatus::Cancelled {
+        return Ok(reviewer_stake.total_staked);
+    }
+    claimable(idea)?;
+
+    let settled = if idea.no_loss || is_winning_vote(idea, vote) {
+        claimable_winnings(idea, vote, reviewer_stake)?
+    } else {
+        loser_refund(idea, vote, reviewer_stake)?.0
+    };
+    let rebate = charge_claim_fees(
+        emitter,
+        idea,
+        voter,
+        reviewer_stake.total_staked,
+        0,
+        fee_accounts,
+    )?;
+    settled.checked_add(rebate).ok_or(ConsensusError::Overflow.into())
+}
+
+/// 校验未获胜评审的退款条件，返回 (未被罚没的本金, 罚没部分)
+fn loser_refund(idea: &Idea, vote: &Vote, reviewer_stake: &ReviewerStake) -> Result<(u64, u64)> {
+    require!(idea.winning_image_index.is_some(), ConsensusError::NoWinner);
+    require!(!is_winning_vote(idea, vote), ConsensusError::InvalidState);
+
+    // 无损模式下本金不参与惩罚
+    let penalty_bps = if idea.no_loss { 0 } else { PENALTY_BPS };
+    let refund = loser_refund_amount(reviewer_stake.total_staked, penalty_bps);
+    let penalty_share = reviewer_stake.total_staked - refund;
+
+    Ok((refund, penalty_share))
+}
+
+/// 获胜图片的份额总量 (中间派模式下为两张获胜图片之和)
+fn winning_payout_weight(idea: &Idea) -> u128 {
+    let primary = idea
+        .winning_image_index
+        .map_or(0, |i| idea.payout_weights[i as usize]);
+    let secondary = idea
+        .secondary_winning_index
+        .map_or(0, |i| idea.payout_weights[i as usize]);
+    primary.saturating_add(secondary)
+}
+
+/// 创意已结算且终局，并处于领取期内
+fn claimable(idea: &Idea) -> Result<()> {
+    idea_in_status(idea.status, IdeaStatus::Completed)?;
+    require_claims_open(idea)?;
+    claim_window_open(idea)
+}
+
+/// 领取期尚未结束 (claim_deadline 为 0 表示在领取期上线前结算，不设截止)
+fn claim_window_open(idea: &Idea) -> Result<()> {
+    require!(
+        idea.claim_deadline == 0 || Clock::get()?.unix_timestamp <= idea.claim_deadline,
+        ConsensusError::ClaimWindowClosed
+    );
+    Ok(())
+}
+
+/// 领取期已结束且无未决争议，剩余资金可清扫 (未设截止的旧创意不可清扫)
+fn claim_window_closed(idea: &Idea) -> Result<()> {
+    require!(!idea.disputed, ConsensusError::SettlementDisputed);
+    require!(
+        idea.claim_deadline != 0 && Clock::get()?.unix_timestamp > idea.claim_deadline,
+        ConsensusError::ClaimWindowOpen
+    );
+    Ok(())
+}
+
+/// 结算后的争议期内且尚无争议
+fn dispute_window_open(idea: &Idea) -> Result<()> {
+    require!(!idea.disputed, ConsensusError::SettlementDisputed);
+    require!(
+        Clock::get()?.unix_timestamp < idea.dispute_deadline,
+        ConsensusError::DisputeWindowClosed
+    );
+    Ok(())
+}
+
+/// 评委加奖仍在授予窗口内
+fn bonus_window_open(idea: &Idea) -> Result<()> {
+    require!(
+        Clock::get()?.unix_timestamp <= idea.voting_deadline + BONUS_AWARD_WINDOW,
+        ConsensusError::BonusWindowClosed
+    );
+    Ok(())
+}
+
+/// 派奖根仍在挑战期内且未被挑战
+fn payout_root_challengeable(payout_root: &PayoutRoot) -> Result<()> {
+    require!(!payout_root.challenged, ConsensusError::PayoutRootChallenged);
+    require!(
+        Clock::get()?.unix_timestamp <= payout_root.posted_at + PAYOUT_CHALLENGE_WINDOW,
+        ConsensusError::InvalidState
+    );
+    Ok(())
+}
+
+/// 派奖根未被挑战且挑战期已过，可凭证明领取
+fn payout_root_final(payout_root: &PayoutRoot) -> Result<()> {
+    require!(!payout_root.challenged, ConsensusError::PayoutRootChallenged);
+    require!(
+        Clock::get()?.unix_timestamp > payout_root.posted_at + PAYOUT_CHALLENGE_WINDOW,
+        ConsensusError::PayoutRootPending
+    );
+    Ok(())
+}
+
+/// 获胜图片 NFT 的铸造资格：发起者随时可铸造，优先期过后获胜评审 (凭投票记录) 也可铸造
+fn can_mint_winner_nft(idea: &Idea, minter: &Pubkey, vote: &Option<Box<Account<Vote>>>) -> Result<()> {
+    if *minter == idea.initiator {
+        return Ok(());
+    }
+    let vote = vote.as_ref().ok_or(ConsensusError::WinnerNftNotAllowed)?;
+    require!(is_winning_vote(idea, vote), ConsensusError::WinnerNftNotAllowed);
+    require!(
+        Clock::get()?.unix_timestamp >= idea.dispute_deadline + WINNER_NFT_PRIORITY_WINDOW,
+        ConsensusError::WinnerNftNotAllowed
+    );
+    Ok(())
+}
+
+/// 版本销售参数：价格非零、时长在上限内、分成合计 10000 且评审份额不低于下限
+fn valid_edition_sale(
+    price: u64,
+    duration: i64,
+    voter_share_bps: u16,
+    initiator_share_bps: u16,
+    buyback_share_bps: u16,
+) -> Result<()> {
+    require!(price > 0, ConsensusError::InvalidAmount);
+    require!(
+        duration > 0 && duration <= MAX_EDITION_SALE_DURATION,
+        ConsensusError::InvalidAmount
+    );
+    require!(
+        voter_share_bps >= MIN_EDITION_VOTER_SHARE_BPS
+            && voter_share_bps as u32 + initiator_share_bps as u32 + buyback_share_bps as u32
+                == BPS_DENOMINATOR as u32,
+        ConsensusError::InvalidRevenueSplit
+    );
+    Ok(())
+}
+
+/// 版本销售仍在进行且剩余数量足够
+fn edition_sale_open(sale: &EditionSale, quantity: u64) -> Result<()> {
+    require!(quantity > 0, ConsensusError::InvalidAmount);
+    require!(
+        Clock::get()?.unix_timestamp < sale.end_ts,
+        ConsensusError::EditionSaleClosed
+    );
+    if sale.max_supply > 0 {
+        let sold = sale.sold.checked_add(quantity).ok_or(ConsensusError::Overflow)?;
+        require!(sold <= sale.max_supply, ConsensusError::EditionSoldOut);
+    }
+    Ok(())
+}
+
+/// 版本销售已结束 (收入总额确定后才可领取分成)
+fn edition_sale_ended(sale: &EditionSale) -> Result<()> {
+    require!(
+        Clock::get()?.unix_timestamp >= sale.end_ts,
+        ConsensusError::EditionSaleActive
+    );
+    Ok(())
+}
+
+/// 投票选择了指定图片
+fn voted_for(image_choice: u8, image_index: u8) -> Result<()> {
+    require!(image_choice == image_index, ConsensusError::NotWinner);
+    Ok(())
+}
+
+/// 构造并调用 Token Metadata 的元数据与主版本创建指令 (nft_authority 同时为铸币与更新权限)
+#[inline(never)]
+fn create_winner_nft_metadata(
+    a: &WinnerNftMetadataAccounts,
+    name: String,
+    uri: String,
+    authority_seeds: &[&[u8]],
+) -> Result<()> {
+    let creators = royalty_creators(a.royalty_vault);
+
+    let ix = Instruction {
+        program_id: TOKEN_METADATA_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(a.metadata.key(), false),
+            AccountMeta::new_readonly(a.nft_mint.key(), false),
+            AccountMeta::new_readonly(a.nft_authority.key(), true),
+            AccountMeta::new(a.payer.key(), true),
+            AccountMeta::new_readonly(a.nft_authority.key(), true),
+            AccountMeta::new_readonly(a.system_program.key(), false),
+            AccountMeta::new_readonly(a.rent.key(), false),
+        ],
+        data: create_metadata_v3_data(
+            name,
+            NFT_SYMBOL.to_string(),
+            uri,
+            WINNER_NFT_ROYALTY_BPS,
+            creators,
+        )?,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            a.metadata.to_account_info(),
+            a.nft_mint.to_account_info(),
+            a.nft_authority.to_account_info(),
+            a.payer.to_account_info(),
+            a.system_program.to_account_info(),
+            a.rent.to_account_info(),
+            a.token_metadata_program.to_account_info(),
+        ],
+        &[authority_seeds],
+    )?;
+
+    // 主版本接管铸币权，供应量固定为 1
+    let ix = Instruction {
+        program_id: TOKEN_METADATA_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(a.master_edition.key(), false),
+            AccountMeta::new(a.nft_mint.key(), false),
+            AccountMeta::new_readonly(a.nft_authority.key(), true),
+            AccountMeta::new_readonly(a.nft_authority.key(), true),
+            AccountMeta::new(a.payer.key(), true),
+            AccountMeta::new(a.metadata.key(), false),
+            AccountMeta::new_readonly(a.token_program.key(), false),
+            AccountMeta::new_readonly(a.system_program.key(), false),
+            AccountMeta::new_readonly(a.rent.key(), false),
+        ],
+        data: create_master_edition_v3_data(Some(0))?,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            a.master_edition.to_account_info(),
+            a.nft_mint.to_account_info(),
+            a.nft_authority.to_account_info(),
+            a.payer.to_account_info(),
+            a.metadata.to_account_info(),
+            a.token_program.to_account_info(),
+            a.system_program.to_account_info(),
+            a.rent.to_account_info(),
+            a.token_metadata_program.to_account_info(),
+        ],
+        &[authority_seeds],
+    )?;
+
+    Ok(())
+}
+
+/// 获胜图片 NFT 元数据创建所需账户 (发起者铸造与获胜评审空投领取共用)
+struct WinnerNftMetadataAccounts<'info> {
+    metadata: AccountInfo<'info>,
+    master_edition: AccountInfo<'info>,
+    nft_mint: AccountInfo<'info>,
+    nft_authority: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    royalty_vault: Pubkey,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    rent: AccountInfo<'info>,
+    token_metadata_program: AccountInfo<'info>,
+}
+
+/// NFT 创作者数组：版税金库独占全部版税份额 (未验证，PDA 无法签署 sign_metadata)
+fn royalty_creators(royalty_vault: Pubkey) -> Vec<MetadataCreator> {
+    vec![MetadataCreator {
+        address: royalty_vault,
+        verified: false,
+        share: 100,
+    }]
+}
+
+/// 为版本铸币创建 Token Metadata 元数据 (无主版本，即 0 位小数的同质化资产)
+#[inline(never)]
+fn create_edition_metadata(
+    ctx: &Context<OpenEditionSale>,
+    name: String,
+    uri: String,
+    authority_seeds: &[&[u8]],
+) -> Result<()> {
+    let a = &ctx.accounts;
+    let creators = royalty_creators(a.royalty_vault.key());
+
+    let ix = Instruction {
+        program_id: TOKEN_METADATA_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(a.metadata.key(), false),
+            AccountMeta::new_readonly(a.edition_mint.key(), false),
+            AccountMeta::new_readonly(a.nft_authority.key(), true),
+            AccountMeta::new(a.initiator.key(), true),
+            AccountMeta::new_readonly(a.nft_authority.key(), true),
+            AccountMeta::new_readonly(a.system_program.key(), false),
+            AccountMeta::new_readonly(a.rent.key(), false),
+        ],
+        data: create_metadata_v3_data(
+            name,
+            NFT_SYMBOL.to_string(),
+            uri,
+            WINNER_NFT_ROYALTY_BPS,
+            creators,
+        )?,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            a.metadata.to_account_info(),
+            a.edition_mint.to_account_info(),
+            a.nft_authority.to_account_info(),
+            a.initiator.to_account_info(),
+            a.system_program.to_account_info(),
+            a.rent.to_account_info(),
+            a.token_metadata_program.to_account_info(),
+        ],
+        &[authority_seeds],
+    )?;
+
+    Ok(())
+}
+
+/// 调用 Bubblegum mint_v1 为单个投票者铸造徽章 (徽章树 PDA 作为树创建者签名)
+#[inline(never)]
+fn mint_badge<'info>(
+    a: &MintParticipationBadges<'info>,
+    leaf_owner: &AccountInfo<'info>,
+    data: Vec<u8>,
+    tree_seeds: &[&[u8]],
+) -> Result<()> {
+    let ix = Instruction {
+        program_id: BUBBLEGUM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(a.tree_config.key(), false),
+            AccountMeta::new_readonly(leaf_owner.key(), false),
+            AccountMeta::new_readonly(leaf_owner.key(), false),
+            AccountMeta::new(a.merkle_tree.key(), false),
+            AccountMeta::new_readonly(a.keeper.key(), true),
+            AccountMeta::new_readonly(a.badge_tree.key(), true),
+            AccountMeta::new_readonly(a.log_wrapper.key(), false),
+            AccountMeta::new_readonly(a.compression_program.key(), false),
+            AccountMeta::new_readonly(a.system_program.key(), false),
+        ],
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            a.tree_config.to_account_info(),
+            leaf_owner.clone(),
+            a.merkle_tree.to_account_info(),
+            a.keeper.to_account_info(),
+            a.badge_tree.to_account_info(),
+            a.log_wrapper.to_account_info(),
+            a.compression_program.to_account_info(),
+            a.system_program.to_account_info(),
+            a.bubblegum_program.to_account_info(),
+        ],
+        &[tree_seeds],
+    )?;
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Contexts
+// -----------------------------------------------------------------------------
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleVoting<'info> {
+    #[account(mut)]
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// 创意所属主题
+    #[account(
+        address = idea.theme @ ConsensusError::InvalidState,
+        constraint = theme.token_mint == idea.theme_token_mint @ ConsensusError::InvalidMint
+    )]
+    pub theme: Box<Account<'info, taste_fun_token::Theme>>,
+
+    /// CHECK: 主题金库 PDA (代币程序派生)，回购贡献 ATA 的所有者
+    #[account(
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump,
+        seeds::program = taste_fun_token::ID
+    )]
+    pub theme_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Theme token mint - must match the theme and the vault token account (burned from at settlement)
+    #[account(
+        mut,
+        address = theme.token_mint @ ConsensusError::InvalidMint,
+        constraint = token_mint.key() == vault_token_account.mint @ ConsensusError::InvalidMint
+    )]
+    pub token_mint: AccountInfo<'info>,
+
+    /// Vault token account holding staked tokens
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    /// Theme buyback token account to receive buyback contribution (主题金库的代币 ATA)
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = theme_vault,
+    )]
+    pub theme_buyback_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Initiator to receive curator fee
+    #[account(mut, address = idea.initiator @ ConsensusError::Unauthorized)]
+    pub initiator: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol treasury to receive platform fee
+    #[account(mut, address = protocol_config.treasury @ ConsensusError::Unauthorized)]
+    pub protocol_treasury: UncheckedAccount<'info>,
+
+    /// Settler's token account to receive settler reward
+    #[account(mut, token::authority = settler)]
+    pub settler_token_account: Account<'info, TokenAccount>,
+
+    /// Permissionless crank caller, paid the settler reward
+    pub settler: Signer<'info>,
+
+    #[account(mut, seeds = [b"insurance"], bump = insurance.bump)]
+    pub insurance: Box<Account<'info, Insurance>>,
+
+    /// Insurance token account for this theme token
+    #[account(mut, token::authority = insurance)]
+    pub insurance_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 主题奖池 (赞助创意约定滚存时必须提供)
+    pub jackpot: Option<Box<Account<'info, taste_fun_core::Jackpot>>>,
+
+    /// Jackpot token account to receive the rolled-over sponsor pool
+    #[account(mut, token::authority = jackpot)]
+    pub jackpot_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// 原创意的衍生版税池 (衍生创意结算时必须提供，在 settle_idea 中校验)
+    #[account(mut)]
+    pub remix_pool: Option<Box<Account<'info, RemixPool>>>,
+
+    /// CHECK: 版税池的主题代币 ATA，在 settle_idea 中校验
+    #[account(mut)]
+    pub remix_pool_token_account: Option<UncheckedAccount<'info>>,
+
+    /// 创意所属合集的大奖池 (合集创意结算时必须提供，在 settle_idea 中校验)
+    #[account(mut)]
+    pub collection_prize: Option<Box<Account<'info, CollectionPrize>>>,
+
+    /// CHECK: 合集大奖池的主题代币 ATA，在 settle_idea 中校验
+    #[account(mut)]
+    pub collection_prize_token_account: Option<UncheckedAccount<'info>>,
+
+    /// 主题画廊 (可选，提供时须同时提供当前写入页，在 settle_idea 中校验)
+    #[account(mut)]
+    pub gallery: Option<Box<Account<'info, Gallery>>>,
+
+    #[account(mut)]
+    pub gallery_page: Option<Box<Account<'info, GalleryPage>>>,
+
+    /// CHECK: SlotHashes sysvar，用于可验证随机平局处理
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    pub recent_slothashes: UncheckedAccount<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleMany<'info> {
+    #[account(mut, seeds = [b"insurance"], bump = insurance.bump)]
+    pub insurance: Box<Account<'info, Insurance>>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    /// Permissionless crank caller, paid the settler reward for each idea
+    pub settler: Signer<'info>,
+
+    /// CHECK: SlotHashes sysvar，用于可验证随机平局处理
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    pub recent_slothashes: UncheckedAccount<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawWinnings<'info> {
+    #[account(mut)]
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        seeds = [b"vote", idea.key().as_ref(), reviewer.key().as_ref()],
+        bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vote: Account<'info, Vote>,
+
+    #[account(
+        mut,
+        seeds = [b"reviewer_stake", idea.key().as_ref(), reviewer.key().as_ref()],
+        bump = reviewer_stake.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub reviewer_stake: Account<'info, ReviewerStake>,
+
+    /// 评审的跨创意声誉记录，领取时更新
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + Reputation::SPACE,
+        seeds = [b"reputation", reviewer.key().as_ref()],
+        bump
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault token account
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    /// Curator's token account to receive the reviewer's share of the curator fee
+    #[account(
+        mut,
+        constraint = curator_token_account.owner == idea.curator @ ConsensusError::Unauthorized,
+        constraint = curator_token_account.mint == vault_token_account.mint @ ConsensusError::InvalidMint
+    )]
+    pub curator_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol treasury token account to receive the reviewer's share of the platform fee
+    #[account(
+        mut,
+        token::mint = vault_token_account.mint,
+        token::authority = protocol_config.treasury,
+    )]
+    pub protocol_treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 评审在创意所属主题的质押仓位 (申请质押档位的费用折扣时提供)
+    pub stake_position: Option<Box<Account<'info, taste_fun_token::StakePosition>>>,
+
+    /// 全局赛季排行榜 (提供时领取后更新)
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + Leaderboard::SPACE,
+        seeds = [
+            b"leaderboard",
+            GLOBAL_LEADERBOARD_SCOPE.as_ref(),
+            protocol_config.season.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub leaderboard: Option<Box<Account<'info, Leaderboard>>>,
+
+    /// 创意所属主题的赛季排行榜 (提供时领取后更新)
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + Leaderboard::SPACE,
+        seeds = [b"leaderboard", idea.theme.as_ref(), protocol_config.season.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub theme_leaderboard: Option<Box<Account<'info, Leaderboard>>>,
+
+    /// Reviewer's token account to receive winnings
+    #[account(mut)]
+    pub reviewer_token_account: Account<'info, TokenAccount>,
+
+    /// 大额奖金的线性释放记录 (超过阈值时必须提供)
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + VestingClaim::SPACE,
+        seeds = [b"vesting_claim", idea.key().as_ref(), reviewer.key().as_ref()],
+        bump
+    )]
+    pub vesting_claim: Option<Box<Account<'info, VestingClaim>>>,
+
+    #[account(mut)]
+    pub reviewer: Signer<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut)]
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        mut,
+        seeds = [b"vesting_claim", idea.key().as_ref(), vesting_claim.reviewer.as_ref()],
+        bump = vesting_claim.bump
+    )]
+    pub vesting_claim: Account<'info, VestingClaim>,
+
+    #[account(
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault token account
+    #[account(mut, token::authority = vault)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// 受益人 (评审、委托人、战队或跟投关注) 的代币账户，接收已释放的奖金
+    #[account(
+        mut,
+        token::mint = vault_token_account.mint,
+        token::authority = vesting_claim.reviewer,
+    )]
+    pub reviewer_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawWinningsAsSol<'info> {
+    #[account(mut)]
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        seeds = [b"vote", idea.key().as_ref(), reviewer.key().as_ref()],
+        bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vote: Box<Account<'info, Vote>>,
+
+    #[account(
+        mut,
+        seeds = [b"reviewer_stake", idea.key().as_ref(), reviewer.key().as_ref()],
+        bump = reviewer_stake.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub reviewer_stake: Box<Account<'info, ReviewerStake>>,
+
+    /// 评审的跨创意声誉记录，领取时更新
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + Reputation::SPACE,
+        seeds = [b"reputation", reviewer.key().as_ref()],
+        bump
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// Vault token account
+    #[account(mut, token::authority = vault)]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    /// Curator's token account to receive the reviewer's share of the curator fee
+    #[account(
+        mut,
+        constraint = curator_token_account.owner == idea.curator @ ConsensusError::Unauthorized,
+        constraint = curator_token_account.mint == vault_token_account.mint @ ConsensusError::InvalidMint
+    )]
+    pub curator_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol treasury token account to receive the reviewer's share of the platform fee
+    #[account(
+        mut,
+        token::mint = vault_token_account.mint,
+        token::authority = protocol_config.treasury,
+    )]
+    pub protocol_treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 评审在创意所属主题的质押仓位 (申请质押档位的费用折扣时提供)
+    pub stake_position: Option<Box<Account<'info, taste_fun_token::StakePosition>>>,
+
+    /// 全局赛季排行榜 (提供时领取后更新)
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + Leaderboard::SPACE,
+        seeds = [
+            b"leaderboard",
+            GLOBAL_LEADERBOARD_SCOPE.as_ref(),
+            protocol_config.season.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub leaderboard: Option<Box<Account<'info, Leaderboard>>>,
+
+    /// 创意所属主题的赛季排行榜 (提供时领取后更新)
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + Leaderboard::SPACE,
+        seeds = [b"leaderboard", idea.theme.as_ref(), protocol_config.season.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub theme_leaderboard: Option<Box<Account<'info, Leaderboard>>>,
+
+    /// Reviewer's token account, receives winnings before the sale
+    #[account(mut, token::authority = reviewer)]
+    pub reviewer_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Theme - validated by token program
+    #[account(mut)]
+    pub theme: UncheckedAccount<'info>,
+
+    /// CHECK: Theme vault - validated by token program
+    #[account(mut)]
+    pub theme_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Theme token mint - validated by token program
+    #[account(mut)]
+    pub token_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Theme vault token account - validated by token program
+    #[account(mut)]
+    pub theme_vault_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Trading config - validated by token program
+    pub trading_config: UncheckedAccount<'info>,
+
+    /// CHECK: Theme price oracle - validated by token program
+    #[account(mut)]
+    pub price_oracle: UncheckedAccount<'info>,
+
+    /// CHECK: Reviewer trader state (required when the theme has a swap cooldown) - validated by token program
+    #[account(mut)]
+    pub trader_state: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Theme vault SOL account - validated by token program
+    #[account(mut)]
+    pub vault_sol_account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub reviewer: Signer<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Theme program event authority - validated by token program
+    pub theme_event_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub theme_program: Program<'info, TasteFunToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimAndVote<'info> {
+    #[account(mut)]
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        seeds = [b"vote", idea.key().as_ref(), reviewer.key().as_ref()],
+        bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vote: Box<Account<'info, Vote>>,
+
+    #[account(
+        mut,
+        seeds = [b"reviewer_stake", idea.key().as_ref(), reviewer.key().as_ref()],
+        bump = reviewer_stake.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub reviewer_stake: Box<Account<'info, ReviewerStake>>,
+
+    /// 评审的跨创意声誉记录，领取时更新
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + Reputation::SPACE,
+        seeds = [b"reputation", reviewer.key().as_ref()],
+        bump
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// Vault token account
+    #[account(mut, token::authority = vault)]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    /// Curator's token account to receive the reviewer's share of the curator fee
+    #[account(
+        mut,
+        constraint = curator_token_account.owner == idea.curator @ ConsensusError::Unauthorized,
+        constraint = curator_token_account.mint == vault_token_account.mint @ ConsensusError::InvalidMint
+    )]
+    pub curator_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol treasury token account to receive the reviewer's share of the platform fee
+    #[account(
+        mut,
+        token::mint = vault_token_account.mint,
+        token::authority = protocol_config.treasury,
+    )]
+    pub protocol_treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 评审在创意所属主题的质押仓位 (申请质押档位的费用折扣时提供)
+    pub stake_position: Option<Box<Account<'info, taste_fun_token::StakePosition>>>,
+
+    /// 全局赛季排行榜 (提供时领取后更新)
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + Leaderboard::SPACE,
+        seeds = [
+            b"leaderboard",
+            GLOBAL_LEADERBOARD_SCOPE.as_ref(),
+            protocol_config.season.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub leaderboard: Option<Box<Account<'info, Leaderboard>>>,
+
+    /// 创意所属主题的赛季排行榜 (提供时领取后更新)
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + Leaderboard::SPACE,
+        seeds = [b"leaderboard", idea.theme.as_ref(), protocol_config.season.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub theme_leaderboard: Option<Box<Account<'info, Leaderboard>>>,
+
+    /// CHECK: Target idea - validated by core program
+    #[account(mut)]
+    pub target_idea: UncheckedAccount<'info>,
+
+    /// CHECK: Target vote PDA - initialized by core program
+    #[account(mut)]
+    pub target_vote: UncheckedAccount<'info>,
+
+    /// CHECK: Target reviewer stake PDA - initialized by core program
+    #[account(mut)]
+    pub target_reviewer_stake: UncheckedAccount<'info>,
+
+    /// CHECK: Target vault PDA, derived from core program
+    #[account(
+        seeds = [b"vault", target_idea.key().as_ref()],
+        bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub target_vault: UncheckedAccount<'info>,
+
+    /// Target vault token account; same mint ensures the same theme token
+    #[account(
+        mut,
+        token::authority = target_vault,
+        constraint = target_vault_token_account.mint == vault_token_account.mint @ ConsensusError::InvalidTheme
+    )]
+    pub target_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Restake authority PDA, signs the core CPI
+    #[account(seeds = [b"restake_authority"], bump)]
+    pub restake_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub reviewer: Signer<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Core program event authority - validated by core program
+    pub core_event_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawRefund<'info> {
+    #[account(mut)]
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        seeds = [b"vote", idea.key().as_ref(), reviewer.key().as_ref()],
+        bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vote: Account<'info, Vote>,
+
+    #[account(
+        mut,
+        seeds = [b"reviewer_stake", idea.key().as_ref(), reviewer.key().as_ref()],
+        bump = reviewer_stake.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub reviewer_stake: Account<'info, ReviewerStake>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault token account
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Reviewer's token account to receive refund
+    #[account(mut)]
+    pub reviewer_token_account: Account<'info, TokenAccount>,
+
+    /// 退款 Gas 补贴财库 (系统账户 PDA，由协议注资)
+    #[account(mut, seeds = [b"rebate_treasury"], bump)]
+    pub rebate_treasury: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub reviewer: Signer<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawDelegatedWinnings<'info> {
+    #[account(mut)]
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        seeds = [
+            b"delegation",
+            delegation.delegator.as_ref(),
+            delegation.curator.as_ref(),
+            idea.theme_token_mint.as_ref()
+        ],
+        bump = delegation.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub delegation: Box<Account<'info, taste_fun_core::Delegation>>,
+
+    #[account(
+        seeds = [b"vote", idea.key().as_ref(), delegation.key().as_ref()],
+        bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vote: Account<'info, Vote>,
+
+    #[account(
+        mut,
+        seeds = [b"reviewer_stake", idea.key().as_ref(), delegation.key().as_ref()],
+        bump = reviewer_stake.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub reviewer_stake: Account<'info, ReviewerStake>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault token account
+    #[account(mut, token::mint = idea.theme_token_mint, token::authority = vault)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    /// Curator's token account to receive the delegated stake's share of the curator fee
+    #[account(
+        mut,
+        constraint = curator_token_account.owner == idea.curator @ ConsensusError::Unauthorized,
+        constraint = curator_token_account.mint == vault_token_account.mint @ ConsensusError::InvalidMint
+    )]
+    pub curator_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol treasury token account to receive the delegated stake's share of the platform fee
+    #[account(
+        mut,
+        token::mint = vault_token_account.mint,
+        token::authority = protocol_config.treasury,
+    )]
+    pub protocol_treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 受托策展人的代币账户，接收收益分成
+    #[account(
+        mut,
+        token::mint = vault_token_account.mint,
+        token::authority = delegation.curator,
+    )]
+    pub delegation_curator_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 委托人的代币账户，接收本金与其余收益
+    #[account(
+        mut,
+        token::mint = vault_token_account.mint,
+        token::authority = delegation.delegator,
+    )]
+    pub delegator_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 大额所得的线性释放记录 (归属委托人，超过阈值时必须提供)
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + VestingClaim::SPACE,
+        seeds = [b"vesting_claim", idea.key().as_ref(), delegation.delegator.as_ref()],
+        bump
+    )]
+    pub vesting_claim: Option<Box<Account<'info, VestingClaim>>>,
+
+    /// 委托人或受托策展人均可发起结算
+    #[account(
+        mut,
+        constraint = authority.key() == delegation.delegator
+            || authority.key() == delegation.curator @ ConsensusError::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawSquadWinnings<'info> {
+    #[account(mut)]
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        mut,
+        seeds = [b"squad", squad.captain.as_ref(), squad.squad_id.to_le_bytes().as_ref()],
+        bump = squad.bump,
+        seeds::program = taste_fun_core::ID,
+        constraint = squad.theme_token_mint == idea.theme_token_mint @ ConsensusError::InvalidMint
+    )]
+    pub squad: Box<Account<'info, taste_fun_core::Squad>>,
+
+    #[account(
+        seeds = [b"vote", idea.key().as_ref(), squad.key().as_ref()],
+        bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vote: Account<'info, Vote>,
+
+    #[account(
+        mut,
+        seeds = [b"reviewer_stake", idea.key().as_ref(), squad.key().as_ref()],
+        bump = reviewer_stake.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub reviewer_stake: Account<'info, ReviewerStake>,
+
+    /// 战队的跨创意声誉记录，领取时更新
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Reputation::SPACE,
+        seeds = [b"reputation", squad.key().as_ref()],
+        bump
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault token account
+    #[account(mut, token::mint = idea.theme_token_mint, token::authority = vault)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    /// Curator's token account to receive the squad's share of the curator fee
+    #[account(
+        mut,
+        constraint = curator_token_account.owner == idea.curator @ ConsensusError::Unauthorized,
+        constraint = curator_token_account.mint == vault_token_account.mint @ ConsensusError::InvalidMint
+    )]
+    pub curator_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol treasury token account to receive the squad's share of the platform fee
+    #[account(
+        mut,
+        token::mint = vault_token_account.mint,
+        token::authority = protocol_config.treasury,
+    )]
+    pub protocol_treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 全局赛季排行榜 (提供时领取后更新)
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Leaderboard::SPACE,
+        seeds = [
+            b"leaderboard",
+            GLOBAL_LEADERBOARD_SCOPE.as_ref(),
+            protocol_config.season.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub leaderboard: Option<Box<Account<'info, Leaderboard>>>,
+
+    /// 创意所属主题的赛季排行榜 (提供时领取后更新)
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Leaderboard::SPACE,
+        seeds = [b"leaderboard", idea.theme.as_ref(), protocol_config.season.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub theme_leaderboard: Option<Box<Account<'info, Leaderboard>>>,
+
+    /// 战队托管账户，接收本金与收益
+    #[account(
+        mut,
+        associated_token::mint = idea.theme_token_mint,
+        associated_token::authority = squad,
+    )]
+    pub squad_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 大额所得的线性释放记录 (归属战队，超过阈值时必须提供)
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + VestingClaim::SPACE,
+        seeds = [b"vesting_claim", idea.key().as_ref(), squad.key().as_ref()],
+        bump
+    )]
+    pub vesting_claim: Option<Box<Account<'info, VestingClaim>>>,
+
+    /// CHECK: Squad authority PDA, signs the core CPI
+    #[account(seeds = [b"squad_authority"], bump)]
+    pub squad_authority: UncheckedAccount<'info>,
+
+    /// 任何人均可发起结算 (支付声誉、排行榜与线性释放账户的租金)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawMirroredWinnings<'info> {
+    #[account(mut)]
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        seeds = [
+            b"follow",
+            follow.follower.as_ref(),
+            follow.curator.as_ref(),
+            idea.theme_token_mint.as_ref()
+        ],
+        bump = follow.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub follow: Box<Account<'info, taste_fun_core::Follow>>,
+
+    #[account(
+        seeds = [b"vote", idea.key().as_ref(), follow.key().as_ref()],
+        bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vote: Account<'info, Vote>,
+
+    #[account(
+        mut,
+        seeds = [b"reviewer_stake", idea.key().as_ref(), follow.key().as_ref()],
+        bump = reviewer_stake.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub reviewer_stake: Account<'info, ReviewerStake>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault token account
+    #[account(mut, token::mint = idea.theme_token_mint, token::authority = vault)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    /// Curator's token account to receive the mirrored stake's share of the curator fee
+    #[account(
+        mut,
+        constraint = curator_token_account.owner == idea.curator @ ConsensusError::Unauthorized,
+        constraint = curator_token_account.mint == vault_token_account.mint @ ConsensusError::InvalidMint
+    )]
+    pub curator_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol treasury token account to receive the mirrored stake's share of the platform fee
+    #[account(
+        mut,
+        token::mint = vault_token_account.mint,
+        token::authority = protocol_config.treasury,
+    )]
+    pub protocol_treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 被关注策展人的代币账户，接收获胜小费
+    #[account(
+        mut,
+        token::mint = vault_token_account.mint,
+        token::authority = follow.curator,
+    )]
+    pub followed_curator_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 关注者的跟投托管账户，接收本金与其余收益
+    #[account(
+        mut,
+        associated_token::mint = idea.theme_token_mint,
+        associated_token::authority = follow,
+    )]
+    pub follow_escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 大额所得的线性释放记录 (归属跟投关注，超过阈值时必须提供)
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + VestingClaim::SPACE,
+        seeds = [b"vesting_claim", idea.key().as_ref(), follow.key().as_ref()],
+        bump
+    )]
+    pub vesting_claim: Option<Box<Account<'info, VestingClaim>>>,
+
+    /// 任何人均可发起结算 (支付线性释放账户的租金)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PostStateRoot<'info> {
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        space = 8 + StateRoot::SPACE,
+        seeds = [b"state_root"],
+        bump
+    )]
+    pub state_root: Account<'info, StateRoot>,
+
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AwardBonus<'info> {
+    #[account(
+        mut,
+        constraint = idea.bonus_judge == Some(judge.key()) @ ConsensusError::Unauthorized
+    )]
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        init,
+        payer = judge,
+        space = 8 + BonusAward::SPACE,
+        seeds = [b"bonus_award", idea.key().as_ref()],
+        bump
+    )]
+    pub bonus_award: Account<'info, BonusAward>,
+
+    #[account(mut)]
+    pub judge: Signer<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimBonus<'info> {
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        seeds = [b"vote", idea.key().as_ref(), reviewer.key().as_ref()],
+        bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vote: Account<'info, Vote>,
+
+    #[account(
+        seeds = [b"bonus_award", idea.key().as_ref()],
+        bump = bonus_award.bump
+    )]
+    pub bonus_award: Account<'info, BonusAward>,
+
+    #[account(
+        init,
+        payer = reviewer,
+        space = 8 + BonusClaim::SPACE,
+        seeds = [b"bonus_claim", idea.key().as_ref(), reviewer.key().as_ref()],
+        bump
+    )]
+    pub bonus_claim: Account<'info, BonusClaim>,
+
+    #[account(
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault token account
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Reviewer's token account to receive bonus
+    #[account(mut)]
+    pub reviewer_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub reviewer: Signer<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReclaimBonus<'info> {
+    #[account(
+        mut,
+        constraint = idea.sponsor == Some(sponsor.key()) @ ConsensusError::Unauthorized
+    )]
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault token account
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Sponsor's token account to receive the unawarded bonus
+    #[account(mut)]
+    pub sponsor_token_account: Account<'info, TokenAccount>,
+
+    pub sponsor: Signer<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SweepUnclaimed<'info> {
+    #[account(mut)]
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault token account
+    #[account(mut, token::authority = vault)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    /// Protocol treasury token account to receive unclaimed funds
+    #[account(mut, token::authority = protocol_config.treasury)]
+    pub protocol_treasury_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Protocol treasury to receive closed account rent
+    #[account(mut, address = protocol_config.treasury @ ConsensusError::Unauthorized)]
+    pub protocol_treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct OpenDispute<'info> {
+    #[account(mut)]
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        init,
+        payer = challenger,
+        space = 8 + Dispute::SPACE,
+        seeds = [b"dispute", idea.key().as_ref()],
+        bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    #[account(
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault token account to hold the bond
+    #[account(mut, token::authority = vault)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Challenger's token account paying the bond
+    #[account(mut)]
+    pub challenger_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub challenger: Signer<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(mut)]
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        mut,
+        seeds = [b"dispute", idea.key().as_ref()],
+        bump = dispute.bump,
+        has_one = challenger,
+        close = challenger
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    #[account(
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault token account holding the bond
+    #[account(mut, token::authority = vault)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Challenger's token account to receive the returned bond
+    #[account(mut, token::authority = challenger)]
+    pub challenger_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    /// Protocol treasury token account to receive a forfeited bond
+    #[account(mut, token::authority = protocol_config.treasury)]
+    pub protocol_treasury_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Challenger to receive dispute account rent
+    #[account(mut)]
+    pub challenger: UncheckedAccount<'info>,
+
+    pub arbiter: Signer<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeInsurance<'info> {
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Insurance::SPACE,
+        seeds = [b"insurance"],
+        bump
+    )]
+    pub insurance: Account<'info, Insurance>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PayInsuranceClaim<'info> {
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    #[account(mut, seeds = [b"insurance"], bump = insurance.bump)]
+    pub insurance: Account<'info, Insurance>,
+
+    /// Insurance token account paying the claim
+    #[account(mut, token::authority = insurance)]
+    pub insurance_token_account: Account<'info, TokenAccount>,
+
+    /// Claimant's token account
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == insurance_token_account.mint @ ConsensusError::InvalidMint
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PostPayoutRoot<'info> {
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        init_if_needed,
+        payer = settler,
+        space = 8 + PayoutRoot::SPACE,
+        seeds = [b"payout_root", idea.key().as_ref()],
+        bump
+    )]
+    pub payout_root: Account<'info, PayoutRoot>,
+
+    /// CHECK: 派奖根的挑战记录，未决挑战裁决关闭前不可替换派奖根
+    #[account(seeds = [b"payout_challenge", payout_root.key().as_ref()], bump)]
+    pub payout_challenge: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault token account
+    #[account(token::authority = vault)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub settler: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ChallengePayoutRoot<'info> {
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        mut,
+        seeds = [b"payout_root", idea.key().as_ref()],
+        bump = payout_root.bump
+    )]
+    pub payout_root: Account<'info, PayoutRoot>,
+
+    /// 挑战记录，存放保证金直至仲裁
+    #[account(
+        init,
+        payer = challenger,
+        space = 8 + PayoutChallenge::SPACE,
+        seeds = [b"payout_challenge", payout_root.key().as_ref()],
+        bump
+    )]
+    pub payout_challenge: Account<'info, PayoutChallenge>,
+
+    /// 只有参与投票的评审可以挑战
+    #[account(
+        seeds = [b"vote", idea.key().as_ref(), challenger.key().as_ref()],
+        bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vote: Account<'info, Vote>,
+
+    #[account(mut)]
+    pub challenger: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ResolvePayoutChallenge<'info> {
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        mut,
+        seeds = [b"payout_root", idea.key().as_ref()],
+        bump = payout_root.bump
+    )]
+    pub payout_root: Account<'info, PayoutRoot>,
+
+    #[account(
+        mut,
+        seeds = [b"payout_challenge", payout_root.key().as_ref()],
+        bump = payout_challenge.bump,
+        has_one = payout_root,
+        has_one = challenger,
+        close = challenger
+    )]
+    pub payout_challenge: Account<'info, PayoutChallenge>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    /// CHECK: Protocol treasury to receive a forfeited bond
+    #[account(mut, address = protocol_config.treasury @ ConsensusError::Unauthorized)]
+    pub protocol_treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Challenger to receive the returned bond and challenge account rent
+    #[account(mut)]
+    pub challenger: UncheckedAccount<'info>,
+
+    pub arbiter: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimWithProof<'info> {
+    #[account(mut)]
+    pub idea: Account<'info, Idea>,
+
+    #[account(
+        mut,
+        seeds = [b"payout_root", idea.key().as_ref()],
+        bump = payout_root.bump
+    )]
+    pub payout_root: Account<'info, PayoutRoot>,
+
+    #[account(
+        mut,
+        seeds = [b"reviewer_stake", idea.key().as_ref(), reviewer.key().as_ref()],
+        bump = reviewer_stake.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub reviewer_stake: Account<'info, ReviewerStake>,
+
+    /// 评审的跨创意声誉记录，领取时更新
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + Reputation::SPACE,
+        seeds = [b"reputation", reviewer.key().as_ref()],
+        bump
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        seeds = [b"vault", idea.key().as_ref()],
+        bump = idea.vault_bump,
+        seeds::program = taste_fun_core::ID
+    )]
     pub vault: Account<'info, Vault>,
 
-    /// Vault token account
-    #[account(mut)]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    /// Vault token account
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    /// Curator's token account to receive the reviewer's share of the curator fee
+    #[account(
+        mut,
+        constraint = curator_token_account.owner == idea.curator @ ConsensusError::Unauthorized,
+        constraint = curator_token_account.mint == vault_token_account.mint @ ConsensusError::InvalidMint
+    )]
+    pub curator_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Protocol treasury token account to receive the reviewer's share of the platform fee
+    #[account(
+        mut,
+        token::mint = vault_token_account.mint,
+        token::authority = protocol_config.treasury,
+    )]
+    pub protocol_treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 评审在创意所属主题的质押仓位 (申请质押档位的费用折扣时提供)
+    pub stake_position: Option<Box<Account<'info, taste_fun_token::StakePosition>>>,
+
+    /// 全局赛季排行榜 (提供时领取后更新)
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + Leaderboard::SPACE,
+        seeds = [
+            b"leaderboard",
+            GLOBAL_LEADERBOARD_SCOPE.as_ref(),
+            protocol_config.season.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub leaderboard: Option<Box<Account<'info, Leaderboard>>>,
+
+    /// 创意所属主题的赛季排行榜 (提供时领取后更新)
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + Leaderboard::SPACE,
+        seeds = [b"leaderboard", idea.theme.as_ref(), protocol_config.season.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub theme_leaderboard: Option<Box<Account<'info, Leaderboard>>>,
+
+    /// Reviewer's token account to receive the payout
+    #[account(mut)]
+    pub reviewer_token_account: Account<'info, TokenAccount>,
+
+    /// 大额奖金的线性释放记录 (超过阈值时必须提供)
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + VestingClaim::SPACE,
+        seeds = [b"vesting_claim", idea.key().as_ref(), reviewer.key().as_ref()],
+        bump
+    )]
+    pub vesting_claim: Option<Box<Account<'info, VestingClaim>>>,
+
+    #[account(mut)]
+    pub reviewer: Signer<'info>,
+
+    /// CHECK: Settlement authority PDA, signs core vault and settlement CPIs
+    #[account(seeds = [b"settlement_authority"], bump)]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub core_program: Program<'info, TasteFunCore>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(season: u32)]
+pub struct FundSeasonRewards<'info> {
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    /// 赛季奖励池，lamports 即奖励预算
+    #[account(
+        init,
+        payer = treasury,
+        space = 8 + RewardsSeason::SPACE,
+        seeds = [b"rewards_season", season.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub rewards_season: Account<'info, RewardsSeason>,
+
+    /// 协议财库钱包，注资奖励预算
+    #[account(mut, address = protocol_config.treasury @ ConsensusError::Unauthorized)]
+    pub treasury: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimSeasonRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"rewards_season", rewards_season.season.to_le_bytes().as_ref()],
+        bump = rewards_season.bump
+    )]
+    pub rewards_season: Account<'info, RewardsSeason>,
+
+    /// 领取记录，重复领取时 init 失败
+    #[account(
+        init,
+        payer = claimant,
+        space = 8 + RewardsClaim::SPACE,
+        seeds = [b"rewards_claim", rewards_season.key().as_ref(), claimant.key().as_ref()],
+        bump
+    )]
+    pub rewards_claim: Account<'info, RewardsClaim>,
+
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReclaimSeasonRewards<'info> {
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"rewards_season", rewards_season.season.to_le_bytes().as_ref()],
+        bump = rewards_season.bump
+    )]
+    pub rewards_season: Account<'info, RewardsSeason>,
+
+    /// CHECK: 协议财库钱包，接收未领取的奖励
+    #[account(mut, address = protocol_config.treasury @ ConsensusError::Unauthorized)]
+    pub treasury: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MintWinnerNft<'info> {
+    pub idea: Box<Account<'info, Idea>>,
+
+    /// 获胜评审铸造时提供其投票记录 (发起者铸造时省略)
+    #[account(
+        seeds = [b"vote", idea.key().as_ref(), minter.key().as_ref()],
+        bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vote: Option<Box<Account<'info, Vote>>>,
+
+    /// 版税金库，作为 NFT 唯一创作者接收二级市场版税
+    #[account(
+        init,
+        payer = minter,
+        space = 8 + RoyaltyVault::SPACE,
+        seeds = [b"royalty_vault", idea.key().as_ref()],
+        bump
+    )]
+    pub royalty_vault: Box<Account<'info, RoyaltyVault>>,
+
+    #[account(
+        init,
+        payer = minter,
+        space = 8 + WinnerNft::SPACE,
+        seeds = [b"winner_nft", idea.key().as_ref()],
+        bump
+    )]
+    pub winner_nft: Box<Account<'info, WinnerNft>>,
+
+    #[account(
+        init,
+        payer = minter,
+        seeds = [b"winner_nft_mint", idea.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = nft_authority,
+        mint::freeze_authority = nft_authority,
+    )]
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = minter,
+        associated_token::mint = nft_mint,
+        associated_token::authority = minter,
+    )]
+    pub minter_nft_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: NFT 铸币与元数据更新权限 PDA (无数据)
+    #[account(seeds = [b"nft_authority"], bump)]
+    pub nft_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Token Metadata 元数据 PDA，由 Token Metadata 程序创建
+    #[account(
+        mut,
+        seeds = [b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), nft_mint.key().as_ref()],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Token Metadata 主版本 PDA，由 Token Metadata 程序创建
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            nft_mint.key().as_ref(),
+            b"edition"
+        ],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub master_edition: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub minter: Signer<'info>,
+
+    /// CHECK: Metaplex Token Metadata 程序
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PostWinnerNftRoot<'info> {
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub protocol_config: Box<Account<'info, taste_fun_core::ProtocolConfig>>,
+
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        init_if_needed,
+        payer = settler,
+        space = 8 + WinnerNftDrop::SPACE,
+        seeds = [b"winner_nft_drop", idea.key().as_ref()],
+        bump
+    )]
+    pub winner_nft_drop: Box<Account<'info, WinnerNftDrop>>,
+
+    #[account(mut)]
+    pub settler: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimWinnerNft<'info> {
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        mut,
+        seeds = [b"winner_nft_drop", idea.key().as_ref()],
+        bump = winner_nft_drop.bump
+    )]
+    pub winner_nft_drop: Box<Account<'info, WinnerNftDrop>>,
+
+    #[account(
+        init,
+        payer = winner,
+        space = 8 + WinnerNftClaim::SPACE,
+        seeds = [b"winner_nft_claim", idea.key().as_ref(), winner.key().as_ref()],
+        bump
+    )]
+    pub winner_nft_claim: Box<Account<'info, WinnerNftClaim>>,
+
+    /// CHECK: 创意的版税金库 PDA，作为 NFT 唯一创作者 (可能尚未由 mint_winner_nft 初始化)
+    #[account(seeds = [b"royalty_vault", idea.key().as_ref()], bump)]
+    pub royalty_vault: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = winner,
+        seeds = [b"winner_claim_mint", idea.key().as_ref(), winner.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = nft_authority,
+        mint::freeze_authority = nft_authority,
+    )]
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = winner,
+        associated_token::mint = nft_mint,
+        associated_token::authority = winner,
+    )]
+    pub winner_nft_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: NFT 铸币与元数据更新权限 PDA (无数据)
+    #[account(seeds = [b"nft_authority"], bump)]
+    pub nft_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Token Metadata 元数据 PDA，由 Token Metadata 程序创建
+    #[account(
+        mut,
+        seeds = [b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), nft_mint.key().as_ref()],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Token Metadata 主版本 PDA，由 Token Metadata 程序创建
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            nft_mint.key().as_ref(),
+            b"edition"
+        ],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub master_edition: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub winner: Signer<'info>,
+
+    /// CHECK: Metaplex Token Metadata 程序
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct OpenEditionSale<'info> {
+    #[account(has_one = initiator @ ConsensusError::Unauthorized)]
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        seeds = [b"winner_nft", idea.key().as_ref()],
+        bump = winner_nft.bump
+    )]
+    pub winner_nft: Box<Account<'info, WinnerNft>>,
+
+    /// 版税金库，作为版本元数据的唯一创作者
+    #[account(
+        seeds = [b"royalty_vault", idea.key().as_ref()],
+        bump = royalty_vault.bump
+    )]
+    pub royalty_vault: Box<Account<'info, RoyaltyVault>>,
+
+    #[account(
+        init,
+        payer = initiator,
+        space = 8 + EditionSale::SPACE,
+        seeds = [b"edition_sale", idea.key().as_ref()],
+        bump
+    )]
+    pub edition_sale: Box<Account<'info, EditionSale>>,
+
+    #[account(
+        init,
+        payer = initiator,
+        seeds = [b"edition_mint", idea.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = nft_authority,
+        mint::freeze_authority = nft_authority,
+    )]
+    pub edition_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: NFT 铸币与元数据更新权限 PDA (无数据)
+    #[account(seeds = [b"nft_authority"], bump)]
+    pub nft_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Token Metadata 元数据 PDA，由 Token Metadata 程序创建
+    #[account(
+        mut,
+        seeds = [b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), edition_mint.key().as_ref()],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub initiator: Signer<'info>,
+
+    /// CHECK: Metaplex Token Metadata 程序
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BuyEdition<'info> {
+    #[account(has_one = initiator @ ConsensusError::Unauthorized)]
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        mut,
+        seeds = [b"edition_sale", idea.key().as_ref()],
+        bump = edition_sale.bump
+    )]
+    pub edition_sale: Box<Account<'info, EditionSale>>,
+
+    #[account(mut, address = edition_sale.mint @ ConsensusError::InvalidMint)]
+    pub edition_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = edition_mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_edition_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: NFT 铸币权限 PDA (无数据)
+    #[account(seeds = [b"nft_authority"], bump)]
+    pub nft_authority: UncheckedAccount<'info>,
+
+    /// CHECK: 创意发起者，接收其收入份额
+    #[account(mut)]
+    pub initiator: UncheckedAccount<'info>,
+
+    #[account(mut, address = idea.theme @ ConsensusError::InvalidState)]
+    pub theme: Box<Account<'info, taste_fun_token::Theme>>,
+
+    /// CHECK: 主题金库 PDA (代币程序派生)，回购份额存入此处，由代币程序校验
+    #[account(mut)]
+    pub theme_vault: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: Theme program event authority - validated by token program
+    pub theme_event_authority: UncheckedAccount<'info>,
+
+    pub theme_program: Program<'info, TasteFunToken>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimEditionRevenue<'info> {
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        seeds = [b"vote", idea.key().as_ref(), voter.key().as_ref()],
+        bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vote: Box<Account<'info, Vote>>,
+
+    #[account(
+        mut,
+        seeds = [b"edition_sale", idea.key().as_ref()],
+        bump = edition_sale.bump
+    )]
+    pub edition_sale: Box<Account<'info, EditionSale>>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + EditionClaim::SPACE,
+        seeds = [b"edition_claim", idea.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub edition_claim: Box<Account<'info, EditionClaim>>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimRoyalties<'info> {
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        seeds = [b"vote", idea.key().as_ref(), voter.key().as_ref()],
+        bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vote: Box<Account<'info, Vote>>,
+
+    #[account(
+        mut,
+        seeds = [b"royalty_vault", idea.key().as_ref()],
+        bump = royalty_vault.bump
+    )]
+    pub royalty_vault: Box<Account<'info, RoyaltyVault>>,
+
+    #[account(
+        init_if_needed,
+        payer = voter,
+        space = 8 + RoyaltyClaim::SPACE,
+        seeds = [b"royalty_claim", idea.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub royalty_claim: Box<Account<'info, RoyaltyClaim>>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitRemixPool<'info> {
+    pub parent_idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RemixPool::SPACE,
+        seeds = [b"remix_pool", parent_idea.key().as_ref()],
+        bump
+    )]
+    pub remix_pool: Box<Account<'info, RemixPool>>,
+
+    #[account(address = parent_idea.theme_token_mint @ ConsensusError::InvalidMint)]
+    pub theme_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = theme_token_mint,
+        associated_token::authority = remix_pool,
+    )]
+    pub remix_pool_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimRemixRoyalties<'info> {
+    pub parent_idea: Box<Account<'info, Idea>>,
+
+    /// 原获胜评审领取时提供其在原创意的投票记录 (仅领取发起者份额时省略)
+    #[account(
+        seeds = [b"vote", parent_idea.key().as_ref(), claimer.key().as_ref()],
+        bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vote: Option<Box<Account<'info, Vote>>>,
+
+    #[account(
+        mut,
+        seeds = [b"remix_pool", parent_idea.key().as_ref()],
+        bump = remix_pool.bump
+    )]
+    pub remix_pool: Box<Account<'info, RemixPool>>,
+
+    #[account(
+        mut,
+        associated_token::mint = remix_pool.token_mint,
+        associated_token::authority = remix_pool,
+    )]
+    pub remix_pool_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = claimer,
+        space = 8 + RemixClaim::SPACE,
+        seeds = [b"remix_claim", parent_idea.key().as_ref(), claimer.key().as_ref()],
+        bump
+    )]
+    pub remix_claim: Box<Account<'info, RemixClaim>>,
+
+    #[account(mut, token::mint = remix_pool.token_mint, token::authority = claimer)]
+    pub claimer_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitCollectionPrize<'info> {
+    pub collection: Box<Account<'info, taste_fun_core::Collection>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + CollectionPrize::SPACE,
+        seeds = [b"collection_prize", collection.key().as_ref()],
+        bump
+    )]
+    pub collection_prize: Box<Account<'info, CollectionPrize>>,
+
+    #[account(address = collection.theme_token_mint @ ConsensusError::InvalidMint)]
+    pub theme_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = theme_token_mint,
+        associated_token::authority = collection_prize,
+    )]
+    pub collection_prize_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizeCollection<'info> {
+    #[account(
+        mut,
+        seeds = [b"collection_prize", collection_prize.collection.as_ref()],
+        bump = collection_prize.bump
+    )]
+    pub collection_prize: Box<Account<'info, CollectionPrize>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimCollectionPrize<'info> {
+    #[account(
+        seeds = [b"collection_prize", collection_prize.collection.as_ref()],
+        bump = collection_prize.bump
+    )]
+    pub collection_prize: Box<Account<'info, CollectionPrize>>,
+
+    #[account(address = collection_prize.top_idea @ ConsensusError::InvalidCollection)]
+    pub top_idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        seeds = [b"vote", top_idea.key().as_ref(), voter.key().as_ref()],
+        bump,
+        seeds::program = taste_fun_core::ID
+    )]
+    pub vote: Box<Account<'info, Vote>>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + CollectionPrizeClaim::SPACE,
+        seeds = [b"collection_prize_claim", collection_prize.collection.as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub collection_prize_claim: Box<Account<'info, CollectionPrizeClaim>>,
+
+    #[account(
+        mut,
+        associated_token::mint = collection_prize.token_mint,
+        associated_token::authority = collection_prize,
+    )]
+    pub collection_prize_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = collection_prize.token_mint, token::authority = voter)]
+    pub voter_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitGallery<'info> {
+    pub theme: Box<Account<'info, taste_fun_token::Theme>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Gallery::SPACE,
+        seeds = [b"gallery", theme.key().as_ref()],
+        bump
+    )]
+    pub gallery: Box<Account<'info, Gallery>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + GalleryPage::SPACE,
+        seeds = [b"gallery_page", theme.key().as_ref(), &0u32.to_le_bytes()],
+        bump
+    )]
+    pub gallery_page: Box<Account<'info, GalleryPage>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitGalleryPage<'info> {
+    #[account(
+        mut,
+        seeds = [b"gallery", gallery.theme.as_ref()],
+        bump = gallery.bump
+    )]
+    pub gallery: Box<Account<'info, Gallery>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + GalleryPage::SPACE,
+        seeds = [b"gallery_page", gallery.theme.as_ref(), &gallery.page_count.to_le_bytes()],
+        bump
+    )]
+    pub gallery_page: Box<Account<'info, GalleryPage>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitBadgeTree<'info> {
+    pub theme: Box<Account<'info, taste_fun_token::Theme>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + BadgeTree::SPACE,
+        seeds = [b"badge_tree", theme.key().as_ref()],
+        bump
+    )]
+    pub badge_tree: Box<Account<'info, BadgeTree>>,
+
+    /// CHECK: Bubblegum 树配置 PDA，由 Bubblegum 创建
+    #[account(
+        mut,
+        seeds = [merkle_tree.key().as_ref()],
+        bump,
+        seeds::program = BUBBLEGUM_PROGRAM_ID
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: 预先分配给压缩程序的空 merkle 树账户，由 Bubblegum 初始化
+    #[account(mut, owner = SPL_ACCOUNT_COMPRESSION_PROGRAM_ID @ ConsensusError::InvalidBadgeTree)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// CHECK: SPL Noop 程序
+    #[account(address = SPL_NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: SPL Account Compression 程序
+    #[account(address = SPL_ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex Bubblegum 程序
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetBadgeConfig<'info> {
+    pub theme: Box<Account<'info, taste_fun_token::Theme>>,
+
+    #[account(
+        mut,
+        seeds = [b"badge_tree", theme.key().as_ref()],
+        bump = badge_tree.bump
+    )]
+    pub badge_tree: Box<Account<'info, BadgeTree>>,
+
+    pub creator: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MintParticipationBadges<'info> {
+    pub idea: Box<Account<'info, Idea>>,
+
+    #[account(
+        mut,
+        seeds = [b"badge_tree", idea.theme.as_ref()],
+        bump = badge_tree.bump,
+        constraint = badge_tree.enabled @ ConsensusError::BadgesDisabled
+    )]
+    pub badge_tree: Box<Account<'info, BadgeTree>>,
+
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        space = 8 + BadgeDrop::SPACE,
+        seeds = [b"badge_drop", idea.key().as_ref()],
+        bump
+    )]
+    pub badge_drop: Box<Account<'info, BadgeDrop>>,
+
+    /// CHECK: Bubblegum 树配置 PDA
+    #[account(
+        mut,
+        seeds = [merkle_tree.key().as_ref()],
+        bump,
+        seeds::program = BUBBLEGUM_PROGRAM_ID
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: 主题徽章 merkle 树
+    #[account(mut, address = badge_tree.merkle_tree @ ConsensusError::InvalidBadgeTree)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    /// CHECK: SPL Noop 程序
+    #[account(address = SPL_NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: SPL Account Compression 程序
+    #[account(address = SPL_ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex Bubblegum 程序
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// -----------------------------------------------------------------------------
+// Events
+// -----------------------------------------------------------------------------
+
+#[event]
+pub struct VotingSettled {
+    pub idea: Pubkey,
+    pub winning_image_index: u8,
+    pub secondary_winning_index: Option<u8>,
+    pub total_staked: u64,
+    pub curator_fee: u64,
+    pub platform_fee: u64,
+    pub penalty_pool: u64,
+    pub winner_count: u64,
+    pub settler: Pubkey,
+    pub settler_reward: u64,
+    pub insurance_contribution: u64,
+    pub penalty_burned: u64,
+    pub remix_royalty: u64,
+    pub collection_contribution: u64,
+}
+
+#[event]
+pub struct BatchSettled {
+    pub settler: Pubkey,
+    pub settled: u32,
+    pub skipped: u32,
+}
+
+#[event]
+pub struct WinningsWithdrawn {
+    pub idea: Pubkey,
+    pub reviewer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReputationUpdated {
+    pub reviewer: Pubkey,
+    pub won: bool,
+    pub wins: u32,
+    pub losses: u32,
+    pub current_streak: u16,
+    pub best_streak: u16,
+}
+
+/// 赛季积分累计 (链下按赛季汇总，见 POINTS_KIND_*)
+#[event]
+pub struct PointsAccrued {
+    pub user: Pubkey,
+    pub kind: u8,
+    pub points: u64,
+}
+
+#[event]
+pub struct SeasonRewardsFunded {
+    pub rewards_season: Pubkey,
+    pub season: u32,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct SeasonRewardsClaimed {
+    pub rewards_season: Pubkey,
+    pub season: u32,
+    pub claimant: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SeasonRewardsReclaimed {
+    pub rewards_season: Pubkey,
+    pub season: u32,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ClaimFeesCharged {
+    pub idea: Pubkey,
+    pub reviewer: Pubkey,
+    pub curator_fee: u64,
+    pub platform_fee: u64,
+    pub discount_bps: u16,
+    pub rebate: u64,
+}
+
+#[event]
+pub struct LoserRefundWithdrawn {
+    pub idea: Pubkey,
+    pub reviewer: Pubkey,
+    pub amount: u64,
+    pub penalty: u64,
+}
+
+#[event]
+pub struct VotingCancelled {
+    pub idea: Pubkey,
+    pub reason: String,
+}
+
+#[event]
+pub struct RefundWithdrawn {
+    pub idea: Pubkey,
+    pub reviewer: Pubkey,
+    pub amount: u64,
+    pub gas_rebate: u64,
+}
+
+#[event]
+pub struct StateRootPosted {
+    pub epoch: u64,
+    pub root: [u8; 32],
+    pub leaf_count: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct BonusAwarded {
+    pub idea: Pubkey,
+    pub judge: Pubkey,
+    pub image_index: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BonusClaimed {
+    pub idea: Pubkey,
+    pub reviewer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BonusReclaimed {
+    pub idea: Pubkey,
+    pub sponsor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct InsuranceClaimPaid {
+    pub idea: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct JackpotRolledOver {
+    pub idea: Pubkey,
+    pub jackpot: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct VestingStarted {
+    pub idea: Pubkey,
+    pub reviewer: Pubkey,
+    pub total_amount: u64,
+    pub end_ts: i64,
+}
+
+#[event]
+pub struct VestedClaimed {
+    pub idea: Pubkey,
+    pub reviewer: Pubkey,
+    pub amount: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct WinningsRestaked {
+    pub idea: Pubkey,
+    pub target_idea: Pubkey,
+    pub reviewer: Pubkey,
+    pub image_index: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct DelegatedWinningsWithdrawn {
+    pub idea: Pubkey,
+    pub delegation: Pubkey,
+    pub delegator: Pubkey,
+    pub curator: Pubkey,
+    pub amount: u64,
+    pub curator_cut: u64,
+}
+
+#[event]
+pub struct SquadWinningsReturned {
+    pub idea: Pubkey,
+    pub squad: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct MirroredWinningsWithdrawn {
+    pub idea: Pubkey,
+    pub follow: Pubkey,
+    pub follower: Pubkey,
+    pub curator: Pubkey,
+    pub amount: u64,
+    pub tip: u64,
+}
+
+#[event]
+pub struct UnclaimedSwept {
+    pub idea: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct DisputeOpened {
+    pub idea: Pubkey,
+    pub challenger: Pubkey,
+    pub bond: u64,
+    pub evidence_hash: [u8; 32],
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub idea: Pubkey,
+    pub upheld: bool,
+    pub winning_image_index: Option<u8>,
+    pub bond: u64,
+    /// 改判后重新分账的结果 (驳回时与结算时一致)
+    pub curator_fee: u64,
+    pub platform_fee: u64,
+    pub penalty_pool: u64,
+    pub winner_count: u64,
+}
+
+#[event]
+pub struct PayoutRootPosted {
+    pub idea: Pubkey,
+    pub root: [u8; 32],
+    pub total_amount: u64,
+    pub challenge_deadline: i64,
+}
+
+#[event]
+pub struct PayoutRootChallenged {
+    pub idea: Pubkey,
+    pub challenger: Pubkey,
+    pub bond: u64,
+}
+
+#[event]
+pub struct PayoutChallengeResolved {
+    pub idea: Pubkey,
+    pub challenger: Pubkey,
+    pub upheld: bool,
+    pub bond: u64,
+}
+
+#[event]
+pub struct WinnerNftRootPosted {
+    pub idea: Pubkey,
+    pub root: [u8; 32],
+    pub winner_count: u64,
+}
+
+#[event]
+pub struct WinnerNftClaimed {
+    pub idea: Pubkey,
+    pub winner: Pubkey,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct WinnerNftMinted {
+    pub idea: Pubkey,
+    pub mint: Pubkey,
+    pub minter: Pubkey,
+    pub image_index: u8,
+    pub image_hash: [u8; 32],
+}
+
+#[event]
+pub struct EditionSaleOpened {
+    pub idea: Pubkey,
+    pub mint: Pubkey,
+    pub price: u64,
+    pub max_supply: u64,
+    pub end_ts: i64,
+    pub voter_share_bps: u16,
+    pub initiator_share_bps: u16,
+    pub buyback_share_bps: u16,
+}
+
+#[event]
+pub struct EditionPurchased {
+    pub idea: Pubkey,
+    pub buyer: Pubkey,
+    pub quantity: u64,
+    pub voter_amount: u64,
+    pub initiator_amount: u64,
+    pub buyback_amount: u64,
+}
 
-    /// Reviewer's token account to receive winnings
-    #[account(mut)]
-    pub reviewer_token_account: Account<'info, TokenAccount>,
+#[event]
+pub struct EditionRevenueClaimed {
+    pub idea: Pubkey,
+    pub voter: Pubkey,
+    pub amount: u64,
+}
 
-    #[account(mut)]
-    pub reviewer: Signer<'info>,
+#[event]
+pub struct RoyaltiesClaimed {
+    pub idea: Pubkey,
+    pub voter: Pubkey,
+    pub amount: u64,
+    pub total_claimed: u64,
+}
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct RemixPoolInitialized {
+    pub parent: Pubkey,
+    pub token_mint: Pubkey,
 }
 
-#[derive(Accounts)]
-pub struct WithdrawRefund<'info> {
-    #[account(mut)]
-    pub idea: Account<'info, Idea>,
+#[event]
+pub struct RemixRoyaltiesClaimed {
+    pub parent: Pubkey,
+    pub claimer: Pubkey,
+    pub amount: u64,
+}
 
-    #[account(
-        seeds = [b"vote", idea.key().as_ref(), reviewer.key().as_ref()],
-        bump
-    )]
-    pub vote: Account<'info, Vote>,
+#[event]
+pub struct CollectionPrizeInitialized {
+    pub collection: Pubkey,
+    pub token_mint: Pubkey,
+    pub grand_prize_bps: u16,
+}
 
-    #[account(
-        mut,
-        seeds = [b"reviewer_stake", idea.key().as_ref(), reviewer.key().as_ref()],
-        bump = reviewer_stake.bump
-    )]
-    pub reviewer_stake: Account<'info, ReviewerStake>,
+#[event]
+pub struct CollectionFinalized {
+    pub collection: Pubkey,
+    pub top_idea: Pubkey,
+    pub grand_prize: u64,
+    pub total_prize: u64,
+    pub settled_count: u64,
+}
 
-    #[account(mut, seeds = [b"vault", idea.key().as_ref()], bump = idea.vault_bump)]
-    pub vault: Account<'info, Vault>,
+#[event]
+pub struct CollectionPrizeClaimed {
+    pub collection: Pubkey,
+    pub voter: Pubkey,
+    pub amount: u64,
+}
 
-    /// Vault token account
-    #[account(mut)]
-    pub vault_token_account: Account<'info, TokenAccount>,
+#[event]
+pub struct GalleryPageCreated {
+    pub theme: Pubkey,
+    pub page: u32,
+}
 
-    /// Reviewer's token account to receive refund
-    #[account(mut)]
-    pub reviewer_token_account: Account<'info, TokenAccount>,
+#[event]
+pub struct GalleryEntryAdded {
+    pub theme: Pubkey,
+    pub page: u32,
+    pub entry: GalleryEntry,
+}
 
-    #[account(mut)]
-    pub reviewer: Signer<'info>,
+#[event]
+pub struct BadgeTreeInitialized {
+    pub theme: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+}
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct BadgeConfigUpdated {
+    pub theme: Pubkey,
+    pub enabled: bool,
+    pub uri: String,
+}
+
+#[event]
+pub struct ParticipationBadgesMinted {
+    pub idea: Pubkey,
+    pub keeper: Pubkey,
+    pub minted: u64,
+    pub total_minted: u64,
 }
 
 // -----------------------------------------------------------------------------
-// Events
+// Account Structures
 // -----------------------------------------------------------------------------
 
-#[event]
-pub struct VotingSettled {
+#[account]
+pub struct StateRoot {
+    pub root: [u8; 32],
+    pub epoch: u64,
+    pub leaf_count: u64,
+    pub slot: u64,
+    pub posted_at: i64,
+    pub bump: u8,
+}
+
+impl StateRoot {
+    pub const SPACE: usize = STATE_ROOT_SPACE;
+}
+
+#[account]
+pub struct BonusAward {
     pub idea: Pubkey,
-    pub winning_image_index: u8,
-    pub total_staked: u64,
-    pub curator_fee: u64,
-    pub platform_fee: u64,
-    pub penalty_pool: u64,
-    pub winner_count: u64,
+    pub judge: Pubkey,
+    pub image_index: u8,
+    pub amount: u64,
+    pub total_weight: u64,
+    pub awarded_at: i64,
+    pub bump: u8,
 }
 
-#[event]
-pub struct WinningsWithdrawn {
+impl BonusAward {
+    pub const SPACE: usize = BONUS_AWARD_SPACE;
+}
+
+#[account]
+pub struct BonusClaim {
     pub idea: Pubkey,
     pub reviewer: Pubkey,
     pub amount: u64,
+    pub bump: u8,
 }
 
-#[event]
-pub struct VotingCancelled {
-    pub idea: Pubkey,
-    pub reason: String,
+impl BonusClaim {
+    pub const SPACE: usize = BONUS_CLAIM_SPACE;
 }
 
-#[event]
-pub struct RefundWithdrawn {
+#[account]
+pub struct Dispute {
     pub idea: Pubkey,
+    pub challenger: Pubkey,
+    pub bond: u64,
+    pub evidence_hash: [u8; 32],
+    pub opened_at: i64,
+    pub bump: u8,
+}
+
+impl Dispute {
+    pub const SPACE: usize = DISPUTE_SPACE;
+}
+
+#[account]
+pub struct Insurance {
+    pub total_contributed: u64,
+    pub total_paid: u64,
+    pub claim_count: u64,
+    pub bump: u8,
+}
+
+impl Insurance {
+    pub const SPACE: usize = INSURANCE_SPACE;
+}
+
+/// 评审声誉 (每钱包一个)，作为权重加成、费用折扣与排行榜的数据来源
+/// core 按布局读取 reviewer/wins/losses，这三个字段的位置不可调整
+#[account]
+pub struct Reputation {
+    pub reviewer: Pubkey,
+    pub wins: u32,
+    pub losses: u32,
+    pub total_staked: u64,
+    pub total_returned: u64,
+    pub current_streak: u16,
+    pub best_streak: u16,
+    pub last_updated: i64,
+    pub bump: u8,
+    /// 当前赛季战绩，赛季编号变化时清零
+    pub season: u32,
+    pub season_wins: u32,
+    pub season_losses: u32,
+    pub season_winnings: u64,
+}
+
+impl Reputation {
+    pub const SPACE: usize = REPUTATION_SPACE;
+}
+
+/// 赛季排行榜 PDA：scope 为主题地址或 GLOBAL_LEADERBOARD_SCOPE，按评审的赛季战绩排名
+#[account]
+pub struct Leaderboard {
+    pub scope: Pubkey,
+    pub season: u32,
+    pub updated_at: i64,
+    pub by_winnings: [LeaderboardEntry; LEADERBOARD_SIZE],
+    pub by_accuracy: [LeaderboardEntry; LEADERBOARD_SIZE],
+    pub bump: u8,
+}
+
+impl Leaderboard {
+    pub const SPACE: usize = LEADERBOARD_SPACE;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct LeaderboardEntry {
     pub reviewer: Pubkey,
+    pub winnings: u64,
+    pub wins: u32,
+    pub losses: u32,
+}
+
+/// 赛季积分奖励池 PDA，领取名单由链下按积分生成 merkle 根
+#[account]
+pub struct RewardsSeason {
+    pub season: u32,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub expires_at: i64,
+    pub reclaimed: bool,
+    pub bump: u8,
+}
+
+impl RewardsSeason {
+    pub const SPACE: usize = REWARDS_SEASON_SPACE;
+}
+
+#[account]
+pub struct RewardsClaim {
+    pub rewards_season: Pubkey,
+    pub claimant: Pubkey,
     pub amount: u64,
 }
 
-// -----------------------------------------------------------------------------
-// Account Structures (same as core program)
-// -----------------------------------------------------------------------------
+impl RewardsClaim {
+    pub const SPACE: usize = REWARDS_CLAIM_SPACE;
+}
 
 #[account]
-pub struct Idea {
-    pub initiator: Pubkey,
-    pub idea_id: u64,
-    pub prompt: String,
-    pub created_at: i64,
-    pub image_uris: Vec<String>,
-    pub generation_status: GenerationStatus,
-    pub generation_deadline: i64,
-    pub depin_provider: Pubkey,
-    pub sponsor: Option<Pubkey>,
-    pub initial_prize_pool: u64,
-    pub total_staked: u64,
-    pub min_stake: u64,
-    pub curator_fee_bps: u16,
-    pub votes: [u64; 4],
-    pub reject_all_weight: u64,
-    pub total_voters: u64,
-    pub winning_image_index: Option<u8>,
-    pub curator_fee_collected: u64,
-    pub platform_fee_collected: u64,
-    pub penalty_pool_amount: u64,
+pub struct VestingClaim {
+    pub idea: Pubkey,
+    pub reviewer: Pubkey,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub start_ts: i64,
+    pub duration: i64,
+    pub bump: u8,
+}
+
+impl VestingClaim {
+    pub const SPACE: usize = VESTING_CLAIM_SPACE;
+}
+
+#[account]
+pub struct PayoutRoot {
+    pub idea: Pubkey,
+    pub root: [u8; 32],
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub posted_at: i64,
+    pub challenged: bool,
+    pub bump: u8,
+}
+
+impl PayoutRoot {
+    pub const SPACE: usize = PAYOUT_ROOT_SPACE;
+}
+
+/// 派奖根挑战：挑战者缴纳的保证金，派奖根被维持时罚没
+#[account]
+pub struct PayoutChallenge {
+    pub payout_root: Pubkey,
+    pub challenger: Pubkey,
+    pub bond: u64,
+    pub opened_at: i64,
+    pub bump: u8,
+}
+
+impl PayoutChallenge {
+    pub const SPACE: usize = PAYOUT_CHALLENGE_SPACE;
+}
+
+/// 获胜评审 NFT 空投：链下按获胜投票生成的 Merkle 根与领取进度
+#[account]
+pub struct WinnerNftDrop {
+    pub idea: Pubkey,
+    pub root: [u8; 32],
     pub winner_count: u64,
-    pub voting_deadline: i64,
-    pub status: IdeaStatus,
-    pub vault_bump: u8,
-    pub idea_bump: u8,
+    pub claimed_count: u64,
+    pub posted_at: i64,
+    pub bump: u8,
+}
+
+impl WinnerNftDrop {
+    pub const SPACE: usize = WINNER_NFT_DROP_SPACE;
+}
+
+/// 获胜评审 NFT 领取记录 (每位获胜评审一次)
+#[account]
+pub struct WinnerNftClaim {
+    pub idea: Pubkey,
+    pub winner: Pubkey,
+    pub mint: Pubkey,
+    pub claimed_at: i64,
+    pub bump: u8,
+}
+
+impl WinnerNftClaim {
+    pub const SPACE: usize = WINNER_NFT_CLAIM_SPACE;
 }
 
+/// 获胜图片 NFT 记录：每个创意一枚，image_hash 为图片 URI 的 keccak 来源凭证
 #[account]
-pub struct Vault {
+pub struct WinnerNft {
     pub idea: Pubkey,
+    pub mint: Pubkey,
+    pub minter: Pubkey,
+    pub image_index: u8,
+    pub image_hash: [u8; 32],
+    pub minted_at: i64,
     pub bump: u8,
 }
 
+impl WinnerNft {
+    pub const SPACE: usize = WINNER_NFT_SPACE;
+}
+
+/// 创意的版税金库：获胜图片 NFT 与版本的唯一创作者，二级市场版税以 lamports 存入
+#[account]
+pub struct RoyaltyVault {
+    pub idea: Pubkey,
+    pub total_claimed: u64,
+    pub bump: u8,
+}
+
+impl RoyaltyVault {
+    pub const SPACE: usize = ROYALTY_VAULT_SPACE;
+}
+
+/// 评审已领取的版税累计 (按累计应得额差值领取)
 #[account]
-pub struct Vote {
+pub struct RoyaltyClaim {
     pub idea: Pubkey,
     pub voter: Pubkey,
-    pub image_choice: u8,
-    pub stake_amount: u64,
-    pub vote_weight: u64,
-    pub ts: i64,
+    pub claimed: u64,
+    pub bump: u8,
+}
+
+impl RoyaltyClaim {
+    pub const SPACE: usize = ROYALTY_CLAIM_SPACE;
 }
 
+/// 获胜图片的限时版本销售；评审份额以 lamports 留存在本账户，销售结束后领取
 #[account]
-pub struct ReviewerStake {
+pub struct EditionSale {
     pub idea: Pubkey,
-    pub reviewer: Pubkey,
-    pub total_staked: u64,
-    pub is_winner: bool,
-    pub winnings: u64,
+    pub mint: Pubkey,
+    pub price: u64,
+    pub max_supply: u64,
+    pub sold: u64,
+    pub end_ts: i64,
+    pub voter_share_bps: u16,
+    pub initiator_share_bps: u16,
+    pub buyback_share_bps: u16,
+    pub voter_revenue: u64,
+    pub voter_claimed: u64,
+    pub bump: u8,
+}
+
+impl EditionSale {
+    pub const SPACE: usize = EDITION_SALE_SPACE;
+}
+
+#[account]
+pub struct EditionClaim {
+    pub idea: Pubkey,
+    pub voter: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl EditionClaim {
+    pub const SPACE: usize = EDITION_CLAIM_SPACE;
+}
+
+/// 原创意的衍生版税池：各衍生创意结算时转入主题代币，按份额累计待领取
+#[account]
+pub struct RemixPool {
+    pub parent: Pubkey,
+    pub token_mint: Pubkey,
+    pub winner_revenue: u64,
+    pub initiator_revenue: u64,
+    pub initiator_claimed: u64,
+    pub bump: u8,
+}
+
+impl RemixPool {
+    pub const SPACE: usize = REMIX_POOL_SPACE;
+}
+
+/// 原获胜评审已领取的衍生版税累计
+#[account]
+pub struct RemixClaim {
+    pub parent: Pubkey,
+    pub claimer: Pubkey,
+    pub claimed: u64,
+    pub bump: u8,
+}
+
+impl RemixClaim {
+    pub const SPACE: usize = REMIX_CLAIM_SPACE;
+}
+
+/// 创意合集的大奖池：合集创意结算时注入代币并累计奖金统计，记录总质押最高的最佳创意
+#[account]
+pub struct CollectionPrize {
+    pub collection: Pubkey,
+    pub token_mint: Pubkey,
+    pub grand_prize_bps: u16,
+    pub ends_at: i64,
+    pub grand_prize: u64,
+    pub total_prize: u64,
+    pub settled_count: u64,
+    pub top_idea: Pubkey,
+    pub top_stake: u64,
+    pub finalized: bool,
+    pub bump: u8,
+}
+
+impl CollectionPrize {
+    pub const SPACE: usize = COLLECTION_PRIZE_SPACE;
+}
+
+/// 合集大奖领取记录 (每位获胜评审一次)
+#[account]
+pub struct CollectionPrizeClaim {
+    pub collection: Pubkey,
+    pub voter: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl CollectionPrizeClaim {
+    pub const SPACE: usize = COLLECTION_PRIZE_CLAIM_SPACE;
+}
+
+/// 主题画廊：获胜图片的永久名人堂，entry_count 决定下一条记录写入的页
+#[account]
+pub struct Gallery {
+    pub theme: Pubkey,
+    pub entry_count: u64,
+    pub page_count: u32,
+    pub bump: u8,
+}
+
+impl Gallery {
+    pub const SPACE: usize = GALLERY_SPACE;
+}
+
+/// 画廊分页：每页最多 GALLERY_PAGE_CAPACITY 条，按结算顺序追加
+#[account]
+pub struct GalleryPage {
+    pub theme: Pubkey,
+    pub page: u32,
+    pub entries: Vec<GalleryEntry>,
+    pub bump: u8,
+}
+
+impl GalleryPage {
+    pub const SPACE: usize = GALLERY_PAGE_SPACE;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct GalleryEntry {
+    pub idea: Pubkey,
+    pub image_hash: [u8; 32],
+    pub image_uri: String,
+    pub settled_at: i64,
+}
+
+/// 主题参与徽章配置：徽章树 PDA 同时是 Bubblegum merkle 树的创建者
+#[account]
+pub struct BadgeTree {
+    pub theme: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub enabled: bool,
+    pub uri: String,
+    pub minted: u64,
+    pub bump: u8,
+}
+
+impl BadgeTree {
+    pub const SPACE: usize = BADGE_TREE_SPACE;
+}
+
+/// 单个创意的徽章发放进度 (cursor 为已铸造的最大投票者公钥)
+#[account]
+pub struct BadgeDrop {
+    pub idea: Pubkey,
+    pub cursor: Pubkey,
+    pub minted: u64,
     pub bump: u8,
 }
+
+impl BadgeDrop {
+    pub const SPACE: usize = BADGE_DROP_SPACE;
+}
@@ -0,0 +1,274 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use taste_fun_shared::*;
+use crate::{AirdropCampaign, AirdropClaim, AirdropClaimed, AirdropCreated, AirdropReclaimed, Theme};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(campaign_id: u64)]
+pub struct CreateAirdrop<'info> {
+    #[account(
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Box<Account<'info, Theme>>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + AirdropCampaign::SPACE,
+        seeds = [b"airdrop", theme.key().as_ref(), campaign_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub campaign: Box<Account<'info, AirdropCampaign>>,
+    
+    #[account(address = theme.token_mint @ ConsensusError::InvalidMint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+    
+    /// 活动 ATA：存放待领取的空投代币
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = token_mint,
+        associated_token::authority = campaign,
+    )]
+    pub campaign_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = creator,
+    )]
+    pub creator_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimAirdrop<'info> {
+    #[account(
+        mut,
+        seeds = [b"airdrop", campaign.theme.as_ref(), campaign.campaign_id.to_le_bytes().as_ref()],
+        bump = campaign.bump
+    )]
+    pub campaign: Box<Account<'info, AirdropCampaign>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = campaign,
+    )]
+    pub campaign_token_account: Box<Account<'info, TokenAccount>>,
+    
+    pub token_mint: Box<Account<'info, Mint>>,
+    
+    /// 领取记录，重复领取时 init 失败
+    #[account(
+        init,
+        payer = claimant,
+        space = 8 + AirdropClaim::SPACE,
+        seeds = [b"airdrop_claim", campaign.key().as_ref(), claimant.key().as_ref()],
+        bump
+    )]
+    pub airdrop_claim: Box<Account<'info, AirdropClaim>>,
+    
+    #[account(
+        init_if_needed,
+        payer = claimant,
+        associated_token::mint = token_mint,
+        associated_token::authority = claimant,
+    )]
+    pub claimant_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReclaimAirdrop<'info> {
+    #[account(
+        mut,
+        seeds = [b"airdrop", campaign.theme.as_ref(), campaign.campaign_id.to_le_bytes().as_ref()],
+        bump = campaign.bump,
+        has_one = creator @ ConsensusError::Unauthorized
+    )]
+    pub campaign: Box<Account<'info, AirdropCampaign>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = creator_token_account.mint,
+        associated_token::authority = campaign,
+    )]
+    pub campaign_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        token::authority = creator,
+    )]
+    pub creator_token_account: Box<Account<'info, TokenAccount>>,
+    
+    pub creator: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+/// 创建空投活动：创建者一次性注资，领取名单由链下生成 merkle 根
+pub fn create_airdrop(
+    ctx: Context<CreateAirdrop>,
+    campaign_id: u64,
+    merkle_root: [u8; 32],
+    total_amount: u64,
+    expires_at: i64,
+) -> Result<()> {
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.creator_token_account.to_account_info(),
+                to: ctx.accounts.campaign_token_account.to_account_info(),
+                authority: ctx.accounts.creator.to_account_info(),
+            },
+        ),
+        total_amount,
+    )?;
+    
+    let campaign = &mut ctx.accounts.campaign;
+    campaign.theme = ctx.accounts.theme.key();
+    campaign.creator = ctx.accounts.creator.key();
+    campaign.campaign_id = campaign_id;
+    campaign.merkle_root = merkle_root;
+    campaign.total_amount = total_amount;
+    campaign.claimed_amount = 0;
+    campaign.expires_at = expires_at;
+    campaign.reclaimed = false;
+    campaign.bump = ctx.bumps.campaign;
+    
+    emit_cpi!(AirdropCreated {
+        campaign: campaign.key(),
+        theme: campaign.theme,
+        campaign_id,
+        merkle_root,
+        total_amount,
+        expires_at,
+    });
+    
+    msg!("Airdrop campaign {} funded with {} tokens", campaign_id, total_amount);
+    
+    Ok(())
+}
+
+/// 到期前凭 merkle 证明领取
+pub fn claim_airdrop(
+    ctx: Context<ClaimAirdrop>,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let campaign = &mut ctx.accounts.campaign;
+    let campaign_key = campaign.key();
+    let claimant = ctx.accounts.claimant.key();
+    require!(
+        verify_merkle_proof(&proof, campaign.merkle_root, airdrop_leaf(&campaign_key, &claimant, amount)),
+        ConsensusError::InvalidMerkleProof
+    );
+    
+    campaign.claimed_amount = campaign.claimed_amount
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    require!(
+        campaign.claimed_amount <= campaign.total_amount,
+        ConsensusError::PayoutExceedsTotal
+    );
+    
+    transfer_from_campaign(
+        &ctx.accounts.campaign,
+        ctx.accounts.campaign_token_account.to_account_info(),
+        ctx.accounts.claimant_token_account.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        amount,
+    )?;
+    
+    let claim = &mut ctx.accounts.airdrop_claim;
+    claim.campaign = campaign_key;
+    claim.claimant = claimant;
+    claim.amount = amount;
+    
+    emit_cpi!(AirdropClaimed {
+        campaign: campaign_key,
+        claimant,
+        amount,
+    });
+    
+    msg!("Airdrop claimed: {} tokens", amount);
+    
+    Ok(())
+}
+
+/// 到期后创建者收回活动 ATA 中的剩余代币
+pub fn reclaim_airdrop(ctx: Context<ReclaimAirdrop>) -> Result<()> {
+    let campaign = &ctx.accounts.campaign;
+    let amount = ctx.accounts.campaign_token_account.amount;
+    if amount > 0 {
+        transfer_from_campaign(
+            campaign,
+            ctx.accounts.campaign_token_account.to_account_info(),
+            ctx.accounts.creator_token_account.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            amount,
+        )?;
+    }
+    
+    let campaign = &mut ctx.accounts.campaign;
+    campaign.reclaimed = true;
+    
+    emit_cpi!(AirdropReclaimed {
+        campaign: campaign.key(),
+        creator: campaign.creator,
+        amount,
+    });
+    
+    msg!("Airdrop reclaimed: {} tokens", amount);
+    
+    Ok(())
+}
+
+/// 由活动 PDA 签名转出代币
+fn transfer_from_campaign<'info>(
+    campaign: &Account<'info, AirdropCampaign>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let campaign_id_bytes = campaign.campaign_id.to_le_bytes();
+    let seeds: &[&[u8]] = &[
+        b"airdrop",
+        campaign.theme.as_ref(),
+        campaign_id_bytes.as_ref(),
+        &[campaign.bump],
+    ];
+    
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program,
+            Transfer {
+                from,
+                to,
+                authority: campaign.to_account_info(),
+            },
+            &[seeds],
+        ),
+        amount,
+    )
+}
@@ -0,0 +1,219 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use taste_fun_shared::*;
+use crate::{FeesClaimed, Theme, ThemeVault, TradingConfiguration};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimCreatorFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump,
+        has_one = creator @ ConsensusError::Unauthorized
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    #[account(
+        mut,
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump
+    )]
+    pub vault: Account<'info, ThemeVault>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimPlatformFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    #[account(
+        mut,
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump
+    )]
+    pub vault: Account<'info, ThemeVault>,
+    
+    #[account(
+        seeds = [b"trading_config"],
+        bump,
+        has_one = authority @ ConsensusError::Unauthorized
+    )]
+    pub trading_config: Account<'info, TradingConfiguration>,
+    
+    pub authority: Signer<'info>,
+    
+    /// CHECK: 平台财库（接收平台费），由协议管理员指定
+    #[account(mut)]
+    pub platform_treasury: AccountInfo<'info>,
+}
+
+/// USDC 计价主题的费用提取：创建者费与平台费共用
+/// recipient 为创建者或协议管理员 (由 lib.rs 的 access_control 校验)
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimQuoteFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    #[account(
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump
+    )]
+    pub vault: Account<'info, ThemeVault>,
+    
+    #[account(address = theme.quote_mint @ ConsensusError::QuoteMintMismatch)]
+    pub quote_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_quote_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = quote_mint,
+    )]
+    pub recipient_quote_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"trading_config"],
+        bump
+    )]
+    pub trading_config: Account<'info, TradingConfiguration>,
+    
+    pub recipient: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+/// 创建者提取累计的创建者费
+pub fn claim_creator_fees(ctx: Context<ClaimCreatorFees>) -> Result<()> {
+    let amount = ctx.accounts.theme.creator_fees_accrued;
+    require!(amount > 0, ConsensusError::InvalidAmount);
+    
+    pay_from_vault(&ctx.accounts.vault.to_account_info(), &ctx.accounts.creator.to_account_info(), amount)?;
+    ctx.accounts.theme.creator_fees_accrued = 0;
+    
+    emit_cpi!(FeesClaimed {
+        theme: ctx.accounts.theme.key(),
+        recipient: ctx.accounts.creator.key(),
+        amount,
+        is_creator_fee: true,
+    });
+    
+    msg!("Creator fees claimed: {} lamports", amount);
+    
+    Ok(())
+}
+
+/// 协议管理员提取累计的平台费
+pub fn claim_platform_fees(ctx: Context<ClaimPlatformFees>) -> Result<()> {
+    let amount = ctx.accounts.theme.platform_fees_accrued;
+    require!(amount > 0, ConsensusError::InvalidAmount);
+    
+    pay_from_vault(&ctx.accounts.vault.to_account_info(), &ctx.accounts.platform_treasury, amount)?;
+    ctx.accounts.theme.platform_fees_accrued = 0;
+    
+    emit_cpi!(FeesClaimed {
+        theme: ctx.accounts.theme.key(),
+        recipient: ctx.accounts.platform_treasury.key(),
+        amount,
+        is_creator_fee: false,
+    });
+    
+    msg!("Platform fees claimed: {} lamports", amount);
+    
+    Ok(())
+}
+
+/// 创建者提取 USDC 计价主题累计的创建者费
+pub fn claim_creator_quote_fees(ctx: Context<ClaimQuoteFees>) -> Result<()> {
+    let amount = ctx.accounts.theme.creator_fees_accrued;
+    require!(amount > 0, ConsensusError::InvalidAmount);
+    
+    pay_quote_from_vault(&ctx, amount)?;
+    ctx.accounts.theme.creator_fees_accrued = 0;
+    
+    emit_cpi!(FeesClaimed {
+        theme: ctx.accounts.theme.key(),
+        recipient: ctx.accounts.recipient_quote_account.key(),
+        amount,
+        is_creator_fee: true,
+    });
+    
+    msg!("Creator fees claimed: {} quote units", amount);
+    
+    Ok(())
+}
+
+/// 协议管理员提取 USDC 计价主题累计的平台费
+pub fn claim_platform_quote_fees(ctx: Context<ClaimQuoteFees>) -> Result<()> {
+    let amount = ctx.accounts.theme.platform_fees_accrued;
+    require!(amount > 0, ConsensusError::InvalidAmount);
+    
+    pay_quote_from_vault(&ctx, amount)?;
+    ctx.accounts.theme.platform_fees_accrued = 0;
+    
+    emit_cpi!(FeesClaimed {
+        theme: ctx.accounts.theme.key(),
+        recipient: ctx.accounts.recipient_quote_account.key(),
+        amount,
+        is_creator_fee: false,
+    });
+    
+    msg!("Platform fees claimed: {} quote units", amount);
+    
+    Ok(())
+}
+
+/// 由金库 PDA 签名转出计价资产
+fn pay_quote_from_vault(ctx: &Context<ClaimQuoteFees>, amount: u64) -> Result<()> {
+    let theme = &ctx.accounts.theme;
+    let theme_id_bytes = theme.theme_id.to_le_bytes();
+    let bump_bytes = [theme.vault_bump];
+    let seeds: &[&[u8]] = &[
+        b"theme_vault",
+        theme.creator.as_ref(),
+        theme_id_bytes.as_ref(),
+        bump_bytes.as_ref(),
+    ];
+    
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_quote_account.to_account_info(),
+                to: ctx.accounts.recipient_quote_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            &[seeds],
+        ),
+        amount,
+    )
+}
+
+/// 从金库（程序所有）直接划转 lamports
+fn pay_from_vault(vault: &AccountInfo, recipient: &AccountInfo, amount: u64) -> Result<()> {
+    **vault.try_borrow_mut_lamports()? = vault.lamports()
+        .checked_sub(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    **recipient.try_borrow_mut_lamports()? = recipient.lamports()
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{burn, Burn, Mint, Token, TokenAccount};
+use taste_fun_shared::*;
+use crate::{CommunityBurned, Theme};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CommunityBurn<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    /// Theme token mint
+    #[account(
+        mut,
+        address = theme.token_mint @ ConsensusError::InvalidMint
+    )]
+    pub token_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = holder,
+    )]
+    pub holder_token_account: Account<'info, TokenAccount>,
+    
+    pub holder: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+/// 社区销毁：持有者通过程序销毁自己的主题代币，并计入主题累计销毁量
+pub fn community_burn(ctx: Context<CommunityBurn>, amount: u64) -> Result<()> {
+    burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.token_mint.to_account_info(),
+                from: ctx.accounts.holder_token_account.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    
+    let theme = &mut ctx.accounts.theme;
+    theme.circulating_supply = theme.circulating_supply
+        .checked_sub(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    theme.community_burned = theme.community_burned
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    
+    emit_cpi!(CommunityBurned {
+        theme: theme.key(),
+        holder: ctx.accounts.holder.key(),
+        amount,
+        total_community_burned: theme.community_burned,
+    });
+    
+    msg!("Community burn: {} tokens, cumulative {}", amount, theme.community_burned);
+    
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::associated_token::AssociatedToken;
+use taste_fun_shared::*;
+use crate::{CreatorReserveBurned, CreatorVesting, Theme, VestedTokensClaimed};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimVestedTokens<'info> {
+    #[account(
+        mut,
+        seeds = [b"creator_vesting", creator_vesting.theme.as_ref()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Account<'info, CreatorVesting>,
+    
+    pub token_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = creator_vesting,
+    )]
+    pub vesting_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = token_mint,
+        associated_token::authority = creator,
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 领取截至当前已释放但未领取的储备代币
+pub fn claim_vested_tokens(ctx: Context<ClaimVestedTokens>) -> Result<()> {
+    let vesting = &ctx.accounts.creator_vesting;
+    let vested = vesting.vested_amount(Clock::get()?.unix_timestamp);
+    // 销毁储备会按比例降低已释放量，可能暂时低于已领取量
+    let amount = vested.saturating_sub(vesting.claimed_amount);
+    require!(amount > 0, ConsensusError::NothingVested);
+    
+    let theme_key = vesting.theme;
+    let seeds: &[&[u8]] = &[
+        b"creator_vesting",
+        theme_key.as_ref(),
+        &[vesting.bump],
+    ];
+    
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vesting_token_account.to_account_info(),
+                to: ctx.accounts.creator_token_account.to_account_info(),
+                authority: ctx.accounts.creator_vesting.to_account_info(),
+            },
+            &[seeds],
+        ),
+        amount,
+    )?;
+    
+    let vesting = &mut ctx.accounts.creator_vesting;
+    vesting.claimed_amount = vesting.claimed_amount
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    
+    emit_cpi!(VestedTokensClaimed {
+        theme: theme_key,
+        creator: vesting.creator,
+        amount,
+        total_claimed: vesting.claimed_amount,
+    });
+    
+    msg!("Claimed {} vested tokens ({} / {})", amount, vesting.claimed_amount, vesting.total_amount);
+    
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BurnCreatorReserve<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump,
+        has_one = creator @ ConsensusError::Unauthorized
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    #[account(
+        mut,
+        seeds = [b"creator_vesting", theme.key().as_ref()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Account<'info, CreatorVesting>,
+    
+    #[account(
+        mut,
+        address = theme.token_mint @ ConsensusError::InvalidMint
+    )]
+    pub token_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = creator_vesting,
+    )]
+    pub vesting_token_account: Account<'info, TokenAccount>,
+    
+    pub creator: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+/// 创建者放弃部分尚未领取的储备：直接从锁仓账户销毁，后续释放量按剩余总量计算
+pub fn burn_creator_reserve(ctx: Context<BurnCreatorReserve>, amount: u64) -> Result<()> {
+    let vesting = &ctx.accounts.creator_vesting;
+    let theme_key = ctx.accounts.theme.key();
+    let seeds: &[&[u8]] = &[
+        b"creator_vesting",
+        theme_key.as_ref(),
+        &[vesting.bump],
+    ];
+    
+    token::burn(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.token_mint.to_account_info(),
+                from: ctx.accounts.vesting_token_account.to_account_info(),
+                authority: ctx.accounts.creator_vesting.to_account_info(),
+            },
+            &[seeds],
+        ),
+        amount,
+    )?;
+    ctx.accounts.token_mint.reload()?;
+    
+    let vesting = &mut ctx.accounts.creator_vesting;
+    vesting.total_amount -= amount;
+    
+    let theme = &mut ctx.accounts.theme;
+    theme.creator_reserve = theme.creator_reserve.saturating_sub(amount);
+    
+    emit_cpi!(CreatorReserveBurned {
+        theme: theme_key,
+        creator: ctx.accounts.creator.key(),
+        amount,
+        remaining_reserve: vesting.total_amount,
+        new_supply: ctx.accounts.token_mint.supply,
+    });
+    
+    msg!("Creator reserve burned: {} tokens, remaining {}", amount, vesting.total_amount);
+    
+    Ok(())
+}
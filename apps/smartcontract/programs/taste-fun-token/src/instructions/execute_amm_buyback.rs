@@ -0,0 +1,287 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::{self, spl_token, Burn, Mint, SyncNative, Token, TokenAccount};
+use taste_fun_shared::*;
+use crate::{BuybackExecuted, Theme, ThemeVault};
+
+// 调用方按顺序在 remaining_accounts 中传入的池子账户数量
+const RAYDIUM_SWAP_POOL_ACCOUNTS: usize = 6;
+const METEORA_SWAP_POOL_ACCOUNTS: usize = 11;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteAmmBuyback<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Box<Account<'info, Theme>>,
+    
+    /// 主题金库（回购池 SOL 存放处）
+    #[account(
+        mut,
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump
+    )]
+    pub vault: Box<Account<'info, ThemeVault>>,
+    
+    #[account(mut, address = theme.token_mint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+    
+    #[account(address = spl_token::native_mint::ID)]
+    pub wsol_mint: Box<Account<'info, Mint>>,
+    
+    /// CHECK: 迁移权限 PDA，在 AMM 中代表主题执行回购
+    #[account(
+        seeds = [b"migration_authority", theme.key().as_ref()],
+        bump
+    )]
+    pub migration_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = migration_authority,
+    )]
+    pub authority_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = wsol_mint,
+        associated_token::authority = migration_authority,
+    )]
+    pub authority_wsol_account: Box<Account<'info, TokenAccount>>,
+    
+    /// CHECK: core 程序的全局协议配置，由 protocol_config_active 校验
+    pub protocol_config: UncheckedAccount<'info>,
+    
+    /// CHECK: 毕业池所在的 AMM 程序，按主题迁移目标校验
+    pub amm_program: UncheckedAccount<'info>,
+    
+    /// CHECK: Anyone can trigger buyback
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+/// 毕业后的回购销毁：回购池 SOL 在毕业池中买入代币并销毁
+/// remaining_accounts 为池子相关账户（顺序见 build_*_swap），池子地址必须与迁移时记录的一致
+pub fn execute_amm_buyback<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteAmmBuyback<'info>>,
+    min_tokens_out: u64,
+) -> Result<()> {
+    let theme = &ctx.accounts.theme;
+    require!(
+        theme.buyback_pool >= BUYBACK_THRESHOLD,
+        ConsensusError::InvalidAmount
+    );
+    
+    let sol_to_spend = theme.buyback_pool;
+    
+    // 1. 回购池 SOL 从金库包装为迁移权限的 wSOL
+    let vault_info = ctx.accounts.vault.to_account_info();
+    **vault_info.try_borrow_mut_lamports()? = vault_info.lamports()
+        .checked_sub(sol_to_spend)
+        .ok_or(ConsensusError::Overflow)?;
+    let wsol_info = ctx.accounts.authority_wsol_account.to_account_info();
+    **wsol_info.try_borrow_mut_lamports()? = wsol_info.lamports()
+        .checked_add(sol_to_spend)
+        .ok_or(ConsensusError::Overflow)?;
+    token::sync_native(CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        SyncNative {
+            account: ctx.accounts.authority_wsol_account.to_account_info(),
+        },
+    ))?;
+    
+    // 2. 在毕业池中用 wSOL 买入代币
+    let theme_key = theme.key();
+    let authority_seeds: &[&[u8]] = &[
+        b"migration_authority",
+        theme_key.as_ref(),
+        &[ctx.bumps.migration_authority],
+    ];
+    let tokens_before = ctx.accounts.authority_token_account.amount;
+    let (ix, infos) = match theme.migration_target {
+        MIGRATION_TARGET_RAYDIUM => build_raydium_swap(&ctx, sol_to_spend, min_tokens_out)?,
+        MIGRATION_TARGET_METEORA => build_meteora_swap(&ctx, sol_to_spend, min_tokens_out)?,
+        _ => return err!(ConsensusError::InvalidMigrationTarget),
+    };
+    invoke_signed(&ix, &infos, &[authority_seeds])?;
+    
+    ctx.accounts.authority_token_account.reload()?;
+    let tokens_bought = ctx.accounts.authority_token_account.amount
+        .checked_sub(tokens_before)
+        .ok_or(ConsensusError::Overflow)?;
+    require!(
+        tokens_bought >= min_tokens_out,
+        ConsensusError::SlippageExceeded
+    );
+    
+    // 3. 销毁买入的代币
+    token::burn(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.token_mint.to_account_info(),
+                from: ctx.accounts.authority_token_account.to_account_info(),
+                authority: ctx.accounts.migration_authority.to_account_info(),
+            },
+            &[authority_seeds],
+        ),
+        tokens_bought,
+    )?;
+    
+    let theme = &mut ctx.accounts.theme;
+    theme.circulating_supply = theme.circulating_supply
+        .checked_sub(tokens_bought)
+        .ok_or(ConsensusError::Overflow)?;
+    theme.buyback_pool = 0;
+    
+    emit_cpi!(BuybackExecuted {
+        theme: theme_key,
+        sol_spent: sol_to_spend,
+        tokens_burned: tokens_bought,
+        new_token_reserves: theme.token_reserves,
+        keeper: ctx.accounts.authority.key(),
+        keeper_reward: 0,
+        policy: BUYBACK_POLICY_BURN,
+    });
+    
+    msg!("AMM buyback completed: {} SOL spent, {} tokens burned", sol_to_spend, tokens_bought);
+    
+    Ok(())
+}
+
+/// Raydium CPMM swap_base_input
+/// remaining_accounts: [authority, amm_config, pool_state, input_vault, output_vault, observation_state]
+fn build_raydium_swap<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, ExecuteAmmBuyback<'info>>,
+    amount_in: u64,
+    min_tokens_out: u64,
+) -> Result<(Instruction, Vec<AccountInfo<'info>>)> {
+    let a = &ctx.accounts;
+    let pool = ctx.remaining_accounts;
+    require!(
+        a.amm_program.key() == RAYDIUM_CPMM_PROGRAM_ID,
+        ConsensusError::InvalidMigrationTarget
+    );
+    require!(
+        pool.len() == RAYDIUM_SWAP_POOL_ACCOUNTS,
+        ConsensusError::InvalidAmmPool
+    );
+    require!(
+        pool[2].key() == a.theme.amm_pool,
+        ConsensusError::InvalidAmmPool
+    );
+    
+    let mut data = Vec::with_capacity(8 + 16);
+    data.extend_from_slice(&RAYDIUM_CPMM_SWAP_BASE_INPUT_DISCRIMINATOR);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&min_tokens_out.to_le_bytes());
+    
+    let infos = vec![
+        a.migration_authority.to_account_info(),
+        pool[0].clone(),
+        pool[1].clone(),
+        pool[2].clone(),
+        a.authority_wsol_account.to_account_info(),
+        a.authority_token_account.to_account_info(),
+        pool[3].clone(),
+        pool[4].clone(),
+        a.token_program.to_account_info(),
+        a.token_program.to_account_info(),
+        a.wsol_mint.to_account_info(),
+        a.token_mint.to_account_info(),
+        pool[5].clone(),
+    ];
+    let ix = Instruction {
+        program_id: RAYDIUM_CPMM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(infos[0].key(), true),
+            AccountMeta::new_readonly(infos[1].key(), false),
+            AccountMeta::new_readonly(infos[2].key(), false),
+            AccountMeta::new(infos[3].key(), false),
+            AccountMeta::new(infos[4].key(), false),
+            AccountMeta::new(infos[5].key(), false),
+            AccountMeta::new(infos[6].key(), false),
+            AccountMeta::new(infos[7].key(), false),
+            AccountMeta::new_readonly(infos[8].key(), false),
+            AccountMeta::new_readonly(infos[9].key(), false),
+            AccountMeta::new_readonly(infos[10].key(), false),
+            AccountMeta::new_readonly(infos[11].key(), false),
+            AccountMeta::new(infos[12].key(), false),
+        ],
+        data,
+    };
+    
+    let mut infos = infos;
+    infos.push(a.amm_program.to_account_info());
+    Ok((ix, infos))
+}
+
+/// Meteora DAMM swap
+/// remaining_accounts: [pool, a_vault, b_vault, a_token_vault, b_token_vault, a_vault_lp_mint,
+///                      b_vault_lp_mint, a_vault_lp, b_vault_lp, protocol_token_fee, vault_program]
+fn build_meteora_swap<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, ExecuteAmmBuyback<'info>>,
+    amount_in: u64,
+    min_tokens_out: u64,
+) -> Result<(Instruction, Vec<AccountInfo<'info>>)> {
+    let a = &ctx.accounts;
+    let pool = ctx.remaining_accounts;
+    require!(
+        a.amm_program.key() == METEORA_DAMM_PROGRAM_ID,
+        ConsensusError::InvalidMigrationTarget
+    );
+    require!(
+        pool.len() == METEORA_SWAP_POOL_ACCOUNTS,
+        ConsensusError::InvalidAmmPool
+    );
+    require!(
+        pool[0].key() == a.theme.amm_pool,
+        ConsensusError::InvalidAmmPool
+    );
+    
+    let mut data = Vec::with_capacity(8 + 16);
+    data.extend_from_slice(&METEORA_SWAP_DISCRIMINATOR);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&min_tokens_out.to_le_bytes());
+    
+    let infos = vec![
+        pool[0].clone(),
+        a.authority_wsol_account.to_account_info(),
+        a.authority_token_account.to_account_info(),
+        pool[1].clone(),
+        pool[2].clone(),
+        pool[3].clone(),
+        pool[4].clone(),
+        pool[5].clone(),
+        pool[6].clone(),
+        pool[7].clone(),
+        pool[8].clone(),
+        pool[9].clone(),
+        a.migration_authority.to_account_info(),
+        pool[10].clone(),
+        a.token_program.to_account_info(),
+    ];
+    let mut metas: Vec<AccountMeta> = infos[..12]
+        .iter()
+        .map(|info| AccountMeta::new(info.key(), false))
+        .collect();
+    metas.push(AccountMeta::new_readonly(infos[12].key(), true));
+    metas.push(AccountMeta::new_readonly(infos[13].key(), false));
+    metas.push(AccountMeta::new_readonly(infos[14].key(), false));
+    
+    let ix = Instruction {
+        program_id: METEORA_DAMM_PROGRAM_ID,
+        accounts: metas,
+        data,
+    };
+    
+    let mut infos = infos;
+    infos.push(a.amm_program.to_account_info());
+    Ok((ix, infos))
+}
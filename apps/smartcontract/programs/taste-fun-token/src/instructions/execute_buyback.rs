@@ -1,10 +1,11 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::System;
+use anchor_lang::system_program::{self, System};
 use anchor_spl::token::{burn, Mint, Token, TokenAccount, Burn};
 use anchor_spl::associated_token::AssociatedToken;
 use taste_fun_shared::*;
-use crate::{Theme, ThemeVault, TradingConfiguration, BuybackExecuted};
+use crate::{emit_reserve_health, Theme, ThemeVault, TradingConfiguration, BuybackExecuted, BuybackFunded};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ExecuteBuyback<'info> {
     #[account(
@@ -38,11 +39,19 @@ pub struct ExecuteBuyback<'info> {
     )]
     pub trading_config: Account<'info, TradingConfiguration>,
     
-    /// CHECK: Anyone can trigger buyback
-    pub authority: Signer<'info>,
+    /// CHECK: core 程序的全局协议配置，由 protocol_config_active 校验
+    pub protocol_config: UncheckedAccount<'info>,
     
-    /// CHECK: Vault SOL account（包含回购资金）
+    /// CHECK: Anyone can trigger buyback，SOL 计价主题的调用者领取执行奖励
     #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Vault SOL account（即主题金库本身，包含回购资金）
+    #[account(
+        mut,
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump
+    )]
     pub vault_sol_account: AccountInfo<'info>,
     
     pub system_program: Program<'info, System>,
@@ -50,8 +59,82 @@ pub struct ExecuteBuyback<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
-/// 执行回购销毁机制
-/// 基于文档建议：从联合曲线回购代币并销毁
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetBuybackPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    pub creator: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FundBuybackPool<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    #[account(
+        mut,
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump
+    )]
+    pub vault: Account<'info, ThemeVault>,
+    
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// 创建者设置回购策略，对之后的回购生效
+pub fn set_buyback_policy(ctx: Context<SetBuybackPolicy>, buyback_policy: u8) -> Result<()> {    ctx.accounts.theme.buyback_policy = buyback_policy;
+    
+    msg!("Buyback policy set to {}", buyback_policy);
+    
+    Ok(())
+}
+
+/// 外部收入 (如获胜图片版本销售) 直接注入回购池，不参与质押分成
+pub fn fund_buyback_pool(ctx: Context<FundBuybackPool>, amount: u64) -> Result<()> {
+    require!(amount > 0, ConsensusError::InvalidAmount);
+    
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.funder.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    
+    let theme = &mut ctx.accounts.theme;
+    theme.buyback_pool = theme.buyback_pool
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    
+    emit_cpi!(BuybackFunded {
+        theme: theme.key(),
+        funder: ctx.accounts.funder.key(),
+        amount,
+        buyback_pool: theme.buyback_pool,
+    });
+    
+    Ok(())
+}
+
+/// 执行回购机制
+/// 基于文档建议：从联合曲线回购代币并销毁；流动性策略下回购资金留在曲线中
 #[inline(never)]
 pub fn execute_buyback(ctx: Context<ExecuteBuyback>) -> Result<()> {
     let theme = &mut ctx.accounts.theme;
@@ -61,58 +144,95 @@ pub fn execute_buyback(ctx: Context<ExecuteBuyback>) -> Result<()> {
     // 验证基本条件
     validate_buyback_conditions(theme, &ctx.accounts.token_mint)?;
     
-    let sol_to_spend = theme.buyback_pool;
-    msg!("Buyback pool balance: {} lamports", sol_to_spend);
-    
-    // 计算可回购的代币数量（使用联合曲线公式）
-    let tokens_to_buy = calculate_buyback_tokens(
-        sol_to_spend,
-        theme.token_reserves,
-        theme.sol_reserves,
-    )?;
-    
-    msg!("Tokens to buy back and burn: {}", tokens_to_buy);
-    
-    require!(
-        tokens_to_buy <= theme.token_reserves,
-        ConsensusError::InsufficientReserves
-    );
-    require!(
-        tokens_to_buy <= ctx.accounts.vault_token_account.amount,
-        ConsensusError::InsufficientReserves
-    );
-    
-    // 执行回购交易：SOL已经在vault中，更新储备状态
-    update_reserves_after_buyback(theme, sol_to_spend, tokens_to_buy)?;
-    
-    // 销毁回购的代币
-    burn_bought_tokens(
-        &ctx.accounts.token_mint,
-        &ctx.accounts.vault_token_account,
-        &ctx.accounts.vault,
-        &ctx.accounts.token_program,
-        tokens_to_buy,
-        theme.vault_bump,
-        &theme.creator,
-        theme.theme_id,
-    )?;
+    let buyback_pool = theme.buyback_pool;
+    msg!("Buyback pool balance: {} lamports", buyback_pool);
     
-    // 更新总供应量
-    theme.circulating_supply = theme.circulating_supply
-        .checked_sub(tokens_to_buy)
+    // 执行奖励从回购池中切出，激励机器人在达到阈值后及时触发
+    let keeper_reward = if theme.is_sol_quoted() {
+        calculate_keeper_reward(buyback_pool)?
+    } else {
+        0
+    };
+    let sol_to_spend = buyback_pool
+        .checked_sub(keeper_reward)
         .ok_or(ConsensusError::Overflow)?;
     
+    let tokens_to_buy = if theme.buyback_policy == BUYBACK_POLICY_LIQUIDITY {
+        // 买入的代币随即回到储备，等价于只向曲线注入 SOL，形成协议自有流动性
+        theme.sol_reserves = theme.sol_reserves
+            .checked_add(sol_to_spend)
+            .ok_or(ConsensusError::Overflow)?;
+        
+        msg!("Buyback added {} lamports of liquidity to the curve", sol_to_spend);
+        0
+    } else {
+        // 计算可回购的代币数量（使用联合曲线公式，回购无手续费）
+        let tokens_to_buy = theme.buy_tokens(sol_to_spend, 0)?;
+        
+        msg!("Tokens to buy back and burn: {}", tokens_to_buy);
+        
+        require!(
+            tokens_to_buy <= theme.token_reserves,
+            ConsensusError::InsufficientReserves
+        );
+        require!(
+            tokens_to_buy <= ctx.accounts.vault_token_account.amount,
+            ConsensusError::InsufficientReserves
+        );
+        
+        // 执行回购交易：SOL已经在vault中，更新储备状态
+        update_reserves_after_buyback(theme, sol_to_spend, tokens_to_buy)?;
+        
+        // 销毁回购的代币
+        burn_bought_tokens(
+            &ctx.accounts.token_mint,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.vault,
+            &ctx.accounts.token_program,
+            tokens_to_buy,
+            theme.vault_bump,
+            &theme.creator,
+            theme.theme_id,
+        )?;
+        ctx.accounts.vault_token_account.reload()?;
+        
+        // 更新总供应量
+        theme.circulating_supply = theme.circulating_supply
+            .checked_sub(tokens_to_buy)
+            .ok_or(ConsensusError::Overflow)?;
+        tokens_to_buy
+    };
+    
     // 重置回购池
     theme.buyback_pool = 0;
     
-    emit!(BuybackExecuted {
+    if keeper_reward > 0 {
+        pay_keeper_reward(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.authority.to_account_info(),
+            keeper_reward,
+        )?;
+    }
+    
+    emit_cpi!(BuybackExecuted {
         theme: theme.key(),
         sol_spent: sol_to_spend,
         tokens_burned: tokens_to_buy,
         new_token_reserves: theme.token_reserves,
+        keeper: ctx.accounts.authority.key(),
+        keeper_reward,
+        policy: theme.buyback_policy,
     });
     
-    msg!("Buyback completed: {} SOL spent, {} tokens burned", sol_to_spend, tokens_to_buy);
+    emit_reserve_health(
+        &event_emitter!(ctx),
+        theme.key(),
+        theme,
+        ctx.accounts.vault_sol_account.lamports(),
+        ctx.accounts.vault_token_account.amount,
+    )?;
+    
+    msg!("Buyback completed: {} SOL spent, {} tokens burned, keeper reward {}", sol_to_spend, tokens_to_buy, keeper_reward);
     msg!("New reserves - SOL: {}, Tokens: {}", theme.sol_reserves, theme.token_reserves);
     msg!("=== ExecuteBuyback COMPLETE ===");
     
@@ -128,31 +248,40 @@ fn validate_buyback_conditions(theme: &Theme, token_mint: &Account<Mint>) -> Res
     );
     
     require!(
-        theme.status == THEME_STATUS_ACTIVE,
-        ConsensusError::InvalidTheme
-    );
-    
-    require!(
-        theme.buyback_pool >= BUYBACK_THRESHOLD,
+        theme.buyback_pool >= theme.buyback_threshold(),
         ConsensusError::InvalidAmount
     );
     
     Ok(())
 }
 
-/// 计算回购代币数量（无手续费）
-#[inline(always)]
-fn calculate_buyback_tokens(
-    sol_amount: u64,
-    token_reserves: u64,
-    sol_reserves: u64,
-) -> Result<u64> {
-    calculate_buy_tokens(
-        sol_amount,
-        token_reserves,
-        sol_reserves,
-        0, // 回购无手续费
-    )
+/// 计算执行奖励：回购池的固定比例，不超过上限
+#[inline(never)]
+fn calculate_keeper_reward(buyback_pool: u64) -> Result<u64> {
+    let reward = (buyback_pool as u128)
+        .checked_mul(BUYBACK_KEEPER_REWARD_BPS as u128)
+        .ok_or(ConsensusError::Overflow)?
+        .checked_div(BPS_DENOMINATOR as u128)
+        .ok_or(ConsensusError::DivisionByZero)? as u64;
+    
+    Ok(reward.min(MAX_BUYBACK_KEEPER_REWARD))
+}
+
+/// 从金库 PDA 向调用者支付执行奖励
+#[inline(never)]
+fn pay_keeper_reward<'info>(
+    vault: &AccountInfo<'info>,
+    keeper: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    **vault.try_borrow_mut_lamports()? = vault.lamports()
+        .checked_sub(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    **keeper.try_borrow_mut_lamports()? = keeper.lamports()
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    
+    Ok(())
 }
 
 /// 更新储备状态
@@ -211,3 +340,168 @@ fn burn_bought_tokens<'info>(
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::error::ErrorOrigin;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::associated_token::get_associated_token_address;
+    use anchor_spl::token::spl_token;
+    use std::collections::BTreeSet;
+
+    /// 测试账户的持有数据，AccountInfo 借用其中的 lamports 与 data
+    struct TestAccount {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+        is_signer: bool,
+        executable: bool,
+    }
+
+    impl TestAccount {
+        fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+            Self { key, owner, lamports: 1_000_000_000, data, is_signer: false, executable: false }
+        }
+
+        fn program(key: Pubkey) -> Self {
+            Self { executable: true, ..Self::new(key, Pubkey::default(), Vec::new()) }
+        }
+
+        fn info(&mut self) -> AccountInfo<'_> {
+            AccountInfo::new(
+                &self.key,
+                self.is_signer,
+                true,
+                &mut self.lamports,
+                &mut self.data,
+                &self.owner,
+                self.executable,
+                0,
+            )
+        }
+    }
+
+    /// 全零字节反序列化出的账户，由调用方填入需要校验的字段
+    fn zeroed<T: AnchorDeserialize>() -> T {
+        T::deserialize(&mut &[0u8; 4096][..]).unwrap()
+    }
+
+    fn account_data<T: AccountSerialize>(account: &T) -> Vec<u8> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    /// 按 ExecuteBuyback 的账户顺序构造一组合法账户，vault_sol_account 由 sol_vault 指定
+    fn buyback_accounts(sol_vault: impl Fn(&Pubkey) -> Pubkey) -> Vec<TestAccount> {
+        let creator = Pubkey::new_unique();
+        let theme_id = 7u64;
+        let (theme_key, theme_bump) = Pubkey::find_program_address(
+            &[b"theme", creator.as_ref(), theme_id.to_le_bytes().as_ref()],
+            &crate::ID,
+        );
+        let (vault_key, vault_bump) = Pubkey::find_program_address(
+            &[b"theme_vault", creator.as_ref(), theme_id.to_le_bytes().as_ref()],
+            &crate::ID,
+        );
+        let (trading_config_key, _) = Pubkey::find_program_address(&[b"trading_config"], &crate::ID);
+        let (event_authority_key, _) =
+            Pubkey::find_program_address(&[b"__event_authority"], &crate::ID);
+        let mint_key = Pubkey::new_unique();
+
+        let mut theme: Theme = zeroed();
+        theme.creator = creator;
+        theme.theme_id = theme_id;
+        theme.token_mint = mint_key;
+        theme.theme_bump = theme_bump;
+        theme.vault_bump = vault_bump;
+
+        let mut vault: ThemeVault = zeroed();
+        vault.theme = theme_key;
+        vault.bump = vault_bump;
+
+        let mut mint = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut mint);
+
+        let mut vault_tokens = vec![0u8; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint: mint_key,
+            owner: vault_key,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut vault_tokens);
+
+        let trading_config: TradingConfiguration = zeroed();
+
+        vec![
+            TestAccount::new(theme_key, crate::ID, account_data(&theme)),
+            TestAccount::new(vault_key, crate::ID, account_data(&vault)),
+            TestAccount::new(mint_key, spl_token::ID, mint),
+            TestAccount::new(
+                get_associated_token_address(&vault_key, &mint_key),
+                spl_token::ID,
+                vault_tokens,
+            ),
+            TestAccount::new(trading_config_key, crate::ID, account_data(&trading_config)),
+            TestAccount::new(Pubkey::new_unique(), Pubkey::new_unique(), Vec::new()),
+            TestAccount {
+                is_signer: true,
+                ..TestAccount::new(Pubkey::new_unique(), system_program::ID, Vec::new())
+            },
+            TestAccount::new(sol_vault(&vault_key), crate::ID, Vec::new()),
+            TestAccount::program(system_program::ID),
+            TestAccount::program(spl_token::ID),
+            TestAccount::program(anchor_spl::associated_token::ID),
+            TestAccount::new(event_authority_key, Pubkey::default(), Vec::new()),
+            TestAccount::program(crate::ID),
+        ]
+    }
+
+    fn try_buyback_accounts(accounts: &mut [TestAccount]) -> Result<()> {
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(TestAccount::info).collect();
+        ExecuteBuyback::try_accounts(
+            &crate::ID,
+            &mut &infos[..],
+            &[],
+            &mut ExecuteBuybackBumps::default(),
+            &mut BTreeSet::new(),
+        )
+        .map(|_| ())
+    }
+
+    #[test]
+    fn accepts_theme_vault_as_sol_account() {
+        let mut accounts = buyback_accounts(|vault| *vault);
+        try_buyback_accounts(&mut accounts).unwrap();
+    }
+
+    #[test]
+    fn rejects_foreign_vault_sol_account() {
+        // 回购与储备健康度读取的是 vault_sol_account 的 lamports，换成他人的金库必须被拒绝
+        let mut accounts = buyback_accounts(|_| {
+            Pubkey::find_program_address(
+                &[b"theme_vault", Pubkey::new_unique().as_ref(), 7u64.to_le_bytes().as_ref()],
+                &crate::ID,
+            )
+            .0
+        });
+        let err = try_buyback_accounts(&mut accounts).unwrap_err();
+
+        assert_eq!(err, ErrorCode::ConstraintSeeds.into());
+        match err {
+            Error::AnchorError(e) => assert!(matches!(
+                e.error_origin,
+                Some(ErrorOrigin::AccountName(ref name)) if name == "vault_sol_account"
+            )),
+            Error::ProgramError(e) => panic!("unexpected program error: {e}"),
+        }
+    }
+}
@@ -2,9 +2,23 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::Token;
 use anchor_spl::token_interface::Mint;
 use taste_fun_shared::*;
-use crate::{Theme, ThemeVault, ThemeCreated};
+use crate::{Theme, ThemeRegistry, ThemeRegistryEntry, ThemeRegistryPage, ThemeVault, ThemeCreated};
+
+/// 创建主题时一次性设定的规则参数
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ThemeParams {
+    pub payout_curve: u8,                    // 惩罚池分配曲线 (PAYOUT_CURVE_*)
+    pub payout_exponent: u8,                 // 分配曲线指数
+    pub tie_break_rule: u8,                  // 平局处理规则 (TIE_BREAK_*)
+    pub dispute_period: i64,                 // 结算争议期 (秒)
+    pub reject_all_threshold_bps: u16,       // RejectAll 否决阈值
+    pub penalty_burn_bps: u16,               // 惩罚池销毁比例
+    pub curve_phases: Vec<CurvePhaseParams>, // 分段曲线 (为空则使用默认单段曲线)
+    pub quote_mint: Option<Pubkey>,          // 计价资产，None 为 SOL
+}
 
 /// Context for initializing a new theme - Step 1: Create theme account
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(theme_id: u64)]
 pub struct InitializeThemeStep1<'info> {
@@ -24,6 +38,7 @@ pub struct InitializeThemeStep1<'info> {
 }
 
 /// Context for initializing a new theme - Step 2: Create vault and mint
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(theme_id: u64)]
 pub struct InitializeThemeStep2<'info> {
@@ -61,6 +76,7 @@ pub struct InitializeThemeStep2<'info> {
 }
 
 /// For backward compatibility, keep the original struct but simplified
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(theme_id: u64)]
 pub struct InitializeTheme<'info> {
@@ -73,6 +89,29 @@ pub struct InitializeTheme<'info> {
     )]
     pub theme: Account<'info, Theme>,
 
+    /// 全局主题注册表 (首个主题创建时初始化)
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + ThemeRegistry::SPACE,
+        seeds = [b"theme_registry"],
+        bump
+    )]
+    pub theme_registry: Box<Account<'info, ThemeRegistry>>,
+
+    /// 当前写入的注册表分页 (页满时由下一个主题创建新页)
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + ThemeRegistryPage::SPACE,
+        seeds = [
+            b"theme_registry_page",
+            ((theme_registry.total_themes / THEME_REGISTRY_PAGE_SIZE as u64) as u32).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub registry_page: Box<Account<'info, ThemeRegistryPage>>,
+
     #[account(mut)]
     pub creator: Signer<'info>,
 
@@ -89,6 +128,39 @@ fn voting_mode_to_u8(voting_mode: VotingMode) -> u8 {
     }
 }
 
+/// 主题结算规则：平局规则、争议期、全拒阈值与罚没销毁比例
+pub(crate) fn valid_theme_rules(
+    tie_break_rule: u8,
+    dispute_period: i64,
+    reject_all_threshold_bps: u16,
+    penalty_burn_bps: u16,
+) -> Result<()> {
+    require!(tie_break_rule <= TIE_BREAK_RANDOM, ConsensusError::InvalidTieBreakRule);
+    require!(
+        (0..=MAX_DISPUTE_PERIOD).contains(&dispute_period),
+        ConsensusError::InvalidDisputePeriod
+    );
+    require!(
+        (MIN_REJECT_ALL_THRESHOLD_BPS..=MAX_REJECT_ALL_THRESHOLD_BPS).contains(&reject_all_threshold_bps),
+        ConsensusError::InvalidRejectThreshold
+    );
+    require!(
+        penalty_burn_bps <= MAX_PENALTY_BURN_BPS,
+        ConsensusError::InvalidPenaltyBurnBps
+    );
+    Ok(())
+}
+
+/// 计价资产为 SOL (None) 或 USDC
+pub(crate) fn supported_quote_mint(quote_mint: Option<Pubkey>) -> Result<()> {
+    let quote_mint = quote_mint.unwrap_or_default();
+    require!(
+        quote_mint == Pubkey::default() || quote_mint == USDC_MINT,
+        ConsensusError::UnsupportedQuoteMint
+    );
+    Ok(())
+}
+
 /// Helper function to initialize theme data
 #[inline(never)]
 fn init_theme_data(
@@ -115,6 +187,36 @@ fn init_theme_data(
     theme.status = THEME_STATUS_ACTIVE;
     theme.vault_bump = vault_bump;
     theme.theme_bump = theme_bump;
+    theme.community_burned = 0;
+    theme.payout_curve = DEFAULT_PAYOUT_CURVE;
+    theme.payout_exponent = 1;
+    theme.tie_break_rule = TIE_BREAK_CANCEL;
+    theme.dispute_period = 0;
+    theme.reject_all_threshold_bps = REJECT_ALL_THRESHOLD_BPS;
+    theme.penalty_burn_bps = 0;
+    theme.virtual_sol_reserves = VIRTUAL_SOL_RESERVES;
+    theme.virtual_token_reserves = VIRTUAL_TOKEN_RESERVES;
+    theme.migration_target = MIGRATION_TARGET_RAYDIUM;
+    theme.amm_pool = Pubkey::default();
+    theme.min_stake_tokens = 0;
+    theme.curve_phases = [CurvePhase::default(); MAX_CURVE_PHASES];
+    theme.curve_phase_count = 0;
+    theme.creator_fees_accrued = 0;
+    theme.platform_fees_accrued = 0;
+    theme.launch_slot = 0;
+    theme.launch_fee_bps = 0;
+    theme.fee_decay_slots = 0;
+    theme.swap_cooldown_secs = 0;
+    theme.volume_slot = 0;
+    theme.slot_buy_volume = 0;
+    theme.slot_sell_volume = 0;
+    theme.metadata_updated_at = 0;
+    theme.retire_eta = 0;
+    theme.quote_mint = Pubkey::default();
+    theme.stake_fee_share_bps = 0;
+    theme.staking_rewards_accrued = 0;
+    theme.buyback_policy = BUYBACK_POLICY_BURN;
+    theme.version = THEME_ACCOUNT_VERSION;
 }
 
 /// Helper function to initialize vault data
@@ -127,40 +229,98 @@ fn init_vault_data(vault: &mut ThemeVault, theme_key: Pubkey, vault_bump: u8) {
 /// Helper function to emit theme created event
 #[inline(never)]
 fn emit_theme_created_event(
+    emitter: &EventEmitter,
     theme_key: Pubkey,
     creator_key: Pubkey,
     token_mint_key: Pubkey,
     voting_mode: VotingMode,
-) {
-    emit!(ThemeCreated {
+) -> Result<()> {
+    emitter.emit(ThemeCreated {
         theme: theme_key,
         creator: creator_key,
         token_mint: token_mint_key,
         voting_mode,
         total_supply: TOKEN_TOTAL_SUPPLY,
-    });
+    })
 }
 
 /// Initializes a new theme - Step 1: Create theme account only
 /// This reduces stack usage by splitting the initialization process
 #[inline(never)]
 pub fn initialize_theme(
-    ctx: Context<InitializeTheme>,
+    mut ctx: Context<InitializeTheme>,
     theme_id: u64,
     name: [u8; 12],
     description: [u8; 48],
     voting_mode: VotingMode,
+    params: ThemeParams,
 ) -> Result<()> {
     msg!("=== InitializeTheme START ===");
     msg!("Theme ID: {}", theme_id);
 
+    // 计价资产只能在创建时选择：None 为 SOL
+    let quote_mint = params.quote_mint.unwrap_or_default();
+    let curve_phases = params.curve_phases;
+
     // Initialize theme account only
     init_theme_basic_data(&mut ctx.accounts.theme, &ctx.accounts.creator, theme_id, name, description, voting_mode, ctx.bumps.theme)?;
+    ctx.accounts.theme.payout_curve = params.payout_curve;
+    ctx.accounts.theme.payout_exponent = params.payout_exponent;
+    ctx.accounts.theme.tie_break_rule = params.tie_break_rule;
+    ctx.accounts.theme.dispute_period = params.dispute_period;
+    ctx.accounts.theme.reject_all_threshold_bps = params.reject_all_threshold_bps;
+    ctx.accounts.theme.penalty_burn_bps = params.penalty_burn_bps;
+    if quote_mint == USDC_MINT {
+        ctx.accounts.theme.quote_mint = quote_mint;
+        ctx.accounts.theme.virtual_sol_reserves = USDC_VIRTUAL_QUOTE_RESERVES;
+    }
+    
+    // 分段曲线 (为空则使用默认单段曲线)
+    if !curve_phases.is_empty() {
+        let theme = &mut ctx.accounts.theme;
+        theme.curve_phases = build_curve_phases(
+            &curve_phases,
+            theme.token_reserves,
+            theme.virtual_token_reserves,
+        )?;
+        theme.curve_phase_count = curve_phases.len() as u8;
+    }
+
+    append_to_registry(&mut ctx, theme_id)?;
 
     msg!("Theme account initialized: {}", ctx.accounts.theme.key());
     Ok(())
 }
 
+/// 追加到全局主题注册表；mint 由 PDA 推导 (在 init_vault_and_mint 中创建)
+#[inline(never)]
+fn append_to_registry(ctx: &mut Context<InitializeTheme>, theme_id: u64) -> Result<()> {
+    let creator_key = ctx.accounts.creator.key();
+    let (token_mint, _) = Pubkey::find_program_address(
+        &[b"theme_mint", creator_key.as_ref(), theme_id.to_le_bytes().as_ref()],
+        &crate::ID,
+    );
+
+    let registry = &mut ctx.accounts.theme_registry;
+    let page = &mut ctx.accounts.registry_page;
+    if page.entries.is_empty() {
+        page.page_index = (registry.total_themes / THEME_REGISTRY_PAGE_SIZE as u64) as u32;
+        page.bump = ctx.bumps.registry_page;
+    }
+    registry.bump = ctx.bumps.theme_registry;
+
+    page.entries.push(ThemeRegistryEntry {
+        theme: ctx.accounts.theme.key(),
+        token_mint,
+        created_slot: Clock::get()?.slot,
+    });
+    registry.total_themes = registry.total_themes
+        .checked_add(1)
+        .ok_or(ConsensusError::Overflow)?;
+
+    Ok(())
+}
+
 /// Helper function to initialize basic theme data (without vault/mint references)
 #[inline(never)]
 fn init_theme_basic_data(
@@ -191,6 +351,36 @@ fn init_theme_basic_data(
     theme.status = THEME_STATUS_ACTIVE;
     theme.vault_bump = 0; // Will be set in step 2
     theme.theme_bump = theme_bump;
+    theme.community_burned = 0;
+    theme.payout_curve = DEFAULT_PAYOUT_CURVE;
+    theme.payout_exponent = 1;
+    theme.tie_break_rule = TIE_BREAK_CANCEL;
+    theme.dispute_period = 0;
+    theme.reject_all_threshold_bps = REJECT_ALL_THRESHOLD_BPS;
+    theme.penalty_burn_bps = 0;
+    theme.virtual_sol_reserves = VIRTUAL_SOL_RESERVES;
+    theme.virtual_token_reserves = VIRTUAL_TOKEN_RESERVES;
+    theme.migration_target = MIGRATION_TARGET_RAYDIUM;
+    theme.amm_pool = Pubkey::default();
+    theme.min_stake_tokens = 0;
+    theme.curve_phases = [CurvePhase::default(); MAX_CURVE_PHASES];
+    theme.curve_phase_count = 0;
+    theme.creator_fees_accrued = 0;
+    theme.platform_fees_accrued = 0;
+    theme.launch_slot = 0;
+    theme.launch_fee_bps = 0;
+    theme.fee_decay_slots = 0;
+    theme.swap_cooldown_secs = 0;
+    theme.volume_slot = 0;
+    theme.slot_buy_volume = 0;
+    theme.slot_sell_volume = 0;
+    theme.metadata_updated_at = 0;
+    theme.retire_eta = 0;
+    theme.quote_mint = Pubkey::default();
+    theme.stake_fee_share_bps = 0;
+    theme.staking_rewards_accrued = 0;
+    theme.buyback_policy = BUYBACK_POLICY_BURN;
+    theme.version = THEME_ACCOUNT_VERSION;
     
     Ok(())
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
+use taste_fun_shared::{MAX_TRADE_FEE_BPS, TRADING_CONFIG_VERSION};
 use crate::{TradingConfiguration};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct InitializeTradingConfig<'info> {
     #[account(
@@ -12,12 +14,16 @@ pub struct InitializeTradingConfig<'info> {
     )]
     pub trading_config: Account<'info, TradingConfiguration>,
     
+    /// CHECK: core 程序的全局协议配置，由 is_protocol_config_authority 校验
+    pub protocol_config: UncheckedAccount<'info>,
+    
     #[account(mut)]
     pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
+/// 仅协议管理员可初始化
 pub fn initialize_trading_config(
     ctx: Context<InitializeTradingConfig>,
     trade_fee_bps: u16,
@@ -27,16 +33,20 @@ pub fn initialize_trading_config(
 ) -> Result<()> {
     let config = &mut ctx.accounts.trading_config;
     
-    // Validate that splits add up to 10000 (100%)
-    require!(
-        buyback_fee_split_bps + platform_fee_split_bps + creator_fee_split_bps == 10000,
-        ErrorCode::InvalidFeeSplits
-    );
-    
     config.trade_fee_bps = trade_fee_bps;
     config.buyback_fee_split_bps = buyback_fee_split_bps;
     config.platform_fee_split_bps = platform_fee_split_bps;
     config.creator_fee_split_bps = creator_fee_split_bps;
+    config.sol_usd_feed = Pubkey::default();
+    config.min_trade_usd = 0;
+    config.min_stake_usd = 0;
+    config.min_trade_lamports = 0;
+    config.authority = ctx.accounts.authority.key();
+    config.pending_update_eta = 0;
+    config.pending_authority = Pubkey::default();
+    config.referral_fee_split_bps = 0;
+    config.max_slot_volume_bps = 0;
+    config.version = TRADING_CONFIG_VERSION;
     
     msg!("Trading configuration initialized");
     msg!("Trade fee: {} bps", trade_fee_bps);
@@ -47,8 +57,38 @@ pub fn initialize_trading_config(
     Ok(())
 }
 
+/// 校验交易费与费用分配比例 (初始化与变更共用)
+pub(crate) fn validate_trading_fees(
+    trade_fee_bps: u16,
+    buyback_fee_split_bps: u16,
+    platform_fee_split_bps: u16,
+    creator_fee_split_bps: u16,
+) -> Result<()> {
+    require!(trade_fee_bps > 0, ErrorCode::ZeroTradeFee);
+    require!(
+        trade_fee_bps <= MAX_TRADE_FEE_BPS,
+        ErrorCode::TradeFeeTooHigh
+    );
+    // 回购是结算回流的核心机制，不允许关闭
+    require!(buyback_fee_split_bps > 0, ErrorCode::ZeroBuybackSplit);
+    
+    // Validate that splits add up to 10000 (100%)
+    require!(
+        buyback_fee_split_bps as u32 + platform_fee_split_bps as u32 + creator_fee_split_bps as u32 == 10000,
+        ErrorCode::InvalidFeeSplits
+    );
+    
+    Ok(())
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Fee splits must add up to 10000 (100%)")]
     InvalidFeeSplits,
+    #[msg("Trade fee must be non-zero")]
+    ZeroTradeFee,
+    #[msg("Trade fee exceeds the maximum (300 bps)")]
+    TradeFeeTooHigh,
+    #[msg("Buyback fee split must be non-zero")]
+    ZeroBuybackSplit,
 }
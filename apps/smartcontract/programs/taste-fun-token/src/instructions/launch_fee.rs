@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use taste_fun_shared::*;
+use crate::Theme;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetLaunchFeeSchedule<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    pub creator: Signer<'info>,
+}
+
+/// 开盘费率计划：不超过上限，开启时衰减区间不能为 0
+pub(crate) fn valid_launch_fee_schedule(launch_fee_bps: u16, fee_decay_slots: u64) -> Result<()> {
+    require!(
+        launch_fee_bps <= MAX_LAUNCH_FEE_BPS,
+        ConsensusError::InvalidLaunchFeeSchedule
+    );
+    require!(
+        launch_fee_bps == 0 || fee_decay_slots > 0,
+        ConsensusError::InvalidLaunchFeeSchedule
+    );
+    Ok(())
+}
+
+/// 主题尚未开盘 (首笔交易前)
+pub(crate) fn theme_not_launched(launch_slot: u64) -> Result<()> {
+    require!(launch_slot == 0, ConsensusError::InvalidState);
+    Ok(())
+}
+
+/// 创建者在开盘前设置开盘费率计划；开盘后不可修改，避免临时抬高费率
+/// launch_fee_bps 为 0 表示关闭，始终使用基础费率
+pub fn set_launch_fee_schedule(
+    ctx: Context<SetLaunchFeeSchedule>,
+    launch_fee_bps: u16,
+    fee_decay_slots: u64,
+) -> Result<()> {
+    let theme = &mut ctx.accounts.theme;
+    theme.launch_fee_bps = launch_fee_bps;
+    theme.fee_decay_slots = fee_decay_slots;
+    
+    msg!("Launch fee schedule: {} bps decaying over {} slots", launch_fee_bps, fee_decay_slots);
+    
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use taste_fun_shared::*;
+use crate::{AccountMigrated, Theme, TradingConfiguration};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: 待升级的本程序账户，按 discriminator 识别类型后再反序列化
+    #[account(mut, owner = crate::ID @ ConsensusError::UnsupportedMigration)]
+    pub account: UncheckedAccount<'info>,
+    
+    /// CHECK: core 程序的全局协议配置，由 is_protocol_config_authority 校验
+    pub protocol_config: UncheckedAccount<'info>,
+    
+    /// 协议管理员，补足 realloc 所需租金
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// 按 discriminator 识别 Theme / TradingConfiguration，realloc 到当前大小并写入当前版本号
+pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+    let info = ctx.accounts.account.to_account_info();
+    let payer = ctx.accounts.authority.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let discriminator: [u8; 8] = info.try_borrow_data()?[..8]
+        .try_into()
+        .map_err(|_| ConsensusError::UnsupportedMigration)?;
+    
+    let (kind, from_version, to_version) = if discriminator == Theme::DISCRIMINATOR {
+        realloc_account(&info, &payer, &system_program, 8 + Theme::INIT_SPACE)?;
+        let mut theme = Theme::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        let from_version = theme.version;
+        require!(from_version < THEME_ACCOUNT_VERSION, ConsensusError::AccountUpToDate);
+        theme.version = THEME_ACCOUNT_VERSION;
+        theme.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        (ACCOUNT_KIND_THEME, from_version, THEME_ACCOUNT_VERSION)
+    } else if discriminator == TradingConfiguration::DISCRIMINATOR {
+        realloc_account(&info, &payer, &system_program, 8 + TradingConfiguration::SPACE)?;
+        let mut config = TradingConfiguration::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        let from_version = config.version;
+        require!(from_version < TRADING_CONFIG_VERSION, ConsensusError::AccountUpToDate);
+        config.version = TRADING_CONFIG_VERSION;
+        config.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        (ACCOUNT_KIND_TRADING_CONFIG, from_version, TRADING_CONFIG_VERSION)
+    } else {
+        return err!(ConsensusError::UnsupportedMigration);
+    };
+    
+    emit_cpi!(AccountMigrated {
+        account: info.key(),
+        kind,
+        from_version,
+        to_version,
+        size: info.data_len() as u32,
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,297 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{spl_token, Mint, Token, TokenAccount};
+use taste_fun_shared::*;
+use crate::{LpLock, Theme, ThemeVault};
+use super::migration::{complete_migration, fund_migration_authority, graduation_amounts, lock_lp_tokens, revoke_vault_mint_authority};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateToMeteora<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Box<Account<'info, Theme>>,
+    
+    /// 主题金库（同时承载曲线 SOL 储备）
+    #[account(
+        mut,
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump
+    )]
+    pub vault: Box<Account<'info, ThemeVault>>,
+    
+    /// 迁移完成时放弃铸币权
+    #[account(mut, address = theme.token_mint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+    
+    #[account(address = spl_token::native_mint::ID)]
+    pub wsol_mint: Box<Account<'info, Mint>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    
+    /// CHECK: 迁移权限 PDA（无数据），作为 Meteora 池的创建者并持有 LP 代币
+    #[account(
+        mut,
+        seeds = [b"migration_authority", theme.key().as_ref()],
+        bump
+    )]
+    pub migration_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = token_mint,
+        associated_token::authority = migration_authority,
+    )]
+    pub authority_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = wsol_mint,
+        associated_token::authority = migration_authority,
+    )]
+    pub authority_wsol_account: Box<Account<'info, TokenAccount>>,
+    
+    /// LP 锁仓记录，建池后 LP 全部转入其 ATA
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + LpLock::SPACE,
+        seeds = [b"lp_lock", theme.key().as_ref()],
+        bump
+    )]
+    pub lp_lock: Box<Account<'info, LpLock>>,
+    
+    /// CHECK: lp_lock 的 LP ATA，在建池 CPI 之后创建 (地址在 lock_lp_tokens 中校验)
+    #[account(mut)]
+    pub lp_lock_token_account: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// CHECK: Meteora 池配置，校验 owner
+    #[account(owner = METEORA_DAMM_PROGRAM_ID)]
+    pub pool_config: UncheckedAccount<'info>,
+    
+    /// CHECK: 以下账户由 Meteora 程序校验
+    #[account(mut)]
+    pub pool: UncheckedAccount<'info>,
+    /// CHECK: Meteora LP mint
+    #[account(mut)]
+    pub lp_mint: UncheckedAccount<'info>,
+    /// CHECK: token_a 的 Meteora vault
+    #[account(mut)]
+    pub a_vault: UncheckedAccount<'info>,
+    /// CHECK: token_b 的 Meteora vault
+    #[account(mut)]
+    pub b_vault: UncheckedAccount<'info>,
+    /// CHECK: a_vault 的代币账户
+    #[account(mut)]
+    pub a_token_vault: UncheckedAccount<'info>,
+    /// CHECK: b_vault 的代币账户
+    #[account(mut)]
+    pub b_token_vault: UncheckedAccount<'info>,
+    /// CHECK: a_vault LP mint
+    #[account(mut)]
+    pub a_vault_lp_mint: UncheckedAccount<'info>,
+    /// CHECK: b_vault LP mint
+    #[account(mut)]
+    pub b_vault_lp_mint: UncheckedAccount<'info>,
+    /// CHECK: 池子持有的 a_vault LP
+    #[account(mut)]
+    pub a_vault_lp: UncheckedAccount<'info>,
+    /// CHECK: 池子持有的 b_vault LP
+    #[account(mut)]
+    pub b_vault_lp: UncheckedAccount<'info>,
+    /// CHECK: 迁移权限的 LP ATA（由 Meteora 创建）
+    #[account(mut)]
+    pub authority_lp_account: UncheckedAccount<'info>,
+    /// CHECK: 协议 token_a 手续费账户
+    #[account(mut)]
+    pub protocol_token_a_fee: UncheckedAccount<'info>,
+    /// CHECK: 协议 token_b 手续费账户
+    #[account(mut)]
+    pub protocol_token_b_fee: UncheckedAccount<'info>,
+    /// CHECK: LP mint 元数据
+    #[account(mut)]
+    pub mint_metadata: UncheckedAccount<'info>,
+    /// CHECK: Metaplex 元数据程序
+    pub metadata_program: UncheckedAccount<'info>,
+    /// CHECK: Meteora vault 程序
+    pub vault_program: UncheckedAccount<'info>,
+    
+    /// CHECK: Meteora DAMM 程序
+    #[account(address = METEORA_DAMM_PROGRAM_ID)]
+    pub meteora_program: UncheckedAccount<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// 毕业迁移：曲线 SOL 储备达到阈值后，把储备 SOL (wSOL) 与剩余代币注入 Meteora DAMM 恒定乘积池
+pub fn migrate_to_meteora(ctx: Context<MigrateToMeteora>) -> Result<()> {
+    let (wsol_amount, token_amount) =
+        graduation_amounts(&ctx.accounts.theme, MIGRATION_TARGET_METEORA)?;
+    
+    // 1. 储备转给迁移权限 PDA
+    fund_migration_authority(
+        &ctx.accounts.theme,
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.vault_token_account.to_account_info(),
+        ctx.accounts.migration_authority.to_account_info(),
+        ctx.accounts.authority_token_account.to_account_info(),
+        ctx.accounts.authority_wsol_account.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        wsol_amount,
+    )?;
+    
+    // 2. CPI Meteora 建池
+    let theme_key = ctx.accounts.theme.key();
+    let authority_seeds: &[&[u8]] = &[
+        b"migration_authority",
+        theme_key.as_ref(),
+        &[ctx.bumps.migration_authority],
+    ];
+    create_meteora_pool(&ctx, token_amount, wsol_amount, authority_seeds)?;
+    
+    // 3. LP 锁仓
+    let emitter = event_emitter!(ctx);
+    let a = &mut *ctx.accounts;
+    lock_lp_tokens(
+        &mut a.lp_lock,
+        ctx.bumps.lp_lock,
+        theme_key,
+        a.pool.key(),
+        a.lp_mint.to_account_info(),
+        a.authority_lp_account.to_account_info(),
+        a.lp_lock_token_account.to_account_info(),
+        a.migration_authority.to_account_info(),
+        a.payer.to_account_info(),
+        a.token_program.to_account_info(),
+        a.associated_token_program.to_account_info(),
+        a.system_program.to_account_info(),
+        authority_seeds,
+    )?;
+    
+    // 4. 放弃铸币权，供应量自此固定
+    revoke_vault_mint_authority(
+        &emitter,
+        &a.theme,
+        theme_key,
+        a.vault.to_account_info(),
+        &a.token_mint,
+        a.token_program.to_account_info(),
+    )?;
+    
+    // 5. 状态切换
+    complete_migration(&emitter, &mut a.theme, theme_key, &a.lp_lock, wsol_amount, token_amount)?;
+    
+    msg!("Theme migrated to Meteora: {} SOL + {} tokens", wsol_amount, token_amount);
+    
+    Ok(())
+}
+
+/// 构造并调用 Meteora DAMM initialize_permissionless_constant_product_pool_with_config 指令
+/// token_a / token_b 按 mint 地址排序，与池子 PDA 推导一致
+#[inline(never)]
+fn create_meteora_pool(
+    ctx: &Context<MigrateToMeteora>,
+    token_amount: u64,
+    wsol_amount: u64,
+    authority_seeds: &[&[u8]],
+) -> Result<()> {
+    let a = &ctx.accounts;
+    let token_is_a = a.token_mint.key() < a.wsol_mint.key();
+    let (mint_a, mint_b, payer_a, payer_b, amount_a, amount_b) = if token_is_a {
+        (&a.token_mint, &a.wsol_mint, &a.authority_token_account, &a.authority_wsol_account, token_amount, wsol_amount)
+    } else {
+        (&a.wsol_mint, &a.token_mint, &a.authority_wsol_account, &a.authority_token_account, wsol_amount, token_amount)
+    };
+    
+    let mut data = Vec::with_capacity(8 + 16);
+    data.extend_from_slice(&METEORA_INITIALIZE_POOL_DISCRIMINATOR);
+    data.extend_from_slice(&amount_a.to_le_bytes());
+    data.extend_from_slice(&amount_b.to_le_bytes());
+    
+    let ix = Instruction {
+        program_id: METEORA_DAMM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(a.pool.key(), false),
+            AccountMeta::new_readonly(a.pool_config.key(), false),
+            AccountMeta::new(a.lp_mint.key(), false),
+            AccountMeta::new_readonly(mint_a.key(), false),
+            AccountMeta::new_readonly(mint_b.key(), false),
+            AccountMeta::new(a.a_vault.key(), false),
+            AccountMeta::new(a.b_vault.key(), false),
+            AccountMeta::new(a.a_token_vault.key(), false),
+            AccountMeta::new(a.b_token_vault.key(), false),
+            AccountMeta::new(a.a_vault_lp_mint.key(), false),
+            AccountMeta::new(a.b_vault_lp_mint.key(), false),
+            AccountMeta::new(a.a_vault_lp.key(), false),
+            AccountMeta::new(a.b_vault_lp.key(), false),
+            AccountMeta::new(payer_a.key(), false),
+            AccountMeta::new(payer_b.key(), false),
+            AccountMeta::new(a.authority_lp_account.key(), false),
+            AccountMeta::new(a.protocol_token_a_fee.key(), false),
+            AccountMeta::new(a.protocol_token_b_fee.key(), false),
+            AccountMeta::new(a.migration_authority.key(), true),
+            AccountMeta::new_readonly(a.rent.key(), false),
+            AccountMeta::new(a.mint_metadata.key(), false),
+            AccountMeta::new_readonly(a.metadata_program.key(), false),
+            AccountMeta::new_readonly(a.vault_program.key(), false),
+            AccountMeta::new_readonly(a.token_program.key(), false),
+            AccountMeta::new_readonly(a.associated_token_program.key(), false),
+            AccountMeta::new_readonly(a.system_program.key(), false),
+        ],
+        data,
+    };
+    
+    invoke_signed(
+        &ix,
+        &[
+            a.pool.to_account_info(),
+            a.pool_config.to_account_info(),
+            a.lp_mint.to_account_info(),
+            mint_a.to_account_info(),
+            mint_b.to_account_info(),
+            a.a_vault.to_account_info(),
+            a.b_vault.to_account_info(),
+            a.a_token_vault.to_account_info(),
+            a.b_token_vault.to_account_info(),
+            a.a_vault_lp_mint.to_account_info(),
+            a.b_vault_lp_mint.to_account_info(),
+            a.a_vault_lp.to_account_info(),
+            a.b_vault_lp.to_account_info(),
+            payer_a.to_account_info(),
+            payer_b.to_account_info(),
+            a.authority_lp_account.to_account_info(),
+            a.protocol_token_a_fee.to_account_info(),
+            a.protocol_token_b_fee.to_account_info(),
+            a.migration_authority.to_account_info(),
+            a.rent.to_account_info(),
+            a.mint_metadata.to_account_info(),
+            a.metadata_program.to_account_info(),
+            a.vault_program.to_account_info(),
+            a.token_program.to_account_info(),
+            a.associated_token_program.to_account_info(),
+            a.system_program.to_account_info(),
+            a.meteora_program.to_account_info(),
+        ],
+        &[authority_seeds],
+    )?;
+    
+    Ok(())
+}
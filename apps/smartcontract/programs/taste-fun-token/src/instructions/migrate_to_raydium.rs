@@ -0,0 +1,260 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{spl_token, Mint, Token, TokenAccount};
+use taste_fun_shared::*;
+use crate::{LpLock, Theme, ThemeVault};
+use super::migration::{complete_migration, fund_migration_authority, graduation_amounts, lock_lp_tokens, revoke_vault_mint_authority};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateToRaydium<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Box<Account<'info, Theme>>,
+    
+    /// 主题金库（同时承载曲线 SOL 储备）
+    #[account(
+        mut,
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump
+    )]
+    pub vault: Box<Account<'info, ThemeVault>>,
+    
+    /// 迁移完成时放弃铸币权
+    #[account(mut, address = theme.token_mint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+    
+    #[account(address = spl_token::native_mint::ID)]
+    pub wsol_mint: Box<Account<'info, Mint>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    
+    /// CHECK: 迁移权限 PDA（无数据），作为 Raydium 池的创建者并持有 LP 代币
+    #[account(
+        mut,
+        seeds = [b"migration_authority", theme.key().as_ref()],
+        bump
+    )]
+    pub migration_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = token_mint,
+        associated_token::authority = migration_authority,
+    )]
+    pub authority_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = wsol_mint,
+        associated_token::authority = migration_authority,
+    )]
+    pub authority_wsol_account: Box<Account<'info, TokenAccount>>,
+    
+    /// LP 锁仓记录，建池后 LP 全部转入其 ATA
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + LpLock::SPACE,
+        seeds = [b"lp_lock", theme.key().as_ref()],
+        bump
+    )]
+    pub lp_lock: Box<Account<'info, LpLock>>,
+    
+    /// CHECK: lp_lock 的 LP ATA，在建池 CPI 之后创建 (地址在 lock_lp_tokens 中校验)
+    #[account(mut)]
+    pub lp_lock_token_account: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// CHECK: Raydium CPMM 费率配置，校验 owner
+    #[account(owner = RAYDIUM_CPMM_PROGRAM_ID)]
+    pub amm_config: UncheckedAccount<'info>,
+    
+    /// CHECK: 以下账户由 Raydium 程序校验
+    pub raydium_authority: UncheckedAccount<'info>,
+    /// CHECK: Raydium 池状态 PDA
+    #[account(mut)]
+    pub pool_state: UncheckedAccount<'info>,
+    /// CHECK: Raydium LP mint PDA
+    #[account(mut)]
+    pub lp_mint: UncheckedAccount<'info>,
+    /// CHECK: 迁移权限的 LP ATA（由 Raydium 创建）
+    #[account(mut)]
+    pub authority_lp_account: UncheckedAccount<'info>,
+    /// CHECK: Raydium token_0 金库
+    #[account(mut)]
+    pub token_0_vault: UncheckedAccount<'info>,
+    /// CHECK: Raydium token_1 金库
+    #[account(mut)]
+    pub token_1_vault: UncheckedAccount<'info>,
+    /// CHECK: Raydium 建池手续费接收账户
+    #[account(mut)]
+    pub create_pool_fee: UncheckedAccount<'info>,
+    /// CHECK: Raydium 价格观测账户
+    #[account(mut)]
+    pub observation_state: UncheckedAccount<'info>,
+    
+    /// CHECK: Raydium CPMM 程序
+    #[account(address = RAYDIUM_CPMM_PROGRAM_ID)]
+    pub raydium_program: UncheckedAccount<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// 毕业迁移：曲线 SOL 储备达到阈值后，把储备 SOL (wSOL) 与剩余代币注入 Raydium CPMM 新池
+pub fn migrate_to_raydium(ctx: Context<MigrateToRaydium>) -> Result<()> {
+    let (wsol_amount, token_amount) =
+        graduation_amounts(&ctx.accounts.theme, MIGRATION_TARGET_RAYDIUM)?;
+    
+    // 1. 储备转给迁移权限 PDA
+    fund_migration_authority(
+        &ctx.accounts.theme,
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.vault_token_account.to_account_info(),
+        ctx.accounts.migration_authority.to_account_info(),
+        ctx.accounts.authority_token_account.to_account_info(),
+        ctx.accounts.authority_wsol_account.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        wsol_amount,
+    )?;
+    
+    // 2. CPI Raydium CPMM initialize（token_0 必须是地址较小的 mint）
+    let theme_key = ctx.accounts.theme.key();
+    let authority_seeds: &[&[u8]] = &[
+        b"migration_authority",
+        theme_key.as_ref(),
+        &[ctx.bumps.migration_authority],
+    ];
+    create_raydium_pool(&ctx, token_amount, wsol_amount, authority_seeds)?;
+    
+    // 3. LP 锁仓
+    let emitter = event_emitter!(ctx);
+    let a = &mut *ctx.accounts;
+    lock_lp_tokens(
+        &mut a.lp_lock,
+        ctx.bumps.lp_lock,
+        theme_key,
+        a.pool_state.key(),
+        a.lp_mint.to_account_info(),
+        a.authority_lp_account.to_account_info(),
+        a.lp_lock_token_account.to_account_info(),
+        a.migration_authority.to_account_info(),
+        a.payer.to_account_info(),
+        a.token_program.to_account_info(),
+        a.associated_token_program.to_account_info(),
+        a.system_program.to_account_info(),
+        authority_seeds,
+    )?;
+    
+    // 4. 放弃铸币权，供应量自此固定
+    revoke_vault_mint_authority(
+        &emitter,
+        &a.theme,
+        theme_key,
+        a.vault.to_account_info(),
+        &a.token_mint,
+        a.token_program.to_account_info(),
+    )?;
+    
+    // 5. 状态切换
+    complete_migration(&emitter, &mut a.theme, theme_key, &a.lp_lock, wsol_amount, token_amount)?;
+    
+    msg!("Theme migrated to Raydium: {} SOL + {} tokens", wsol_amount, token_amount);
+    
+    Ok(())
+}
+
+/// 构造并调用 Raydium CPMM initialize 指令
+#[inline(never)]
+fn create_raydium_pool(
+    ctx: &Context<MigrateToRaydium>,
+    token_amount: u64,
+    wsol_amount: u64,
+    authority_seeds: &[&[u8]],
+) -> Result<()> {
+    let a = &ctx.accounts;
+    let token_is_0 = a.token_mint.key() < a.wsol_mint.key();
+    let (mint_0, mint_1, creator_0, creator_1, amount_0, amount_1) = if token_is_0 {
+        (&a.token_mint, &a.wsol_mint, &a.authority_token_account, &a.authority_wsol_account, token_amount, wsol_amount)
+    } else {
+        (&a.wsol_mint, &a.token_mint, &a.authority_wsol_account, &a.authority_token_account, wsol_amount, token_amount)
+    };
+    
+    let mut data = Vec::with_capacity(8 + 24);
+    data.extend_from_slice(&RAYDIUM_CPMM_INITIALIZE_DISCRIMINATOR);
+    data.extend_from_slice(&amount_0.to_le_bytes());
+    data.extend_from_slice(&amount_1.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes()); // open_time: 立即开放
+    
+    let ix = Instruction {
+        program_id: RAYDIUM_CPMM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(a.migration_authority.key(), true),
+            AccountMeta::new_readonly(a.amm_config.key(), false),
+            AccountMeta::new_readonly(a.raydium_authority.key(), false),
+            AccountMeta::new(a.pool_state.key(), false),
+            AccountMeta::new_readonly(mint_0.key(), false),
+            AccountMeta::new_readonly(mint_1.key(), false),
+            AccountMeta::new(a.lp_mint.key(), false),
+            AccountMeta::new(creator_0.key(), false),
+            AccountMeta::new(creator_1.key(), false),
+            AccountMeta::new(a.authority_lp_account.key(), false),
+            AccountMeta::new(a.token_0_vault.key(), false),
+            AccountMeta::new(a.token_1_vault.key(), false),
+            AccountMeta::new(a.create_pool_fee.key(), false),
+            AccountMeta::new(a.observation_state.key(), false),
+            AccountMeta::new_readonly(a.token_program.key(), false),
+            AccountMeta::new_readonly(a.token_program.key(), false),
+            AccountMeta::new_readonly(a.token_program.key(), false),
+            AccountMeta::new_readonly(a.associated_token_program.key(), false),
+            AccountMeta::new_readonly(a.system_program.key(), false),
+            AccountMeta::new_readonly(a.rent.key(), false),
+        ],
+        data,
+    };
+    
+    invoke_signed(
+        &ix,
+        &[
+            a.migration_authority.to_account_info(),
+            a.amm_config.to_account_info(),
+            a.raydium_authority.to_account_info(),
+            a.pool_state.to_account_info(),
+            mint_0.to_account_info(),
+            mint_1.to_account_info(),
+            a.lp_mint.to_account_info(),
+            creator_0.to_account_info(),
+            creator_1.to_account_info(),
+            a.authority_lp_account.to_account_info(),
+            a.token_0_vault.to_account_info(),
+            a.token_1_vault.to_account_info(),
+            a.create_pool_fee.to_account_info(),
+            a.observation_state.to_account_info(),
+            a.token_program.to_account_info(),
+            a.associated_token_program.to_account_info(),
+            a.system_program.to_account_info(),
+            a.rent.to_account_info(),
+            a.raydium_program.to_account_info(),
+        ],
+        &[authority_seeds],
+    )?;
+    
+    Ok(())
+}
@@ -0,0 +1,344 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::{self, get_associated_token_address, Create};
+use anchor_spl::token::{self, spl_token::instruction::AuthorityType, Mint, SetAuthority, SyncNative, Token, TokenAccount, Transfer};
+use taste_fun_shared::*;
+use crate::{LpLock, LpUnlocked, MintAuthorityRevoked, Theme, ThemeMigrated, ThemeVault, TradingConfiguration};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetMigrationTarget<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump,
+        constraint = theme.creator == creator.key() @ ConsensusError::Unauthorized
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    pub creator: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UnlockLp<'info> {
+    #[account(
+        mut,
+        seeds = [b"lp_lock", lp_lock.theme.as_ref()],
+        bump = lp_lock.bump
+    )]
+    pub lp_lock: Account<'info, LpLock>,
+    
+    #[account(
+        mut,
+        associated_token::mint = lp_lock.lp_mint,
+        associated_token::authority = lp_lock,
+    )]
+    pub lp_lock_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = lp_lock.lp_mint,
+    )]
+    pub destination: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"trading_config"],
+        bump,
+        has_one = authority @ ConsensusError::Unauthorized
+    )]
+    pub trading_config: Account<'info, TradingConfiguration>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevokeMintAuthority<'info> {
+    #[account(
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    #[account(
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump
+    )]
+    pub vault: Account<'info, ThemeVault>,
+    
+    #[account(
+        mut,
+        address = theme.token_mint @ ConsensusError::InvalidMint,
+        constraint = token_mint.mint_authority == Some(vault.key()).into() @ ConsensusError::InvalidState
+    )]
+    pub token_mint: Account<'info, Mint>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+/// 创建者在毕业前选择迁移目标 (Raydium / Meteora)
+pub fn set_migration_target(ctx: Context<SetMigrationTarget>, migration_target: u8) -> Result<()> {
+    require!(
+        migration_target <= MIGRATION_TARGET_METEORA,
+        ConsensusError::InvalidMigrationTarget
+    );
+    ctx.accounts.theme.migration_target = migration_target;
+    
+    msg!("Migration target set to {}", migration_target);
+    
+    Ok(())
+}
+
+/// 毕业检查：迁移目标匹配且 SOL 储备达到阈值
+/// 返回 (注入池子的 wSOL 数量, 代币数量)，预留部分 SOL 用于建池费与租金
+pub(crate) fn graduation_amounts(theme: &Theme, migration_target: u8) -> Result<(u64, u64)> {
+    require!(
+        theme.migration_target == migration_target,
+        ConsensusError::InvalidMigrationTarget
+    );
+    require!(
+        theme.sol_reserves >= MIGRATION_THRESHOLD,
+        ConsensusError::MigrationThresholdNotReached
+    );
+    
+    let wsol_amount = theme.sol_reserves
+        .checked_sub(MIGRATION_LAMPORTS_RESERVE)
+        .ok_or(ConsensusError::Overflow)?;
+    
+    Ok((wsol_amount, theme.token_reserves))
+}
+
+/// 曲线储备转给迁移权限 PDA：SOL 主体包装为 wSOL，预留部分直接转入 PDA，代币从金库转出
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn fund_migration_authority<'info>(
+    theme: &Theme,
+    vault: AccountInfo<'info>,
+    vault_token_account: AccountInfo<'info>,
+    migration_authority: AccountInfo<'info>,
+    authority_token_account: AccountInfo<'info>,
+    authority_wsol_account: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    wsol_amount: u64,
+) -> Result<()> {
+    **vault.try_borrow_mut_lamports()? = vault.lamports()
+        .checked_sub(theme.sol_reserves)
+        .ok_or(ConsensusError::Overflow)?;
+    **authority_wsol_account.try_borrow_mut_lamports()? = authority_wsol_account.lamports()
+        .checked_add(wsol_amount)
+        .ok_or(ConsensusError::Overflow)?;
+    **migration_authority.try_borrow_mut_lamports()? = migration_authority.lamports()
+        .checked_add(MIGRATION_LAMPORTS_RESERVE)
+        .ok_or(ConsensusError::Overflow)?;
+    
+    token::sync_native(CpiContext::new(
+        token_program.clone(),
+        SyncNative {
+            account: authority_wsol_account,
+        },
+    ))?;
+    
+    let theme_id_bytes = theme.theme_id.to_le_bytes();
+    let vault_seeds: &[&[u8]] = &[
+        b"theme_vault",
+        theme.creator.as_ref(),
+        theme_id_bytes.as_ref(),
+        &[theme.vault_bump],
+    ];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program,
+            Transfer {
+                from: vault_token_account,
+                to: authority_token_account,
+                authority: vault,
+            },
+            &[vault_seeds],
+        ),
+        theme.token_reserves,
+    )?;
+    
+    Ok(())
+}
+
+/// 建池后把迁移权限持有的全部 LP 转入 LpLock PDA 的 ATA 并记录锁仓
+/// LP mint 由 AMM 在同一指令内创建，因此锁仓 ATA 只能在 CPI 之后手动创建
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn lock_lp_tokens<'info>(
+    lp_lock: &mut Account<'info, LpLock>,
+    lp_lock_bump: u8,
+    theme_key: Pubkey,
+    pool: Pubkey,
+    lp_mint: AccountInfo<'info>,
+    authority_lp_account: AccountInfo<'info>,
+    lp_lock_token_account: AccountInfo<'info>,
+    migration_authority: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    associated_token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    authority_seeds: &[&[u8]],
+) -> Result<()> {
+    require_keys_eq!(
+        lp_lock_token_account.key(),
+        get_associated_token_address(&lp_lock.key(), &lp_mint.key()),
+        ConsensusError::InvalidLpLockAccount
+    );
+    
+    associated_token::create(CpiContext::new(
+        associated_token_program,
+        Create {
+            payer,
+            associated_token: lp_lock_token_account.clone(),
+            authority: lp_lock.to_account_info(),
+            mint: lp_mint.clone(),
+            system_program,
+            token_program: token_program.clone(),
+        },
+    ))?;
+    
+    let lp_amount = TokenAccount::try_deserialize(&mut &authority_lp_account.data.borrow()[..])?.amount;
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program,
+            Transfer {
+                from: authority_lp_account,
+                to: lp_lock_token_account,
+                authority: migration_authority,
+            },
+            &[authority_seeds],
+        ),
+        lp_amount,
+    )?;
+    
+    let now = Clock::get()?.unix_timestamp;
+    lp_lock.theme = theme_key;
+    lp_lock.pool = pool;
+    lp_lock.lp_mint = lp_mint.key();
+    lp_lock.amount = lp_amount;
+    lp_lock.locked_at = now;
+    lp_lock.unlock_at = now
+        .checked_add(LP_LOCK_DURATION)
+        .ok_or(ConsensusError::Overflow)?;
+    lp_lock.bump = lp_lock_bump;
+    
+    Ok(())
+}
+
+/// 状态切换：储备清零，主题标记为 Migrated，曲线交易随之关闭
+pub(crate) fn complete_migration(
+    emitter: &EventEmitter,
+    theme: &mut Theme,
+    theme_key: Pubkey,
+    lp_lock: &Account<LpLock>,
+    sol_amount: u64,
+    token_amount: u64,
+) -> Result<()> {
+    theme.sol_reserves = 0;
+    theme.token_reserves = 0;
+    theme.status = THEME_STATUS_MIGRATED;
+    theme.amm_pool = lp_lock.pool;
+    
+    emitter.emit(ThemeMigrated {
+        theme: theme_key,
+        migration_target: theme.migration_target,
+        pool: lp_lock.pool,
+        lp_mint: lp_lock.lp_mint,
+        sol_amount,
+        token_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+        lp_lock: lp_lock.key(),
+        lp_amount: lp_lock.amount,
+        lp_unlock_at: lp_lock.unlock_at,
+    })
+}
+
+/// 已毕业主题放弃铸币权 (迁移时自动执行，此入口供此前已迁移的主题补做，任何人可调用)
+pub fn revoke_mint_authority(ctx: Context<RevokeMintAuthority>) -> Result<()> {
+    revoke_vault_mint_authority(
+        &event_emitter!(ctx),
+        &ctx.accounts.theme,
+        ctx.accounts.theme.key(),
+        ctx.accounts.vault.to_account_info(),
+        &ctx.accounts.token_mint,
+        ctx.accounts.token_program.to_account_info(),
+    )
+}
+
+/// 金库签名将 mint 的铸币权设为 None，并发出事件供浏览器核验供应量已固定
+pub(crate) fn revoke_vault_mint_authority<'info>(
+    emitter: &EventEmitter<'info>,
+    theme: &Theme,
+    theme_key: Pubkey,
+    vault: AccountInfo<'info>,
+    token_mint: &Account<'info, Mint>,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    let theme_id_bytes = theme.theme_id.to_le_bytes();
+    let seeds: &[&[u8]] = &[
+        b"theme_vault",
+        theme.creator.as_ref(),
+        theme_id_bytes.as_ref(),
+        &[theme.vault_bump],
+    ];
+    token::set_authority(
+        CpiContext::new_with_signer(
+            token_program,
+            SetAuthority {
+                current_authority: vault.clone(),
+                account_or_mint: token_mint.to_account_info(),
+            },
+            &[seeds],
+        ),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+    
+    emitter.emit(MintAuthorityRevoked {
+        theme: theme_key,
+        mint: token_mint.key(),
+        previous_authority: vault.key(),
+        supply: token_mint.supply,
+        timestamp: Clock::get()?.unix_timestamp,
+    })?;
+    
+    msg!("Mint authority revoked, supply fixed at {}", token_mint.supply);
+    
+    Ok(())
+}
+
+/// 锁仓期满后由协议治理转出全部 LP
+pub fn unlock_lp(ctx: Context<UnlockLp>) -> Result<()> {
+    let lp_lock = &ctx.accounts.lp_lock;
+    let amount = ctx.accounts.lp_lock_token_account.amount;
+    require!(amount > 0, ConsensusError::InvalidAmount);
+    
+    let seeds: &[&[u8]] = &[b"lp_lock", lp_lock.theme.as_ref(), &[lp_lock.bump]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.lp_lock_token_account.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.lp_lock.to_account_info(),
+            },
+            &[seeds],
+        ),
+        amount,
+    )?;
+    
+    let lp_lock = &mut ctx.accounts.lp_lock;
+    lp_lock.amount = 0;
+    
+    emit_cpi!(LpUnlocked {
+        theme: lp_lock.theme,
+        lp_lock: lp_lock.key(),
+        destination: ctx.accounts.destination.key(),
+        amount,
+    });
+    
+    msg!("Unlocked {} LP tokens", amount);
+    
+    Ok(())
+}
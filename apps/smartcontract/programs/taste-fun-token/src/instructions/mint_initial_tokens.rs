@@ -2,9 +2,10 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{mint_to, transfer, Mint, Token, TokenAccount, MintTo, Transfer};
 use anchor_spl::associated_token::AssociatedToken;
 use taste_fun_shared::*;
-use crate::{Theme, ThemeVault};
+use crate::{CreatorVesting, CreatorVestingCreated, Theme, ThemeVault};
 
 /// 步骤1: 初始化vault和mint（拆分以减少栈使用）
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(theme_id: u64)]
 pub struct InitVaultAndMint<'info> {
@@ -42,6 +43,7 @@ pub struct InitVaultAndMint<'info> {
 }
 
 /// 步骤2: 铸造代币并分配（拆分以减少栈使用）
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(theme_id: u64)]
 pub struct MintInitialTokens<'info> {
@@ -70,13 +72,23 @@ pub struct MintInitialTokens<'info> {
     )]
     pub vault_token_account: Account<'info, TokenAccount>,
 
+    /// 创建者储备锁仓账户 (储备不再直接发给创建者)
     #[account(
-        init_if_needed,
+        init,
+        payer = creator,
+        space = 8 + CreatorVesting::SPACE,
+        seeds = [b"creator_vesting", theme.key().as_ref()],
+        bump
+    )]
+    pub creator_vesting: Box<Account<'info, CreatorVesting>>,
+    
+    #[account(
+        init,
         payer = creator,
         associated_token::mint = token_mint,
-        associated_token::authority = creator,
+        associated_token::authority = creator_vesting,
     )]
-    pub creator_token_account: Account<'info, TokenAccount>,
+    pub vesting_token_account: Box<Account<'info, TokenAccount>>,
 
     #[account(mut)]
     pub creator: Signer<'info>,
@@ -103,13 +115,14 @@ pub fn init_vault_and_mint(ctx: Context<InitVaultAndMint>, _theme_id: u64) -> Re
 }
 
 /// 步骤2: 铸造初始代币供应量并分配
-pub fn mint_initial_tokens(ctx: Context<MintInitialTokens>, theme_id: u64) -> Result<()> {
+pub fn mint_initial_tokens(mut ctx: Context<MintInitialTokens>, theme_id: u64) -> Result<()> {
     // 铸造总供应量到vault
     mint_to_vault(&ctx, theme_id)?;
     
-    // 计算并转移创建者储备
+    // 计算创建者储备并存入锁仓账户
     let creator_reserve = calculate_creator_reserve()?;
-    transfer_to_creator(&ctx, theme_id, creator_reserve)?;
+    transfer_to_vesting(&ctx, theme_id, creator_reserve)?;
+    init_creator_vesting(&mut ctx, creator_reserve)?;
     
     // 更新theme储备
     update_theme_reserves(&mut ctx.accounts.theme, creator_reserve)?;
@@ -158,9 +171,9 @@ fn mint_to_vault(ctx: &Context<MintInitialTokens>, theme_id: u64) -> Result<()>
     )
 }
 
-/// 转移到创建者 - 优化版本，直接使用数组而非Vec
+/// 转移到锁仓账户 - 优化版本，直接使用数组而非Vec
 #[inline(never)]
-fn transfer_to_creator(ctx: &Context<MintInitialTokens>, theme_id: u64, amount: u64) -> Result<()> {
+fn transfer_to_vesting(ctx: &Context<MintInitialTokens>, theme_id: u64, amount: u64) -> Result<()> {
     let theme_id_bytes = theme_id.to_le_bytes();
     let bump_bytes = [ctx.accounts.theme.vault_bump];
     let creator_key = ctx.accounts.creator.key();
@@ -178,7 +191,7 @@ fn transfer_to_creator(ctx: &Context<MintInitialTokens>, theme_id: u64, amount:
             ctx.accounts.token_program.to_account_info(),
             Transfer {
                 from: ctx.accounts.vault_token_account.to_account_info(),
-                to: ctx.accounts.creator_token_account.to_account_info(),
+                to: ctx.accounts.vesting_token_account.to_account_info(),
                 authority: ctx.accounts.vault.to_account_info(),
             },
             signer,
@@ -187,19 +200,46 @@ fn transfer_to_creator(ctx: &Context<MintInitialTokens>, theme_id: u64, amount:
     )
 }
 
+/// 初始化锁仓计划 - 独立函数
+#[inline(never)]
+fn init_creator_vesting(ctx: &mut Context<MintInitialTokens>, amount: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let theme_key = ctx.accounts.theme.key();
+    let vesting = &mut ctx.accounts.creator_vesting;
+    vesting.theme = theme_key;
+    vesting.creator = ctx.accounts.creator.key();
+    vesting.total_amount = amount;
+    vesting.claimed_amount = 0;
+    vesting.start_ts = now;
+    vesting.cliff_ts = now + CREATOR_VESTING_CLIFF;
+    vesting.end_ts = now + CREATOR_VESTING_DURATION;
+    vesting.bump = ctx.bumps.creator_vesting;
+    
+    emit_cpi!(CreatorVestingCreated {
+        theme: theme_key,
+        creator: vesting.creator,
+        total_amount: amount,
+        cliff_ts: vesting.cliff_ts,
+        end_ts: vesting.end_ts,
+    });
+    Ok(())
+}
+
 /// 更新theme储备 - 独立函数
 #[inline(never)]
 fn update_theme_reserves(theme: &mut Account<Theme>, creator_reserve: u64) -> Result<()> {
     theme.token_reserves = TOKEN_TOTAL_SUPPLY
         .checked_sub(creator_reserve)
         .ok_or(ConsensusError::Overflow)?;
+    // 记录开盘 slot，开启防狙击窗口
+    theme.launch_slot = Clock::get()?.slot;
     Ok(())
 }
 
 /// 发出事件 - 独立函数
 #[inline(never)]
 fn emit_theme_created_event(ctx: &Context<MintInitialTokens>) -> Result<()> {
-    emit!(crate::ThemeCreated {
+    emit_cpi!(crate::ThemeCreated {
         theme: ctx.accounts.theme.key(),
         creator: ctx.accounts.creator.key(),
         token_mint: ctx.accounts.token_mint.key(),
@@ -1,13 +1,59 @@
 pub mod initialize_trading_config;
+pub mod update_trading_config;
 pub mod initialize_theme;
 pub mod mint_initial_tokens;
+pub mod creator_vesting;
 pub mod swap_sol_for_tokens;
+pub mod swap_quote_token;
+pub mod stake_pool;
+pub mod airdrop;
+pub mod swap_sol_for_exact_tokens;
 pub mod swap_tokens_for_sol;
 pub mod execute_buyback;
+pub mod community_burn;
+pub mod pause;
+pub mod retire_theme;
+pub mod update_theme_metadata;
+pub mod theme_metadata;
+pub mod claim_fees;
+pub mod launch_fee;
+pub mod swap_cooldown;
+pub mod referral;
+pub mod quote;
+pub mod price_oracle;
+pub mod usd_minimums;
+pub mod migration;
+pub mod migrate_to_raydium;
+pub mod migrate_to_meteora;
+pub mod execute_amm_buyback;
+pub mod migrate_account;
 
 pub use initialize_trading_config::*;
+pub use update_trading_config::*;
 pub use initialize_theme::*;
 pub use mint_initial_tokens::*;
+pub use creator_vesting::*;
 pub use swap_sol_for_tokens::*;
+pub use swap_quote_token::*;
+pub use stake_pool::*;
+pub use airdrop::*;
+pub use swap_sol_for_exact_tokens::*;
 pub use swap_tokens_for_sol::*;
 pub use execute_buyback::*;
+pub use community_burn::*;
+pub use pause::*;
+pub use retire_theme::*;
+pub use update_theme_metadata::*;
+pub use theme_metadata::*;
+pub use claim_fees::*;
+pub use launch_fee::*;
+pub use swap_cooldown::*;
+pub use referral::*;
+pub use quote::*;
+pub use price_oracle::*;
+pub use usd_minimums::*;
+pub use migration::*;
+pub use migrate_to_raydium::*;
+pub use migrate_to_meteora::*;
+pub use execute_amm_buyback::*;
+pub use migrate_account::*;
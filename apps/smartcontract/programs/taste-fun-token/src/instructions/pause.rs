@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use taste_fun_shared::*;
+use crate::{Theme, ThemePaused, ThemeUnpaused, TradingConfiguration};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetThemePause<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    #[account(
+        seeds = [b"trading_config"],
+        bump
+    )]
+    pub trading_config: Account<'info, TradingConfiguration>,
+    
+    pub authority: Signer<'info>,
+}
+
+/// 暂停活跃主题；已迁移主题的曲线交易已关闭，不适用
+pub fn pause_theme(ctx: Context<SetThemePause>) -> Result<()> {
+    let theme = &mut ctx.accounts.theme;
+    theme.status = THEME_STATUS_PAUSED;
+    
+    emit_cpi!(ThemePaused {
+        theme: theme.key(),
+        authority: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Theme paused: {}", theme.key());
+    
+    Ok(())
+}
+
+/// 恢复已暂停的主题
+pub fn unpause_theme(ctx: Context<SetThemePause>) -> Result<()> {
+    let theme = &mut ctx.accounts.theme;
+    theme.status = THEME_STATUS_ACTIVE;
+    
+    emit_cpi!(ThemeUnpaused {
+        theme: theme.key(),
+        authority: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Theme unpaused: {}", theme.key());
+    
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+use taste_fun_shared::*;
+use crate::PriceOracle;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct GetTwap<'info> {
+    #[account(
+        seeds = [b"price_oracle", price_oracle.theme.as_ref()],
+        bump = price_oracle.bump
+    )]
+    pub price_oracle: Account<'info, PriceOracle>,
+}
+
+/// 读取时间加权均价 (只读，结果通过 return_data 返回)
+/// 价格单位：每个代币最小单位的 lamports * PRICE_SCALE
+pub fn get_twap(ctx: Context<GetTwap>, window_secs: i64) -> Result<u64> {
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.price_oracle
+        .twap(now, window_secs)
+        .ok_or(error!(ConsensusError::OracleNotReady))
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use taste_fun_shared::*;
+use crate::{Theme, TradingConfiguration};
+use super::swap_sol_for_tokens::{calculate_fee_portion, calculate_total_fee};
+
+/// 报价结果（通过 return_data 返回，不修改任何状态）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct SwapQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub total_fee: u64,
+    pub buyback_fee: u64,
+    pub platform_fee: u64,
+    pub creator_fee: u64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Quote<'info> {
+    #[account(
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    #[account(
+        seeds = [b"trading_config"],
+        bump
+    )]
+    pub trading_config: Account<'info, TradingConfiguration>,
+}
+
+/// 买入报价：与 swap_sol_for_tokens 使用相同的曲线与费用计算
+pub fn quote_buy(ctx: Context<Quote>, sol_amount: u64) -> Result<SwapQuote> {
+    let theme = &ctx.accounts.theme;
+    let config = &ctx.accounts.trading_config;
+    
+    let fee_bps = theme.current_fee_bps(config.trade_fee_bps, Clock::get()?.slot);
+    
+    let tokens_out = theme.buy_tokens(sol_amount, fee_bps)?;
+    require!(
+        tokens_out <= theme.token_reserves,
+        ConsensusError::InsufficientReserves
+    );
+    
+    let total_fee = calculate_total_fee(sol_amount, fee_bps)?;
+    
+    Ok(SwapQuote {
+        amount_in: sol_amount,
+        amount_out: tokens_out,
+        total_fee,
+        buyback_fee: calculate_fee_portion(total_fee, config.buyback_fee_split_bps)?,
+        platform_fee: calculate_fee_portion(total_fee, config.platform_fee_split_bps)?,
+        creator_fee: calculate_fee_portion(total_fee, config.creator_fee_split_bps)?,
+    })
+}
+
+/// 卖出报价：与 swap_tokens_for_sol 使用相同的曲线与费用计算
+pub fn quote_sell(ctx: Context<Quote>, token_amount: u64) -> Result<SwapQuote> {
+    let theme = &ctx.accounts.theme;
+    let config = &ctx.accounts.trading_config;
+    
+    let fee_bps = theme.current_fee_bps(config.trade_fee_bps, Clock::get()?.slot);
+    
+    let sol_out = theme.sell_sol(token_amount, fee_bps)?;
+    require!(
+        sol_out <= theme.sol_reserves,
+        ConsensusError::InsufficientReserves
+    );
+    
+    let sol_before_fee = theme.sell_sol(token_amount, 0)?;
+    let total_fee = sol_before_fee
+        .checked_sub(sol_out)
+        .ok_or(ConsensusError::Overflow)?;
+    
+    Ok(SwapQuote {
+        amount_in: token_amount,
+        amount_out: sol_out,
+        total_fee,
+        buyback_fee: calculate_fee_portion(total_fee, config.buyback_fee_split_bps)?,
+        platform_fee: calculate_fee_portion(total_fee, config.platform_fee_split_bps)?,
+        creator_fee: calculate_fee_portion(total_fee, config.creator_fee_split_bps)?,
+    })
+}
@@ -0,0 +1,144 @@
+use anchor_lang::prelude::*;
+use taste_fun_shared::*;
+use crate::{Referral, ReferralFeePaid, ReferralFeeSplitUpdated, ReferralFeesClaimed, ReferralRegistered};
+use super::swap_sol_for_tokens::calculate_fee_portion;
+use super::update_trading_config::UpdateTradingConfig;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RegisterReferral<'info> {
+    #[account(
+        init,
+        payer = referrer,
+        space = 8 + Referral::SPACE,
+        seeds = [b"referral", referrer.key().as_ref()],
+        bump
+    )]
+    pub referral: Account<'info, Referral>,
+    
+    /// CHECK: core 程序的全局协议配置，由 protocol_config_active 校验
+    pub protocol_config: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimReferralFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"referral", referrer.key().as_ref()],
+        bump = referral.bump
+    )]
+    pub referral: Account<'info, Referral>,
+    
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+}
+
+/// 注册推荐人 PDA，之后交易者可在 swap 中携带该账户
+pub fn register_referral(ctx: Context<RegisterReferral>) -> Result<()> {
+    let referral = &mut ctx.accounts.referral;
+    referral.referrer = ctx.accounts.referrer.key();
+    referral.fees_accrued = 0;
+    referral.total_earned = 0;
+    referral.referred_trades = 0;
+    referral.bump = ctx.bumps.referral;
+    
+    emit_cpi!(ReferralRegistered {
+        referral: referral.key(),
+        referrer: referral.referrer,
+    });
+    
+    msg!("Referral registered for {}", referral.referrer);
+    
+    Ok(())
+}
+
+/// 推荐人提取累计分成 (lamports 直接从 PDA 划出，租金部分保留)
+pub fn claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+    let amount = ctx.accounts.referral.fees_accrued;
+    require!(amount > 0, ConsensusError::InvalidAmount);
+    
+    let referral_info = ctx.accounts.referral.to_account_info();
+    let referrer_info = ctx.accounts.referrer.to_account_info();
+    **referral_info.try_borrow_mut_lamports()? = referral_info.lamports()
+        .checked_sub(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    **referrer_info.try_borrow_mut_lamports()? = referrer_info.lamports()
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    ctx.accounts.referral.fees_accrued = 0;
+    
+    emit_cpi!(ReferralFeesClaimed {
+        referral: ctx.accounts.referral.key(),
+        referrer: ctx.accounts.referrer.key(),
+        amount,
+    });
+    
+    msg!("Referral fees claimed: {} lamports", amount);
+    
+    Ok(())
+}
+
+/// 设置推荐人分成比例 (占平台费)，只重新分配平台费，不改变交易者成本，因此无需时间锁
+pub fn set_referral_fee_split(
+    ctx: Context<UpdateTradingConfig>,
+    referral_fee_split_bps: u16,
+) -> Result<()> {
+    ctx.accounts.trading_config.referral_fee_split_bps = referral_fee_split_bps;
+    
+    emit_cpi!(ReferralFeeSplitUpdated { referral_fee_split_bps });
+    
+    msg!("Referral fee split: {} bps", referral_fee_split_bps);
+    
+    Ok(())
+}
+
+/// 计算推荐人分成；交易者不能推荐自己
+pub(crate) fn referral_cut(
+    referral: Option<&Account<Referral>>,
+    user: &Pubkey,
+    platform_fee: u64,
+    referral_fee_split_bps: u16,
+) -> Result<u64> {
+    let Some(referral) = referral else {
+        return Ok(0);
+    };
+    require!(referral.referrer != *user, ConsensusError::SelfReferral);
+    
+    calculate_fee_portion(platform_fee, referral_fee_split_bps)
+}
+
+/// 记账推荐分成 (lamports 已由调用方转入推荐人 PDA)
+pub(crate) fn credit_referral(
+    emitter: &EventEmitter,
+    referral: &mut Account<Referral>,
+    theme: Pubkey,
+    trader: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    referral.fees_accrued = referral.fees_accrued
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    referral.total_earned = referral.total_earned
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    referral.referred_trades = referral.referred_trades
+        .checked_add(1)
+        .ok_or(ConsensusError::Overflow)?;
+    
+    if amount > 0 {
+        emitter.emit(ReferralFeePaid {
+            referral: referral.key(),
+            theme,
+            trader,
+            amount,
+        })?;
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,252 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, spl_token::instruction::AuthorityType, Mint, SetAuthority, Token};
+use taste_fun_shared::*;
+use crate::{
+    RetirementClaim, RetirementClaimed, RetirementPool, Theme, ThemeRetired,
+    ThemeRetirementCancelled, ThemeRetirementProposed, ThemeVault, TradingConfiguration,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeRetirement<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    #[account(
+        seeds = [b"trading_config"],
+        bump
+    )]
+    pub trading_config: Account<'info, TradingConfiguration>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RetireTheme<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    #[account(
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump
+    )]
+    pub vault: Account<'info, ThemeVault>,
+    
+    #[account(
+        mut,
+        address = theme.token_mint @ ConsensusError::InvalidMint
+    )]
+    pub token_mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RetirementPool::SPACE,
+        seeds = [b"retirement", theme.key().as_ref()],
+        bump
+    )]
+    pub retirement_pool: Account<'info, RetirementPool>,
+    
+    #[account(
+        seeds = [b"trading_config"],
+        bump
+    )]
+    pub trading_config: Account<'info, TradingConfiguration>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimRetirement<'info> {
+    #[account(
+        mut,
+        seeds = [b"retirement", retirement_pool.theme.as_ref()],
+        bump = retirement_pool.bump
+    )]
+    pub retirement_pool: Account<'info, RetirementPool>,
+    
+    #[account(
+        mut,
+        constraint = vault.theme == retirement_pool.theme @ ConsensusError::InvalidTheme
+    )]
+    pub vault: Account<'info, ThemeVault>,
+    
+    /// 领取记录，重复领取时 init 失败
+    #[account(
+        init,
+        payer = holder,
+        space = 8 + RetirementClaim::SPACE,
+        seeds = [b"retirement_claim", retirement_pool.theme.as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub retirement_claim: Account<'info, RetirementClaim>,
+    
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// 提议退役：时间锁期间曲线照常交易，持有者可自行卖出离场
+pub fn propose_retirement(ctx: Context<ProposeRetirement>) -> Result<()> {
+    let theme = &mut ctx.accounts.theme;
+    require!(theme.retire_eta == 0, ConsensusError::InvalidState);
+    
+    let eta = Clock::get()?.unix_timestamp
+        .checked_add(THEME_RETIRE_TIMELOCK)
+        .ok_or(ConsensusError::Overflow)?;
+    theme.retire_eta = eta;
+    
+    emit_cpi!(ThemeRetirementProposed {
+        theme: theme.key(),
+        authority: ctx.accounts.authority.key(),
+        eta,
+    });
+    
+    msg!("Theme retirement proposed, executable at {}", eta);
+    
+    Ok(())
+}
+
+/// 撤回尚未执行的退役提议
+pub fn cancel_retirement(ctx: Context<ProposeRetirement>) -> Result<()> {
+    let theme = &mut ctx.accounts.theme;
+    theme.retire_eta = 0;
+    
+    emit_cpi!(ThemeRetirementCancelled {
+        theme: theme.key(),
+        authority: ctx.accounts.authority.key(),
+    });
+    
+    msg!("Theme retirement cancelled");
+    
+    Ok(())
+}
+
+/// 执行退役：曲线 SOL 与回购池转为按持有者快照分配，主题标记为 Retired 并放弃铸币权
+/// merkle 根由链下按执行时的持有者余额按比例计算；取整余数留在金库
+pub fn retire_theme(
+    ctx: Context<RetireTheme>,
+    merkle_root: [u8; 32],
+    total_claimable: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let theme = &ctx.accounts.theme;
+    
+    let distributable = theme.sol_reserves
+        .checked_add(theme.buyback_pool)
+        .ok_or(ConsensusError::Overflow)?;
+    require!(
+        total_claimable <= distributable,
+        ConsensusError::RetirementExceedsReserves
+    );
+    
+    // mint 没有冻结权限，退役时放弃铸币权使供应量固定
+    let vault_key = ctx.accounts.vault.key();
+    if ctx.accounts.token_mint.mint_authority == Some(vault_key).into() {
+        let theme_id_bytes = theme.theme_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[
+            b"theme_vault",
+            theme.creator.as_ref(),
+            theme_id_bytes.as_ref(),
+            &[theme.vault_bump],
+        ];
+        token::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                SetAuthority {
+                    current_authority: ctx.accounts.vault.to_account_info(),
+                    account_or_mint: ctx.accounts.token_mint.to_account_info(),
+                },
+                &[seeds],
+            ),
+            AuthorityType::MintTokens,
+            None,
+        )?;
+    }
+    
+    let pool = &mut ctx.accounts.retirement_pool;
+    pool.theme = ctx.accounts.theme.key();
+    pool.merkle_root = merkle_root;
+    pool.total_claimable = total_claimable;
+    pool.claimed_amount = 0;
+    pool.retired_at = now;
+    pool.bump = ctx.bumps.retirement_pool;
+    
+    let theme = &mut ctx.accounts.theme;
+    theme.status = THEME_STATUS_RETIRED;
+    theme.sol_reserves = 0;
+    theme.buyback_pool = 0;
+    theme.retire_eta = 0;
+    
+    emit_cpi!(ThemeRetired {
+        theme: theme.key(),
+        merkle_root,
+        total_claimable,
+        timestamp: now,
+    });
+    
+    msg!("Theme retired, {} lamports claimable by holders", total_claimable);
+    
+    Ok(())
+}
+
+/// 持有者领取退役分配
+pub fn claim_retirement(
+    ctx: Context<ClaimRetirement>,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.retirement_pool;
+    let holder = ctx.accounts.holder.key();
+    require!(
+        verify_merkle_proof(&proof, pool.merkle_root, retirement_leaf(&pool.theme, &holder, amount)),
+        ConsensusError::InvalidMerkleProof
+    );
+    
+    pool.claimed_amount = pool.claimed_amount
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    require!(
+        pool.claimed_amount <= pool.total_claimable,
+        ConsensusError::PayoutExceedsTotal
+    );
+    
+    let vault_info = ctx.accounts.vault.to_account_info();
+    let holder_info = ctx.accounts.holder.to_account_info();
+    **vault_info.try_borrow_mut_lamports()? = vault_info.lamports()
+        .checked_sub(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    **holder_info.try_borrow_mut_lamports()? = holder_info.lamports()
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    
+    let claim = &mut ctx.accounts.retirement_claim;
+    claim.theme = pool.theme;
+    claim.holder = holder;
+    claim.amount = amount;
+    
+    emit_cpi!(RetirementClaimed {
+        theme: pool.theme,
+        holder,
+        amount,
+    });
+    
+    msg!("Retirement payout claimed: {} lamports", amount);
+    
+    Ok(())
+}
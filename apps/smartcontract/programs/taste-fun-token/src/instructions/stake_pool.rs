@@ -0,0 +1,535 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::associated_token::AssociatedToken;
+use taste_fun_shared::*;
+use crate::{
+    StakeFeeShareUpdated, StakePool, StakePoolCreated, StakePosition, StakeRewardsClaimed,
+    StakeRewardsSynced, Staked, Theme, ThemeVault, UnstakeRequested, Unstaked,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeStakePool<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Box<Account<'info, Theme>>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + StakePool::SPACE,
+        seeds = [b"stake_pool", theme.key().as_ref()],
+        bump
+    )]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    
+    #[account(address = theme.token_mint @ ConsensusError::InvalidMint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+    
+    /// 质押池 ATA：存放质押中、冷却中的代币及已分配的代币奖励
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = token_mint,
+        associated_token::authority = stake_pool,
+    )]
+    pub pool_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetStakeFeeShare<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    #[account(
+        seeds = [b"stake_pool", theme.key().as_ref()],
+        bump = stake_pool.bump
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+    
+    pub creator: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SyncStakeRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Box<Account<'info, Theme>>,
+    
+    #[account(
+        mut,
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump
+    )]
+    pub vault: Box<Account<'info, ThemeVault>>,
+    
+    #[account(address = theme.token_mint @ ConsensusError::InvalidMint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake_pool", theme.key().as_ref()],
+        bump = stake_pool.bump
+    )]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = stake_pool,
+    )]
+    pub pool_token_account: Box<Account<'info, TokenAccount>>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+/// 质押、解除质押、提取与领取共用：每次操作前先同步池子奖励
+#[event_cpi]
+#[derive(Accounts)]
+pub struct StakeAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Box<Account<'info, Theme>>,
+    
+    #[account(
+        mut,
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump
+    )]
+    pub vault: Box<Account<'info, ThemeVault>>,
+    
+    #[account(address = theme.token_mint @ ConsensusError::InvalidMint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake_pool", theme.key().as_ref()],
+        bump = stake_pool.bump
+    )]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = stake_pool,
+    )]
+    pub pool_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + StakePosition::SPACE,
+        seeds = [b"stake_position", theme.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub position: Box<Account<'info, StakePosition>>,
+    
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = token_mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 创建质押池；分成比例写入主题，交易回购费按比例留给质押者
+pub fn initialize_stake_pool(
+    ctx: Context<InitializeStakePool>,
+    stake_fee_share_bps: u16,
+) -> Result<()> {
+    require!(
+        stake_fee_share_bps <= MAX_STAKE_FEE_SHARE_BPS,
+        ConsensusError::StakeFeeShareTooHigh
+    );
+    
+    let pool = &mut ctx.accounts.stake_pool;
+    pool.theme = ctx.accounts.theme.key();
+    pool.total_staked = 0;
+    pool.total_unstaking = 0;
+    pool.acc_sol_per_share = 0;
+    pool.acc_token_per_share = 0;
+    pool.token_rewards_reserved = 0;
+    pool.vault_excess_synced = 0;
+    pool.bump = ctx.bumps.stake_pool;
+    
+    ctx.accounts.theme.stake_fee_share_bps = stake_fee_share_bps;
+    
+    emit_cpi!(StakePoolCreated {
+        theme: pool.theme,
+        stake_pool: pool.key(),
+        stake_fee_share_bps,
+    });
+    
+    msg!("Stake pool created, fee share {} bps", stake_fee_share_bps);
+    
+    Ok(())
+}
+
+/// 调整分成比例；已记账的质押分成不受影响
+pub fn set_stake_fee_share(
+    ctx: Context<SetStakeFeeShare>,
+    stake_fee_share_bps: u16,
+) -> Result<()> {
+    require!(
+        stake_fee_share_bps <= MAX_STAKE_FEE_SHARE_BPS,
+        ConsensusError::StakeFeeShareTooHigh
+    );
+    
+    ctx.accounts.theme.stake_fee_share_bps = stake_fee_share_bps;
+    
+    emit_cpi!(StakeFeeShareUpdated {
+        theme: ctx.accounts.theme.key(),
+        stake_fee_share_bps,
+    });
+    
+    msg!("Stake fee share: {} bps", stake_fee_share_bps);
+    
+    Ok(())
+}
+
+pub fn sync_stake_rewards(ctx: Context<SyncStakeRewards>) -> Result<()> {
+    let emitter = event_emitter!(ctx);
+    let a = ctx.accounts;
+    sync_pool(
+        &mut a.theme,
+        &a.vault,
+        &a.vault_token_account,
+        &mut a.stake_pool,
+        &a.pool_token_account,
+        &a.token_program,
+        &emitter,
+    )
+}
+
+pub fn stake_tokens(ctx: Context<StakeAction>, amount: u64) -> Result<()> {    sync_action(ctx.accounts, &event_emitter!(ctx))?;
+    init_position(&mut ctx.accounts.position, ctx.accounts.theme.key(), ctx.accounts.owner.key(), ctx.bumps.position);
+    
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner_token_account.to_account_info(),
+                to: ctx.accounts.pool_token_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    
+    let pool = &mut ctx.accounts.stake_pool;
+    let position = &mut ctx.accounts.position;
+    settle_position(pool, position)?;
+    position.amount = position.amount
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    pool.total_staked = pool.total_staked
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    reset_reward_debt(pool, position);
+    
+    emit_cpi!(Staked {
+        theme: pool.theme,
+        owner: position.owner,
+        amount,
+        total_staked: pool.total_staked,
+    });
+    
+    msg!("Staked {} tokens", amount);
+    
+    Ok(())
+}
+
+/// 申请解除质押：立即停止计息，冷却期满后提取 (再次申请会重置冷却)
+pub fn request_unstake(ctx: Context<StakeAction>, amount: u64) -> Result<()> {    sync_action(ctx.accounts, &event_emitter!(ctx))?;
+    
+    let available_at = Clock::get()?.unix_timestamp
+        .checked_add(STAKE_UNSTAKE_COOLDOWN)
+        .ok_or(ConsensusError::Overflow)?;
+    
+    let pool = &mut ctx.accounts.stake_pool;
+    let position = &mut ctx.accounts.position;
+    settle_position(pool, position)?;
+    position.amount -= amount;
+    position.unstaking_amount = position.unstaking_amount
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    position.unstake_available_at = available_at;
+    pool.total_staked -= amount;
+    pool.total_unstaking = pool.total_unstaking
+        .checked_add(amount)
+        .ok_or(ConsensusError::Overflow)?;
+    reset_reward_debt(pool, position);
+    
+    emit_cpi!(UnstakeRequested {
+        theme: pool.theme,
+        owner: position.owner,
+        amount,
+        available_at,
+    });
+    
+    msg!("Unstake requested: {} tokens, available at {}", amount, available_at);
+    
+    Ok(())
+}
+
+pub fn withdraw_unstaked(ctx: Context<StakeAction>) -> Result<()> {
+    let amount = ctx.accounts.position.unstaking_amount;
+    
+    transfer_from_pool(&ctx, ctx.accounts.owner_token_account.to_account_info(), amount)?;
+    
+    let pool = &mut ctx.accounts.stake_pool;
+    let position = &mut ctx.accounts.position;
+    position.unstaking_amount = 0;
+    position.unstake_available_at = 0;
+    pool.total_unstaking = pool.total_unstaking.saturating_sub(amount);
+    
+    emit_cpi!(Unstaked {
+        theme: pool.theme,
+        owner: position.owner,
+        amount,
+    });
+    
+    msg!("Withdrew {} unstaked tokens", amount);
+    
+    Ok(())
+}
+
+pub fn claim_stake_rewards(ctx: Context<StakeAction>) -> Result<()> {
+    sync_action(ctx.accounts, &event_emitter!(ctx))?;
+    
+    {
+        let pool = &ctx.accounts.stake_pool;
+        let position = &mut ctx.accounts.position;
+        settle_position(pool, position)?;
+        reset_reward_debt(pool, position);
+    }
+    let sol_amount = ctx.accounts.position.pending_sol;
+    let token_amount = ctx.accounts.position.pending_tokens;
+    require!(sol_amount > 0 || token_amount > 0, ConsensusError::InvalidAmount);
+    
+    if sol_amount > 0 {
+        let pool_info = ctx.accounts.stake_pool.to_account_info();
+        let owner_info = ctx.accounts.owner.to_account_info();
+        **pool_info.try_borrow_mut_lamports()? = pool_info.lamports()
+            .checked_sub(sol_amount)
+            .ok_or(ConsensusError::Overflow)?;
+        **owner_info.try_borrow_mut_lamports()? = owner_info.lamports()
+            .checked_add(sol_amount)
+            .ok_or(ConsensusError::Overflow)?;
+    }
+    if token_amount > 0 {
+        transfer_from_pool(&ctx, ctx.accounts.owner_token_account.to_account_info(), token_amount)?;
+        ctx.accounts.stake_pool.token_rewards_reserved = ctx.accounts.stake_pool.token_rewards_reserved
+            .saturating_sub(token_amount);
+    }
+    
+    let position = &mut ctx.accounts.position;
+    position.pending_sol = 0;
+    position.pending_tokens = 0;
+    
+    emit_cpi!(StakeRewardsClaimed {
+        theme: position.theme,
+        owner: position.owner,
+        sol_amount,
+        token_amount,
+    });
+    
+    msg!("Stake rewards claimed: {} lamports, {} tokens", sol_amount, token_amount);
+    
+    Ok(())
+}
+
+#[inline(never)]
+fn sync_action<'info>(a: &mut StakeAction<'info>, emitter: &EventEmitter<'info>) -> Result<()> {
+    sync_pool(
+        &mut a.theme,
+        &a.vault,
+        &a.vault_token_account,
+        &mut a.stake_pool,
+        &a.pool_token_account,
+        &a.token_program,
+        emitter,
+    )
+}
+
+/// 同步奖励到质押池：交易回购费的质押分成 (lamports) 从金库划入池子 PDA；
+/// 金库 ATA 中超出曲线储备的新增代币 (结算回购贡献) 按分成比例转入池子 ATA。
+/// 无人质押时不同步，奖励留待首批质押者
+#[inline(never)]
+fn sync_pool<'info>(
+    theme: &mut Account<'info, Theme>,
+    vault: &Account<'info, ThemeVault>,
+    vault_token_account: &Account<'info, TokenAccount>,
+    pool: &mut Account<'info, StakePool>,
+    pool_token_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    emitter: &EventEmitter<'info>,
+) -> Result<()> {
+    if pool.total_staked == 0 {
+        return Ok(());
+    }
+    let total_staked = pool.total_staked as u128;
+    
+    let sol_amount = theme.staking_rewards_accrued;
+    if sol_amount > 0 {
+        let vault_info = vault.to_account_info();
+        let pool_info = pool.to_account_info();
+        **vault_info.try_borrow_mut_lamports()? = vault_info.lamports()
+            .checked_sub(sol_amount)
+            .ok_or(ConsensusError::Overflow)?;
+        **pool_info.try_borrow_mut_lamports()? = pool_info.lamports()
+            .checked_add(sol_amount)
+            .ok_or(ConsensusError::Overflow)?;
+        theme.staking_rewards_accrued = 0;
+        pool.acc_sol_per_share = pool.acc_sol_per_share
+            .checked_add(sol_amount as u128 * STAKE_ACC_PRECISION / total_staked)
+            .ok_or(ConsensusError::Overflow)?;
+    }
+    
+    let excess = vault_token_account.amount.saturating_sub(theme.token_reserves);
+    let fresh = excess.saturating_sub(pool.vault_excess_synced);
+    let token_amount = (fresh as u128 * theme.stake_fee_share_bps as u128
+        / BPS_DENOMINATOR as u128) as u64;
+    if token_amount > 0 {
+        let theme_id_bytes = theme.theme_id.to_le_bytes();
+        let bump_bytes = [theme.vault_bump];
+        let seeds: &[&[u8]] = &[
+            b"theme_vault",
+            theme.creator.as_ref(),
+            theme_id_bytes.as_ref(),
+            bump_bytes.as_ref(),
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: vault_token_account.to_account_info(),
+                    to: pool_token_account.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                &[seeds],
+            ),
+            token_amount,
+        )?;
+        pool.token_rewards_reserved = pool.token_rewards_reserved
+            .checked_add(token_amount)
+            .ok_or(ConsensusError::Overflow)?;
+        pool.acc_token_per_share = pool.acc_token_per_share
+            .checked_add(token_amount as u128 * STAKE_ACC_PRECISION / total_staked)
+            .ok_or(ConsensusError::Overflow)?;
+    }
+    // 剩余部分视为已处理，留在金库供回购/迁移使用
+    pool.vault_excess_synced = excess - token_amount;
+    
+    if sol_amount > 0 || token_amount > 0 {
+        emitter.emit(StakeRewardsSynced {
+            theme: theme.key(),
+            sol_amount,
+            token_amount,
+        })?;
+    }
+    
+    Ok(())
+}
+
+fn init_position(position: &mut StakePosition, theme: Pubkey, owner: Pubkey, bump: u8) {
+    if position.owner == Pubkey::default() {
+        position.theme = theme;
+        position.owner = owner;
+        position.bump = bump;
+    }
+}
+
+/// 把仓位自上次结算以来的奖励记入 pending
+fn settle_position(pool: &StakePool, position: &mut StakePosition) -> Result<()> {
+    if position.amount == 0 {
+        return Ok(());
+    }
+    let amount = position.amount as u128;
+    let sol = (amount * pool.acc_sol_per_share / STAKE_ACC_PRECISION)
+        .saturating_sub(position.sol_reward_debt);
+    let tokens = (amount * pool.acc_token_per_share / STAKE_ACC_PRECISION)
+        .saturating_sub(position.token_reward_debt);
+    position.pending_sol = position.pending_sol
+        .checked_add(u64::try_from(sol).map_err(|_| ConsensusError::Overflow)?)
+        .ok_or(ConsensusError::Overflow)?;
+    position.pending_tokens = position.pending_tokens
+        .checked_add(u64::try_from(tokens).map_err(|_| ConsensusError::Overflow)?)
+        .ok_or(ConsensusError::Overflow)?;
+    Ok(())
+}
+
+fn reset_reward_debt(pool: &StakePool, position: &mut StakePosition) {
+    let amount = position.amount as u128;
+    position.sol_reward_debt = amount * pool.acc_sol_per_share / STAKE_ACC_PRECISION;
+    position.token_reward_debt = amount * pool.acc_token_per_share / STAKE_ACC_PRECISION;
+}
+
+/// 由质押池 PDA 签名从池子 ATA 转出代币
+#[inline(never)]
+fn transfer_from_pool<'info>(
+    ctx: &Context<StakeAction<'info>>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let theme_key = ctx.accounts.theme.key();
+    let bump_bytes = [ctx.accounts.stake_pool.bump];
+    let seeds: &[&[u8]] = &[b"stake_pool", theme_key.as_ref(), bump_bytes.as_ref()];
+    
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.pool_token_account.to_account_info(),
+                to,
+                authority: ctx.accounts.stake_pool.to_account_info(),
+            },
+            &[seeds],
+        ),
+        amount,
+    )
+}
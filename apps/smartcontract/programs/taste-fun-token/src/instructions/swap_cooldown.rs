@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use taste_fun_shared::*;
+use crate::{Theme, TraderState};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetSwapCooldown<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    pub creator: Signer<'info>,
+}
+
+/// 创建者设置反向交易冷却时间 (0 表示关闭)
+pub fn set_swap_cooldown(ctx: Context<SetSwapCooldown>, swap_cooldown_secs: u32) -> Result<()> {    ctx.accounts.theme.swap_cooldown_secs = swap_cooldown_secs;
+    
+    msg!("Swap cooldown set to {} seconds", swap_cooldown_secs);
+    
+    Ok(())
+}
+
+/// 反向交易冷却：同一钱包买入后需等待冷却时间才能卖出，反之亦然；同向交易不受限
+/// 冷却关闭时不要求携带 TraderState
+pub(crate) fn check_swap_cooldown(
+    theme: &Theme,
+    theme_key: Pubkey,
+    trader_state: Option<&mut Account<TraderState>>,
+    trader: Pubkey,
+    bump: Option<u8>,
+    is_buy: bool,
+) -> Result<()> {
+    if theme.swap_cooldown_secs == 0 {
+        return Ok(());
+    }
+    let trader_state = trader_state.ok_or(ConsensusError::TraderStateRequired)?;
+    let now = Clock::get()?.unix_timestamp;
+    
+    if trader_state.trader == Pubkey::default() {
+        trader_state.theme = theme_key;
+        trader_state.trader = trader;
+        trader_state.bump = bump.ok_or(ConsensusError::TraderStateRequired)?;
+    } else if trader_state.last_trade_is_buy != is_buy {
+        require!(
+            now >= trader_state.last_trade_ts.saturating_add(theme.swap_cooldown_secs as i64),
+            ConsensusError::SwapCooldownActive
+        );
+    }
+    
+    trader_state.last_trade_ts = now;
+    trader_state.last_trade_is_buy = is_buy;
+    
+    Ok(())
+}
@@ -0,0 +1,353 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::associated_token::AssociatedToken;
+use taste_fun_shared::*;
+use super::swap_cooldown::check_swap_cooldown;
+use super::swap_sol_for_tokens::{calculate_fee_portion, calculate_total_fee};
+use crate::{check_swap_deadline, emit_reserve_health, update_price_oracle, PriceOracle, Theme, TraderState, ThemeVault, TradingConfiguration, TokensSwapped};
+
+/// USDC 计价主题的买卖共用账户：计价资产存放在金库的 ATA 中
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SwapQuoteToken<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Box<Account<'info, Theme>>,
+    
+    #[account(
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump
+    )]
+    pub vault: Account<'info, ThemeVault>,
+    
+    /// Theme token mint
+    #[account(
+        mut,
+        address = theme.token_mint @ ConsensusError::InvalidMint
+    )]
+    pub token_mint: Box<Account<'info, Mint>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = token_mint,
+        associated_token::authority = user,
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(address = theme.quote_mint @ ConsensusError::QuoteMintMismatch)]
+    pub quote_mint: Box<Account<'info, Mint>>,
+    
+    /// 金库的计价资产账户（储备、回购池与待提取的费用）
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = quote_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_quote_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        token::mint = quote_mint,
+        token::authority = user,
+    )]
+    pub user_quote_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        seeds = [b"trading_config"],
+        bump
+    )]
+    pub trading_config: Box<Account<'info, TradingConfiguration>>,
+    
+    /// CHECK: core 程序的全局协议配置，由 protocol_config_active 校验
+    pub protocol_config: UncheckedAccount<'info>,
+    
+    /// 主题 TWAP 价格预言机（首次交易时创建，价格以计价资产为单位）
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + PriceOracle::SPACE,
+        seeds = [b"price_oracle", theme.key().as_ref()],
+        bump
+    )]
+    pub price_oracle: Box<Account<'info, PriceOracle>>,
+    
+    /// 主题启用冷却时必须携带：交易者最近一次交易记录
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + TraderState::SPACE,
+        seeds = [b"trader_state", theme.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub trader_state: Option<Box<Account<'info, TraderState>>>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 用计价资产买入主题代币；曲线与费用拆分与 SOL 路径一致
+/// 推荐分成与开盘单钱包限额以 lamports 记账，仅适用于 SOL 主题
+pub fn swap_quote_for_tokens(
+    ctx: Context<SwapQuoteToken>,
+    quote_amount: u64,
+    min_tokens_out: u64,
+    deadline_unix: Option<i64>,
+) -> Result<()> {
+    check_swap_deadline(deadline_unix)?;
+    check_swap_cooldown(
+        &ctx.accounts.theme,
+        ctx.accounts.theme.key(),
+        ctx.accounts.trader_state.as_deref_mut(),
+        ctx.accounts.user.key(),
+        ctx.bumps.trader_state,
+        true,
+    )?;
+    require!(quote_amount >= MIN_USDC_TRADE, ConsensusError::InvalidAmount);
+    
+    let config = &ctx.accounts.trading_config;
+    let fee_bps = ctx.accounts.theme.current_fee_bps(config.trade_fee_bps, Clock::get()?.slot);
+    let tokens_out = ctx.accounts.theme.buy_tokens(quote_amount, fee_bps)?;
+    require!(
+        tokens_out >= min_tokens_out,
+        ConsensusError::SlippageExceeded
+    );
+    require!(
+        tokens_out <= ctx.accounts.theme.token_reserves,
+        ConsensusError::InsufficientReserves
+    );
+    
+    let total_fee = calculate_total_fee(quote_amount, fee_bps)?;
+    let (buyback_fee, platform_fee, creator_fee) = split_fees(total_fee, config)?;
+    let quote_to_reserves = quote_amount
+        .checked_sub(total_fee)
+        .ok_or(ConsensusError::Overflow)?;
+    let vault_amount = quote_to_reserves
+        .checked_add(buyback_fee)
+        .and_then(|x| x.checked_add(platform_fee))
+        .and_then(|x| x.checked_add(creator_fee))
+        .ok_or(ConsensusError::Overflow)?;
+    
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_quote_account.to_account_info(),
+                to: ctx.accounts.vault_quote_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        vault_amount,
+    )?;
+    transfer_from_vault(
+        &ctx,
+        ctx.accounts.vault_token_account.to_account_info(),
+        ctx.accounts.user_token_account.to_account_info(),
+        tokens_out,
+    )?;
+    ctx.accounts.vault_token_account.reload()?;
+    ctx.accounts.vault_quote_account.reload()?;
+    
+    let theme = &mut ctx.accounts.theme;
+    theme.record_slot_volume(
+        Clock::get()?.slot,
+        quote_amount,
+        true,
+        ctx.accounts.trading_config.max_slot_volume_bps,
+    )?;
+    theme.sol_reserves = theme.sol_reserves
+        .checked_add(quote_to_reserves)
+        .ok_or(ConsensusError::Overflow)?;
+    theme.token_reserves = theme.token_reserves
+        .checked_sub(tokens_out)
+        .ok_or(ConsensusError::Overflow)?;
+    accrue_fees(theme, buyback_fee, platform_fee, creator_fee)?;
+    
+    update_price_oracle(&mut ctx.accounts.price_oracle, theme.key(), theme, ctx.bumps.price_oracle)?;
+    
+    emit_cpi!(TokensSwapped {
+        theme: theme.key(),
+        user: ctx.accounts.user.key(),
+        sol_amount: quote_amount,
+        token_amount: tokens_out,
+        is_buy: true,
+        new_sol_reserves: theme.sol_reserves,
+        new_token_reserves: theme.token_reserves,
+    });
+    
+    emit_reserve_health(
+        &event_emitter!(ctx),
+        theme.key(),
+        theme,
+        ctx.accounts.vault_quote_account.amount,
+        ctx.accounts.vault_token_account.amount,
+    )?;
+    
+    msg!("Swapped {} quote for {} tokens", quote_amount, tokens_out);
+    
+    Ok(())
+}
+
+/// 卖出主题代币换回计价资产
+pub fn swap_tokens_for_quote(
+    ctx: Context<SwapQuoteToken>,
+    token_amount: u64,
+    min_quote_out: u64,
+    deadline_unix: Option<i64>,
+) -> Result<()> {
+    check_swap_deadline(deadline_unix)?;
+    check_swap_cooldown(
+        &ctx.accounts.theme,
+        ctx.accounts.theme.key(),
+        ctx.accounts.trader_state.as_deref_mut(),
+        ctx.accounts.user.key(),
+        ctx.bumps.trader_state,
+        false,
+    )?;
+    require!(token_amount >= MIN_TOKEN_STAKE, ConsensusError::InvalidAmount);
+    
+    let config = &ctx.accounts.trading_config;
+    let fee_bps = ctx.accounts.theme.current_fee_bps(config.trade_fee_bps, Clock::get()?.slot);
+    let quote_out = ctx.accounts.theme.sell_sol(token_amount, fee_bps)?;
+    require!(
+        quote_out >= min_quote_out,
+        ConsensusError::SlippageExceeded
+    );
+    require!(
+        quote_out <= ctx.accounts.theme.sol_reserves,
+        ConsensusError::InsufficientReserves
+    );
+    
+    let quote_before_fee = ctx.accounts.theme.sell_sol(token_amount, 0)?;
+    let total_fee = quote_before_fee
+        .checked_sub(quote_out)
+        .ok_or(ConsensusError::Overflow)?;
+    let (buyback_fee, platform_fee, creator_fee) = split_fees(total_fee, config)?;
+    
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        token_amount,
+    )?;
+    transfer_from_vault(
+        &ctx,
+        ctx.accounts.vault_quote_account.to_account_info(),
+        ctx.accounts.user_quote_account.to_account_info(),
+        quote_out,
+    )?;
+    ctx.accounts.vault_token_account.reload()?;
+    ctx.accounts.vault_quote_account.reload()?;
+    
+    let theme = &mut ctx.accounts.theme;
+    theme.record_slot_volume(
+        Clock::get()?.slot,
+        quote_before_fee,
+        false,
+        ctx.accounts.trading_config.max_slot_volume_bps,
+    )?;
+    theme.sol_reserves = theme.sol_reserves
+        .checked_sub(quote_before_fee)
+        .ok_or(ConsensusError::Overflow)?;
+    theme.token_reserves = theme.token_reserves
+        .checked_add(token_amount)
+        .ok_or(ConsensusError::Overflow)?;
+    accrue_fees(theme, buyback_fee, platform_fee, creator_fee)?;
+    
+    update_price_oracle(&mut ctx.accounts.price_oracle, theme.key(), theme, ctx.bumps.price_oracle)?;
+    
+    emit_cpi!(TokensSwapped {
+        theme: theme.key(),
+        user: ctx.accounts.user.key(),
+        sol_amount: quote_out,
+        token_amount,
+        is_buy: false,
+        new_sol_reserves: theme.sol_reserves,
+        new_token_reserves: theme.token_reserves,
+    });
+    
+    emit_reserve_health(
+        &event_emitter!(ctx),
+        theme.key(),
+        theme,
+        ctx.accounts.vault_quote_account.amount,
+        ctx.accounts.vault_token_account.amount,
+    )?;
+    
+    msg!("Swapped {} tokens for {} quote", token_amount, quote_out);
+    
+    Ok(())
+}
+
+/// 按交易配置拆分 (回购, 平台, 创建者) 费用
+#[inline(always)]
+fn split_fees(total_fee: u64, config: &TradingConfiguration) -> Result<(u64, u64, u64)> {
+    let buyback_fee = calculate_fee_portion(total_fee, config.buyback_fee_split_bps)?;
+    let platform_fee = calculate_fee_portion(total_fee, config.platform_fee_split_bps)?;
+    let creator_fee = calculate_fee_portion(total_fee, config.creator_fee_split_bps)?;
+    Ok((buyback_fee, platform_fee, creator_fee))
+}
+
+/// 费用记账：回购池与待提取费用均留在金库的计价资产账户中
+fn accrue_fees(theme: &mut Theme, buyback_fee: u64, platform_fee: u64, creator_fee: u64) -> Result<()> {
+    theme.accrue_buyback_fee(buyback_fee)?;
+    theme.platform_fees_accrued = theme.platform_fees_accrued
+        .checked_add(platform_fee)
+        .ok_or(ConsensusError::Overflow)?;
+    theme.creator_fees_accrued = theme.creator_fees_accrued
+        .checked_add(creator_fee)
+        .ok_or(ConsensusError::Overflow)?;
+    Ok(())
+}
+
+/// 由金库 PDA 签名转出 SPL 代币
+#[inline(never)]
+fn transfer_from_vault<'info>(
+    ctx: &Context<SwapQuoteToken<'info>>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let theme = &ctx.accounts.theme;
+    let theme_id_bytes = theme.theme_id.to_le_bytes();
+    let bump_bytes = [theme.vault_bump];
+    let seeds: &[&[u8]] = &[
+        b"theme_vault",
+        theme.creator.as_ref(),
+        theme_id_bytes.as_ref(),
+        bump_bytes.as_ref(),
+    ];
+    
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from,
+                to,
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            &[seeds],
+        ),
+        amount,
+    )
+}
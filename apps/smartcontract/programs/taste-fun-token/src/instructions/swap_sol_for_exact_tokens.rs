@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use taste_fun_shared::*;
+use crate::check_swap_deadline;
+use super::swap_sol_for_tokens::{execute_buy, SwapSolForTokens};
+
+/// 精确输出买入：指定代币数量，按曲线反推所需 SOL，并以 max_sol_in 作为滑点上限
+/// 账户与 swap_sol_for_tokens 完全相同
+pub fn swap_sol_for_exact_tokens(
+    ctx: Context<SwapSolForTokens>,
+    token_amount: u64,
+    max_sol_in: u64,
+    deadline_unix: Option<i64>,
+) -> Result<()> {
+    check_swap_deadline(deadline_unix)?;
+    let config = &ctx.accounts.trading_config;
+    
+    // Validate token mint matches theme
+    require!(
+        ctx.accounts.token_mint.key() == ctx.accounts.theme.token_mint,
+        ConsensusError::InvalidMint
+    );
+    
+    require!(
+        token_amount <= ctx.accounts.theme.token_reserves,
+        ConsensusError::InsufficientReserves
+    );
+    
+    // 反推所需 SOL（含手续费）
+    let fee_bps = ctx.accounts.theme.current_fee_bps(config.trade_fee_bps, Clock::get()?.slot);
+    let sol_amount = ctx.accounts.theme.buy_sol_for_tokens(token_amount, fee_bps)?;
+    
+    require!(
+        sol_amount <= max_sol_in,
+        ConsensusError::SlippageExceeded
+    );
+    require!(
+        sol_amount >= config.min_sol_trade(),
+        ConsensusError::InvalidAmount
+    );
+    
+    msg!("Exact-out buy: {} tokens for {} SOL (max {})", token_amount, sol_amount, max_sol_in);
+    
+    execute_buy(ctx, sol_amount, token_amount, fee_bps)
+}
@@ -1,10 +1,13 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, spl_token, CloseAccount, Mint, Token, TokenAccount, Transfer};
 use anchor_spl::associated_token::AssociatedToken;
 use taste_fun_shared::*;
-use crate::{Theme, ThemeVault, TradingConfiguration, TokensSwapped};
+use super::referral::{credit_referral, referral_cut};
+use super::swap_cooldown::check_swap_cooldown;
+use crate::{check_launch_limit, check_swap_deadline, emit_reserve_health, update_price_oracle, LaunchLimit, PriceOracle, Referral, Theme, TraderState, ThemeVault, TradingConfiguration, TokensSwapped, PointsAccrued};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct SwapSolForTokens<'info> {
     #[account(
@@ -46,20 +49,77 @@ pub struct SwapSolForTokens<'info> {
     )]
     pub trading_config: Account<'info, TradingConfiguration>,
     
-    #[account(mut)]
-    pub user: Signer<'info>,
+    /// CHECK: core 程序的全局协议配置，由 protocol_config_active 校验
+    pub protocol_config: UncheckedAccount<'info>,
     
-    /// CHECK: Vault SOL account（存储净SOL和回购费）
-    #[account(mut)]
-    pub vault_sol_account: AccountInfo<'info>,
+    /// 主题 TWAP 价格预言机（首次交易时创建）
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + PriceOracle::SPACE,
+        seeds = [b"price_oracle", theme.key().as_ref()],
+        bump
+    )]
+    pub price_oracle: Box<Account<'info, PriceOracle>>,
     
-    /// CHECK: Platform treasury account（接收平台费）
-    #[account(mut)]
-    pub platform_treasury: AccountInfo<'info>,
+    /// 可选：推荐人注册表 PDA，携带时平台费的一部分分给推荐人
+    #[account(
+        mut,
+        seeds = [b"referral", referral.referrer.as_ref()],
+        bump = referral.bump
+    )]
+    pub referral: Option<Account<'info, Referral>>,
+    
+    /// 开盘窗口内必须携带：买家在本主题的累计买入记录
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + LaunchLimit::SPACE,
+        seeds = [b"launch_limit", theme.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub launch_limit: Option<Box<Account<'info, LaunchLimit>>>,
+    
+    /// 主题启用冷却时必须携带：交易者最近一次交易记录
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + TraderState::SPACE,
+        seeds = [b"trader_state", theme.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub trader_state: Option<Box<Account<'info, TraderState>>>,
+    
+    /// 可选：以 wSOL 支付 (需同时携带 wsol_mint 与 vault_wsol_account)
+    #[account(
+        mut,
+        token::mint = wsol_mint,
+        token::authority = user,
+    )]
+    pub user_wsol_account: Option<Box<Account<'info, TokenAccount>>>,
+    
+    #[account(address = spl_token::native_mint::ID)]
+    pub wsol_mint: Option<Box<Account<'info, Mint>>>,
+    
+    /// 金库的临时 wSOL 账户：收到 wSOL 后立即关闭解包为原生 SOL
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = wsol_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_wsol_account: Option<Box<Account<'info, TokenAccount>>>,
     
-    /// CHECK: Theme creator account（接收创建者费）
     #[account(mut)]
-    pub theme_creator: AccountInfo<'info>,
+    pub user: Signer<'info>,
+    
+    /// CHECK: Vault SOL account（即主题金库本身，存储净SOL、回购费与待提取的平台/创建者费）
+    #[account(
+        mut,
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump
+    )]
+    pub vault_sol_account: AccountInfo<'info>,
     
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -67,10 +127,12 @@ pub struct SwapSolForTokens<'info> {
 }
 
 pub fn swap_sol_for_tokens(
-    mut ctx: Context<SwapSolForTokens>,
+    ctx: Context<SwapSolForTokens>,
     sol_amount: u64,
     min_tokens_out: u64,
+    deadline_unix: Option<i64>,
 ) -> Result<()> {
+    check_swap_deadline(deadline_unix)?;
     let config = &ctx.accounts.trading_config;
     
     // Validate token mint matches theme
@@ -80,27 +142,16 @@ pub fn swap_sol_for_tokens(
     );
     
     require!(
-        ctx.accounts.theme_creator.key() == ctx.accounts.theme.creator,
-        ConsensusError::Unauthorized
-    );
-    require!(
-        ctx.accounts.theme.status == THEME_STATUS_ACTIVE,
-        ConsensusError::InvalidTheme
-    );
-    require!(
-        sol_amount >= MIN_SOL_TRADE,
+        sol_amount >= config.min_sol_trade(),
         ConsensusError::InvalidAmount
     );
     
+    // 开盘费率计划生效期间按衰减后的费率收费
+    let fee_bps = ctx.accounts.theme.current_fee_bps(config.trade_fee_bps, Clock::get()?.slot);
+    
     // Calculate tokens out using bonding curve
-    // 注意参数顺序：sol_amount, token_reserves, sol_reserves, fee_bps
-    // 这与pumpfun的恒定乘积公式一致
-    let tokens_out = calculate_buy_tokens(
-        sol_amount,
-        ctx.accounts.theme.token_reserves,  // y: 代币储备 
-        ctx.accounts.theme.sol_reserves,    // x: SOL储备
-        config.trade_fee_bps,
-    )?;
+    // 每段都是pumpfun式的恒定乘积公式，储备包含虚拟部分，跨段交易逐段计算
+    let tokens_out = ctx.accounts.theme.buy_tokens(sol_amount, fee_bps)?;
     
     require!(
         tokens_out >= min_tokens_out,
@@ -111,8 +162,38 @@ pub fn swap_sol_for_tokens(
         ConsensusError::InsufficientReserves
     );
     
+    execute_buy(ctx, sol_amount, tokens_out, fee_bps)
+}
+
+/// 执行买入：分配费用、转出代币并更新储备
+/// swap_sol_for_tokens 与 swap_sol_for_exact_tokens 共用
+pub(crate) fn execute_buy(
+    mut ctx: Context<SwapSolForTokens>,
+    sol_amount: u64,
+    tokens_out: u64,
+    fee_bps: u16,
+) -> Result<()> {
+    check_launch_limit(
+        &ctx.accounts.theme,
+        ctx.accounts.theme.key(),
+        ctx.accounts.launch_limit.as_deref_mut(),
+        ctx.accounts.user.key(),
+        ctx.bumps.launch_limit,
+        sol_amount,
+    )?;
+    check_swap_cooldown(
+        &ctx.accounts.theme,
+        ctx.accounts.theme.key(),
+        ctx.accounts.trader_state.as_deref_mut(),
+        ctx.accounts.user.key(),
+        ctx.bumps.trader_state,
+        true,
+    )?;
+    
+    let config = &ctx.accounts.trading_config;
+    
     // Calculate fees according to configuration
-    let total_fee = calculate_total_fee(sol_amount, config.trade_fee_bps)?;
+    let total_fee = calculate_total_fee(sol_amount, fee_bps)?;
     
     let buyback_fee = calculate_fee_portion(
         total_fee,
@@ -140,17 +221,33 @@ pub fn swap_sol_for_tokens(
         ConsensusError::InvalidAmount
     );
     
+    // 推荐人分成从平台费中切出，直接转入推荐人 PDA
+    let referral_fee = referral_cut(
+        ctx.accounts.referral.as_ref(),
+        &ctx.accounts.user.key(),
+        platform_fee,
+        config.referral_fee_split_bps,
+    )?;
+    let platform_fee = platform_fee - referral_fee;
+    let calculated_total = calculated_total - referral_fee;
+    
     let sol_to_reserves = sol_amount
         .checked_sub(total_fee)
         .ok_or(ConsensusError::Overflow)?;
     
-    // 执行多重转账：净SOL到vault，费用分别转账
-    // 1. 净SOL + 回购费 转给 vault（用于流动性和回购）
+    // 净SOL、回购费与平台/创建者费一次性转入 vault，平台/创建者费记账后按需提取
     let vault_amount = sol_to_reserves
-        .checked_add(buyback_fee)
+        .checked_add(calculated_total)
         .ok_or(ConsensusError::Overflow)?;
     
-    if vault_amount > 0 {
+    // wSOL 支付时全额解包进入金库，推荐分成再由金库划出
+    let pays_with_wsol = ctx.accounts.user_wsol_account.is_some();
+    if pays_with_wsol {
+        let wsol_amount = vault_amount
+            .checked_add(referral_fee)
+            .ok_or(ConsensusError::Overflow)?;
+        unwrap_wsol_into_vault(&ctx, wsol_amount)?;
+    } else if vault_amount > 0 {
         system_program::transfer(
             CpiContext::new(
                 ctx.accounts.system_program.to_account_info(),
@@ -163,39 +260,42 @@ pub fn swap_sol_for_tokens(
         )?;
     }
     
-    // 2. 平台费转给平台财库
-    if platform_fee > 0 {
-        system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.user.to_account_info(),
-                    to: ctx.accounts.platform_treasury.to_account_info(),
-                },
-            ),
-            platform_fee,
-        )?;
-    }
-    
-    // 3. 创建者费转给主题创建者
-    if creator_fee > 0 {
-        system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.user.to_account_info(),
-                    to: ctx.accounts.theme_creator.to_account_info(),
-                },
-            ),
-            creator_fee,
-        )?;
+    if let Some(referral) = ctx.accounts.referral.as_mut() {
+        if referral_fee > 0 && pays_with_wsol {
+            let referral_info = referral.to_account_info();
+            **ctx.accounts.vault_sol_account.try_borrow_mut_lamports()? = ctx.accounts.vault_sol_account.lamports()
+                .checked_sub(referral_fee)
+                .ok_or(ConsensusError::Overflow)?;
+            **referral_info.try_borrow_mut_lamports()? = referral_info.lamports()
+                .checked_add(referral_fee)
+                .ok_or(ConsensusError::Overflow)?;
+        } else if referral_fee > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.user.to_account_info(),
+                        to: referral.to_account_info(),
+                    },
+                ),
+                referral_fee,
+            )?;
+        }
+        credit_referral(&event_emitter!(ctx), referral, ctx.accounts.theme.key(), ctx.accounts.user.key(), referral_fee)?;
     }
     
     // Transfer tokens from vault to user - 优化版本避免栈分配
     transfer_tokens_to_user_optimized(&mut ctx, tokens_out)?;
+    ctx.accounts.vault_token_account.reload()?;
     
     // Update theme state - now we can mutably borrow
     let theme = &mut ctx.accounts.theme;
+    theme.record_slot_volume(
+        Clock::get()?.slot,
+        sol_amount,
+        true,
+        ctx.accounts.trading_config.max_slot_volume_bps,
+    )?;
     
     // 只有净SOL进入储备，回购费单独累积
     theme.sol_reserves = theme.sol_reserves
@@ -204,12 +304,17 @@ pub fn swap_sol_for_tokens(
     theme.token_reserves = theme.token_reserves
         .checked_sub(tokens_out)
         .ok_or(ConsensusError::Overflow)?;
-    theme.buyback_pool = theme.buyback_pool
-        .checked_add(buyback_fee)
+    theme.accrue_buyback_fee(buyback_fee)?;
+    theme.platform_fees_accrued = theme.platform_fees_accrued
+        .checked_add(platform_fee)
+        .ok_or(ConsensusError::Overflow)?;
+    theme.creator_fees_accrued = theme.creator_fees_accrued
+        .checked_add(creator_fee)
         .ok_or(ConsensusError::Overflow)?;
-    // platform_fee_collected, creator_fee_collected, total_traded_volume 移除
     
-    emit!(TokensSwapped {
+    update_price_oracle(&mut ctx.accounts.price_oracle, theme.key(), theme, ctx.bumps.price_oracle)?;
+    
+    emit_cpi!(TokensSwapped {
         theme: theme.key(),
         user: ctx.accounts.user.key(),
         sol_amount,
@@ -219,6 +324,24 @@ pub fn swap_sol_for_tokens(
         new_token_reserves: theme.token_reserves,
     });
     
+    // 按 SOL 交易额累计赛季积分
+    let points = trade_points(sol_amount);
+    if points > 0 {
+        emit_cpi!(PointsAccrued {
+            user: ctx.accounts.user.key(),
+            kind: POINTS_KIND_TRADE,
+            points,
+        });
+    }
+    
+    emit_reserve_health(
+        &event_emitter!(ctx),
+        theme.key(),
+        theme,
+        ctx.accounts.vault_sol_account.lamports(),
+        ctx.accounts.vault_token_account.amount,
+    )?;
+    
     msg!("Swapped {} SOL for {} tokens", sol_amount, tokens_out);
     msg!("New reserves - SOL: {}, Tokens: {}", theme.sol_reserves, theme.token_reserves);
     
@@ -227,7 +350,7 @@ pub fn swap_sol_for_tokens(
 
 /// 计算总交易费用
 #[inline(always)]
-fn calculate_total_fee(sol_amount: u64, fee_bps: u16) -> Result<u64> {
+pub(crate) fn calculate_total_fee(sol_amount: u64, fee_bps: u16) -> Result<u64> {
     Ok((sol_amount as u128)
         .checked_mul(fee_bps as u128)
         .ok_or(ConsensusError::Overflow)?
@@ -238,7 +361,7 @@ fn calculate_total_fee(sol_amount: u64, fee_bps: u16) -> Result<u64> {
 
 /// 计算费用分配部分
 #[inline(always)]
-fn calculate_fee_portion(total_fee: u64, split_bps: u16) -> Result<u64> {
+pub(crate) fn calculate_fee_portion(total_fee: u64, split_bps: u16) -> Result<u64> {
     Ok((total_fee as u128)
         .checked_mul(split_bps as u128)
         .ok_or(ConsensusError::Overflow)?
@@ -247,6 +370,60 @@ fn calculate_fee_portion(total_fee: u64, split_bps: u16) -> Result<u64> {
         as u64)
 }
 
+/// 用户以 wSOL 支付：转入金库的临时 wSOL 账户后关闭，SOL 以原生 lamports 进入金库
+/// 临时账户的租金在同一笔交易内退还给用户
+#[inline(never)]
+fn unwrap_wsol_into_vault(ctx: &Context<SwapSolForTokens>, amount: u64) -> Result<()> {
+    let (Some(user_wsol), Some(vault_wsol)) = (
+        ctx.accounts.user_wsol_account.as_ref(),
+        ctx.accounts.vault_wsol_account.as_ref(),
+    ) else {
+        return err!(ConsensusError::InvalidWsolAccounts);
+    };
+    let rent_reserve: u64 = Option::from(vault_wsol.is_native)
+        .ok_or(ConsensusError::InvalidWsolAccounts)?;
+    
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: user_wsol.to_account_info(),
+                to: vault_wsol.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    
+    let theme = &ctx.accounts.theme;
+    let theme_id_bytes = theme.theme_id.to_le_bytes();
+    let bump_bytes = [theme.vault_bump];
+    let seeds: &[&[u8]] = &[
+        b"theme_vault",
+        theme.creator.as_ref(),
+        theme_id_bytes.as_ref(),
+        bump_bytes.as_ref(),
+    ];
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: vault_wsol.to_account_info(),
+            destination: ctx.accounts.vault_sol_account.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        },
+        &[seeds],
+    ))?;
+    
+    **ctx.accounts.vault_sol_account.try_borrow_mut_lamports()? = ctx.accounts.vault_sol_account.lamports()
+        .checked_sub(rent_reserve)
+        .ok_or(ConsensusError::Overflow)?;
+    **ctx.accounts.user.try_borrow_mut_lamports()? = ctx.accounts.user.lamports()
+        .checked_add(rent_reserve)
+        .ok_or(ConsensusError::Overflow)?;
+    
+    Ok(())
+}
+
 /// 转移代币到用户 - 优化版本，使用栈数组避免Vec
 #[inline(never)]
 fn transfer_tokens_to_user_optimized(
@@ -1,8 +1,11 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, spl_token, Mint, SyncNative, Token, TokenAccount, Transfer};
 use taste_fun_shared::*;
-use crate::{Theme, ThemeVault, TradingConfiguration, TokensSwapped};
+use super::referral::{credit_referral, referral_cut};
+use super::swap_cooldown::check_swap_cooldown;
+use crate::{check_swap_deadline, emit_reserve_health, update_price_oracle, PriceOracle, Referral, Theme, TraderState, ThemeVault, TradingConfiguration, TokensSwapped, PointsAccrued};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct SwapTokensForSol<'info> {
     #[account(
@@ -43,11 +46,54 @@ pub struct SwapTokensForSol<'info> {
     )]
     pub trading_config: Account<'info, TradingConfiguration>,
     
+    /// CHECK: core 程序的全局协议配置，由 protocol_config_active 校验
+    pub protocol_config: UncheckedAccount<'info>,
+    
+    /// 主题 TWAP 价格预言机（首次交易时创建）
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + PriceOracle::SPACE,
+        seeds = [b"price_oracle", theme.key().as_ref()],
+        bump
+    )]
+    pub price_oracle: Box<Account<'info, PriceOracle>>,
+    
+    /// 可选：推荐人注册表 PDA，携带时平台费的一部分分给推荐人
+    #[account(
+        mut,
+        seeds = [b"referral", referral.referrer.as_ref()],
+        bump = referral.bump
+    )]
+    pub referral: Option<Account<'info, Referral>>,
+    
+    /// 主题启用冷却时必须携带：交易者最近一次交易记录
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + TraderState::SPACE,
+        seeds = [b"trader_state", theme.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub trader_state: Option<Box<Account<'info, TraderState>>>,
+    
+    /// 可选：以 wSOL 接收卖出所得 (lamports 记入后 sync_native)
+    #[account(
+        mut,
+        token::authority = user,
+        constraint = user_wsol_account.mint == spl_token::native_mint::ID @ ConsensusError::InvalidWsolAccounts
+    )]
+    pub user_wsol_account: Option<Account<'info, TokenAccount>>,
+    
     #[account(mut)]
     pub user: Signer<'info>,
     
-    /// CHECK: Vault SOL account
-    #[account(mut)]
+    /// CHECK: Vault SOL account, must be the theme vault itself
+    #[account(
+        mut,
+        seeds = [b"theme_vault", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.vault_bump
+    )]
     pub vault_sol_account: AccountInfo<'info>,
     
     pub token_program: Program<'info, Token>,
@@ -58,7 +104,17 @@ pub fn swap_tokens_for_sol(
     ctx: Context<SwapTokensForSol>,
     token_amount: u64,
     min_sol_out: u64,
+    deadline_unix: Option<i64>,
 ) -> Result<()> {
+    check_swap_deadline(deadline_unix)?;
+    check_swap_cooldown(
+        &ctx.accounts.theme,
+        ctx.accounts.theme.key(),
+        ctx.accounts.trader_state.as_deref_mut(),
+        ctx.accounts.user.key(),
+        ctx.bumps.trader_state,
+        false,
+    )?;
     let theme = &mut ctx.accounts.theme;
     let config = &ctx.accounts.trading_config;
     
@@ -68,23 +124,15 @@ pub fn swap_tokens_for_sol(
         ConsensusError::InvalidMint
     );
     
-    require!(
-        theme.status == THEME_STATUS_ACTIVE,
-        ConsensusError::InvalidTheme
-    );
     require!(
         token_amount >= MIN_TOKEN_STAKE,
         ConsensusError::InvalidAmount
     );
     // Token balance will be checked by the token program during transfer
     
-    // Calculate SOL out using bonding curve
-    let sol_out = calculate_sell_sol(
-        token_amount,
-        theme.token_reserves,
-        theme.sol_reserves,
-        config.trade_fee_bps,
-    )?;
+    // Calculate SOL out using bonding curve (real + virtual reserves, per phase)
+    let fee_bps = theme.current_fee_bps(config.trade_fee_bps, Clock::get()?.slot);
+    let sol_out = theme.sell_sol(token_amount, fee_bps)?;
     
     require!(
         sol_out >= min_sol_out,
@@ -95,13 +143,8 @@ pub fn swap_tokens_for_sol(
         ConsensusError::InsufficientReserves
     );
     
-    // Calculate fees (already deducted in calculate_sell_sol)
-    let sol_before_fee = calculate_sell_sol(
-        token_amount,
-        theme.token_reserves,
-        theme.sol_reserves,
-        0, // No fee to get gross amount
-    )?;
+    // Calculate fees (already deducted in sell_sol)
+    let sol_before_fee = theme.sell_sol(token_amount, 0)?; // No fee to get gross amount
     
     let total_fee = sol_before_fee
         .checked_sub(sol_out)
@@ -113,18 +156,27 @@ pub fn swap_tokens_for_sol(
         .checked_div(BPS_DENOMINATOR as u128)
         .ok_or(ConsensusError::DivisionByZero)? as u64;
     
-    let _platform_fee = (total_fee as u128)
+    let platform_fee = (total_fee as u128)
         .checked_mul(config.platform_fee_split_bps as u128)
         .ok_or(ConsensusError::Overflow)?
         .checked_div(BPS_DENOMINATOR as u128)
         .ok_or(ConsensusError::DivisionByZero)? as u64;
     
-    let _creator_fee = (total_fee as u128)
+    let creator_fee = (total_fee as u128)
         .checked_mul(config.creator_fee_split_bps as u128)
         .ok_or(ConsensusError::Overflow)?
         .checked_div(BPS_DENOMINATOR as u128)
         .ok_or(ConsensusError::DivisionByZero)? as u64;
     
+    // 推荐人分成从平台费中切出，由 vault 划给推荐人 PDA
+    let referral_fee = referral_cut(
+        ctx.accounts.referral.as_ref(),
+        &ctx.accounts.user.key(),
+        platform_fee,
+        config.referral_fee_split_bps,
+    )?;
+    let platform_fee = platform_fee - referral_fee;
+    
     // Transfer tokens from user to vault
     token::transfer(
         CpiContext::new(
@@ -137,6 +189,7 @@ pub fn swap_tokens_for_sol(
         ),
         token_amount,
     )?;
+    ctx.accounts.vault_token_account.reload()?;
     
     // Transfer SOL from vault to user
     let creator_key = theme.creator;
@@ -152,23 +205,57 @@ pub fn swap_tokens_for_sol(
     **ctx.accounts.vault_sol_account.try_borrow_mut_lamports()? = ctx.accounts.vault_sol_account.lamports()
         .checked_sub(sol_out)
         .ok_or(ConsensusError::Overflow)?;
-    **ctx.accounts.user.try_borrow_mut_lamports()? = ctx.accounts.user.lamports()
+    let recipient = match ctx.accounts.user_wsol_account.as_ref() {
+        Some(user_wsol) => user_wsol.to_account_info(),
+        None => ctx.accounts.user.to_account_info(),
+    };
+    **recipient.try_borrow_mut_lamports()? = recipient.lamports()
         .checked_add(sol_out)
         .ok_or(ConsensusError::Overflow)?;
+    if ctx.accounts.user_wsol_account.is_some() {
+        token::sync_native(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            SyncNative { account: recipient },
+        ))?;
+    }
+    
+    if let Some(referral) = ctx.accounts.referral.as_mut() {
+        if referral_fee > 0 {
+            let referral_info = referral.to_account_info();
+            **ctx.accounts.vault_sol_account.try_borrow_mut_lamports()? = ctx.accounts.vault_sol_account.lamports()
+                .checked_sub(referral_fee)
+                .ok_or(ConsensusError::Overflow)?;
+            **referral_info.try_borrow_mut_lamports()? = referral_info.lamports()
+                .checked_add(referral_fee)
+                .ok_or(ConsensusError::Overflow)?;
+        }
+        credit_referral(&event_emitter!(ctx), referral, theme.key(), ctx.accounts.user.key(), referral_fee)?;
+    }
     
     // Update theme state
+    theme.record_slot_volume(
+        Clock::get()?.slot,
+        sol_before_fee,
+        false,
+        ctx.accounts.trading_config.max_slot_volume_bps,
+    )?;
     theme.sol_reserves = theme.sol_reserves
         .checked_sub(sol_before_fee)
         .ok_or(ConsensusError::Overflow)?;
     theme.token_reserves = theme.token_reserves
         .checked_add(token_amount)
         .ok_or(ConsensusError::Overflow)?;
-    theme.buyback_pool = theme.buyback_pool
-        .checked_add(buyback_fee)
+    theme.accrue_buyback_fee(buyback_fee)?;
+    theme.platform_fees_accrued = theme.platform_fees_accrued
+        .checked_add(platform_fee)
         .ok_or(ConsensusError::Overflow)?;
-    // 移除统计字段更新
+    theme.creator_fees_accrued = theme.creator_fees_accrued
+        .checked_add(creator_fee)
+        .ok_or(ConsensusError::Overflow)?;
+    
+    update_price_oracle(&mut ctx.accounts.price_oracle, theme.key(), theme, ctx.bumps.price_oracle)?;
     
-    emit!(TokensSwapped {
+    emit_cpi!(TokensSwapped {
         theme: theme.key(),
         user: ctx.accounts.user.key(),
         sol_amount: sol_out,
@@ -178,6 +265,24 @@ pub fn swap_tokens_for_sol(
         new_token_reserves: theme.token_reserves,
     });
     
+    // 按 SOL 交易额累计赛季积分
+    let points = trade_points(sol_out);
+    if points > 0 {
+        emit_cpi!(PointsAccrued {
+            user: ctx.accounts.user.key(),
+            kind: POINTS_KIND_TRADE,
+            points,
+        });
+    }
+    
+    emit_reserve_health(
+        &event_emitter!(ctx),
+        theme.key(),
+        theme,
+        ctx.accounts.vault_sol_account.lamports(),
+        ctx.accounts.vault_token_account.amount,
+    )?;
+    
     msg!("Swapped {} tokens for {} SOL", token_amount, sol_out);
     msg!("New reserves - SOL: {}, Tokens: {}", theme.sol_reserves, theme.token_reserves);
     
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use taste_fun_shared::*;
+use crate::{Theme, ThemeMetadata, ThemeMetadataSet};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetThemeMetadata<'info> {
+    #[account(
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + ThemeMetadata::SPACE,
+        seeds = [b"theme_metadata", theme.key().as_ref()],
+        bump
+    )]
+    pub theme_metadata: Account<'info, ThemeMetadata>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// 创建或整体覆盖主题的完整展示信息；空字符串表示未设置
+pub fn set_theme_metadata(
+    ctx: Context<SetThemeMetadata>,
+    name: String,
+    description: String,
+    icon_uri: String,
+    website: String,
+    twitter: String,
+    telegram: String,
+) -> Result<()> {
+    require!(
+        !name.is_empty()
+            && name.len() <= MAX_THEME_FULL_NAME_LEN
+            && description.len() <= MAX_THEME_FULL_DESCRIPTION_LEN
+            && icon_uri.len() <= MAX_IMAGE_URI_LEN
+            && website.len() <= MAX_THEME_LINK_LEN
+            && twitter.len() <= MAX_THEME_LINK_LEN
+            && telegram.len() <= MAX_THEME_LINK_LEN,
+        ConsensusError::InvalidThemeMetadata
+    );
+    
+    let now = Clock::get()?.unix_timestamp;
+    let metadata = &mut ctx.accounts.theme_metadata;
+    metadata.theme = ctx.accounts.theme.key();
+    metadata.name = name;
+    metadata.description = description;
+    metadata.icon_uri = icon_uri;
+    metadata.website = website;
+    metadata.twitter = twitter;
+    metadata.telegram = telegram;
+    metadata.updated_at = now;
+    metadata.bump = ctx.bumps.theme_metadata;
+    
+    emit_cpi!(ThemeMetadataSet {
+        theme: metadata.theme,
+        metadata: metadata.key(),
+        name: metadata.name.clone(),
+        icon_uri: metadata.icon_uri.clone(),
+        timestamp: now,
+    });
+    
+    msg!("Theme metadata set for {}", metadata.theme);
+    
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use taste_fun_shared::*;
+use crate::{Theme, ThemeMetadataUpdated};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateThemeMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    pub creator: Signer<'info>,
+}
+
+/// 修改主题名称与描述；两次修改间隔至少 THEME_METADATA_UPDATE_COOLDOWN，避免频繁改名误导持有者
+pub fn update_theme_metadata(
+    ctx: Context<UpdateThemeMetadata>,
+    name: [u8; 12],
+    description: [u8; 48],
+) -> Result<()> {
+    require!(name[0] != 0, ConsensusError::InvalidThemeMetadata);
+    
+    let now = Clock::get()?.unix_timestamp;
+    let theme = &mut ctx.accounts.theme;
+    if theme.metadata_updated_at > 0 {
+        require!(
+            now >= theme.metadata_updated_at.saturating_add(THEME_METADATA_UPDATE_COOLDOWN),
+            ConsensusError::MetadataUpdateTooSoon
+        );
+    }
+    
+    theme.name = name;
+    theme.description = description;
+    theme.metadata_updated_at = now;
+    
+    emit_cpi!(ThemeMetadataUpdated {
+        theme: theme.key(),
+        name,
+        description,
+        timestamp: now,
+    });
+    
+    msg!("Theme metadata updated: {}", theme.key());
+    
+    Ok(())
+}
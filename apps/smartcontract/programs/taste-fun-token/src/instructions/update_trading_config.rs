@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+use taste_fun_shared::*;
+use crate::{AuthorityProposed, AuthorityTransferred, TradingConfiguration, TradingConfigUpdateProposed, TradingConfigUpdated};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateTradingConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_config"],
+        bump,
+        has_one = authority @ ConsensusError::Unauthorized
+    )]
+    pub trading_config: Account<'info, TradingConfiguration>,
+    
+    pub authority: Signer<'info>,
+}
+
+/// 提议新的交易费配置，时间锁到期后才能生效
+pub fn propose_trading_config_update(
+    ctx: Context<UpdateTradingConfig>,
+    trade_fee_bps: u16,
+    buyback_fee_split_bps: u16,
+    platform_fee_split_bps: u16,
+    creator_fee_split_bps: u16,
+) -> Result<()> {
+    let eta = Clock::get()?.unix_timestamp
+        .checked_add(TRADING_CONFIG_TIMELOCK)
+        .ok_or(ConsensusError::Overflow)?;
+    
+    let config = &mut ctx.accounts.trading_config;
+    config.pending_trade_fee_bps = trade_fee_bps;
+    config.pending_buyback_fee_split_bps = buyback_fee_split_bps;
+    config.pending_platform_fee_split_bps = platform_fee_split_bps;
+    config.pending_creator_fee_split_bps = creator_fee_split_bps;
+    config.pending_update_eta = eta;
+    
+    emit_cpi!(TradingConfigUpdateProposed {
+        trade_fee_bps,
+        buyback_fee_split_bps,
+        platform_fee_split_bps,
+        creator_fee_split_bps,
+        eta,
+    });
+    
+    msg!("Trading config update proposed, executable at {}", eta);
+    
+    Ok(())
+}
+
+/// 时间锁到期后应用待生效的交易费配置
+pub fn apply_trading_config_update(ctx: Context<UpdateTradingConfig>) -> Result<()> {
+    let config = &mut ctx.accounts.trading_config;
+    
+    config.trade_fee_bps = config.pending_trade_fee_bps;
+    config.buyback_fee_split_bps = config.pending_buyback_fee_split_bps;
+    config.platform_fee_split_bps = config.pending_platform_fee_split_bps;
+    config.creator_fee_split_bps = config.pending_creator_fee_split_bps;
+    config.pending_update_eta = 0;
+    
+    emit_cpi!(TradingConfigUpdated {
+        trade_fee_bps: config.trade_fee_bps,
+        buyback_fee_split_bps: config.buyback_fee_split_bps,
+        platform_fee_split_bps: config.platform_fee_split_bps,
+        creator_fee_split_bps: config.creator_fee_split_bps,
+    });
+    
+    msg!("Trading config updated, trade fee: {} bps", config.trade_fee_bps);
+    
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptTradingConfigAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_config"],
+        bump
+    )]
+    pub trading_config: Account<'info, TradingConfiguration>,
+    
+    pub new_authority: Signer<'info>,
+}
+
+/// 两步移交管理员：第一步由当前管理员提名新管理员
+pub fn propose_authority(ctx: Context<UpdateTradingConfig>, new_authority: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.trading_config;
+    config.pending_authority = new_authority;
+    
+    emit_cpi!(AuthorityProposed {
+        current_authority: config.authority,
+        pending_authority: new_authority,
+    });
+    
+    msg!("Trading config authority proposed: {}", new_authority);
+    
+    Ok(())
+}
+
+/// 两步移交管理员：第二步由被提名者签名接受
+pub fn accept_authority(ctx: Context<AcceptTradingConfigAuthority>) -> Result<()> {
+    let config = &mut ctx.accounts.trading_config;
+    let previous_authority = config.authority;
+    config.authority = config.pending_authority;
+    config.pending_authority = Pubkey::default();
+    
+    emit_cpi!(AuthorityTransferred {
+        previous_authority,
+        new_authority: config.authority,
+    });
+    
+    msg!("Trading config authority transferred to {}", config.authority);
+    
+    Ok(())
+}
+
+/// 设置单 slot 成交额熔断比例；收紧风控不影响交易成本，立即生效
+pub fn set_slot_volume_limit(
+    ctx: Context<UpdateTradingConfig>,
+    max_slot_volume_bps: u16,
+) -> Result<()> {
+    ctx.accounts.trading_config.max_slot_volume_bps = max_slot_volume_bps;
+    
+    msg!("Per-slot volume limit set to {} bps", max_slot_volume_bps);
+    
+    Ok(())
+}
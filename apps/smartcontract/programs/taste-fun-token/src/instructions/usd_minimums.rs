@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+use taste_fun_shared::*;
+use crate::{PriceOracle, Theme, TradingConfiguration};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetUsdMinimums<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_config"],
+        bump,
+        has_one = authority @ ConsensusError::Unauthorized
+    )]
+    pub trading_config: Account<'info, TradingConfiguration>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RefreshUsdMinimums<'info> {
+    #[account(
+        mut,
+        seeds = [b"trading_config"],
+        bump
+    )]
+    pub trading_config: Account<'info, TradingConfiguration>,
+    
+    /// CHECK: Pyth SOL/USD PriceUpdateV2 账户，在 read_pyth_price 中校验 owner 与 feed
+    #[account(address = trading_config.sol_usd_feed @ ConsensusError::InvalidPriceFeed)]
+    pub sol_usd_price: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RefreshThemeMinStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"theme", theme.creator.as_ref(), theme.theme_id.to_le_bytes().as_ref()],
+        bump = theme.theme_bump
+    )]
+    pub theme: Account<'info, Theme>,
+    
+    #[account(
+        seeds = [b"price_oracle", theme.key().as_ref()],
+        bump = price_oracle.bump
+    )]
+    pub price_oracle: Account<'info, PriceOracle>,
+    
+    #[account(
+        seeds = [b"trading_config"],
+        bump
+    )]
+    pub trading_config: Account<'info, TradingConfiguration>,
+    
+    /// CHECK: Pyth SOL/USD PriceUpdateV2 账户，在 read_pyth_price 中校验 owner 与 feed
+    #[account(address = trading_config.sol_usd_feed @ ConsensusError::InvalidPriceFeed)]
+    pub sol_usd_price: UncheckedAccount<'info>,
+}
+
+/// 设置 USD 计价的最小交易额与最小质押额 (micro-USD，0 表示关闭)
+pub fn set_usd_minimums(
+    ctx: Context<SetUsdMinimums>,
+    sol_usd_feed: Pubkey,
+    min_trade_usd: u64,
+    min_stake_usd: u64,
+) -> Result<()> {
+    let config = &mut ctx.accounts.trading_config;
+    config.sol_usd_feed = sol_usd_feed;
+    config.min_trade_usd = min_trade_usd;
+    config.min_stake_usd = min_stake_usd;
+    if min_trade_usd == 0 {
+        config.min_trade_lamports = 0;
+    }
+    
+    msg!("USD minimums set: trade {} / stake {} micro-USD", min_trade_usd, min_stake_usd);
+    
+    Ok(())
+}
+
+/// 按 Pyth 最新 SOL/USD 价格刷新最小交易额 (lamports)，任何人可调用
+pub fn refresh_usd_minimums(ctx: Context<RefreshUsdMinimums>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let (price, exponent) = read_pyth_price(&ctx.accounts.sol_usd_price, now)?;
+    
+    let config = &mut ctx.accounts.trading_config;
+    config.min_trade_lamports = usd_to_lamports(config.min_trade_usd, price, exponent)?;
+    
+    msg!("Min trade refreshed: {} lamports", config.min_trade_lamports);
+    
+    Ok(())
+}
+
+/// 按 SOL/USD 与主题 TWAP 刷新主题最小质押量 (代币)，任何人可调用
+/// 新建创意时 core 会读取该值作为 min_stake 下限
+pub fn refresh_theme_min_stake(ctx: Context<RefreshThemeMinStake>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let config = &ctx.accounts.trading_config;
+    
+    let min_stake_tokens = if config.min_stake_usd == 0 {
+        0
+    } else {
+        let (price, exponent) = read_pyth_price(&ctx.accounts.sol_usd_price, now)?;
+        let lamports = usd_to_lamports(config.min_stake_usd, price, exponent)?;
+        let token_price = ctx.accounts.price_oracle
+            .twap(now, MIN_STAKE_TWAP_WINDOW)
+            .ok_or(ConsensusError::OracleNotReady)?;
+        require!(token_price > 0, ConsensusError::OracleNotReady);
+        
+        u64::try_from((lamports as u128) * PRICE_SCALE / (token_price as u128))
+            .map_err(|_| error!(ConsensusError::Overflow))?
+    };
+    
+    ctx.accounts.theme.min_stake_tokens = min_stake_tokens;
+    
+    msg!("Theme min stake refreshed: {} tokens", min_stake_tokens);
+    
+    Ok(())
+}
@@ -10,7 +10,11 @@ use instructions::*;
 pub mod taste_fun_token {
     use super::*;
 
-    /// 初始化全局交易配置
+    /// 初始化全局交易配置 (仅协议管理员)
+    #[access_control(
+        is_protocol_config_authority(&ctx.accounts.protocol_config, ctx.accounts.authority.key)
+        validate_trading_fees(trade_fee_bps, buyback_fee_split_bps, platform_fee_split_bps, creator_fee_split_bps)
+    )]
     pub fn initialize_trading_config(
         ctx: Context<InitializeTradingConfig>,
         trade_fee_bps: u16,
@@ -27,82 +31,1096 @@ pub mod taste_fun_token {
         )
     }
 
+    /// 提议交易费配置变更 (时间锁后生效)
+    #[access_control(
+        validate_trading_fees(trade_fee_bps, buyback_fee_split_bps, platform_fee_split_bps, creator_fee_split_bps)
+    )]
+    pub fn propose_trading_config_update(
+        ctx: Context<UpdateTradingConfig>,
+        trade_fee_bps: u16,
+        buyback_fee_split_bps: u16,
+        platform_fee_split_bps: u16,
+        creator_fee_split_bps: u16,
+    ) -> Result<()> {
+        instructions::propose_trading_config_update(
+            ctx,
+            trade_fee_bps,
+            buyback_fee_split_bps,
+            platform_fee_split_bps,
+            creator_fee_split_bps,
+        )
+    }
+
+    /// 应用到期的交易费配置变更
+    #[access_control(timelock_elapsed(ctx.accounts.trading_config.pending_update_eta))]
+    pub fn apply_trading_config_update(ctx: Context<UpdateTradingConfig>) -> Result<()> {
+        instructions::apply_trading_config_update(ctx)
+    }
+
+    /// 提名新的交易配置管理员 (两步移交第一步)
+    #[access_control(valid_new_authority(&new_authority))]
+    pub fn propose_authority(ctx: Context<UpdateTradingConfig>, new_authority: Pubkey) -> Result<()> {
+        instructions::propose_authority(ctx, new_authority)
+    }
+
+    /// 被提名者接受交易配置管理员 (两步移交第二步)
+    #[access_control(
+        is_account_owner(&ctx.accounts.trading_config.pending_authority, ctx.accounts.new_authority.key)
+    )]
+    pub fn accept_authority(ctx: Context<AcceptTradingConfigAuthority>) -> Result<()> {
+        instructions::accept_authority(ctx)
+    }
+
     /// 初始化新主题 (第一步) - 包含 name 和 description
+    #[access_control(
+        validate_payout_curve(params.payout_curve, params.payout_exponent)
+        valid_theme_rules(
+            params.tie_break_rule,
+            params.dispute_period,
+            params.reject_all_threshold_bps,
+            params.penalty_burn_bps
+        )
+        supported_quote_mint(params.quote_mint)
+    )]
     pub fn initialize_theme(
         ctx: Context<InitializeTheme>,
         theme_id: u64,
         name: [u8; 12],
         description: [u8; 48],
         voting_mode: VotingMode,
+        params: ThemeParams,
     ) -> Result<()> {
-        instructions::initialize_theme(ctx, theme_id, name, description, voting_mode)
+        instructions::initialize_theme(ctx, theme_id, name, description, voting_mode, params)
     }
 
     /// 初始化vault和mint (第二步)
+    #[access_control(is_theme_admin(&ctx.accounts.theme.creator, ctx.accounts.creator.key))]
     pub fn init_vault_and_mint(ctx: Context<InitVaultAndMint>, theme_id: u64) -> Result<()> {
         instructions::init_vault_and_mint(ctx, theme_id)
     }
 
-    /// 铸造初始代币 (第三步)
-    pub fn mint_initial_tokens(ctx: Context<MintInitialTokens>, theme_id: u64) -> Result<()> {
-        instructions::mint_initial_tokens(ctx, theme_id)
-    }
+    /// 铸造初始代币 (第三步)
+    #[access_control(is_theme_admin(&ctx.accounts.theme.creator, ctx.accounts.creator.key))]
+    pub fn mint_initial_tokens(ctx: Context<MintInitialTokens>, theme_id: u64) -> Result<()> {
+        instructions::mint_initial_tokens(ctx, theme_id)
+    }
+
+    /// 用 SOL 购买主题代币
+    #[access_control(
+        protocol_config_active(&ctx.accounts.protocol_config)
+        theme_active(ctx.accounts.theme.status)
+        sol_quoted(&ctx.accounts.theme.quote_mint)
+    )]
+    pub fn swap_sol_for_tokens(
+        ctx: Context<SwapSolForTokens>,
+        sol_amount: u64,
+        min_tokens_out: u64,
+        deadline_unix: Option<i64>,
+    ) -> Result<()> {
+        instructions::swap_sol_for_tokens(ctx, sol_amount, min_tokens_out, deadline_unix)
+    }
+
+    /// 用不超过 max_sol_in 的 SOL 购买精确数量的主题代币
+    #[access_control(
+        protocol_config_active(&ctx.accounts.protocol_config)
+        theme_active(ctx.accounts.theme.status)
+        sol_quoted(&ctx.accounts.theme.quote_mint)
+    )]
+    pub fn swap_sol_for_exact_tokens(
+        ctx: Context<SwapSolForTokens>,
+        token_amount: u64,
+        max_sol_in: u64,
+        deadline_unix: Option<i64>,
+    ) -> Result<()> {
+        instructions::swap_sol_for_exact_tokens(ctx, token_amount, max_sol_in, deadline_unix)
+    }
+
+    /// 卖出主题代币获得 SOL
+    #[access_control(
+        protocol_config_active(&ctx.accounts.protocol_config)
+        theme_active(ctx.accounts.theme.status)
+        sol_quoted(&ctx.accounts.theme.quote_mint)
+    )]
+    pub fn swap_tokens_for_sol(
+        ctx: Context<SwapTokensForSol>,
+        token_amount: u64,
+        min_sol_out: u64,
+        deadline_unix: Option<i64>,
+    ) -> Result<()> {
+        instructions::swap_tokens_for_sol(ctx, token_amount, min_sol_out, deadline_unix)
+    }
+
+    /// 执行回购销毁
+    #[access_control(
+        protocol_config_active(&ctx.accounts.protocol_config)
+        theme_active(ctx.accounts.theme.status)
+    )]
+    pub fn execute_buyback(ctx: Context<ExecuteBuyback>) -> Result<()> {
+        instructions::execute_buyback(ctx)
+    }
+
+    /// 暂停主题：停止交易、回购与新创意 (创建者或协议管理员)
+    #[access_control(
+        is_theme_admin_or_protocol(&ctx.accounts.theme.creator, &ctx.accounts.trading_config.authority, ctx.accounts.authority.key)
+        theme_active(ctx.accounts.theme.status)
+    )]
+    pub fn pause_theme(ctx: Context<SetThemePause>) -> Result<()> {
+        instructions::pause_theme(ctx)
+    }
+
+    /// 恢复已暂停的主题 (创建者或协议管理员)
+    #[access_control(
+        is_theme_admin_or_protocol(&ctx.accounts.theme.creator, &ctx.accounts.trading_config.authority, ctx.accounts.authority.key)
+        theme_paused(ctx.accounts.theme.status)
+    )]
+    pub fn unpause_theme(ctx: Context<SetThemePause>) -> Result<()> {
+        instructions::unpause_theme(ctx)
+    }
+
+    /// 创建者修改主题名称与描述 (限频)
+    #[access_control(is_theme_admin(&ctx.accounts.theme.creator, ctx.accounts.creator.key))]
+    pub fn update_theme_metadata(
+        ctx: Context<UpdateThemeMetadata>,
+        name: [u8; 12],
+        description: [u8; 48],
+    ) -> Result<()> {
+        instructions::update_theme_metadata(ctx, name, description)
+    }
+
+    /// 创建者创建或更新主题的完整展示信息
+    #[access_control(is_theme_admin(&ctx.accounts.theme.creator, ctx.accounts.creator.key))]
+    pub fn set_theme_metadata(
+        ctx: Context<SetThemeMetadata>,
+        name: String,
+        description: String,
+        icon_uri: String,
+        website: String,
+        twitter: String,
+        telegram: String,
+    ) -> Result<()> {
+        instructions::set_theme_metadata(ctx, name, description, icon_uri, website, twitter, telegram)
+    }
+
+    /// 创建者领取已释放的储备代币
+    #[access_control(
+        is_account_owner(&ctx.accounts.creator_vesting.creator, ctx.accounts.creator.key)
+    )]
+    pub fn claim_vested_tokens(ctx: Context<ClaimVestedTokens>) -> Result<()> {
+        instructions::claim_vested_tokens(ctx)
+    }
+
+    /// 创建者销毁尚未领取的锁仓储备
+    #[access_control(burnable_reserve(&ctx.accounts.creator_vesting, amount))]
+    pub fn burn_creator_reserve(ctx: Context<BurnCreatorReserve>, amount: u64) -> Result<()> {
+        instructions::burn_creator_reserve(ctx, amount)
+    }
+
+    /// 提议退役主题 (创建者或协议管理员)，时间锁后才能执行
+    #[access_control(
+        is_theme_admin_or_protocol(&ctx.accounts.theme.creator, &ctx.accounts.trading_config.authority, ctx.accounts.authority.key)
+        theme_retirable(ctx.accounts.theme.status)
+    )]
+    pub fn propose_retirement(ctx: Context<ProposeRetirement>) -> Result<()> {
+        instructions::propose_retirement(ctx)
+    }
+
+    /// 撤回退役提议
+    #[access_control(
+        is_theme_admin_or_protocol(&ctx.accounts.theme.creator, &ctx.accounts.trading_config.authority, ctx.accounts.authority.key)
+        timelock_pending(ctx.accounts.theme.retire_eta)
+    )]
+    pub fn cancel_retirement(ctx: Context<ProposeRetirement>) -> Result<()> {
+        instructions::cancel_retirement(ctx)
+    }
+
+    /// 时间锁到期后执行退役：写入持有者快照的 merkle 根并关闭曲线
+    #[access_control(
+        is_protocol_authority(&ctx.accounts.trading_config.authority, ctx.accounts.authority.key)
+        sol_quoted(&ctx.accounts.theme.quote_mint)
+        theme_retirable(ctx.accounts.theme.status)
+        timelock_elapsed(ctx.accounts.theme.retire_eta)
+    )]
+    pub fn retire_theme(
+        ctx: Context<RetireTheme>,
+        merkle_root: [u8; 32],
+        total_claimable: u64,
+    ) -> Result<()> {
+        instructions::retire_theme(ctx, merkle_root, total_claimable)
+    }
+
+    /// 持有者凭 merkle 证明领取退役分配的 SOL
+    #[access_control(nonzero_amount(amount))]
+    pub fn claim_retirement(
+        ctx: Context<ClaimRetirement>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::claim_retirement(ctx, amount, proof)
+    }
+
+    /// 创建者创建并注资主题代币空投活动
+    #[access_control(
+        is_theme_admin(&ctx.accounts.theme.creator, ctx.accounts.creator.key)
+        nonzero_amount(total_amount)
+        airdrop_open(expires_at)
+    )]
+    pub fn create_airdrop(
+        ctx: Context<CreateAirdrop>,
+        campaign_id: u64,
+        merkle_root: [u8; 32],
+        total_amount: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::create_airdrop(ctx, campaign_id, merkle_root, total_amount, expires_at)
+    }
+
+    /// 凭 merkle 证明领取空投
+    #[access_control(
+        nonzero_amount(amount)
+        airdrop_open(ctx.accounts.campaign.expires_at)
+    )]
+    pub fn claim_airdrop(
+        ctx: Context<ClaimAirdrop>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::claim_airdrop(ctx, amount, proof)
+    }
+
+    /// 活动到期后创建者收回未领取的代币
+    #[access_control(airdrop_reclaimable(&ctx.accounts.campaign))]
+    pub fn reclaim_airdrop(ctx: Context<ReclaimAirdrop>) -> Result<()> {
+        instructions::reclaim_airdrop(ctx)
+    }
+
+    /// 社区销毁 (持有者销毁自己的主题代币)
+    #[access_control(nonzero_amount(amount))]
+    pub fn community_burn(ctx: Context<CommunityBurn>, amount: u64) -> Result<()> {
+        instructions::community_burn(ctx, amount)
+    }
+
+    /// 买入报价 (只读，结果通过 return_data 返回)
+    #[access_control(theme_active(ctx.accounts.theme.status))]
+    pub fn quote_buy(ctx: Context<Quote>, sol_amount: u64) -> Result<SwapQuote> {
+        instructions::quote_buy(ctx, sol_amount)
+    }
+
+    /// 卖出报价 (只读，结果通过 return_data 返回)
+    #[access_control(theme_active(ctx.accounts.theme.status))]
+    pub fn quote_sell(ctx: Context<Quote>, token_amount: u64) -> Result<SwapQuote> {
+        instructions::quote_sell(ctx, token_amount)
+    }
+
+    /// 设置 USD 计价的最小交易额/质押额
+    #[access_control(price_feed_configured(&sol_usd_feed))]
+    pub fn set_usd_minimums(
+        ctx: Context<SetUsdMinimums>,
+        sol_usd_feed: Pubkey,
+        min_trade_usd: u64,
+        min_stake_usd: u64,
+    ) -> Result<()> {
+        instructions::set_usd_minimums(ctx, sol_usd_feed, min_trade_usd, min_stake_usd)
+    }
+
+    /// 按 Pyth SOL/USD 刷新最小交易额 (任何人可调用)
+    #[access_control(price_feed_configured(&ctx.accounts.trading_config.sol_usd_feed))]
+    pub fn refresh_usd_minimums(ctx: Context<RefreshUsdMinimums>) -> Result<()> {
+        instructions::refresh_usd_minimums(ctx)
+    }
+
+    /// 按 Pyth SOL/USD 与主题 TWAP 刷新主题最小质押量 (任何人可调用)
+    #[access_control(sol_quoted(&ctx.accounts.theme.quote_mint))]
+    pub fn refresh_theme_min_stake(ctx: Context<RefreshThemeMinStake>) -> Result<()> {
+        instructions::refresh_theme_min_stake(ctx)
+    }
+
+    /// 创建者提取累计的创建者费
+    #[access_control(sol_quoted(&ctx.accounts.theme.quote_mint))]
+    pub fn claim_creator_fees(ctx: Context<ClaimCreatorFees>) -> Result<()> {
+        instructions::claim_creator_fees(ctx)
+    }
+
+    /// 协议管理员提取累计的平台费
+    #[access_control(sol_quoted(&ctx.accounts.theme.quote_mint))]
+    pub fn claim_platform_fees(ctx: Context<ClaimPlatformFees>) -> Result<()> {
+        instructions::claim_platform_fees(ctx)
+    }
+
+    /// 创建主题质押池并设置回购费分成比例 (仅创建者，SOL 计价主题)
+    #[access_control(
+        is_theme_admin(&ctx.accounts.theme.creator, ctx.accounts.creator.key)
+        sol_quoted(&ctx.accounts.theme.quote_mint)
+    )]
+    pub fn initialize_stake_pool(
+        ctx: Context<InitializeStakePool>,
+        stake_fee_share_bps: u16,
+    ) -> Result<()> {
+        instructions::initialize_stake_pool(ctx, stake_fee_share_bps)
+    }
+
+    /// 调整质押池的回购费分成比例 (仅创建者)
+    #[access_control(is_theme_admin(&ctx.accounts.theme.creator, ctx.accounts.creator.key))]
+    pub fn set_stake_fee_share(
+        ctx: Context<SetStakeFeeShare>,
+        stake_fee_share_bps: u16,
+    ) -> Result<()> {
+        instructions::set_stake_fee_share(ctx, stake_fee_share_bps)
+    }
+
+    /// 把金库中累计的质押分成同步到质押池 (任何人可调用)
+    #[access_control(sol_quoted(&ctx.accounts.theme.quote_mint))]
+    pub fn sync_stake_rewards(ctx: Context<SyncStakeRewards>) -> Result<()> {
+        instructions::sync_stake_rewards(ctx)
+    }
+
+    /// 质押主题代币
+    #[access_control(nonzero_amount(amount))]
+    pub fn stake_tokens(ctx: Context<StakeAction>, amount: u64) -> Result<()> {
+        instructions::stake_tokens(ctx, amount)
+    }
+
+    /// 申请解除质押，冷却期后才能提取
+    #[access_control(amount_within(amount, ctx.accounts.position.amount))]
+    pub fn request_unstake(ctx: Context<StakeAction>, amount: u64) -> Result<()> {
+        instructions::request_unstake(ctx, amount)
+    }
+
+    /// 冷却期结束后提取解除质押的代币
+    #[access_control(unstake_ready(&ctx.accounts.position))]
+    pub fn withdraw_unstaked(ctx: Context<StakeAction>) -> Result<()> {
+        instructions::withdraw_unstaked(ctx)
+    }
+
+    /// 领取质押奖励 (SOL 与主题代币)
+    #[access_control(sol_quoted(&ctx.accounts.theme.quote_mint))]
+    pub fn claim_stake_rewards(ctx: Context<StakeAction>) -> Result<()> {
+        instructions::claim_stake_rewards(ctx)
+    }
+
+    /// 用 USDC 购买 USDC 计价主题的代币
+    #[access_control(
+        protocol_config_active(&ctx.accounts.protocol_config)
+        theme_active(ctx.accounts.theme.status)
+        token_quoted(&ctx.accounts.theme.quote_mint)
+    )]
+    pub fn swap_quote_for_tokens(
+        ctx: Context<SwapQuoteToken>,
+        quote_amount: u64,
+        min_tokens_out: u64,
+        deadline_unix: Option<i64>,
+    ) -> Result<()> {
+        instructions::swap_quote_for_tokens(ctx, quote_amount, min_tokens_out, deadline_unix)
+    }
+
+    /// 卖出 USDC 计价主题的代币获得 USDC
+    #[access_control(
+        protocol_config_active(&ctx.accounts.protocol_config)
+        theme_active(ctx.accounts.theme.status)
+        token_quoted(&ctx.accounts.theme.quote_mint)
+    )]
+    pub fn swap_tokens_for_quote(
+        ctx: Context<SwapQuoteToken>,
+        token_amount: u64,
+        min_quote_out: u64,
+        deadline_unix: Option<i64>,
+    ) -> Result<()> {
+        instructions::swap_tokens_for_quote(ctx, token_amount, min_quote_out, deadline_unix)
+    }
+
+    /// 创建者提取 USDC 计价主题累计的创建者费
+    #[access_control(
+        is_theme_admin(&ctx.accounts.theme.creator, ctx.accounts.recipient.key)
+        token_quoted(&ctx.accounts.theme.quote_mint)
+    )]
+    pub fn claim_creator_quote_fees(ctx: Context<ClaimQuoteFees>) -> Result<()> {
+        instructions::claim_creator_quote_fees(ctx)
+    }
+
+    /// 协议管理员提取 USDC 计价主题累计的平台费
+    #[access_control(
+        is_protocol_authority(&ctx.accounts.trading_config.authority, ctx.accounts.recipient.key)
+        token_quoted(&ctx.accounts.theme.quote_mint)
+    )]
+    pub fn claim_platform_quote_fees(ctx: Context<ClaimQuoteFees>) -> Result<()> {
+        instructions::claim_platform_quote_fees(ctx)
+    }
+
+    /// 注册推荐人 PDA
+    #[access_control(protocol_config_active(&ctx.accounts.protocol_config))]
+    pub fn register_referral(ctx: Context<RegisterReferral>) -> Result<()> {
+        instructions::register_referral(ctx)
+    }
+
+    /// 推荐人提取累计分成
+    #[access_control(is_account_owner(&ctx.accounts.referral.referrer, ctx.accounts.referrer.key))]
+    pub fn claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+        instructions::claim_referral_fees(ctx)
+    }
+
+    /// 设置单 slot 成交额熔断比例
+    #[access_control(valid_slot_volume_limit(max_slot_volume_bps))]
+    pub fn set_slot_volume_limit(
+        ctx: Context<UpdateTradingConfig>,
+        max_slot_volume_bps: u16,
+    ) -> Result<()> {
+        instructions::set_slot_volume_limit(ctx, max_slot_volume_bps)
+    }
+
+    /// 设置推荐人分成比例 (占平台费)
+    #[access_control(valid_referral_fee_split(referral_fee_split_bps))]
+    pub fn set_referral_fee_split(
+        ctx: Context<UpdateTradingConfig>,
+        referral_fee_split_bps: u16,
+    ) -> Result<()> {
+        instructions::set_referral_fee_split(ctx, referral_fee_split_bps)
+    }
+
+    /// 读取主题代币时间加权均价 (只读，结果通过 return_data 返回)
+    #[access_control(valid_twap_window(window_secs))]
+    pub fn get_twap(ctx: Context<GetTwap>, window_secs: i64) -> Result<u64> {
+        instructions::get_twap(ctx, window_secs)
+    }
+
+    /// 毕业迁移到 Raydium CPMM (SOL 储备达到阈值后任何人可调用)
+    #[access_control(
+        theme_active(ctx.accounts.theme.status)
+        sol_quoted(&ctx.accounts.theme.quote_mint)
+    )]
+    pub fn migrate_to_raydium(ctx: Context<MigrateToRaydium>) -> Result<()> {
+        instructions::migrate_to_raydium(ctx)
+    }
+
+    /// 毕业迁移到 Meteora DAMM (SOL 储备达到阈值后任何人可调用)
+    #[access_control(
+        theme_active(ctx.accounts.theme.status)
+        sol_quoted(&ctx.accounts.theme.quote_mint)
+    )]
+    pub fn migrate_to_meteora(ctx: Context<MigrateToMeteora>) -> Result<()> {
+        instructions::migrate_to_meteora(ctx)
+    }
+
+    /// 创建者在开盘前设置开盘费率衰减计划
+    #[access_control(
+        is_theme_admin(&ctx.accounts.theme.creator, ctx.accounts.creator.key)
+        theme_not_launched(ctx.accounts.theme.launch_slot)
+        valid_launch_fee_schedule(launch_fee_bps, fee_decay_slots)
+    )]
+    pub fn set_launch_fee_schedule(
+        ctx: Context<SetLaunchFeeSchedule>,
+        launch_fee_bps: u16,
+        fee_decay_slots: u64,
+    ) -> Result<()> {
+        instructions::set_launch_fee_schedule(ctx, launch_fee_bps, fee_decay_slots)
+    }
+
+    /// 向 SOL 计价主题的回购池注入外部收入 (任何人可调用)
+    #[access_control(
+        sol_quoted(&ctx.accounts.theme.quote_mint)
+        theme_active(ctx.accounts.theme.status)
+    )]
+    pub fn fund_buyback_pool(ctx: Context<FundBuybackPool>, amount: u64) -> Result<()> {
+        instructions::fund_buyback_pool(ctx, amount)
+    }
+
+    /// 创建者选择回购资金销毁代币或留作曲线流动性
+    #[access_control(
+        is_theme_admin(&ctx.accounts.theme.creator, ctx.accounts.creator.key)
+        valid_buyback_policy(buyback_policy)
+    )]
+    pub fn set_buyback_policy(ctx: Context<SetBuybackPolicy>, buyback_policy: u8) -> Result<()> {
+        instructions::set_buyback_policy(ctx, buyback_policy)
+    }
+
+    /// 创建者设置同一钱包反向交易的冷却时间
+    #[access_control(
+        is_theme_admin(&ctx.accounts.theme.creator, ctx.accounts.creator.key)
+        valid_swap_cooldown(swap_cooldown_secs)
+    )]
+    pub fn set_swap_cooldown(ctx: Context<SetSwapCooldown>, swap_cooldown_secs: u32) -> Result<()> {
+        instructions::set_swap_cooldown(ctx, swap_cooldown_secs)
+    }
+
+    /// 创建者在毕业前选择迁移目标
+    #[access_control(theme_active(ctx.accounts.theme.status))]
+    pub fn set_migration_target(ctx: Context<SetMigrationTarget>, migration_target: u8) -> Result<()> {
+        instructions::set_migration_target(ctx, migration_target)
+    }
+
+    /// 已毕业主题放弃金库的铸币权 (迁移时已自动执行)
+    #[access_control(theme_migrated(ctx.accounts.theme.status))]
+    pub fn revoke_mint_authority(ctx: Context<RevokeMintAuthority>) -> Result<()> {
+        instructions::revoke_mint_authority(ctx)
+    }
+
+    /// 锁仓期满后由协议治理转出迁移 LP
+    #[access_control(lp_unlocked(&ctx.accounts.lp_lock))]
+    pub fn unlock_lp(ctx: Context<UnlockLp>) -> Result<()> {
+        instructions::unlock_lp(ctx)
+    }
+
+    /// 毕业后在 AMM 池中执行回购销毁
+    #[access_control(
+        protocol_config_active(&ctx.accounts.protocol_config)
+        theme_migrated(ctx.accounts.theme.status)
+        sol_quoted(&ctx.accounts.theme.quote_mint)
+    )]
+    pub fn execute_amm_buyback<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteAmmBuyback<'info>>,
+        min_tokens_out: u64,
+    ) -> Result<()> {
+        instructions::execute_amm_buyback(ctx, min_tokens_out)
+    }
+
+    /// 账户结构升级：Theme / TradingConfiguration realloc 到当前大小并写入当前版本号 (仅协议管理员)
+    #[access_control(
+        is_protocol_config_authority(&ctx.accounts.protocol_config, ctx.accounts.authority.key)
+    )]
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        instructions::migrate_account(ctx)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+
+/// 销毁量不超过尚未领取的创建者储备
+pub(crate) fn burnable_reserve(vesting: &CreatorVesting, amount: u64) -> Result<()> {
+    amount_within(amount, vesting.unclaimed_amount())
+}
+
+/// 存在待执行的时间锁操作 (eta 为 0 表示无)
+pub(crate) fn timelock_pending(eta: i64) -> Result<()> {
+    require!(eta > 0, ConsensusError::NoPendingUpdate);
+    Ok(())
+}
+
+/// 待执行的时间锁操作已到期
+pub(crate) fn timelock_elapsed(eta: i64) -> Result<()> {
+    timelock_pending(eta)?;
+    require!(
+        Clock::get()?.unix_timestamp >= eta,
+        ConsensusError::TimelockNotElapsed
+    );
+    Ok(())
+}
+
+/// 被提名的新管理员不能为空
+pub(crate) fn valid_new_authority(new_authority: &Pubkey) -> Result<()> {
+    require!(*new_authority != Pubkey::default(), ConsensusError::InvalidAuthority);
+    Ok(())
+}
+
+/// 单 slot 成交额熔断比例上限
+pub(crate) fn valid_slot_volume_limit(max_slot_volume_bps: u16) -> Result<()> {
+    require!(
+        max_slot_volume_bps <= MAX_SLOT_VOLUME_BPS,
+        ConsensusError::InvalidAmount
+    );
+    Ok(())
+}
+
+/// 推荐人分成比例上限
+pub(crate) fn valid_referral_fee_split(referral_fee_split_bps: u16) -> Result<()> {
+    require!(
+        referral_fee_split_bps <= MAX_REFERRAL_FEE_SPLIT_BPS,
+        ConsensusError::ReferralFeeTooHigh
+    );
+    Ok(())
+}
+
+/// 已配置 Pyth SOL/USD 价格账户
+pub(crate) fn price_feed_configured(sol_usd_feed: &Pubkey) -> Result<()> {
+    require!(*sol_usd_feed != Pubkey::default(), ConsensusError::InvalidPriceFeed);
+    Ok(())
+}
+
+/// TWAP 窗口必须为正
+pub(crate) fn valid_twap_window(window_secs: i64) -> Result<()> {
+    require!(window_secs > 0, ConsensusError::InvalidAmount);
+    Ok(())
+}
+
+/// 回购策略只能为销毁或留作流动性
+pub(crate) fn valid_buyback_policy(buyback_policy: u8) -> Result<()> {
+    require!(
+        buyback_policy == BUYBACK_POLICY_BURN || buyback_policy == BUYBACK_POLICY_LIQUIDITY,
+        ConsensusError::InvalidBuybackPolicy
+    );
+    Ok(())
+}
+
+/// 反向交易冷却时间上限
+pub(crate) fn valid_swap_cooldown(swap_cooldown_secs: u32) -> Result<()> {
+    require!(
+        swap_cooldown_secs <= MAX_SWAP_COOLDOWN_SECS,
+        ConsensusError::InvalidAmount
+    );
+    Ok(())
+}
+
+/// 空投活动未到期 (创建时 expires_at 须在未来)
+pub(crate) fn airdrop_open(expires_at: i64) -> Result<()> {
+    require!(
+        Clock::get()?.unix_timestamp < expires_at,
+        ConsensusError::AirdropExpired
+    );
+    Ok(())
+}
+
+/// 空投活动已到期且尚未收回
+pub(crate) fn airdrop_reclaimable(campaign: &AirdropCampaign) -> Result<()> {
+    require!(
+        Clock::get()?.unix_timestamp >= campaign.expires_at,
+        ConsensusError::AirdropNotExpired
+    );
+    require!(!campaign.reclaimed, ConsensusError::AlreadyWithdrawn);
+    Ok(())
+}
+
+/// 有解除质押中的代币且冷却期已过
+pub(crate) fn unstake_ready(position: &StakePosition) -> Result<()> {
+    require!(position.unstaking_amount > 0, ConsensusError::InvalidAmount);
+    require!(
+        Clock::get()?.unix_timestamp >= position.unstake_available_at,
+        ConsensusError::UnstakeCooldownActive
+    );
+    Ok(())
+}
+
+/// 迁移 LP 锁仓期已满
+pub(crate) fn lp_unlocked(lp_lock: &LpLock) -> Result<()> {
+    require!(
+        Clock::get()?.unix_timestamp >= lp_lock.unlock_at,
+        ConsensusError::LpLockActive
+    );
+    Ok(())
+}
+
+/// 交易截止时间检查：指定了 deadline 且交易落地时已超时则中止，避免过期报价成交
+pub(crate) fn check_swap_deadline(deadline_unix: Option<i64>) -> Result<()> {
+    if let Some(deadline) = deadline_unix {
+        require!(
+            Clock::get()?.unix_timestamp <= deadline,
+            ConsensusError::SwapDeadlineExceeded
+        );
+    }
+    Ok(())
+}
+
+/// 开盘窗口内的买入限制：单笔不超过 LAUNCH_MAX_BUY_PER_TX，单钱包累计不超过 LAUNCH_MAX_BUY_PER_WALLET
+pub(crate) fn check_launch_limit(
+    theme: &Theme,
+    theme_key: Pubkey,
+    launch_limit: Option<&mut Account<LaunchLimit>>,
+    buyer: Pubkey,
+    bump: Option<u8>,
+    sol_amount: u64,
+) -> Result<()> {
+    if !theme.in_launch_window(Clock::get()?.slot) {
+        return Ok(());
+    }
+    let launch_limit = launch_limit.ok_or(ConsensusError::LaunchLimitRequired)?;
+    
+    require!(sol_amount <= LAUNCH_MAX_BUY_PER_TX, ConsensusError::LaunchLimitExceeded);
+    
+    if launch_limit.buyer == Pubkey::default() {
+        launch_limit.theme = theme_key;
+        launch_limit.buyer = buyer;
+        launch_limit.bump = bump.ok_or(ConsensusError::LaunchLimitRequired)?;
+    }
+    
+    let sol_bought = launch_limit.sol_bought
+        .checked_add(sol_amount)
+        .ok_or(ConsensusError::Overflow)?;
+    require!(sol_bought <= LAUNCH_MAX_BUY_PER_WALLET, ConsensusError::LaunchLimitExceeded);
+    launch_limit.sol_bought = sol_bought;
+    
+    Ok(())
+}
+
+/// 交易后更新主题价格预言机 (首次使用时写入主题与 bump)
+pub(crate) fn update_price_oracle(
+    oracle: &mut PriceOracle,
+    theme_key: Pubkey,
+    theme: &Theme,
+    bump: u8,
+) -> Result<()> {
+    if oracle.theme == Pubkey::default() {
+        oracle.theme = theme_key;
+        oracle.bump = bump;
+    }
+    let clock = Clock::get()?;
+    oracle.record(clock.slot, clock.unix_timestamp, theme.spot_price());
+    Ok(())
+}
+
+/// 账实比率 (bps)，账面为 0 时视为满额
+fn reserve_ratio_bps(actual: u64, recorded: u64) -> u64 {
+    if recorded == 0 {
+        return BPS_DENOMINATOR as u64;
+    }
+    ((actual as u128) * (BPS_DENOMINATOR as u128) / (recorded as u128)).min(u64::MAX as u128) as u64
+}
+
+/// 发出储备健康度事件：对比 vault 实际余额与账面储备，供监控检测偏差
+pub(crate) fn emit_reserve_health(
+    emitter: &EventEmitter,
+    theme_key: Pubkey,
+    theme: &Theme,
+    vault_lamports: u64,
+    vault_token_balance: u64,
+) -> Result<()> {
+    // vault SOL 同时承载储备、回购池与待提取的费用
+    let recorded_sol = theme.sol_reserves
+        .saturating_add(theme.buyback_pool)
+        .saturating_add(theme.staking_rewards_accrued)
+        .saturating_add(theme.creator_fees_accrued)
+        .saturating_add(theme.platform_fees_accrued);
+
+    emitter.emit(ReserveHealth {
+        theme: theme_key,
+        vault_lamports,
+        recorded_sol,
+        sol_ratio_bps: reserve_ratio_bps(vault_lamports, recorded_sol),
+        vault_token_balance,
+        token_reserves: theme.token_reserves,
+        token_ratio_bps: reserve_ratio_bps(vault_token_balance, theme.token_reserves),
+    })
+}
+
+// -----------------------------------------------------------------------------
+// Events
+// -----------------------------------------------------------------------------
+
+#[event]
+pub struct ThemeCreated {
+    pub theme: Pubkey,
+    pub creator: Pubkey,
+    pub token_mint: Pubkey,
+    // name 移除，存储在链下
+    pub voting_mode: VotingMode,
+    pub total_supply: u64,
+}
+
+#[event]
+pub struct StakePoolCreated {
+    pub theme: Pubkey,
+    pub stake_pool: Pubkey,
+    pub stake_fee_share_bps: u16,
+}
+
+#[event]
+pub struct StakeFeeShareUpdated {
+    pub theme: Pubkey,
+    pub stake_fee_share_bps: u16,
+}
+
+#[event]
+pub struct Staked {
+    pub theme: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub total_staked: u64,
+}
+
+#[event]
+pub struct UnstakeRequested {
+    pub theme: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub available_at: i64,
+}
+
+#[event]
+pub struct Unstaked {
+    pub theme: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct StakeRewardsSynced {
+    pub theme: Pubkey,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+}
+
+#[event]
+pub struct StakeRewardsClaimed {
+    pub theme: Pubkey,
+    pub owner: Pubkey,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+}
+
+#[event]
+pub struct TokensSwapped {
+    pub theme: Pubkey,
+    pub user: Pubkey,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    pub is_buy: bool,
+    pub new_sol_reserves: u64,
+    pub new_token_reserves: u64,
+}
+
+/// 赛季积分累计 (链下按赛季汇总，见 POINTS_KIND_*)
+#[event]
+pub struct PointsAccrued {
+    pub user: Pubkey,
+    pub kind: u8,
+    pub points: u64,
+}
+
+#[event]
+pub struct BuybackFunded {
+    pub theme: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub buyback_pool: u64,
+}
+
+#[event]
+pub struct BuybackExecuted {
+    pub theme: Pubkey,
+    pub sol_spent: u64,
+    pub tokens_burned: u64,
+    pub new_token_reserves: u64,
+    pub keeper: Pubkey,
+    pub keeper_reward: u64,
+    pub policy: u8,
+}
+
+#[event]
+pub struct CommunityBurned {
+    pub theme: Pubkey,
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub total_community_burned: u64,
+}
+
+#[event]
+pub struct ThemeMigrated {
+    pub theme: Pubkey,
+    pub migration_target: u8,
+    pub pool: Pubkey,
+    pub lp_mint: Pubkey,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    pub timestamp: i64,
+    pub lp_lock: Pubkey,
+    pub lp_amount: u64,
+    pub lp_unlock_at: i64,
+}
+
+#[event]
+pub struct AirdropCreated {
+    pub campaign: Pubkey,
+    pub theme: Pubkey,
+    pub campaign_id: u64,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct AirdropClaimed {
+    pub campaign: Pubkey,
+    pub claimant: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct AirdropReclaimed {
+    pub campaign: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct MintAuthorityRevoked {
+    pub theme: Pubkey,
+    pub mint: Pubkey,
+    pub previous_authority: Pubkey,
+    pub supply: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LpUnlocked {
+    pub theme: Pubkey,
+    pub lp_lock: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReferralRegistered {
+    pub referral: Pubkey,
+    pub referrer: Pubkey,
+}
+
+#[event]
+pub struct ReferralFeePaid {
+    pub referral: Pubkey,
+    pub theme: Pubkey,
+    pub trader: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReferralFeesClaimed {
+    pub referral: Pubkey,
+    pub referrer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReferralFeeSplitUpdated {
+    pub referral_fee_split_bps: u16,
+}
+
+#[event]
+pub struct ThemeMetadataUpdated {
+    pub theme: Pubkey,
+    pub name: [u8; 12],
+    pub description: [u8; 48],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ThemeMetadataSet {
+    pub theme: Pubkey,
+    pub metadata: Pubkey,
+    pub name: String,
+    pub icon_uri: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreatorVestingCreated {
+    pub theme: Pubkey,
+    pub creator: Pubkey,
+    pub total_amount: u64,
+    pub cliff_ts: i64,
+    pub end_ts: i64,
+}
+
+#[event]
+pub struct VestedTokensClaimed {
+    pub theme: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub total_claimed: u64,
+}
+
+#[event]
+pub struct CreatorReserveBurned {
+    pub theme: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub remaining_reserve: u64,
+    pub new_supply: u64,
+}
+
+#[event]
+pub struct ThemeRetirementProposed {
+    pub theme: Pubkey,
+    pub authority: Pubkey,
+    pub eta: i64,
+}
 
-    /// 用 SOL 购买主题代币
-    pub fn swap_sol_for_tokens(
-        ctx: Context<SwapSolForTokens>,
-        sol_amount: u64,
-        min_tokens_out: u64,
-    ) -> Result<()> {
-        instructions::swap_sol_for_tokens(ctx, sol_amount, min_tokens_out)
-    }
+#[event]
+pub struct ThemeRetirementCancelled {
+    pub theme: Pubkey,
+    pub authority: Pubkey,
+}
 
-    /// 卖出主题代币获得 SOL
-    pub fn swap_tokens_for_sol(
-        ctx: Context<SwapTokensForSol>,
-        token_amount: u64,
-        min_sol_out: u64,
-    ) -> Result<()> {
-        instructions::swap_tokens_for_sol(ctx, token_amount, min_sol_out)
-    }
+#[event]
+pub struct ThemeRetired {
+    pub theme: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub total_claimable: u64,
+    pub timestamp: i64,
+}
 
-    /// 执行回购销毁
-    pub fn execute_buyback(ctx: Context<ExecuteBuyback>) -> Result<()> {
-        instructions::execute_buyback(ctx)
-    }
+#[event]
+pub struct RetirementClaimed {
+    pub theme: Pubkey,
+    pub holder: Pubkey,
+    pub amount: u64,
 }
 
-// -----------------------------------------------------------------------------
-// Events
-// -----------------------------------------------------------------------------
+#[event]
+pub struct ThemePaused {
+    pub theme: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
 
 #[event]
-pub struct ThemeCreated {
+pub struct ThemeUnpaused {
     pub theme: Pubkey,
-    pub creator: Pubkey,
-    pub token_mint: Pubkey,
-    // name 移除，存储在链下
-    pub voting_mode: VotingMode,
-    pub total_supply: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
 }
 
 #[event]
-pub struct TokensSwapped {
+pub struct FeesClaimed {
     pub theme: Pubkey,
-    pub user: Pubkey,
-    pub sol_amount: u64,
-    pub token_amount: u64,
-    pub is_buy: bool,
-    pub new_sol_reserves: u64,
-    pub new_token_reserves: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub is_creator_fee: bool,
 }
 
 #[event]
-pub struct BuybackExecuted {
+pub struct AuthorityProposed {
+    pub current_authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct TradingConfigUpdateProposed {
+    pub trade_fee_bps: u16,
+    pub buyback_fee_split_bps: u16,
+    pub platform_fee_split_bps: u16,
+    pub creator_fee_split_bps: u16,
+    pub eta: i64,
+}
+
+#[event]
+pub struct TradingConfigUpdated {
+    pub trade_fee_bps: u16,
+    pub buyback_fee_split_bps: u16,
+    pub platform_fee_split_bps: u16,
+    pub creator_fee_split_bps: u16,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub kind: u8,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub size: u32,
+}
+
+#[event]
+pub struct ReserveHealth {
     pub theme: Pubkey,
-    pub sol_spent: u64,
-    pub tokens_burned: u64,
-    pub new_token_reserves: u64,
+    pub vault_lamports: u64,
+    pub recorded_sol: u64,
+    pub sol_ratio_bps: u64,
+    pub vault_token_balance: u64,
+    pub token_reserves: u64,
+    pub token_ratio_bps: u64,
 }
 
 // -----------------------------------------------------------------------------
@@ -144,10 +1162,193 @@ pub struct Theme {
     // Bumps
     pub vault_bump: u8,
     pub theme_bump: u8,
+    
+    // 社区累计销毁量
+    pub community_burned: u64,
+    
+    // 惩罚池分配曲线 (PAYOUT_CURVE_*) 与指数
+    pub payout_curve: u8,
+    pub payout_exponent: u8,
+    
+    // 平局处理规则 (TIE_BREAK_*)
+    pub tie_break_rule: u8,
+    
+    // 结算争议期 (秒)，0 表示结算即终局
+    pub dispute_period: i64,
+    
+    // RejectAll 否决阈值 (bps)
+    pub reject_all_threshold_bps: u16,
+    
+    // 结算时销毁的惩罚池比例 (bps)
+    pub penalty_burn_bps: u16,
+    
+    // 虚拟储备，仅用于联合曲线定价
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+    
+    // 毕业迁移目标 (Raydium / Meteora)
+    pub migration_target: u8,
+    
+    // 毕业池地址 (迁移后记录，AMM 回购校验)
+    pub amm_pool: Pubkey,
+    
+    // USD 计价的最小质押量 (代币，keeper 刷新，0 表示仅用 MIN_TOKEN_STAKE)
+    pub min_stake_tokens: u64,
+    
+    // 分段联合曲线 (段数为 0 时使用 virtual_* 的单段曲线)
+    pub curve_phases: [CurvePhase; MAX_CURVE_PHASES],
+    pub curve_phase_count: u8,
+    
+    // 累计待提取的创建者费与平台费 (lamports，存于金库)
+    pub creator_fees_accrued: u64,
+    pub platform_fees_accrued: u64,
+    
+    // 代币铸造完成 (开盘) 的 slot，0 表示尚未开盘
+    pub launch_slot: u64,
+    
+    // 开盘费率计划：从 launch_fee_bps 经 fee_decay_slots 个 slot 线性衰减到基础费率 (0 表示不启用)
+    pub launch_fee_bps: u16,
+    pub fee_decay_slots: u64,
+    
+    // 同一钱包反向交易的最短间隔 (秒，0 表示关闭)
+    pub swap_cooldown_secs: u32,
+    
+    // 当前 slot 内的累计买入/卖出成交额 (lamports)，换 slot 时清零
+    pub volume_slot: u64,
+    pub slot_buy_volume: u64,
+    pub slot_sell_volume: u64,
+    
+    // 最近一次修改名称/描述的时间 (0 表示从未修改)
+    pub metadata_updated_at: i64,
+    
+    // 退役提议的可执行时间 (0 表示无待执行的退役)
+    pub retire_eta: i64,
+    
+    // 计价资产：Pubkey::default() 表示原生 SOL，否则为 USDC mint
+    // 储备、费用与回购池均以该资产的最小单位记账
+    pub quote_mint: Pubkey,
+    
+    // 交易回购费中划给质押池的比例，及尚未同步到质押池的部分 (lamports，存于金库)
+    pub stake_fee_share_bps: u16,
+    pub staking_rewards_accrued: u64,
+    
+    // 回购策略：BUYBACK_POLICY_BURN 或 BUYBACK_POLICY_LIQUIDITY
+    pub buyback_policy: u8,
+    
+    // 账户结构版本 (THEME_ACCOUNT_VERSION)，0 为未记录版本的旧账户
+    pub version: u8,
 }
 
 impl Theme {
     pub const INIT_SPACE: usize = THEME_SPACE;
+
+    /// 是否以原生 SOL 计价
+    pub fn is_sol_quoted(&self) -> bool {
+        self.quote_mint == Pubkey::default()
+    }
+    
+    /// 回购触发阈值 (按计价资产)
+    pub fn buyback_threshold(&self) -> u64 {
+        if self.is_sol_quoted() { BUYBACK_THRESHOLD } else { USDC_BUYBACK_THRESHOLD }
+    }
+
+    /// 记账交易回购费：质押分成部分留待同步到质押池，其余进入回购池
+    pub fn accrue_buyback_fee(&mut self, buyback_fee: u64) -> Result<()> {
+        let stake_share = (buyback_fee as u128 * self.stake_fee_share_bps as u128
+            / BPS_DENOMINATOR as u128) as u64;
+        self.staking_rewards_accrued = self.staking_rewards_accrued
+            .checked_add(stake_share)
+            .ok_or(ConsensusError::Overflow)?;
+        self.buyback_pool = self.buyback_pool
+            .checked_add(buyback_fee - stake_share)
+            .ok_or(ConsensusError::Overflow)?;
+        Ok(())
+    }
+
+    /// 当前是否处于开盘防狙击窗口
+    pub fn in_launch_window(&self, slot: u64) -> bool {
+        self.launch_slot > 0 && slot < self.launch_slot.saturating_add(LAUNCH_PROTECTION_SLOTS)
+    }
+    
+    /// 记录本 slot 的成交额；单向累计超过 (真实 + 虚拟 SOL 储备) 的 max_slot_volume_bps 时熔断
+    /// max_slot_volume_bps 为 0 表示关闭
+    pub fn record_slot_volume(
+        &mut self,
+        slot: u64,
+        sol_amount: u64,
+        is_buy: bool,
+        max_slot_volume_bps: u16,
+    ) -> Result<()> {
+        if self.volume_slot != slot {
+            self.volume_slot = slot;
+            self.slot_buy_volume = 0;
+            self.slot_sell_volume = 0;
+        }
+        let volume = if is_buy { &mut self.slot_buy_volume } else { &mut self.slot_sell_volume };
+        *volume = volume.checked_add(sol_amount).ok_or(ConsensusError::Overflow)?;
+        
+        if max_slot_volume_bps > 0 {
+            let reserves = self.sol_reserves.saturating_add(self.virtual_sol_reserves) as u128;
+            let limit = reserves * max_slot_volume_bps as u128 / BPS_DENOMINATOR as u128;
+            require!(
+                (*volume as u128) <= limit,
+                ConsensusError::SlotVolumeLimitExceeded
+            );
+        }
+        Ok(())
+    }
+    
+    /// 当前生效的交易费率：开盘后按计划从 launch_fee_bps 线性衰减到基础费率
+    pub fn current_fee_bps(&self, base_fee_bps: u16, slot: u64) -> u16 {
+        if self.launch_slot == 0 || self.fee_decay_slots == 0 || self.launch_fee_bps <= base_fee_bps {
+            return base_fee_bps;
+        }
+        let elapsed = slot.saturating_sub(self.launch_slot);
+        if elapsed >= self.fee_decay_slots {
+            return base_fee_bps;
+        }
+        let spread = (self.launch_fee_bps - base_fee_bps) as u128;
+        let remaining = self.fee_decay_slots - elapsed;
+        base_fee_bps + (spread * remaining as u128 / self.fee_decay_slots as u128) as u16
+    }
+
+    /// 曲线分段；未配置分段时退化为 virtual_* 定义的单段曲线
+    pub fn curve(&self) -> ([CurvePhase; MAX_CURVE_PHASES], usize) {
+        if self.curve_phase_count == 0 {
+            let mut phases = [CurvePhase::default(); MAX_CURVE_PHASES];
+            phases[0] = CurvePhase {
+                sol_end: u64::MAX,
+                virtual_sol: self.virtual_sol_reserves,
+                virtual_token: self.virtual_token_reserves,
+            };
+            return (phases, 1);
+        }
+        (self.curve_phases, self.curve_phase_count as usize)
+    }
+
+    /// 曲线现货价格 (lamports / 代币最小单位 * PRICE_SCALE)
+    pub fn spot_price(&self) -> u64 {
+        let (phases, count) = self.curve();
+        phased_spot_price(&phases[..count], self.token_reserves, self.sol_reserves)
+    }
+
+    /// 买入 sol_amount 可得的代币 (扣除 fee_bps 手续费)
+    pub fn buy_tokens(&self, sol_amount: u64, fee_bps: u16) -> Result<u64> {
+        let (phases, count) = self.curve();
+        phased_buy_tokens(&phases[..count], self.token_reserves, self.sol_reserves, sol_amount, fee_bps)
+    }
+
+    /// 卖出 token_amount 可得的 SOL (扣除 fee_bps 手续费)
+    pub fn sell_sol(&self, token_amount: u64, fee_bps: u16) -> Result<u64> {
+        let (phases, count) = self.curve();
+        phased_sell_sol(&phases[..count], self.token_reserves, self.sol_reserves, token_amount, fee_bps)
+    }
+
+    /// 买入精确 tokens_out 所需的 SOL (含 fee_bps 手续费)
+    pub fn buy_sol_for_tokens(&self, tokens_out: u64, fee_bps: u16) -> Result<u64> {
+        let (phases, count) = self.curve();
+        phased_buy_sol_for_tokens(&phases[..count], self.token_reserves, self.sol_reserves, tokens_out, fee_bps)
+    }
 }
 
 #[account]
@@ -160,14 +1361,362 @@ impl ThemeVault {
     pub const SPACE: usize = THEME_VAULT_SPACE;
 }
 
+/// 价格观测点
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct PriceObservation {
+    pub slot: u64,
+    pub timestamp: i64,
+    pub price: u64,
+    // 截至 timestamp 的累计价格 (price * 秒)
+    pub cumulative_price: u128,
+}
+
+/// 主题 TWAP 价格预言机 (每次交易后更新)
+#[account]
+pub struct PriceOracle {
+    pub theme: Pubkey,
+    pub observations: [PriceObservation; TWAP_OBSERVATIONS],
+    pub head: u8,  // 最新观测下标
+    pub count: u8, // 有效观测数
+    pub bump: u8,
+}
+
+impl PriceOracle {
+    pub const SPACE: usize = PRICE_ORACLE_SPACE;
+
+    /// 记录一次观测；同一 slot 内只保留最后价格，限制单 slot 操纵
+    pub fn record(&mut self, slot: u64, timestamp: i64, price: u64) {
+        if self.count == 0 {
+            self.observations[0] = PriceObservation { slot, timestamp, price, cumulative_price: 0 };
+            self.head = 0;
+            self.count = 1;
+            return;
+        }
+
+        let last = self.observations[self.head as usize];
+        if last.slot == slot {
+            self.observations[self.head as usize].price = price;
+            return;
+        }
+
+        let elapsed = timestamp.saturating_sub(last.timestamp).max(0) as u128;
+        let cumulative_price = last.cumulative_price
+            .saturating_add((last.price as u128).saturating_mul(elapsed));
+        self.head = ((self.head as usize + 1) % TWAP_OBSERVATIONS) as u8;
+        self.observations[self.head as usize] = PriceObservation { slot, timestamp, price, cumulative_price };
+        if (self.count as usize) < TWAP_OBSERVATIONS {
+            self.count += 1;
+        }
+    }
+
+    /// 最近 window 秒的时间加权均价；历史不足 window 时按已有区间计算
+    pub fn twap(&self, now: i64, window: i64) -> Option<u64> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let latest = self.observations[self.head as usize];
+        let cumulative_at = |obs: &PriceObservation, ts: i64| {
+            obs.cumulative_price
+                .saturating_add((obs.price as u128).saturating_mul(ts.saturating_sub(obs.timestamp).max(0) as u128))
+        };
+        let cumulative_now = cumulative_at(&latest, now);
+        let target = now.saturating_sub(window);
+
+        // 从最新向前找第一个不晚于窗口起点的观测
+        let mut start = latest;
+        for i in 0..self.count as usize {
+            let index = (self.head as usize + TWAP_OBSERVATIONS - i) % TWAP_OBSERVATIONS;
+            start = self.observations[index];
+            if start.timestamp <= target {
+                let span = (now - target) as u128;
+                return Some(((cumulative_now - cumulative_at(&start, target)) / span) as u64);
+            }
+        }
+
+        // 历史不足窗口：从最早观测开始计算
+        if now <= start.timestamp {
+            return Some(latest.price);
+        }
+        let span = (now - start.timestamp) as u128;
+        Some(((cumulative_now - start.cumulative_price) / span) as u64)
+    }
+}
+
 #[account]
 pub struct TradingConfiguration {
     pub trade_fee_bps: u16,
     pub buyback_fee_split_bps: u16,
     pub platform_fee_split_bps: u16,
     pub creator_fee_split_bps: u16,
+    
+    // Pyth SOL/USD 价格账户与 USD 计价最小额 (micro-USD，0 表示关闭)
+    pub sol_usd_feed: Pubkey,
+    pub min_trade_usd: u64,
+    pub min_stake_usd: u64,
+    
+    // 按最近 Pyth 价格换算的最小交易额 (lamports)，由 refresh_usd_minimums 维护
+    pub min_trade_lamports: u64,
+    
+    // 配置管理员与时间锁内待生效的费用配置 (pending_update_eta 为 0 表示无待生效变更)
+    pub authority: Pubkey,
+    pub pending_trade_fee_bps: u16,
+    pub pending_buyback_fee_split_bps: u16,
+    pub pending_platform_fee_split_bps: u16,
+    pub pending_creator_fee_split_bps: u16,
+    pub pending_update_eta: i64,
+    
+    // 两步移交中被提名的新管理员 (默认值表示无)
+    pub pending_authority: Pubkey,
+    
+    // 带推荐人的交易中，平台费分给推荐人的比例
+    pub referral_fee_split_bps: u16,
+    
+    // 单 slot 单向成交额熔断 (占 SOL 储备的 bps，0 表示关闭)
+    pub max_slot_volume_bps: u16,
+    
+    // 账户结构版本 (TRADING_CONFIG_VERSION)，0 为未记录版本的旧账户
+    pub version: u8,
+}
+
+/// 开盘窗口内单个买家的累计买入 (lamports)
+#[account]
+pub struct LaunchLimit {
+    pub theme: Pubkey,
+    pub buyer: Pubkey,
+    pub sol_bought: u64,
+    pub bump: u8,
+}
+
+impl LaunchLimit {
+    pub const SPACE: usize = LAUNCH_LIMIT_SPACE;
+}
+
+/// 交易者在主题内的最近一次交易 (反向交易冷却)
+#[account]
+pub struct TraderState {
+    pub theme: Pubkey,
+    pub trader: Pubkey,
+    pub last_trade_ts: i64,
+    pub last_trade_is_buy: bool,
+    pub bump: u8,
+}
+
+impl TraderState {
+    pub const SPACE: usize = TRADER_STATE_SPACE;
+}
+
+/// 主题的完整展示信息 (可选附属账户，Theme 本身保留紧凑的 name/description)
+#[account]
+pub struct ThemeMetadata {
+    pub theme: Pubkey,
+    pub name: String,
+    pub description: String,
+    pub icon_uri: String,
+    pub website: String,
+    pub twitter: String,
+    pub telegram: String,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl ThemeMetadata {
+    pub const SPACE: usize = THEME_METADATA_SPACE;
+}
+
+/// 创建者储备锁仓 (代币存于该 PDA 的 ATA)
+#[account]
+pub struct CreatorVesting {
+    pub theme: Pubkey,
+    pub creator: Pubkey,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub start_ts: i64,
+    pub cliff_ts: i64,
+    pub end_ts: i64,
+    pub bump: u8,
+}
+
+impl CreatorVesting {
+    pub const SPACE: usize = CREATOR_VESTING_SPACE;
+
+    /// 尚未领取的储备 (含未释放部分)，创建者可销毁的上限
+    pub fn unclaimed_amount(&self) -> u64 {
+        self.total_amount.saturating_sub(self.claimed_amount)
+    }
+
+    /// 截至 now 已释放的总量：cliff 前为 0，之后从 start 起线性释放
+    pub fn vested_amount(&self, now: i64) -> u64 {
+        if now < self.cliff_ts {
+            return 0;
+        }
+        if now >= self.end_ts {
+            return self.total_amount;
+        }
+        let elapsed = (now - self.start_ts) as u128;
+        let duration = (self.end_ts - self.start_ts) as u128;
+        (self.total_amount as u128 * elapsed / duration) as u64
+    }
+}
+
+/// 全局主题注册表头
+#[account]
+pub struct ThemeRegistry {
+    pub total_themes: u64,
+    pub bump: u8,
+}
+
+impl ThemeRegistry {
+    pub const SPACE: usize = THEME_REGISTRY_SPACE;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ThemeRegistryEntry {
+    pub theme: Pubkey,
+    pub token_mint: Pubkey,
+    pub created_slot: u64,
+}
+
+/// 主题注册表分页 (每页 THEME_REGISTRY_PAGE_SIZE 条，按创建顺序追加)
+#[account]
+pub struct ThemeRegistryPage {
+    pub page_index: u32,
+    pub entries: Vec<ThemeRegistryEntry>,
+    pub bump: u8,
+}
+
+impl ThemeRegistryPage {
+    pub const SPACE: usize = THEME_REGISTRY_PAGE_SPACE;
+}
+
+/// 主题退役后的 SOL 分配池
+#[account]
+pub struct RetirementPool {
+    pub theme: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub total_claimable: u64,
+    pub claimed_amount: u64,
+    pub retired_at: i64,
+    pub bump: u8,
+}
+
+impl RetirementPool {
+    pub const SPACE: usize = RETIREMENT_POOL_SPACE;
+}
+
+/// 持有者退役分配领取记录 (存在即已领取)
+#[account]
+pub struct RetirementClaim {
+    pub theme: Pubkey,
+    pub holder: Pubkey,
+    pub amount: u64,
+}
+
+impl RetirementClaim {
+    pub const SPACE: usize = RETIREMENT_CLAIM_SPACE;
+}
+
+/// 推荐人注册表条目
+#[account]
+pub struct Referral {
+    pub referrer: Pubkey,
+    pub fees_accrued: u64,   // 待提取的推荐分成 (lamports，存于本 PDA)
+    pub total_earned: u64,
+    pub referred_trades: u64,
+    pub bump: u8,
+}
+
+impl Referral {
+    pub const SPACE: usize = REFERRAL_SPACE;
+}
+
+/// 主题代币 merkle 空投活动
+#[account]
+pub struct AirdropCampaign {
+    pub theme: Pubkey,
+    pub creator: Pubkey,
+    pub campaign_id: u64,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub expires_at: i64,
+    pub reclaimed: bool,
+    pub bump: u8,
+}
+
+impl AirdropCampaign {
+    pub const SPACE: usize = AIRDROP_CAMPAIGN_SPACE;
+}
+
+/// 空投领取记录 (存在即已领取)
+#[account]
+pub struct AirdropClaim {
+    pub campaign: Pubkey,
+    pub claimant: Pubkey,
+    pub amount: u64,
+}
+
+impl AirdropClaim {
+    pub const SPACE: usize = AIRDROP_CLAIM_SPACE;
+}
+
+/// 毕业迁移产生的 LP 锁仓：LP 代币存于本 PDA 的 ATA，解锁时间前无法转出
+#[account]
+pub struct LpLock {
+    pub theme: Pubkey,
+    pub pool: Pubkey,
+    pub lp_mint: Pubkey,
+    pub amount: u64,
+    pub locked_at: i64,
+    pub unlock_at: i64,
+    pub bump: u8,
+}
+
+impl LpLock {
+    pub const SPACE: usize = LP_LOCK_SPACE;
+}
+
+/// 主题代币质押池：按份额累计交易回购费 (SOL) 与结算回购贡献 (主题代币) 的分成
+#[account]
+pub struct StakePool {
+    pub theme: Pubkey,
+    pub total_staked: u64,          // 计息中的质押量
+    pub total_unstaking: u64,       // 冷却中的质押量 (不计息，仍在池子 ATA 中)
+    pub acc_sol_per_share: u128,    // 每份累计 SOL 奖励 (× STAKE_ACC_PRECISION)
+    pub acc_token_per_share: u128,  // 每份累计代币奖励 (× STAKE_ACC_PRECISION)
+    pub token_rewards_reserved: u64, // 已分配未领取的代币奖励 (在池子 ATA 中)
+    pub vault_excess_synced: u64,   // 金库 ATA 中已处理过的结算回购贡献
+    pub bump: u8,
+}
+
+impl StakePool {
+    pub const SPACE: usize = STAKE_POOL_SPACE;
+}
+
+/// 单个持有者在主题质押池中的仓位
+#[account]
+pub struct StakePosition {
+    pub theme: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub sol_reward_debt: u128,
+    pub token_reward_debt: u128,
+    pub pending_sol: u64,
+    pub pending_tokens: u64,
+    pub unstaking_amount: u64,
+    pub unstake_available_at: i64,
+    pub bump: u8,
+}
+
+impl StakePosition {
+    pub const SPACE: usize = STAKE_POSITION_SPACE;
 }
 
 impl TradingConfiguration {
     pub const SPACE: usize = TRADING_CONFIG_SPACE;
+
+    /// 当前生效的最小买入额：固定下限与 USD 换算值取大
+    pub fn min_sol_trade(&self) -> u64 {
+        MIN_SOL_TRADE.max(self.min_trade_lamports)
+    }
 }
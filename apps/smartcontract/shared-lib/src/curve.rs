@@ -0,0 +1,383 @@
+// -----------------------------------------------------------------------------
+// 分段联合曲线
+// -----------------------------------------------------------------------------
+//
+// 每段是一条带虚拟储备的恒定乘积曲线，按真实 SOL 储备划分区间：
+//   有效储备 = (真实 SOL + virtual_sol, 真实代币 + virtual_token)
+// virtual_sol 越小价格上涨越陡。段边界处的 virtual_token 在创建时推导，
+// 保证相邻两段在边界价格连续；跨段交易按段拆分逐段计算。
+
+use anchor_lang::prelude::*;
+use crate::{ConsensusError, BPS_DENOMINATOR, PRICE_SCALE};
+
+pub const MAX_CURVE_PHASES: usize = 4;
+
+/// 创建主题时传入的分段参数
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct CurvePhaseParams {
+    pub sol_end: u64,     // 本段结束时的真实 SOL 储备 (最后一段必须为 u64::MAX)
+    pub virtual_sol: u64, // 本段虚拟 SOL 储备
+}
+
+/// 存储在 Theme 上的分段
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CurvePhase {
+    pub sol_end: u64,
+    pub virtual_sol: u64,
+    pub virtual_token: u64, // 由边界价格连续性推导
+}
+
+/// 校验分段参数并推导每段的虚拟代币储备
+pub fn build_curve_phases(
+    params: &[CurvePhaseParams],
+    initial_token_reserves: u64,
+    initial_virtual_token: u64,
+) -> Result<[CurvePhase; MAX_CURVE_PHASES]> {
+    require!(
+        !params.is_empty() && params.len() <= MAX_CURVE_PHASES,
+        ConsensusError::InvalidCurvePhases
+    );
+    require!(
+        params[params.len() - 1].sol_end == u64::MAX,
+        ConsensusError::InvalidCurvePhases
+    );
+
+    let mut phases = [CurvePhase::default(); MAX_CURVE_PHASES];
+    let mut start_sol = 0u64;
+    let mut start_token = initial_token_reserves;
+    let mut virtual_token = initial_virtual_token;
+
+    for (i, p) in params.iter().enumerate() {
+        require!(
+            p.virtual_sol > 0 && p.sol_end > start_sol,
+            ConsensusError::InvalidCurvePhases
+        );
+
+        if i > 0 {
+            // 边界处价格连续：(B + V_i) / (T_B + W_i) == (B + V_prev) / (T_B + W_prev)
+            let prev = phases[i - 1];
+            let boundary_effective_token = start_token as u128 + prev.virtual_token as u128;
+            let scaled = (start_sol as u128 + p.virtual_sol as u128)
+                .checked_mul(boundary_effective_token)
+                .ok_or(ConsensusError::Overflow)?
+                / (start_sol as u128 + prev.virtual_sol as u128);
+            virtual_token = u64::try_from(
+                scaled
+                    .checked_sub(start_token as u128)
+                    .ok_or(ConsensusError::InvalidCurvePhases)?,
+            )
+            .map_err(|_| error!(ConsensusError::Overflow))?;
+        }
+
+        phases[i] = CurvePhase {
+            sol_end: p.sol_end,
+            virtual_sol: p.virtual_sol,
+            virtual_token,
+        };
+
+        // 推进到本段终点，得到下一段起点的真实代币储备
+        if p.sol_end != u64::MAX {
+            let k = (start_sol as u128 + p.virtual_sol as u128)
+                .checked_mul(start_token as u128 + virtual_token as u128)
+                .ok_or(ConsensusError::Overflow)?;
+            let end_effective_token = k / (p.sol_end as u128 + p.virtual_sol as u128);
+            start_token = u64::try_from(
+                end_effective_token
+                    .checked_sub(virtual_token as u128)
+                    .ok_or(ConsensusError::InvalidCurvePhases)?,
+            )
+            .map_err(|_| error!(ConsensusError::Overflow))?;
+            require!(start_token > 0, ConsensusError::InvalidCurvePhases);
+            start_sol = p.sol_end;
+        }
+    }
+
+    Ok(phases)
+}
+
+/// 当前 SOL 储备所在的段 (买入方向：sol < sol_end)
+fn phase_index(phases: &[CurvePhase], sol_reserves: u64) -> usize {
+    phases
+        .iter()
+        .position(|p| sol_reserves < p.sol_end)
+        .unwrap_or(phases.len() - 1)
+}
+
+/// 段起点的真实 SOL 储备
+fn phase_start(phases: &[CurvePhase], index: usize) -> u64 {
+    if index == 0 { 0 } else { phases[index - 1].sol_end }
+}
+
+fn div_ceil(numerator: u128, denominator: u128) -> Result<u128> {
+    require!(denominator > 0, ConsensusError::DivisionByZero);
+    Ok(numerator.div_ceil(denominator))
+}
+
+fn deduct_fee(amount: u128, fee_bps: u16) -> u128 {
+    amount * (BPS_DENOMINATOR - fee_bps) as u128 / BPS_DENOMINATOR as u128
+}
+
+/// 分段曲线买入：SOL (扣费后) 逐段换出代币
+pub fn phased_buy_tokens(
+    phases: &[CurvePhase],
+    token_reserves: u64,
+    sol_reserves: u64,
+    sol_amount: u64,
+    fee_bps: u16,
+) -> Result<u64> {
+    if sol_amount == 0 || token_reserves == 0 {
+        return err!(ConsensusError::InvalidAmount);
+    }
+
+    let mut remaining = deduct_fee(sol_amount as u128, fee_bps);
+    let mut sol = sol_reserves as u128;
+    let mut token = token_reserves as u128;
+    let mut tokens_out = 0u128;
+
+    while remaining > 0 && token > 0 {
+        let p = phases[phase_index(phases, sol as u64)];
+        let step = remaining.min(p.sol_end as u128 - sol);
+        let effective_sol = sol + p.virtual_sol as u128;
+        let effective_token = token + p.virtual_token as u128;
+
+        // tokens = T_eff * s / (S_eff + s)
+        let out = (effective_token
+            .checked_mul(step)
+            .ok_or(ConsensusError::Overflow)?
+            / (effective_sol + step))
+            .min(token);
+
+        tokens_out += out;
+        token -= out;
+        sol += step;
+        remaining -= step;
+    }
+
+    u64::try_from(tokens_out).map_err(|_| error!(ConsensusError::Overflow))
+}
+
+/// 分段曲线卖出：代币逐段换回 SOL (税后)
+pub fn phased_sell_sol(
+    phases: &[CurvePhase],
+    token_reserves: u64,
+    sol_reserves: u64,
+    token_amount: u64,
+    fee_bps: u16,
+) -> Result<u64> {
+    if token_amount == 0 || sol_reserves == 0 {
+        return err!(ConsensusError::InvalidAmount);
+    }
+
+    let mut remaining = token_amount as u128;
+    let mut sol = sol_reserves as u128;
+    let mut token = token_reserves as u128;
+    let mut sol_out = 0u128;
+
+    while remaining > 0 && sol > 0 {
+        // 卖出方向：sol 恰在边界时属于前一段
+        let index = phase_index(phases, (sol - 1) as u64);
+        let p = phases[index];
+        let start = phase_start(phases, index) as u128;
+        let effective_sol = sol + p.virtual_sol as u128;
+        let effective_token = token + p.virtual_token as u128;
+        let k = effective_sol
+            .checked_mul(effective_token)
+            .ok_or(ConsensusError::Overflow)?;
+
+        // 把 SOL 储备推回段起点所需的代币
+        let to_start = div_ceil(k, start + p.virtual_sol as u128)? - effective_token;
+        if index == 0 || remaining <= to_start {
+            // sol = S_eff * t / (T_eff + t)
+            let out = (effective_sol * remaining / (effective_token + remaining)).min(sol - start);
+            sol_out += out;
+            break;
+        }
+
+        sol_out += sol - start;
+        token += to_start;
+        remaining -= to_start;
+        sol = start;
+    }
+
+    let net = deduct_fee(sol_out, fee_bps);
+    u64::try_from(net).map_err(|_| error!(ConsensusError::Overflow))
+}
+
+/// 分段曲线精确输出买入：买到 tokens_out 所需的 SOL (含手续费，向上取整)
+pub fn phased_buy_sol_for_tokens(
+    phases: &[CurvePhase],
+    token_reserves: u64,
+    sol_reserves: u64,
+    tokens_out: u64,
+    fee_bps: u16,
+) -> Result<u64> {
+    if tokens_out == 0 || tokens_out >= token_reserves {
+        return err!(ConsensusError::InvalidAmount);
+    }
+
+    let mut remaining = tokens_out as u128;
+    let mut sol = sol_reserves as u128;
+    let mut token = token_reserves as u128;
+    let mut sol_in = 0u128;
+
+    loop {
+        let p = phases[phase_index(phases, sol as u64)];
+        let effective_sol = sol + p.virtual_sol as u128;
+        let effective_token = token + p.virtual_token as u128;
+
+        // 本段到终点最多能买出的代币
+        let available = if p.sol_end == u64::MAX {
+            u128::MAX
+        } else {
+            let k = effective_sol
+                .checked_mul(effective_token)
+                .ok_or(ConsensusError::Overflow)?;
+            effective_token - div_ceil(k, p.sol_end as u128 + p.virtual_sol as u128)?
+        };
+
+        if remaining <= available {
+            // s = S_eff * t / (T_eff - t)
+            let step = div_ceil(
+                effective_sol
+                    .checked_mul(remaining)
+                    .ok_or(ConsensusError::Overflow)?,
+                effective_token - remaining,
+            )?;
+            sol_in += step;
+            break;
+        }
+
+        sol_in += p.sol_end as u128 - sol;
+        token -= available;
+        remaining -= available;
+        sol = p.sol_end as u128;
+    }
+
+    // 加回手续费
+    let gross = div_ceil(
+        sol_in
+            .checked_mul(BPS_DENOMINATOR as u128)
+            .ok_or(ConsensusError::Overflow)?,
+        (BPS_DENOMINATOR - fee_bps) as u128,
+    )?;
+    u64::try_from(gross).map_err(|_| error!(ConsensusError::Overflow))
+}
+
+/// 分段曲线现货价格 (lamports / 代币最小单位 * PRICE_SCALE)
+pub fn phased_spot_price(phases: &[CurvePhase], token_reserves: u64, sol_reserves: u64) -> u64 {
+    let p = phases[phase_index(phases, sol_reserves)];
+    let effective_token = token_reserves as u128 + p.virtual_token as u128;
+    if effective_token == 0 {
+        return 0;
+    }
+    let effective_sol = sol_reserves as u128 + p.virtual_sol as u128;
+    (effective_sol * PRICE_SCALE / effective_token).min(u64::MAX as u128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{INITIAL_TOKEN_RESERVES, VIRTUAL_SOL_RESERVES, VIRTUAL_TOKEN_RESERVES};
+
+    const SOL: u64 = 1_000_000_000;
+    const FEE_BPS: u16 = 100;
+    const PHASE_COUNT: usize = 3;
+
+    /// 三段曲线，后两段的虚拟 SOL 依次增大 (价格上涨趋缓)
+    fn phases() -> [CurvePhase; MAX_CURVE_PHASES] {
+        build_curve_phases(
+            &[
+                CurvePhaseParams { sol_end: 10 * SOL, virtual_sol: VIRTUAL_SOL_RESERVES },
+                CurvePhaseParams { sol_end: 40 * SOL, virtual_sol: 60 * SOL },
+                CurvePhaseParams { sol_end: u64::MAX, virtual_sol: 150 * SOL },
+            ],
+            INITIAL_TOKEN_RESERVES,
+            VIRTUAL_TOKEN_RESERVES,
+        )
+        .unwrap()
+    }
+
+    /// 从初始储备无手续费买入 sol 后的 (代币储备, SOL 储备)
+    fn reserves_at(phases: &[CurvePhase], sol: u64) -> (u64, u64) {
+        if sol == 0 {
+            return (INITIAL_TOKEN_RESERVES, 0);
+        }
+        let bought = phased_buy_tokens(phases, INITIAL_TOKEN_RESERVES, 0, sol, 0).unwrap();
+        (INITIAL_TOKEN_RESERVES - bought, sol)
+    }
+
+    #[test]
+    fn price_is_monotonic_across_phase_boundaries() {
+        let phases = phases();
+        let phases = &phases[..PHASE_COUNT];
+
+        let mut points: Vec<u64> = (0..=120).map(|i| i * SOL / 2).collect();
+        for boundary in [10 * SOL, 40 * SOL] {
+            points.extend([boundary - 1, boundary, boundary + 1]);
+        }
+        points.sort_unstable();
+
+        let mut last_price = 0;
+        for sol in points {
+            let (token_reserves, sol_reserves) = reserves_at(phases, sol);
+            let price = phased_spot_price(phases, token_reserves, sol_reserves);
+            assert!(price >= last_price, "price dropped at {sol}: {price} < {last_price}");
+            last_price = price;
+        }
+    }
+
+    #[test]
+    fn buy_then_sell_never_returns_more_sol() {
+        let phases = phases();
+        let phases = &phases[..PHASE_COUNT];
+
+        for start in [0, 3 * SOL, 10 * SOL - 1, 10 * SOL, 25 * SOL, 40 * SOL - 7] {
+            for amount in [1, 999, SOL / 3, 7 * SOL + 11, 45 * SOL] {
+                for fee_bps in [0, FEE_BPS] {
+                    let (token_reserves, sol_reserves) = reserves_at(phases, start);
+                    let tokens = phased_buy_tokens(phases, token_reserves, sol_reserves, amount, fee_bps).unwrap();
+                    if tokens == 0 {
+                        continue;
+                    }
+                    // 买入只有扣费后的 SOL 进入储备
+                    let net = (amount as u128 * (BPS_DENOMINATOR - fee_bps) as u128
+                        / BPS_DENOMINATOR as u128) as u64;
+                    let sol_back = phased_sell_sol(
+                        phases,
+                        token_reserves - tokens,
+                        sol_reserves + net,
+                        tokens,
+                        fee_bps,
+                    )
+                    .unwrap();
+                    assert!(
+                        sol_back <= amount,
+                        "round trip from {start} with {amount} returned {sol_back}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn buy_sol_for_tokens_is_exact_inverse_of_buy_tokens() {
+        let phases = phases();
+        let phases = &phases[..PHASE_COUNT];
+
+        for start in [0, 9 * SOL, 10 * SOL, 39 * SOL] {
+            let (token_reserves, sol_reserves) = reserves_at(phases, start);
+            for tokens in [1, 1_000_003, 10_000_000_000_000, 100_000_000_000_000] {
+                for fee_bps in [0, FEE_BPS] {
+                    let sol_in =
+                        phased_buy_sol_for_tokens(phases, token_reserves, sol_reserves, tokens, fee_bps).unwrap();
+                    // 向上取整：付出 sol_in 至少买到 tokens，少付 1 lamport 则买不到
+                    let bought = phased_buy_tokens(phases, token_reserves, sol_reserves, sol_in, fee_bps).unwrap();
+                    assert!(bought >= tokens, "{sol_in} bought {bought} < {tokens}");
+                    let short = phased_buy_tokens(phases, token_reserves, sol_reserves, sol_in - 1, fee_bps)
+                        .unwrap_or(0);
+                    assert!(short < tokens, "{} already buys {short} >= {tokens}", sol_in - 1);
+                }
+            }
+        }
+    }
+}
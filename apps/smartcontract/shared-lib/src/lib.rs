@@ -1,4 +1,8 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+
+pub mod curve;
+pub use curve::*;
 
 // This crate only exports constants, enums, and utility functions
 // Account structures are defined separately in each program
@@ -11,31 +15,214 @@ pub const BPS_DENOMINATOR: u16 = 10_000;
 pub const MIN_REVIEWERS: u64 = 10; // 最小参与者数量
 pub const CURATOR_FEE_BPS: u16 = 100; // 1% 策展费
 pub const PENALTY_BPS: u16 = 5_000; // 50% 惩罚比例
-pub const PLATFORM_FEE_BPS: u16 = 200; // 2% 平台费
+pub const MAX_PLATFORM_FEE_BPS: u16 = 500; // 平台费上限 5% (ProtocolConfig 可配置)
+pub const SETTLER_REWARD_BPS: u16 = 10; // 0.1% 结算者奖励
+pub const MAX_SETTLER_REWARD: u64 = 1_000_000_000; // 结算者奖励上限 (代币最小单位)
+pub const INSURANCE_FEE_BPS: u16 = 25; // 0.25% 进入协议保险基金
+pub const MAX_PENALTY_BURN_BPS: u16 = 5_000; // 惩罚池销毁比例上限 50% (主题可配置)
+pub const SETTLE_MANY_ACCOUNTS_PER_IDEA: usize = 8; // 批量结算每个创意的账户数
+// 衍生创意 (remix)：结算时按约定比例从费用基数中提取版税，归原创意的发起者与获胜评审
+pub const MAX_REMIX_FEE_BPS: u16 = 1_000; // 最高 10%
+pub const REMIX_INITIATOR_SHARE_BPS: u16 = 5_000; // 版税中原发起者的份额 50%，其余归原获胜评审
+
+// 账户结构版本：字段只追加在末尾，旧账户剩余缓冲区读出的 0 即未记录版本的旧布局
+// 布局变化时递增，已有账户由管理员经 migrate_account realloc 到当前大小并写入新版本号
+// Idea v1 为记录版本号的布局；v2 的每图统计 (质押、人数、赔付权重) 已按投票记录重算，由 migrate_idea_stats 完成
+pub const IDEA_LAYOUT_VERSION: u8 = 1;
+pub const IDEA_ACCOUNT_VERSION: u8 = 2;
+pub const PROTOCOL_CONFIG_VERSION: u8 = 1;
+pub const THEME_ACCOUNT_VERSION: u8 = 1;
+pub const TRADING_CONFIG_VERSION: u8 = 1;
+pub const ACCOUNT_KIND_IDEA: u8 = 0;
+pub const ACCOUNT_KIND_PROTOCOL_CONFIG: u8 = 1;
+pub const ACCOUNT_KIND_THEME: u8 = 2;
+pub const ACCOUNT_KIND_TRADING_CONFIG: u8 = 3;
 
 // 序列化限制
 pub const MAX_PROMPT_LEN: usize = 512;
 pub const MAX_IMAGE_URI_LEN: usize = 128;
+// 图片存储配置：DePIN 提交的 URI 须以某个允许的前缀开头 (列表为空则不限制)
+pub const MAX_STORAGE_PREFIXES: usize = 8;
+pub const MAX_STORAGE_PREFIX_LEN: usize = 64;
 pub const MAX_THEME_NAME_LEN: usize = 12;      // 减小到 12 避免栈溢出
 pub const MAX_THEME_DESCRIPTION_LEN: usize = 48; // 减小到 48
+pub const THEME_METADATA_UPDATE_COOLDOWN: i64 = 24 * 60 * 60; // 主题名称/描述每天最多修改一次
+// ThemeMetadata 附属账户 (完整名称、描述、图标与社交链接)
+pub const MAX_THEME_FULL_NAME_LEN: usize = 64;
+pub const MAX_THEME_FULL_DESCRIPTION_LEN: usize = 512;
+pub const MAX_THEME_LINK_LEN: usize = 128;
+pub const MAX_SPONSOR_NAME_LEN: usize = 32;
+pub const MAX_SPONSOR_LINK_LEN: usize = 128;
 
 // 质押参数
 pub const MIN_STAKE: u64 = 10_000_000; // 0.01 SOL
-pub const CREATION_FEE: u64 = 5_000_000; // 0.005 SOL
+pub const MAX_CREATION_FEE: u64 = 1_000_000_000; // 发起费上限 1 SOL (ProtocolConfig 可配置)
 
 // 时间加权参数
 pub const EARLY_BIRD_BONUS_BPS: u16 = 2_000; // 早期投票20%奖励
 pub const EARLY_BIRD_THRESHOLD: i64 = 24 * 3600; // 第一天算早期
 
+// 声誉权重加成：胜率超过 50% 的部分线性映射到 0 ~ 25% 的加成
+pub const MAX_REPUTATION_BONUS_BPS: u16 = 2_500; // 最高 1.25x
+pub const MIN_REPUTATION_SAMPLE: u32 = 10; // 至少 10 场已领取的结果才计算加成
+
+// 领取费用折扣：声誉或主题代币质押达标的评审少付其份额的策展费与平台费
+pub const MAX_FEE_DISCOUNT_BPS: u16 = 5_000; // 折扣上限 50% (ProtocolConfig 可配置)
+
+// 赛季排行榜：每个主题与全局各一张，按赛季净赢取与胜率各取前 N 名
+pub const LEADERBOARD_SIZE: usize = 10;
+pub const LEADERBOARD_MIN_SAMPLE: u32 = 5; // 赛季内至少 5 场结果才进入胜率榜
+pub const GLOBAL_LEADERBOARD_SCOPE: Pubkey = Pubkey::new_from_array([0; 32]); // 全局榜的 scope
+
+// 赛季积分：三个程序在投票、发起创意、获胜领取与交易时发出 PointsAccrued 事件，
+// 链下按赛季汇总后由财库注资奖励池并发布 Merkle 根，用户在 settlement 程序领取
+pub const POINTS_KIND_VOTE: u8 = 0;
+pub const POINTS_KIND_IDEA: u8 = 1;
+pub const POINTS_KIND_WIN: u8 = 2;
+pub const POINTS_KIND_TRADE: u8 = 3;
+pub const POINTS_PER_VOTE: u64 = 10;
+pub const POINTS_PER_IDEA: u64 = 50;
+pub const POINTS_PER_WIN: u64 = 100;
+pub const TRADE_LAMPORTS_PER_POINT: u64 = 10_000_000; // 每 0.01 SOL 交易额 1 分 (仅 SOL 计价主题)
+pub const MIN_REWARDS_CLAIM_WINDOW: i64 = 7 * 24 * 3600; // 奖励领取期至少 7 天
+
+// 质押委托：持币者将主题代币托管给策展人代为投票，收益扣除策展人分成后归还委托人
+pub const MAX_DELEGATION_CUT_BPS: u16 = 3_000; // 策展人分成上限 30% (仅按净收益计算)
+
+// 关注策展人：keeper 用关注者预存的余额复制策展人的投票，获胜时按净收益给策展人小费
+pub const MAX_FOLLOW_TIP_BPS: u16 = 2_000; // 小费上限 20%
+
+// 创意评论押金 (主题代币)：结算后作者关闭评论取回，被版主标记滥用则罚没至财库
+pub const MIN_COMMENT_DEPOSIT: u64 = 1_000_000; // 1 代币 (6 位小数)
+
+// 获胜图片 NFT：唯一创作者为创意的版税金库 PDA，发起者优先铸造，逾期后获胜评审可铸造
+pub const NFT_SYMBOL: &str = "TASTE"; // 获胜图片 NFT 与参与徽章共用
+pub const WINNER_NFT_ROYALTY_BPS: u16 = 500; // 二级市场版税 5%，全部进入版税金库由获胜评审分领
+pub const WINNER_NFT_PRIORITY_WINDOW: i64 = 7 * 24 * 3600; // 结算终局后发起者独占铸造 7天
+
+// 参与徽章 (压缩 NFT)：主题创建者配置徽章 merkle 树，结算后 keeper 按投票者公钥升序分批铸造
+pub const MAX_BADGE_TREE_DEPTH: u32 = 30;
+
+// 主题画廊：结算时追加获胜图片记录，按固定容量分页存储 (页满后由任何人预先创建下一页)
+pub const GALLERY_PAGE_CAPACITY: usize = 32;
+
+// 获胜图片限时版本销售 (SOL 计价)：收入按销售开启时记录的 bps 分给获胜评审、发起者与主题回购池
+pub const MAX_EDITION_SALE_DURATION: i64 = 30 * 24 * 3600; // 最长 30天
+pub const MIN_EDITION_VOTER_SHARE_BPS: u16 = 3_000; // 获胜评审分成下限 30%
+
 // RejectAll 阈值
-pub const REJECT_ALL_THRESHOLD_BPS: u16 = 6_667; // 2/3 = 66.67%
+pub const REJECT_ALL_THRESHOLD_BPS: u16 = 6_667; // 2/3 = 66.67% (主题默认值)
+pub const MIN_REJECT_ALL_THRESHOLD_BPS: u16 = 5_000; // 主题可配置下限 50%
+pub const MAX_REJECT_ALL_THRESHOLD_BPS: u16 = 9_000; // 主题可配置上限 90%
 
 // DePIN 参数
 pub const IMAGE_GENERATION_TIMEOUT: i64 = 24 * 3600; // 24小时
 pub const DEFAULT_VOTING_DURATION: i64 = 72 * 3600; // 72小时
+pub const MAX_REVEAL_DELAY: i64 = 24 * 3600; // 两阶段揭晓最长等待 24小时
+
+// 取消退款 Gas 补贴 (非评审过错的取消)
+pub const REFUND_GAS_REBATE: u64 = 10_000; // 每次领取补贴 lamports
+pub const MAX_REFUND_REBATE_PER_IDEA: u64 = 100 * REFUND_GAS_REBATE; // 每个创意补贴上限
+
+// 赞助系列赛参数
+pub const MIN_SERIES_INTERVAL_DAYS: u16 = 1;
+pub const MAX_SERIES_INTERVAL_DAYS: u16 = 90;
+pub const MAX_SERIES_EPISODES: u64 = 52;
+
+// 创意合集：发起者将多个创意归入合集，结算时可按比例注入合集大奖，合集结束后由最佳创意的获胜评审瓜分
+pub const MAX_COLLECTION_NAME_LEN: usize = 32;
+pub const MAX_COLLECTION_PRIZE_BPS: u16 = 1_000; // 最高 10%
+pub const MAX_COLLECTION_DURATION_DAYS: u16 = 90;
+
+// 主题内最近 Prompt 哈希登记数 (防止同一发起者重复刷屏)
+pub const RECENT_PROMPT_SLOTS: usize = 16;
+
+// 赞助评委加奖：结算后评委可在此窗口内指定加奖图片，逾期赞助商可收回
+pub const BONUS_AWARD_WINDOW: i64 = 7 * 24 * 3600; // 7天
+pub const CLAIM_WINDOW: i64 = 90 * 24 * 3600; // 结算后 90天内领取，逾期由财库清扫
+pub const PAYOUT_CHALLENGE_WINDOW: i64 = 24 * 3600; // 派奖 Merkle 根挑战期 24小时
+pub const PAYOUT_CHALLENGE_BOND: u64 = 100_000_000; // 派奖根挑战保证金 0.1 SOL，根被维持时罚没
+
+// 大额奖金线性释放 (降低新主题代币的即时抛压)
+pub const VESTING_THRESHOLD: u64 = 100_000_000_000; // 10万代币 (6 位小数)
+pub const VESTING_DURATION: i64 = 30 * 24 * 3600; // 30天线性释放
+
+// 结算争议期 (主题级配置，0 表示结算即终局)
+pub const MAX_DISPUTE_PERIOD: i64 = 7 * 24 * 3600; // 最长 7天
+pub const DISPUTE_BOND_BPS: u16 = 500; // 争议保证金为总质押的 5%
+
+// 协议角色 (Roles 账户中各自独立授予与轮换)
+pub const ROLE_GUARDIAN: u8 = 0; // 紧急暂停
+pub const ROLE_FEE_SETTER: u8 = 1; // 调整协议费用
+pub const ROLE_DEPIN_ADMIN: u8 = 2; // 管理 DePIN 服务
+pub const ROLE_TREASURER: u8 = 3; // 管理协议国库
+pub const ROLE_MODERATOR: u8 = 4; // 处理滥用评论
+
+// Core 程序 ID DiyEKJXPDNJ4Phfe3wVYkgi2NbJQuHtifgDgBYbCRuGe (token 程序读取 core 的全局协议配置)
+pub const CORE_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    189, 12, 162, 161, 105, 22, 228, 81, 160, 97, 87, 220, 97, 151, 66, 125, 234, 187, 203, 249,
+    6, 253, 220, 233, 204, 109, 40, 163, 46, 13, 151, 75,
+]);
+
+// 结算程序 ID EeHN1oagPFzfyaye9FPyUjNx4nbnsFy2z3xhWPetVRxH (core 校验结算程序 PDA 签名的复投 CPI)
+pub const SETTLEMENT_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    202, 180, 235, 192, 3, 186, 208, 31, 198, 12, 24, 53, 121, 239, 116, 156, 221, 179, 12, 242,
+    170, 131, 131, 229, 52, 67, 53, 43, 247, 110, 150, 150,
+]);
 
-// 授权的 DePIN 服务公钥 (实际部署时替换)
-pub const AUTHORIZED_DEPIN_PUBKEY: Pubkey = Pubkey::new_from_array([0; 32]);
+// USDC mint EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v (主题可选的稳定币计价资产)
+pub const USDC_MINT: Pubkey = Pubkey::new_from_array([
+    198, 250, 122, 243, 190, 219, 173, 58, 61, 101, 243, 106, 171, 201, 116, 49, 177, 187, 228, 194,
+    210, 246, 224, 228, 124, 166, 2, 3, 69, 47, 93, 97,
+]);
+
+// Raydium CPMM 程序 ID CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C (毕业迁移目标)
+pub const RAYDIUM_CPMM_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    169, 42, 90, 139, 79, 41, 89, 82, 132, 37, 80, 170, 147, 253, 91, 149, 181, 172, 230, 168,
+    235, 146, 12, 147, 148, 46, 67, 105, 12, 32, 236, 115,
+]);
+// sha256("global:initialize")[..8]
+pub const RAYDIUM_CPMM_INITIALIZE_DISCRIMINATOR: [u8; 8] = [175, 175, 109, 31, 13, 152, 155, 237];
+// sha256("global:swap_base_input")[..8]
+pub const RAYDIUM_CPMM_SWAP_BASE_INPUT_DISCRIMINATOR: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+
+// Meteora DAMM 程序 ID Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB (备选毕业迁移目标)
+pub const METEORA_DAMM_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    204, 248, 2, 212, 204, 204, 132, 215, 251, 33, 181, 247, 59, 73, 216, 26, 22, 197, 180, 200,
+    142, 227, 35, 148, 225, 201, 29, 53, 136, 204, 64, 128,
+]);
+// sha256("global:initialize_permissionless_constant_product_pool_with_config")[..8]
+pub const METEORA_INITIALIZE_POOL_DISCRIMINATOR: [u8; 8] = [7, 166, 138, 171, 206, 171, 236, 244];
+// sha256("global:swap")[..8]
+pub const METEORA_SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+// Metaplex Token Metadata 程序 ID metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s (获胜图片 NFT)
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    11, 112, 101, 177, 227, 209, 124, 69, 56, 157, 82, 127, 107, 4, 195, 205, 88, 184, 108, 115,
+    26, 160, 253, 181, 73, 182, 209, 188, 3, 248, 41, 70,
+]);
+pub const TOKEN_METADATA_CREATE_METADATA_V3: u8 = 33;
+pub const TOKEN_METADATA_CREATE_MASTER_EDITION_V3: u8 = 17;
+
+// Metaplex Bubblegum 程序 ID BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY (压缩 NFT 参与徽章)
+pub const BUBBLEGUM_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    152, 139, 128, 235, 121, 53, 40, 105, 178, 36, 116, 95, 89, 221, 191, 138, 38, 88, 202, 19,
+    220, 104, 129, 33, 38, 53, 28, 174, 7, 193, 165, 165,
+]);
+// SPL Account Compression 程序 ID cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK (徽章 merkle 树的 owner)
+pub const SPL_ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    9, 42, 19, 238, 149, 196, 28, 186, 8, 166, 127, 90, 198, 126, 141, 247, 225, 218, 17, 98, 94,
+    29, 100, 19, 127, 143, 79, 35, 131, 3, 127, 20,
+]);
+// SPL Noop 程序 ID noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV (Bubblegum 的日志包装器)
+pub const SPL_NOOP_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    11, 188, 15, 192, 187, 71, 202, 47, 116, 196, 17, 46, 148, 171, 19, 207, 163, 198, 52, 229,
+    220, 23, 234, 203, 3, 205, 26, 35, 205, 126, 120, 124,
+]);
+// sha256("global:create_tree")[..8]
+pub const BUBBLEGUM_CREATE_TREE_DISCRIMINATOR: [u8; 8] = [165, 83, 136, 142, 89, 202, 47, 220];
+// sha256("global:mint_v1")[..8]
+pub const BUBBLEGUM_MINT_V1_DISCRIMINATOR: [u8; 8] = [145, 98, 192, 118, 184, 147, 118, 104];
 
 // -----------------------------------------------------------------------------
 // 代币发行参数（基于 Pumpfun 标准）
@@ -43,6 +230,9 @@ pub const AUTHORIZED_DEPIN_PUBKEY: Pubkey = Pubkey::new_from_array([0; 32]);
 pub const TOKEN_TOTAL_SUPPLY: u64 = 1_000_000_000_000_000; // 1B * 10^6 decimals
 pub const TOKEN_DECIMALS: u8 = 6;
 pub const CREATOR_RESERVE_PERCENT: u8 = 20;
+// 创建者储备锁仓：开盘后 cliff 之前不可领取，之后按时间线性释放至 duration 结束
+pub const CREATOR_VESTING_CLIFF: i64 = 30 * 24 * 60 * 60; // 30 天
+pub const CREATOR_VESTING_DURATION: i64 = 180 * 24 * 60 * 60; // 180 天
 pub const CIRCULATING_PERCENT: u8 = 80;
 
 // -----------------------------------------------------------------------------
@@ -50,8 +240,20 @@ pub const CIRCULATING_PERCENT: u8 = 80;
 // -----------------------------------------------------------------------------
 pub const INITIAL_TOKEN_RESERVES: u64 = 800_000_000_000_000; // 80% of total supply
 pub const INITIAL_SOL_RESERVES: u64 = 0; // 初始为 0，等待首次交易
+// 虚拟储备 (pump.fun 风格)：只参与曲线定价，不代表真实资产
+// 使首笔买入的价格从 30 SOL / 1.073B 代币起步，而不是拿走几乎全部储备
+pub const VIRTUAL_SOL_RESERVES: u64 = 30_000_000_000; // 30 SOL
+pub const VIRTUAL_TOKEN_RESERVES: u64 = 273_000_000_000_000; // 273M (加上真实储备共 1.073B)
+// USDC 计价主题的虚拟报价储备 (6 位小数)，起始价格与 SOL 主题大致相当
+pub const USDC_VIRTUAL_QUOTE_RESERVES: u64 = 5_000_000_000; // 5,000 USDC
 pub const MIGRATION_THRESHOLD: u64 = 80_000_000_000; // 80 SOL (in lamports)
+pub const MIGRATION_LAMPORTS_RESERVE: u64 = 200_000_000; // 0.2 SOL，迁移时预留用于建池费与租金
 pub const TRADE_FEE_BPS: u16 = 100; // 1%
+pub const MAX_TRADE_FEE_BPS: u16 = 300; // 交易费硬上限 3%
+pub const TRADING_CONFIG_TIMELOCK: i64 = 2 * 24 * 60 * 60; // 交易配置变更时间锁 2 天
+pub const MAX_REFERRAL_FEE_SPLIT_BPS: u16 = 5000; // 推荐人最多分走平台费的 50%
+pub const MAX_SLOT_VOLUME_BPS: u16 = 5000; // 单 slot 单向成交额上限最多设为储备的 50%
+pub const MAX_LAUNCH_FEE_BPS: u16 = 1000; // 开盘初始交易费上限 10%，随后线性衰减到基础费率
 pub const BUYBACK_FEE_SPLIT_BPS: u16 = 5000; // 50% of fees
 pub const PLATFORM_FEE_SPLIT_BPS: u16 = 3000; // 30% of fees
 pub const CREATOR_FEE_SPLIT_BPS: u16 = 2000; // 20% of fees
@@ -60,13 +262,27 @@ pub const CREATOR_FEE_SPLIT_BPS: u16 = 2000; // 20% of fees
 // 回购机制
 // -----------------------------------------------------------------------------
 pub const BUYBACK_THRESHOLD: u64 = 100_000_000; // 0.1 SOL
+pub const BUYBACK_KEEPER_REWARD_BPS: u16 = 50; // 执行回购的调用者获得回购池的 0.5%
+pub const MAX_BUYBACK_KEEPER_REWARD: u64 = 5_000_000; // 单次回购奖励上限 0.005 SOL
+pub const BUYBACK_POLICY_BURN: u8 = 0; // 回购的代币直接销毁
+pub const BUYBACK_POLICY_LIQUIDITY: u8 = 1; // 回购资金留在曲线中作为协议自有流动性
+pub const MAX_STAKE_FEE_SHARE_BPS: u16 = 5000; // 质押者最多分走回购费与结算回购贡献的 50%
+pub const STAKE_UNSTAKE_COOLDOWN: i64 = 7 * 24 * 60 * 60; // 解除质押冷却 7 天
+pub const STAKE_ACC_PRECISION: u128 = 1_000_000_000_000; // 每份累计奖励的精度
+pub const USDC_BUYBACK_THRESHOLD: u64 = 20_000_000; // 20 USDC
 pub const SETTLEMENT_BUYBACK_BPS: u16 = 500; // 5% from settlement
 
 // -----------------------------------------------------------------------------
 // 交易限制
 // -----------------------------------------------------------------------------
 pub const MIN_SOL_TRADE: u64 = 1_000_000; // 0.001 SOL
+pub const MIN_USDC_TRADE: u64 = 100_000; // 0.1 USDC
 pub const MIN_TOKEN_STAKE: u64 = 1_000_000; // 1 token (6 decimals)
+// 开盘防狙击窗口：mint_initial_tokens 后的前 N 个 slot 内限制单笔与单钱包买入额
+pub const LAUNCH_PROTECTION_SLOTS: u64 = 150; // 约 1 分钟
+pub const LAUNCH_MAX_BUY_PER_TX: u64 = 1_000_000_000; // 1 SOL
+pub const LAUNCH_MAX_BUY_PER_WALLET: u64 = 3_000_000_000; // 3 SOL
+pub const MAX_SWAP_COOLDOWN_SECS: u32 = 60 * 60; // 反向交易冷却最长 1 小时
 pub const MAX_SLIPPAGE_BPS: u16 = 1000; // 10%
 
 /// 整数平方根 (用于二次方投票)
@@ -83,6 +299,432 @@ pub fn integer_sqrt(n: u64) -> u64 {
     x
 }
 
+/// 验证 Merkle 证明 (keccak256，按字节序排序拼接相邻节点)
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let mut computed = leaf;
+    for node in proof {
+        computed = if computed <= *node {
+            keccak::hashv(&[&computed, node]).to_bytes()
+        } else {
+            keccak::hashv(&[node, &computed]).to_bytes()
+        };
+    }
+    computed == root
+}
+
+// 状态根叶子类型 (跨链/应用链镜像通过轻量证明校验结算结果)
+pub const STATE_LEAF_CONTEST: u8 = 0;
+pub const STATE_LEAF_BALANCE: u8 = 1;
+pub const RETIREMENT_LEAF: u8 = 2; // 主题退役分配 (不进入状态根，仅用于区分域)
+pub const AIRDROP_LEAF: u8 = 3; // 主题代币空投 (不进入状态根，仅用于区分域)
+pub const POINTS_REWARD_LEAF: u8 = 4; // 赛季积分奖励 (不进入状态根，仅用于区分域)
+pub const WINNER_NFT_LEAF: u8 = 5; // 获胜评审 NFT 空投 (不进入状态根，仅用于区分域)
+
+/// 竞赛结果叶子: keccak(0 || idea || status || winning_index(无则 255) || total_staked || penalty_pool || winner_count)
+pub fn contest_leaf(
+    idea: &Pubkey,
+    status: u8,
+    winning_image_index: Option<u8>,
+    total_staked: u64,
+    penalty_pool_amount: u64,
+    winner_count: u64,
+) -> [u8; 32] {
+    keccak::hashv(&[
+        &[STATE_LEAF_CONTEST],
+        idea.as_ref(),
+        &[status, winning_image_index.unwrap_or(u8::MAX)],
+        &total_staked.to_le_bytes(),
+        &penalty_pool_amount.to_le_bytes(),
+        &winner_count.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// 退役分配叶子: keccak(2 || theme || holder || amount)
+pub fn retirement_leaf(theme: &Pubkey, holder: &Pubkey, amount: u64) -> [u8; 32] {
+    keccak::hashv(&[
+        &[RETIREMENT_LEAF],
+        theme.as_ref(),
+        holder.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// 空投领取叶子: keccak(3 || campaign || claimant || amount)
+pub fn airdrop_leaf(campaign: &Pubkey, claimant: &Pubkey, amount: u64) -> [u8; 32] {
+    keccak::hashv(&[
+        &[AIRDROP_LEAF],
+        campaign.as_ref(),
+        claimant.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// 赛季积分奖励叶子: keccak(4 || rewards_season || claimant || lamports)
+pub fn points_reward_leaf(rewards_season: &Pubkey, claimant: &Pubkey, amount: u64) -> [u8; 32] {
+    keccak::hashv(&[
+        &[POINTS_REWARD_LEAF],
+        rewards_season.as_ref(),
+        claimant.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// 获胜评审 NFT 空投叶子: keccak(5 || idea || winner)
+pub fn winner_nft_leaf(idea: &Pubkey, winner: &Pubkey) -> [u8; 32] {
+    keccak::hashv(&[&[WINNER_NFT_LEAF], idea.as_ref(), winner.as_ref()]).to_bytes()
+}
+
+/// 交易额 (lamports) 折算的赛季积分
+pub fn trade_points(sol_amount: u64) -> u64 {
+    sol_amount / TRADE_LAMPORTS_PER_POINT
+}
+
+/// 评审结算余额叶子: keccak(1 || idea || reviewer || amount)
+pub fn balance_leaf(idea: &Pubkey, reviewer: &Pubkey, amount: u64) -> [u8; 32] {
+    keccak::hashv(&[
+        &[STATE_LEAF_BALANCE],
+        idea.as_ref(),
+        reviewer.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+// -----------------------------------------------------------------------------
+// Access Control (配合 #[access_control] 使用，权限逻辑集中在此)
+// -----------------------------------------------------------------------------
+
+/// 调用者必须是协议管理员 (取自 ProtocolConfig / TradingConfiguration 中记录的 authority)
+pub fn is_protocol_authority(authority: &Pubkey, signer: &Pubkey) -> Result<()> {
+    require!(*authority != Pubkey::default(), ConsensusError::Unauthorized);
+    require_keys_eq!(*signer, *authority, ConsensusError::Unauthorized);
+    Ok(())
+}
+
+/// 调用者必须是程序的升级权限 (全局配置初始化时链上尚无 authority 可依据)
+pub fn is_upgrade_authority(upgrade_authority: Option<Pubkey>, signer: &Pubkey) -> Result<()> {
+    require!(upgrade_authority == Some(*signer), ConsensusError::Unauthorized);
+    Ok(())
+}
+
+/// 调用者必须是账户记录的所有人
+pub fn is_account_owner(owner: &Pubkey, signer: &Pubkey) -> Result<()> {
+    require_keys_eq!(*signer, *owner, ConsensusError::Unauthorized);
+    Ok(())
+}
+
+/// 调用者必须持有对应角色 (未授予的角色为默认公钥，任何人都不持有)
+pub fn has_role(holder: &Pubkey, signer: &Pubkey) -> Result<()> {
+    require!(*holder != Pubkey::default(), ConsensusError::MissingRole);
+    require_keys_eq!(*signer, *holder, ConsensusError::MissingRole);
+    Ok(())
+}
+
+/// 调用者必须是主题创建者
+pub fn is_theme_admin(theme_creator: &Pubkey, signer: &Pubkey) -> Result<()> {
+    require_keys_eq!(*signer, *theme_creator, ConsensusError::Unauthorized);
+    Ok(())
+}
+
+/// 调用者必须是 Roles 中授权的 DePIN 服务
+pub fn is_assigned_provider(depin_admin: &Pubkey, signer: &Pubkey) -> Result<()> {
+    require!(*depin_admin != Pubkey::default(), ConsensusError::UnauthorizedDePIN);
+    require_keys_eq!(*signer, *depin_admin, ConsensusError::UnauthorizedDePIN);
+    Ok(())
+}
+
+/// 创意处于指定阶段
+pub fn idea_in_status(status: IdeaStatus, expected: IdeaStatus) -> Result<()> {
+    require!(status == expected, ConsensusError::InvalidState);
+    Ok(())
+}
+
+/// 创意尚未结束 (未结算也未取消)
+pub fn idea_open(status: IdeaStatus) -> Result<()> {
+    require!(
+        status != IdeaStatus::Completed && status != IdeaStatus::Cancelled,
+        ConsensusError::InvalidState
+    );
+    Ok(())
+}
+
+/// 创意已结束 (已结算或已取消)
+pub fn idea_closed(status: IdeaStatus) -> Result<()> {
+    require!(
+        status == IdeaStatus::Completed || status == IdeaStatus::Cancelled,
+        ConsensusError::InvalidState
+    );
+    Ok(())
+}
+
+/// 评审在该创意上的资金尚未领取 (ReviewerStake.is_winner 兼作已处理标记)
+pub fn not_withdrawn(processed: bool) -> Result<()> {
+    require!(!processed, ConsensusError::AlreadyWithdrawn);
+    Ok(())
+}
+
+/// 数量为正 (销毁、质押、凭证明领取等)
+pub fn nonzero_amount(amount: u64) -> Result<()> {
+    require!(amount > 0, ConsensusError::InvalidAmount);
+    Ok(())
+}
+
+/// 数量为正且不超过上限 (提取、解除质押等)
+pub fn amount_within(amount: u64, max: u64) -> Result<()> {
+    require!(amount > 0 && amount <= max, ConsensusError::InvalidAmount);
+    Ok(())
+}
+
+/// 创意处于投票阶段且未过截止时间
+pub fn voting_open(status: IdeaStatus, voting_deadline: i64) -> Result<()> {
+    require!(status == IdeaStatus::Voting, ConsensusError::InvalidState);
+    require!(
+        Clock::get()?.unix_timestamp < voting_deadline,
+        ConsensusError::VotingEnded
+    );
+    Ok(())
+}
+
+/// 创意处于投票阶段且已过截止时间 (可结算)
+pub fn voting_closed(status: IdeaStatus, voting_deadline: i64) -> Result<()> {
+    require!(status == IdeaStatus::Voting, ConsensusError::InvalidState);
+    require!(
+        Clock::get()?.unix_timestamp >= voting_deadline,
+        ConsensusError::VotingNotEnded
+    );
+    Ok(())
+}
+
+/// 主题处于活跃状态 (可交易/回购)
+/// 已迁移的主题返回 ThemeMigrated，提示前端把用户引导到 AMM 池
+pub fn theme_active(status: u8) -> Result<()> {
+    match status {
+        THEME_STATUS_ACTIVE => Ok(()),
+        THEME_STATUS_MIGRATED => err!(ConsensusError::ThemeMigrated),
+        THEME_STATUS_PAUSED => err!(ConsensusError::ThemePaused),
+        _ => err!(ConsensusError::InvalidTheme),
+    }
+}
+
+/// 协议未被守护者紧急暂停
+pub fn protocol_active(paused: bool) -> Result<()> {
+    require!(!paused, ConsensusError::ProtocolPaused);
+    Ok(())
+}
+
+/// 同 protocol_active，供无法依赖 core crate 的 token 程序直接读取 ProtocolConfig 账户
+/// 该账户只能由 core 以固定种子创建，校验所有者与 discriminator 即可确定身份；paused 位于固定偏移
+pub fn protocol_config_active(config: &AccountInfo) -> Result<()> {
+    require_keys_eq!(*config.owner, CORE_PROGRAM_ID, ConsensusError::InvalidProtocolConfig);
+    let data = config.try_borrow_data()?;
+    require!(
+        data.len() > PROTOCOL_CONFIG_PAUSED_OFFSET
+            && data[..8] == anchor_lang::solana_program::hash::hash(b"account:ProtocolConfig").to_bytes()[..8],
+        ConsensusError::InvalidProtocolConfig
+    );
+    protocol_active(data[PROTOCOL_CONFIG_PAUSED_OFFSET] != 0)
+}
+
+/// 同 is_protocol_authority，直接读取 ProtocolConfig 账户 (token 程序与账户升级使用)
+/// authority 是首个字段，旧布局的配置账户同样可读
+pub fn is_protocol_config_authority(config: &AccountInfo, signer: &Pubkey) -> Result<()> {
+    require_keys_eq!(*config.owner, CORE_PROGRAM_ID, ConsensusError::InvalidProtocolConfig);
+    let data = config.try_borrow_data()?;
+    require!(
+        data.len() >= 8 + 32
+            && data[..8] == anchor_lang::solana_program::hash::hash(b"account:ProtocolConfig").to_bytes()[..8],
+        ConsensusError::InvalidProtocolConfig
+    );
+    let authority = Pubkey::try_from(&data[8..40]).map_err(|_| error!(ConsensusError::InvalidProtocolConfig))?;
+    is_protocol_authority(&authority, signer)
+}
+
+/// 创意已升级到当前版本 (旧创意的每图统计重算前不能投票或结算)
+pub fn idea_migrated(version: u8) -> Result<()> {
+    require!(version >= IDEA_ACCOUNT_VERSION, ConsensusError::AccountMigrationRequired);
+    Ok(())
+}
+
+/// 账户升级：不足目标大小时 realloc (新增字节填 0)，差额租金由 payer 补足
+pub fn realloc_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    if account.data_len() >= new_len {
+        return Ok(());
+    }
+    let shortfall = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.realloc(new_len, true)?;
+    Ok(())
+}
+
+/// 主题未被暂停 (可创建新创意；已迁移主题仍可创建)
+pub fn theme_not_paused(status: u8) -> Result<()> {
+    require!(status != THEME_STATUS_PAUSED, ConsensusError::ThemePaused);
+    Ok(())
+}
+
+/// 主题处于暂停状态 (可恢复)
+pub fn theme_paused(status: u8) -> Result<()> {
+    require!(status == THEME_STATUS_PAUSED, ConsensusError::InvalidState);
+    Ok(())
+}
+
+/// 主题仍在曲线上 (活跃或暂停)，可提议或执行退役
+pub fn theme_retirable(status: u8) -> Result<()> {
+    require!(
+        status == THEME_STATUS_ACTIVE || status == THEME_STATUS_PAUSED,
+        ConsensusError::InvalidState
+    );
+    Ok(())
+}
+
+/// 调用者必须是主题创建者或协议管理员
+pub fn is_theme_admin_or_protocol(
+    theme_creator: &Pubkey,
+    protocol_authority: &Pubkey,
+    signer: &Pubkey,
+) -> Result<()> {
+    if *signer == *theme_creator {
+        return Ok(());
+    }
+    is_protocol_authority(protocol_authority, signer)
+}
+
+/// 主题以原生 SOL 计价 (lamports 路径：SOL 交易、费用提取、迁移与退役分配)
+pub fn sol_quoted(quote_mint: &Pubkey) -> Result<()> {
+    require_keys_eq!(*quote_mint, Pubkey::default(), ConsensusError::QuoteMintMismatch);
+    Ok(())
+}
+
+/// 主题以 SPL 代币 (USDC) 计价
+pub fn token_quoted(quote_mint: &Pubkey) -> Result<()> {
+    require!(*quote_mint != Pubkey::default(), ConsensusError::QuoteMintMismatch);
+    Ok(())
+}
+
+/// 主题已毕业迁移 (回购走 AMM)
+pub fn theme_migrated(status: u8) -> Result<()> {
+    require!(status == THEME_STATUS_MIGRATED, ConsensusError::InvalidTheme);
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Event CPI (事件通过自调用 CPI 写入内层指令，不受日志截断影响)
+// -----------------------------------------------------------------------------
+
+/// 与 emit_cpi! 编码一致的事件发送器，供拿不到 ctx 的辅助函数使用
+/// 由 event_emitter!(ctx) 从 #[event_cpi] 注入的 event_authority 构造
+pub struct EventEmitter<'info> {
+    pub program_id: Pubkey,
+    pub event_authority: AccountInfo<'info>,
+    pub bump: u8,
+}
+
+impl<'info> EventEmitter<'info> {
+    pub fn emit<E: anchor_lang::Event>(&self, event: E) -> Result<()> {
+        let ix_data: Vec<u8> = anchor_lang::event::EVENT_IX_TAG_LE
+            .into_iter()
+            .chain(event.data())
+            .collect();
+        let ix = anchor_lang::solana_program::instruction::Instruction::new_with_bytes(
+            self.program_id,
+            &ix_data,
+            vec![AccountMeta::new_readonly(self.event_authority.key(), true)],
+        );
+        anchor_lang::solana_program::program::invoke_signed(
+            &ix,
+            std::slice::from_ref(&self.event_authority),
+            &[&[b"__event_authority", &[self.bump]]],
+        )?;
+        Ok(())
+    }
+}
+
+/// 从带 #[event_cpi] 的上下文构造当前程序的 EventEmitter
+#[macro_export]
+macro_rules! event_emitter {
+    ($ctx:expr) => {
+        $crate::EventEmitter {
+            program_id: *$ctx.program_id,
+            event_authority: $ctx.accounts.event_authority.to_account_info(),
+            bump: $ctx.bumps.event_authority,
+        }
+    };
+}
+
+// -----------------------------------------------------------------------------
+// Token Metadata (按 Metaplex 指令布局直接编码，不引入 mpl crate)
+// -----------------------------------------------------------------------------
+
+/// NFT 创作者 (未验证，仅用于版税分配)
+#[derive(AnchorSerialize, Clone, Copy)]
+pub struct MetadataCreator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+/// CreateMetadataAccountV3 指令数据: DataV2 (无 collection/uses) | is_mutable | collection_details = None
+pub fn create_metadata_v3_data(
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    creators: Vec<MetadataCreator>,
+) -> Result<Vec<u8>> {
+    let mut data = vec![TOKEN_METADATA_CREATE_METADATA_V3];
+    (name, symbol, uri, seller_fee_basis_points, Some(creators)).serialize(&mut data)?;
+    data.extend_from_slice(&[0, 0]); // collection, uses
+    data.push(1); // is_mutable
+    data.push(0); // collection_details
+    Ok(data)
+}
+
+/// Bubblegum mint_v1 指令数据: MetadataArgs (不可变、无版税与创作者的 NonFungible 资产)
+pub fn bubblegum_mint_v1_data(name: String, symbol: String, uri: String) -> Result<Vec<u8>> {
+    let mut data = BUBBLEGUM_MINT_V1_DISCRIMINATOR.to_vec();
+    (name, symbol, uri, 0u16).serialize(&mut data)?; // seller_fee_basis_points
+    data.extend_from_slice(&[
+        0,    // primary_sale_happened
+        0,    // is_mutable
+        0,    // edition_nonce
+        1, 0, // token_standard = Some(NonFungible)
+        0,    // collection
+        0,    // uses
+        0,    // token_program_version = Original
+    ]);
+    Vec::<MetadataCreator>::new().serialize(&mut data)?;
+    Ok(data)
+}
+
+/// CreateMasterEditionV3 指令数据 (max_supply 为 0 表示不可增发)
+pub fn create_master_edition_v3_data(max_supply: Option<u64>) -> Result<Vec<u8>> {
+    let mut data = vec![TOKEN_METADATA_CREATE_MASTER_EDITION_V3];
+    max_supply.serialize(&mut data)?;
+    Ok(data)
+}
+
 // -----------------------------------------------------------------------------
 // Shared Enums
 // -----------------------------------------------------------------------------
@@ -93,6 +735,7 @@ pub enum IdeaStatus {
     Voting,           // 评审投票中
     Completed,        // 已结算
     Cancelled,        // 取消（参与者不足/生成失败）
+    AwaitingReveal,   // 图片已提交哈希，等待统一揭晓
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
@@ -107,8 +750,303 @@ pub const VOTING_MODE_CLASSIC: u8 = 0;
 pub const VOTING_MODE_REVERSE: u8 = 1;
 pub const VOTING_MODE_MIDDLE_WAY: u8 = 2;
 
+// 取消原因
+pub const CANCEL_REASON_NONE: u8 = 0;
+pub const CANCEL_REASON_INITIATOR: u8 = 1;
+pub const CANCEL_REASON_GENERATION_FAILED: u8 = 2;
+pub const CANCEL_REASON_TIMEOUT: u8 = 3;
+pub const CANCEL_REASON_INSUFFICIENT_VOTERS: u8 = 4;
+pub const CANCEL_REASON_REJECTED: u8 = 5;
+pub const CANCEL_REASON_TIE: u8 = 6;
+
+/// 非评审过错导致的取消可获得退款 Gas 补贴
+pub fn is_rebate_eligible(cancel_reason: u8) -> bool {
+    matches!(
+        cancel_reason,
+        CANCEL_REASON_GENERATION_FAILED | CANCEL_REASON_TIE
+    )
+}
+
+// 赞助托管里程碑 (按顺序解锁的奖池分批)
+pub const ESCROW_MILESTONE_IMAGES_CONFIRMED: u8 = 0;
+pub const ESCROW_MILESTONE_MIN_REVIEWERS: u8 = 1;
+pub const ESCROW_MILESTONE_SETTLEMENT: u8 = 2;
+pub const ESCROW_MILESTONE_COUNT: usize = 3;
+
 pub const THEME_STATUS_ACTIVE: u8 = 0;
 pub const THEME_STATUS_PAUSED: u8 = 1;
+pub const THEME_STATUS_MIGRATED: u8 = 2; // 已毕业迁移到 AMM，曲线交易关闭
+pub const THEME_STATUS_RETIRED: u8 = 3; // 已退役，剩余 SOL 按快照 merkle 分配给持有者
+pub const THEME_RETIRE_TIMELOCK: i64 = 7 * 24 * 60 * 60; // 退役提议到执行的时间锁 7 天，期间持有者仍可卖出
+
+// 毕业迁移目标 (创建者在毕业前设置)
+pub const MIGRATION_TARGET_RAYDIUM: u8 = 0;
+pub const MIGRATION_TARGET_METEORA: u8 = 1;
+pub const LP_LOCK_DURATION: i64 = 10 * 365 * 24 * 60 * 60; // 迁移产生的 LP 锁仓 10 年，之后仅协议治理可解锁
+
+// 惩罚池分配曲线 (主题级配置，创建创意时复制到 Idea)
+// 按投票权重比例为默认值 (0)，未写入该字段的旧账户同样按投票权重分配
+pub const PAYOUT_CURVE_WEIGHT: u8 = 0; // 按投票权重^指数比例 (指数越高赢家多得)
+pub const PAYOUT_CURVE_FLAT: u8 = 1;   // 获胜者按人头平分
+pub const PAYOUT_CURVE_STAKE: u8 = 2;  // 按质押量比例
+pub const DEFAULT_PAYOUT_CURVE: u8 = PAYOUT_CURVE_WEIGHT;
+pub const MAX_PAYOUT_EXPONENT: u8 = 3;
+
+// 平局处理规则 (主题级配置，创建创意时复制到 Idea)
+pub const TIE_BREAK_CANCEL: u8 = 0;   // 取消并全员退款
+pub const TIE_BREAK_EARLIEST: u8 = 1; // 最早达到最终权重的图片获胜
+pub const TIE_BREAK_STAKE: u8 = 2;    // 原始质押量更高者获胜
+pub const TIE_BREAK_RANDOM: u8 = 3;   // 最近 slot hash 可验证随机
+
+/// 图片 URI 匹配允许的存储前缀 (未配置前缀时不限制)
+pub fn image_uri_allowed(uri: &str, allowed_prefixes: &[String]) -> Result<()> {
+    require!(
+        allowed_prefixes.is_empty() || allowed_prefixes.iter().any(|p| uri.starts_with(p.as_str())),
+        ConsensusError::ImageUriNotAllowed
+    );
+    Ok(())
+}
+
+/// 校验分配曲线配置
+pub fn validate_payout_curve(payout_curve: u8, payout_exponent: u8) -> Result<()> {
+    require!(
+        payout_curve <= PAYOUT_CURVE_STAKE
+            && (1..=MAX_PAYOUT_EXPONENT).contains(&payout_exponent),
+        ConsensusError::InvalidPayoutCurve
+    );
+    Ok(())
+}
+
+/// 单个评审在分配曲线下的份额单位 (投票时累加到图片总份额，领取时按比例分配)
+/// 旧账户的指数字段为 0，按指数 1 计
+pub fn payout_unit(payout_curve: u8, payout_exponent: u8, stake: u64, vote_weight: u64) -> u128 {
+    match payout_curve {
+        PAYOUT_CURVE_FLAT => 1,
+        PAYOUT_CURVE_STAKE => stake as u128,
+        _ => (vote_weight as u128).pow(payout_exponent.max(1) as u32),
+    }
+}
+
+/// 未获胜评审可取回的本金 (向下取整，罚没部分因此向上取整，不少于结算时按总量计入的罚没额)
+pub fn loser_refund_amount(stake: u64, penalty_bps: u16) -> u64 {
+    (stake as u128 * (BPS_DENOMINATOR - penalty_bps) as u128 / BPS_DENOMINATOR as u128) as u64
+}
+
+/// 结算时的各项费用由罚没部分承担：总额超出罚没部分时按比例缩减
+/// 返回 (缩减后的费用, 剩余的惩罚池)
+pub fn fit_to_forfeit<const N: usize>(fees: [u64; N], forfeited: u64) -> ([u64; N], u64) {
+    let total: u128 = fees.iter().map(|&fee| fee as u128).sum();
+    let fees = if total > forfeited as u128 {
+        fees.map(|fee| (fee as u128 * forfeited as u128 / total) as u64)
+    } else {
+        fees
+    };
+    (fees, forfeited - fees.iter().sum::<u64>())
+}
+
+/// 按 bps 计算份额 (向下取整，余数归回购池)
+pub fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    (amount as u128)
+        .checked_mul(bps as u128)
+        .map(|x| x / BPS_DENOMINATOR as u128)
+        .and_then(|x| u64::try_from(x).ok())
+        .ok_or(ConsensusError::Overflow.into())
+}
+
+/// 评审领取时结清的预留费用份额：按其质押占评审总质押 (不含赞助奖池) 的比例
+/// 赞助奖池没有领取人，其对应的费用由评审分摊，领取完毕后不会滞留在金库
+pub fn claim_fee_share(fee: u64, stake: u64, voter_stake: u64) -> Result<u64> {
+    (fee as u128)
+        .checked_mul(stake as u128)
+        .and_then(|x| x.checked_div(voter_stake as u128))
+        .and_then(|x| u64::try_from(x).ok())
+        .ok_or(ConsensusError::DivisionByZero.into())
+}
+
+/// 结算时按结算基数计提的各项费用 (与获胜图片无关)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SettlementFees {
+    pub curator_fee: u64,
+    pub platform_fee: u64,
+    pub settler_reward: u64,
+    pub insurance_contribution: u64,
+    pub remix_royalty: u64,
+    pub collection_contribution: u64,
+    pub buyback_contribution: u64,
+}
+
+impl SettlementFees {
+    /// 按结算基数计提费用；非衍生创意的 remix_fee_bps 与不注入合集时的 collection_bps 传 0
+    pub fn from_base(
+        fee_base: u64,
+        curator_fee_bps: u16,
+        platform_fee_bps: u16,
+        remix_fee_bps: u16,
+        collection_bps: u16,
+    ) -> Result<Self> {
+        let curator_fee = bps_of(fee_base, curator_fee_bps)?;
+        let platform_fee = bps_of(fee_base, platform_fee_bps)?;
+        let settler_reward = bps_of(fee_base, SETTLER_REWARD_BPS)?.min(MAX_SETTLER_REWARD);
+        let insurance_contribution = bps_of(fee_base, INSURANCE_FEE_BPS)?;
+        let remix_royalty = bps_of(fee_base, remix_fee_bps)?;
+        let collection_contribution = bps_of(fee_base, collection_bps)?;
+        let remaining_pool = fee_base
+            .checked_sub(curator_fee)
+            .and_then(|x| x.checked_sub(platform_fee))
+            .and_then(|x| x.checked_sub(settler_reward))
+            .and_then(|x| x.checked_sub(insurance_contribution))
+            .and_then(|x| x.checked_sub(remix_royalty))
+            .and_then(|x| x.checked_sub(collection_contribution))
+            .ok_or(ConsensusError::Overflow)?;
+
+        Ok(Self {
+            curator_fee,
+            platform_fee,
+            settler_reward,
+            insurance_contribution,
+            remix_royalty,
+            collection_contribution,
+            buyback_contribution: bps_of(remaining_pool, SETTLEMENT_BUYBACK_BPS)?,
+        })
+    }
+
+    fn to_array(self) -> [u64; 7] {
+        [
+            self.curator_fee,
+            self.platform_fee,
+            self.settler_reward,
+            self.insurance_contribution,
+            self.remix_royalty,
+            self.collection_contribution,
+            self.buyback_contribution,
+        ]
+    }
+
+    fn from_array(fees: [u64; 7]) -> Self {
+        let [
+            curator_fee,
+            platform_fee,
+            settler_reward,
+            insurance_contribution,
+            remix_royalty,
+            collection_contribution,
+            buyback_contribution,
+        ] = fees;
+        Self {
+            curator_fee,
+            platform_fee,
+            settler_reward,
+            insurance_contribution,
+            remix_royalty,
+            collection_contribution,
+            buyback_contribution,
+        }
+    }
+}
+
+/// 结算分账：实际收取的费用、销毁额与分给获胜者的惩罚池
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SettlementSplit {
+    pub fees: SettlementFees,
+    pub penalty_burned: u64,
+    pub penalty_pool: u64,
+}
+
+/// 罚没部分：赞助奖池全部罚没，评审的未获胜质押 (含 RejectAll) 按 PENALTY_BPS 罚没
+/// 无损模式下评审本金不参与惩罚，只有赞助奖池
+pub fn forfeited_stake(
+    total_staked: u64,
+    initial_prize_pool: u64,
+    winning_stake: u64,
+    no_loss: bool,
+) -> Result<u64> {
+    let losing_stake = total_staked
+        .checked_sub(initial_prize_pool)
+        .and_then(|x| x.checked_sub(winning_stake))
+        .ok_or(ConsensusError::Overflow)?;
+    let penalty_bps = if no_loss { 0 } else { PENALTY_BPS };
+    initial_prize_pool
+        .checked_add(bps_of(losing_stake, penalty_bps)?)
+        .ok_or(ConsensusError::Overflow.into())
+}
+
+/// 由罚没部分承担各项费用 (超出时按比例缩减)，余下的按 penalty_burn_bps 销毁后作为惩罚池
+pub fn settlement_split(
+    fees: SettlementFees,
+    forfeited: u64,
+    penalty_burn_bps: u16,
+) -> Result<SettlementSplit> {
+    let (fees, penalty_pool) = fit_to_forfeit(fees.to_array(), forfeited);
+    let penalty_burned = bps_of(penalty_pool, penalty_burn_bps)?;
+
+    Ok(SettlementSplit {
+        fees: SettlementFees::from_array(fees),
+        penalty_burned,
+        penalty_pool: penalty_pool - penalty_burned,
+    })
+}
+
+/// 争议改判获胜图片后重新分账 (与 settle_idea 使用同一分账规则)
+/// 结算时已转出金库的费用与销毁无法收回：由原获胜质押的罚没部分减去仍预留在金库中的
+/// 策展费、平台费与惩罚池反推，新罚没部分扣除后余下的重新分给预留费用与惩罚池
+pub fn resettlement_split(
+    total_staked: u64,
+    initial_prize_pool: u64,
+    no_loss: bool,
+    reserved: &SettlementSplit,
+    old_winning_stake: u64,
+    new_winning_stake: u64,
+) -> Result<SettlementSplit> {
+    let retained = reserved
+        .fees
+        .curator_fee
+        .checked_add(reserved.fees.platform_fee)
+        .and_then(|x| x.checked_add(reserved.penalty_pool))
+        .ok_or(ConsensusError::Overflow)?;
+    let paid_out = forfeited_stake(total_staked, initial_prize_pool, old_winning_stake, no_loss)?
+        .checked_sub(retained)
+        .ok_or(ConsensusError::Overflow)?;
+    let available = forfeited_stake(total_staked, initial_prize_pool, new_winning_stake, no_loss)?
+        .checked_sub(paid_out)
+        .ok_or(ConsensusError::DisputeCorrectionUnderfunded)?;
+
+    // 惩罚池的销毁已计入 paid_out，不再重复销毁
+    settlement_split(
+        SettlementFees {
+            curator_fee: reserved.fees.curator_fee,
+            platform_fee: reserved.fees.platform_fee,
+            ..Default::default()
+        },
+        available,
+        0,
+    )
+}
+
+/// 按历史胜率计算投票权重倍数 (bps，10_000 = 1x)
+pub fn reputation_multiplier_bps(wins: u32, losses: u32) -> u16 {
+    let total = wins.saturating_add(losses);
+    if total < MIN_REPUTATION_SAMPLE || wins <= losses {
+        return BPS_DENOMINATOR;
+    }
+    let bonus = (MAX_REPUTATION_BONUS_BPS as u64) * ((wins - losses) as u64) / (total as u64);
+    BPS_DENOMINATOR + bonus as u16
+}
+
+/// 读取结算程序的 Reputation 账户并计算投票者的权重倍数
+/// core 无法依赖 settlement crate，按布局直接读取：discriminator | reviewer | wins | losses
+pub fn reputation_multiplier_from_account(reputation: &AccountInfo, voter: &Pubkey) -> Result<u16> {
+    require_keys_eq!(*reputation.owner, SETTLEMENT_PROGRAM_ID, ConsensusError::InvalidReputation);
+    let data = reputation.try_borrow_data()?;
+    require!(
+        data.len() >= 8 + REPUTATION_SPACE
+            && data[..8] == anchor_lang::solana_program::hash::hash(b"account:Reputation").to_bytes()[..8]
+            && data[8..40] == voter.to_bytes(),
+        ConsensusError::InvalidReputation
+    );
+    let wins = u32::from_le_bytes(data[40..44].try_into().unwrap());
+    let losses = u32::from_le_bytes(data[44..48].try_into().unwrap());
+    Ok(reputation_multiplier_bps(wins, losses))
+}
 
 // 保留原枚举以兼容其他地方的使用
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
@@ -136,32 +1074,174 @@ pub enum ThemeStatus {
     Paused,     // 暂停
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
-#[repr(C)]
-pub struct Vault {
-    pub idea: Pubkey,
-    pub bump: u8,
-}
+/// Idea 及其金库/投票/质押账户的唯一定义，只在 core 中展开 (owner 为 core)
+/// 其他程序直接使用 taste_fun_core 导出的类型读取，不得再次展开，否则 Account<T> 会按展开方的程序 ID 校验 owner
+/// 布局与 core 已创建的账户一致 (theme 字段位于 created_at 之后)，现有账户无需迁移
+/// 新增字段只能追加在末尾，并同步 IDEA_SPACE 与需要 realloc 的指令
+#[macro_export]
+macro_rules! idea_accounts {
+    () => {
+        #[account]
+        pub struct Idea {
+            // 核心字段
+            pub initiator: Pubkey,
+            pub idea_id: u64,
+            pub prompt: String,
+            pub created_at: i64,
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct Vote {
-    pub idea: Pubkey,
-    pub voter: Pubkey,
-    pub image_choice: u8, // 0-3 对应图 A-D, 255 = RejectAll
-    pub stake_amount: u64,
-    pub vote_weight: u64, // 二次方投票权重
-    pub ts: i64,
-}
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
-#[repr(C)]
-pub struct ReviewerStake {
-    pub idea: Pubkey,
-    pub reviewer: Pubkey,
-    pub total_staked: u64,
-    pub is_winner: bool,
-    pub winnings: u64,
-    pub bump: u8,
+            // 主题关联（新增）
+            pub theme: Pubkey,
+            pub theme_token_mint: Pubkey,
+
+            // DePIN 相关
+            pub image_uris: Vec<String>,
+            pub generation_status: GenerationStatus,
+            pub generation_deadline: i64,
+            pub depin_provider: Pubkey,
+
+            // 赞助竞赛相关
+            pub sponsor: Option<Pubkey>,
+            pub initial_prize_pool: u64,
+
+            // 质押池参数
+            pub total_staked: u64,
+            pub min_stake: u64,
+            pub curator_fee_bps: u16,
+
+            // 投票统计 (存储投票权重，非票数)
+            pub votes: [u64; 4],
+            pub reject_all_weight: u64,
+            pub total_voters: u64,
+            pub winning_image_index: Option<u8>,
+
+            // 结算数据
+            pub curator_fee_collected: u64,
+            pub platform_fee_collected: u64,
+            pub penalty_pool_amount: u64,
+            pub winner_count: u64,
+
+            // 时间控制
+            pub voting_deadline: i64,
+
+            // 状态与 bumps
+            pub status: IdeaStatus,
+            pub vault_bump: u8,
+            pub idea_bump: u8,
+
+            // 投票门槛 (赞助竞赛私有评审团)
+            pub allowlist_root: Option<[u8; 32]>,
+            pub gate_mint: Option<Pubkey>,
+            pub gate_min_amount: u64,
+
+            // 取消与退款补贴
+            pub cancel_reason: u8,
+            pub refund_rebates_paid: u64,
+
+            // 赞助商品牌资料
+            pub sponsor_profile: Option<Pubkey>,
+
+            // 无损模式：评审本金不参与惩罚
+            pub no_loss: bool,
+
+            // 所属赞助系列赛
+            pub series: Option<Pubkey>,
+
+            // 两阶段揭晓：图片 URI 哈希与揭晓时间 (0 表示直接公开)
+            pub image_hashes: [[u8; 32]; 4],
+            pub reveal_ts: i64,
+
+            // 赞助评委加奖
+            pub bonus_judge: Option<Pubkey>,
+            pub bonus_pool: u64,
+
+            // Prompt 哈希 (keccak)，用于主题内去重
+            pub prompt_hash: [u8; 32],
+
+            // 惩罚池分配曲线 (创建时复制自主题) 及各图片累计份额
+            pub payout_curve: u8,
+            pub payout_exponent: u8,
+            pub payout_weights: [u128; 4],
+
+            // 各图片投票人数与质押总量 (votes 为二次方权重)
+            pub voter_counts: [u32; 4],
+            pub stake_totals: [u64; 4],
+
+            // 投票模式 (创建时复制自主题，结算时读取)
+            pub voting_mode: VotingMode,
+
+            // 中间派模式下的第二获胜图片 (最少票)
+            pub secondary_winning_index: Option<u8>,
+
+            // 平局处理规则 (创建时复制自主题) 及各图片最后一次获得投票的时间
+            pub tie_break_rule: u8,
+            pub last_vote_ts: [i64; 4],
+            pub claim_deadline: i64,
+            pub dispute_period: i64,
+            pub dispute_deadline: i64,
+            pub disputed: bool,
+            pub curator: Pubkey,
+            pub reject_all_threshold_bps: u16,
+            pub rollover_bps: u16,
+            pub insurance_contribution: u64,
+            pub penalty_burn_bps: u16,
+
+            // 衍生创意：原创意及结算时提取的版税比例与金额
+            pub parent: Option<Pubkey>,
+            pub remix_fee_bps: u16,
+            pub remix_royalty: u64,
+
+            // 所属创意合集
+            pub collection: Option<Pubkey>,
+
+            // 账户结构版本 (IDEA_ACCOUNT_VERSION)，0 为未记录版本的旧账户
+            pub version: u8,
+
+            // 已转为线性释放尚未领取的奖金，清扫时保留在金库
+            pub outstanding_vesting: u64,
+        }
+
+        impl Idea {
+            pub const SPACE: usize = IDEA_SPACE + 64; // Added theme + theme_token_mint
+        }
+
+        #[account]
+        pub struct Vault {
+            pub idea: Pubkey,
+            pub bump: u8,
+        }
+
+        impl Vault {
+            pub const SPACE: usize = VAULT_SPACE;
+        }
+
+        #[account]
+        pub struct Vote {
+            pub idea: Pubkey,
+            pub voter: Pubkey,
+            pub image_choice: u8,
+            pub stake_amount: u64,
+            pub vote_weight: u64,
+            pub ts: i64,
+        }
+
+        impl Vote {
+            pub const SPACE: usize = VOTE_SPACE;
+        }
+
+        #[account]
+        pub struct ReviewerStake {
+            pub idea: Pubkey,
+            pub reviewer: Pubkey,
+            pub total_staked: u64,
+            pub is_winner: bool,
+            pub winnings: u64,
+            pub bump: u8,
+        }
+
+        impl ReviewerStake {
+            pub const SPACE: usize = REVIEWER_STAKE_SPACE;
+        }
+    };
 }
 
 // -----------------------------------------------------------------------------
@@ -195,6 +1275,43 @@ pub const IDEA_SPACE: usize = 32     // initiator
     + 1                         // idea_bump
     + 32                        // theme
     + 32                        // theme_token_mint
+    + (1 + 32)                  // allowlist_root (Option<[u8; 32]>)
+    + (1 + 32)                  // gate_mint (Option<Pubkey>)
+    + 8                         // gate_min_amount
+    + 1                         // cancel_reason
+    + 8                         // refund_rebates_paid
+    + (1 + 32)                  // sponsor_profile (Option<Pubkey>)
+    + 1                         // no_loss
+    + (1 + 32)                  // series (Option<Pubkey>)
+    + 4 * 32                    // image_hashes
+    + 8                         // reveal_ts
+    + (1 + 32)                  // bonus_judge (Option<Pubkey>)
+    + 8                         // bonus_pool
+    + 32                        // prompt_hash
+    + 1                         // payout_curve
+    + 1                         // payout_exponent
+    + 16 * 4                    // payout_weights [u128; 4]
+    + 4 * 4                     // voter_counts [u32; 4]
+    + 8 * 4                     // stake_totals [u64; 4]
+    + 1                         // voting_mode
+    + (1 + 1)                   // secondary_winning_index (Option<u8>)
+    + 1                         // tie_break_rule
+    + 8 * 4                     // last_vote_ts [i64; 4]
+    + 8                         // claim_deadline
+    + 8                         // dispute_period
+    + 8                         // dispute_deadline
+    + 1                         // disputed
+    + 32                        // curator
+    + 2                         // reject_all_threshold_bps
+    + 2                         // rollover_bps
+    + 8                         // insurance_contribution
+    + 2                         // penalty_burn_bps
+    + (1 + 32)                  // parent (Option<Pubkey>)
+    + 2                         // remix_fee_bps
+    + 8                         // remix_royalty
+    + (1 + 32)                  // collection (Option<Pubkey>)
+    + 1                         // version
+    + 8                         // outstanding_vesting
     + 16;                       // minimal buffer
 
 pub const VAULT_SPACE: usize = 32 + 1; // idea + bump
@@ -203,6 +1320,318 @@ pub const VOTE_SPACE: usize = 32 + 32 + 1 + 8 + 8 + 8; // idea + voter + image_c
 
 pub const REVIEWER_STAKE_SPACE: usize = 32 + 32 + 8 + 1 + 8 + 1; // idea + reviewer + total_staked + is_winner + winnings + bump
 
+pub const SPONSOR_PROFILE_SPACE: usize = 32 // sponsor
+    + MAX_SPONSOR_NAME_LEN                  // name [u8; 32]
+    + (4 + MAX_IMAGE_URI_LEN)               // logo_uri
+    + (4 + MAX_SPONSOR_LINK_LEN)            // link
+    + 8                                     // created_at
+    + 8                                     // updated_at
+    + 1;                                    // bump
+
+pub const SERIES_SPACE: usize = 32 // sponsor
+    + 8                            // series_id
+    + 32                           // theme
+    + 32                           // theme_token_mint
+    + (4 + MAX_PROMPT_LEN)         // prompt
+    + 32                           // depin_provider
+    + 8                            // interval_secs
+    + 8                            // prize_per_episode
+    + 8                            // max_episodes
+    + 8                            // episodes_created
+    + 8                            // next_episode_at
+    + 1                            // no_loss
+    + 1                            // active
+    + 1;                           // bump
+
+pub const SPONSOR_ESCROW_SPACE: usize = 32 // idea
+    + 32                                   // sponsor
+    + 32                                   // token_mint
+    + 8 * ESCROW_MILESTONE_COUNT           // tranches [u64; 3]
+    + 1                                    // released_mask
+    + 1                                    // refunded
+    + 1;                                   // bump
+
+pub const DELEGATION_SPACE: usize = 32 // delegator
+    + 32                               // curator
+    + 32                               // theme_token_mint
+    + 8                                // amount
+    + 2                                // curator_cut_bps
+    + 1;                               // bump
+
+pub const FOLLOW_SPACE: usize = 32 // follower
+    + 32                           // curator
+    + 32                           // theme_token_mint
+    + 8                            // mirror_amount
+    + 2                            // tip_bps
+    + 1                            // auto_mirror
+    + 1;                           // bump
+
+pub const COMMENT_SPACE: usize = 32 // idea
+    + 32                            // author
+    + 4                             // comment_id
+    + 32                            // content_hash
+    + 4 + MAX_IMAGE_URI_LEN         // uri
+    + 8                             // deposit
+    + 8                             // created_at
+    + 1;                            // bump
+
+pub const SQUAD_SPACE: usize = 32 // captain
+    + 8                           // squad_id
+    + 32                          // theme_token_mint
+    + 8                           // total_shares
+    + 8                           // active_stake
+    + 4                           // member_count
+    + 1;                          // bump
+
+pub const SQUAD_MEMBER_SPACE: usize = 32 + 32 + 8 + 1; // squad + member + shares + bump
+
+pub const PROMPT_REGISTRY_SPACE: usize = 32 // theme
+    + RECENT_PROMPT_SLOTS * (32 + 32 + 32 + 8)  // entries [idea, initiator, prompt_hash, active_until]
+    + 1;                                        // bump
+
+pub const JACKPOT_SPACE: usize = 32 // theme
+    + 32                                // theme_token_mint
+    + 8                                 // total_paid_out
+    + (1 + 32)                          // last_funded_idea
+    + 1;                                // bump
+
+pub const PROTOCOL_CONFIG_SPACE: usize = 32 // authority
+    + 32                                      // treasury
+    + 8                                       // creation_fee
+    + 2                                       // platform_fee_bps
+    + 4                                       // reputation_discount_min_wins
+    + 2                                       // reputation_discount_min_accuracy_bps
+    + 2                                       // reputation_discount_bps
+    + 8                                       // staker_discount_min_amount
+    + 2                                       // staker_discount_bps
+    + 4                                       // season
+    + 8                                       // season_started_at
+    + 1                                       // bump
+    + 1                                       // paused (token 程序按 PROTOCOL_CONFIG_PAUSED_OFFSET 读取，位置不可变)
+    + 1;                                      // version
+
+/// paused 在账户数据中的偏移 (含 discriminator)，其后只能追加字段
+pub const PROTOCOL_CONFIG_PAUSED_OFFSET: usize = 8 + PROTOCOL_CONFIG_SPACE - 2;
+
+pub const ROLES_SPACE: usize = 32 // guardian
+    + 32                            // fee_setter
+    + 32                            // depin_admin
+    + 32                            // treasurer
+    + 32                            // moderator
+    + 1;                            // bump
+
+pub const REMIX_POOL_SPACE: usize = 32 // parent
+    + 32                               // token_mint
+    + 8                                // winner_revenue
+    + 8                                // initiator_revenue
+    + 8                                // initiator_claimed
+    + 1;                               // bump
+
+pub const REMIX_CLAIM_SPACE: usize = 32 // parent
+    + 32                                // claimer
+    + 8                                 // claimed
+    + 1;                                // bump
+
+pub const COLLECTION_SPACE: usize = 32 // initiator
+    + 8                                // collection_id
+    + 32                               // theme
+    + 32                               // theme_token_mint
+    + (4 + MAX_COLLECTION_NAME_LEN)    // name
+    + (4 + MAX_IMAGE_URI_LEN)          // metadata_uri
+    + 2                                // grand_prize_bps
+    + 8                                // ends_at
+    + 8                                // idea_count
+    + 1;                               // bump
+
+pub const COLLECTION_PRIZE_SPACE: usize = 32 // collection
+    + 32                                     // token_mint
+    + 2                                      // grand_prize_bps
+    + 8                                      // ends_at
+    + 8                                      // grand_prize
+    + 8                                      // total_prize
+    + 8                                      // settled_count
+    + 32                                     // top_idea
+    + 8                                      // top_stake
+    + 1                                      // finalized
+    + 1;                                     // bump
+
+pub const COLLECTION_PRIZE_CLAIM_SPACE: usize = 32 // collection
+    + 32                                           // voter
+    + 8                                            // amount
+    + 1;                                           // bump
+
+pub const IDEA_MIGRATION_SPACE: usize = 32 // idea
+    + 32                                    // cursor
+    + 8                                     // processed
+    + 8 * 4                                 // stake_totals [u64; 4]
+    + 4 * 4                                 // voter_counts [u32; 4]
+    + 16 * 4                                // payout_weights [u128; 4]
+    + 8 * 4                                 // last_vote_ts [i64; 4]
+    + 1;                                    // bump
+
+pub const IMAGE_STORAGE_CONFIG_SPACE: usize = (4 + MAX_STORAGE_PREFIXES * (4 + MAX_STORAGE_PREFIX_LEN)) // allowed_prefixes
+    + (4 + MAX_STORAGE_PREFIX_LEN)                                                                  // gateway_base
+    + 1;                                                                                            // bump
+
+pub const INSURANCE_SPACE: usize = 8 // total_contributed
+    + 8                                 // total_paid
+    + 8                                 // claim_count
+    + 1;                                // bump
+
+pub const STATE_ROOT_SPACE: usize = 32 // root
+    + 8                                 // epoch
+    + 8                                 // leaf_count
+    + 8                                 // slot
+    + 8                                 // posted_at
+    + 1;                                // bump
+
+pub const BONUS_AWARD_SPACE: usize = 32 // idea
+    + 32                                 // judge
+    + 1                                  // image_index
+    + 8                                  // amount
+    + 8                                  // total_weight
+    + 8                                  // awarded_at
+    + 1;                                 // bump
+
+pub const BONUS_CLAIM_SPACE: usize = 32 // idea
+    + 32                                 // reviewer
+    + 8                                  // amount
+    + 1;                                 // bump
+
+pub const DISPUTE_SPACE: usize = 32 // idea
+    + 32                                 // challenger
+    + 8                                  // bond
+    + 32                                 // evidence_hash
+    + 8                                  // opened_at
+    + 1;                                 // bump
+
+pub const VESTING_CLAIM_SPACE: usize = 32 // idea
+    + 32                                 // reviewer
+    + 8                                  // total_amount
+    + 8                                  // claimed_amount
+    + 8                                  // start_ts
+    + 8                                  // duration
+    + 1;                                 // bump
+
+pub const REPUTATION_SPACE: usize = 32 // reviewer
+    + 4                                 // wins
+    + 4                                 // losses
+    + 8                                 // total_staked
+    + 8                                 // total_returned
+    + 2                                 // current_streak
+    + 2                                 // best_streak
+    + 8                                 // last_updated
+    + 1                                 // bump
+    + 4                                 // season
+    + 4                                 // season_wins
+    + 4                                 // season_losses
+    + 8;                                // season_winnings
+
+pub const LEADERBOARD_SPACE: usize = 32 // scope (主题或 GLOBAL_LEADERBOARD_SCOPE)
+    + 4                                  // season
+    + 8                                  // updated_at
+    + 2 * LEADERBOARD_SIZE * (32 + 8 + 4 + 4) // by_winnings, by_accuracy [reviewer, winnings, wins, losses]
+    + 1;                                 // bump
+
+pub const REWARDS_SEASON_SPACE: usize = 4 // season
+    + 32                                    // merkle_root
+    + 8                                     // total_amount
+    + 8                                     // claimed_amount
+    + 8                                     // expires_at
+    + 1                                     // reclaimed
+    + 1;                                    // bump
+
+pub const REWARDS_CLAIM_SPACE: usize = 32 + 32 + 8; // rewards_season + claimant + amount
+
+pub const PAYOUT_ROOT_SPACE: usize = 32 // idea
+    + 32                                 // root
+    + 8                                  // total_amount
+    + 8                                  // claimed_amount
+    + 8                                  // posted_at
+    + 1                                  // challenged
+    + 1;                                 // bump
+
+pub const PAYOUT_CHALLENGE_SPACE: usize = 32 // payout_root
+    + 32                                 // challenger
+    + 8                                  // bond
+    + 8                                  // opened_at
+    + 1;                                 // bump
+
+pub const WINNER_NFT_DROP_SPACE: usize = 32 // idea
+    + 32                                    // root
+    + 8                                     // winner_count
+    + 8                                     // claimed_count
+    + 8                                     // posted_at
+    + 1;                                    // bump
+
+pub const WINNER_NFT_CLAIM_SPACE: usize = 32 // idea
+    + 32                                     // winner
+    + 32                                     // mint
+    + 8                                      // claimed_at
+    + 1;                                     // bump
+
+pub const GALLERY_SPACE: usize = 32 // theme
+    + 8                             // entry_count
+    + 4                             // page_count
+    + 1;                            // bump
+
+pub const GALLERY_ENTRY_SPACE: usize = 32 // idea
+    + 32                                  // image_hash
+    + (4 + MAX_IMAGE_URI_LEN)             // image_uri
+    + 8;                                  // settled_at
+
+pub const GALLERY_PAGE_SPACE: usize = 32                   // theme
+    + 4                                                    // page
+    + (4 + GALLERY_PAGE_CAPACITY * GALLERY_ENTRY_SPACE)    // entries
+    + 1;                                                   // bump
+
+pub const BADGE_TREE_SPACE: usize = 32 // theme
+    + 32                                // merkle_tree
+    + 1                                 // enabled
+    + (4 + MAX_IMAGE_URI_LEN)           // uri
+    + 8                                 // minted
+    + 1;                                // bump
+
+pub const BADGE_DROP_SPACE: usize = 32 // idea
+    + 32                                // cursor (最后一个已铸造的投票者)
+    + 8                                 // minted
+    + 1;                                // bump
+
+pub const EDITION_SALE_SPACE: usize = 32 // idea
+    + 32                                 // mint
+    + 8                                  // price
+    + 8                                  // max_supply (0 = 不限量)
+    + 8                                  // sold
+    + 8                                  // end_ts
+    + 2                                  // voter_share_bps
+    + 2                                  // initiator_share_bps
+    + 2                                  // buyback_share_bps
+    + 8                                  // voter_revenue
+    + 8                                  // voter_claimed
+    + 1;                                 // bump
+
+pub const EDITION_CLAIM_SPACE: usize = 32 // idea
+    + 32                                  // voter
+    + 8                                   // amount
+    + 1;                                  // bump
+
+pub const ROYALTY_VAULT_SPACE: usize = 32 // idea
+    + 8                                    // total_claimed
+    + 1;                                   // bump
+
+pub const ROYALTY_CLAIM_SPACE: usize = 32 // idea
+    + 32                                   // voter
+    + 8                                    // claimed
+    + 1;                                   // bump
+
+pub const WINNER_NFT_SPACE: usize = 32 // idea
+    + 32                                // mint
+    + 32                                // minter
+    + 1                                 // image_index
+    + 32                                // image_hash
+    + 8                                 // minted_at
+    + 1;                                // bump
+
 // -----------------------------------------------------------------------------
 // Theme Token Account Sizes
 // -----------------------------------------------------------------------------
@@ -226,11 +1655,235 @@ pub const THEME_SPACE: usize = 32      // creator
     + 1                          // status (u8)
     + 1                          // vault_bump
     + 1                          // theme_bump
+    + 8                          // community_burned
+    + 1                          // payout_curve
+    + 1                          // payout_exponent
+    + 1                          // tie_break_rule
+    + 8                          // dispute_period
+    + 2                          // reject_all_threshold_bps
+    + 2                          // penalty_burn_bps
+    + 8                          // virtual_sol_reserves
+    + 8                          // virtual_token_reserves
+    + 1                          // migration_target
+    + 32                         // amm_pool
+    + 8                          // min_stake_tokens
+    + MAX_CURVE_PHASES * (8 + 8 + 8) // curve_phases
+    + 1                          // curve_phase_count
+    + 8                          // creator_fees_accrued
+    + 8                          // platform_fees_accrued
+    + 8                          // launch_slot
+    + 2                          // launch_fee_bps
+    + 8                          // fee_decay_slots
+    + 4                          // swap_cooldown_secs
+    + 8                          // volume_slot
+    + 8                          // slot_buy_volume
+    + 8                          // slot_sell_volume
+    + 8                          // metadata_updated_at
+    + 8                          // retire_eta
+    + 32                         // quote_mint
+    + 2                          // stake_fee_share_bps
+    + 8                          // staking_rewards_accrued
+    + 1                          // buyback_policy
+    + 1                          // version
     + 16;                        // 减少buffer，仅保留16字节
 
 pub const THEME_VAULT_SPACE: usize = 32 + 1; // theme + bump
 
-pub const TRADING_CONFIG_SPACE: usize = 2 + 2 + 2 + 2 + 64; // trade_fee_bps + buyback_fee_split_bps + platform_fee_split_bps + creator_fee_split_bps + buffer
+// TWAP 价格预言机：环形缓冲区保存最近的 (slot, 价格) 观测
+pub const TWAP_OBSERVATIONS: usize = 16;
+pub const PRICE_SCALE: u128 = 1_000_000_000; // 价格精度 (lamports / 代币最小单位 * 1e9)
+pub const PRICE_ORACLE_SPACE: usize = 32 // theme
+    + TWAP_OBSERVATIONS * (8 + 8 + 8 + 16) // observations (slot, timestamp, price, cumulative_price)
+    + 1 // head
+    + 1 // count
+    + 1 // bump
+    + 16; // buffer
+
+pub const TRADING_CONFIG_SPACE: usize = 2 + 2 + 2 + 2 // trade_fee_bps + buyback_fee_split_bps + platform_fee_split_bps + creator_fee_split_bps
+    + 32 // sol_usd_feed
+    + 8 // min_trade_usd
+    + 8 // min_stake_usd
+    + 8 // min_trade_lamports
+    + 32 // authority
+    + 2 + 2 + 2 + 2 // pending_* fee 配置
+    + 8 // pending_update_eta
+    + 32 // pending_authority
+    + 2 // referral_fee_split_bps
+    + 2 // max_slot_volume_bps
+    + 1 // version
+    + 8; // buffer
+
+// 开盘窗口内每个买家的累计买入记录
+pub const LAUNCH_LIMIT_SPACE: usize = 32 // theme
+    + 32 // buyer
+    + 8 // sol_bought
+    + 1; // bump
+
+// 每个交易者在主题内的最近交易记录 (反向交易冷却)
+pub const TRADER_STATE_SPACE: usize = 32 // theme
+    + 32 // trader
+    + 8 // last_trade_ts
+    + 1 // last_trade_is_buy
+    + 1; // bump
+
+pub const THEME_METADATA_SPACE: usize = 32 // theme
+    + (4 + MAX_THEME_FULL_NAME_LEN)        // name
+    + (4 + MAX_THEME_FULL_DESCRIPTION_LEN) // description
+    + (4 + MAX_IMAGE_URI_LEN)              // icon_uri
+    + (4 + MAX_THEME_LINK_LEN)             // website
+    + (4 + MAX_THEME_LINK_LEN)             // twitter
+    + (4 + MAX_THEME_LINK_LEN)             // telegram
+    + 8                                    // updated_at
+    + 1;                                   // bump
+
+pub const CREATOR_VESTING_SPACE: usize = 32 // theme
+    + 32 // creator
+    + 8 // total_amount
+    + 8 // claimed_amount
+    + 8 // start_ts
+    + 8 // cliff_ts
+    + 8 // end_ts
+    + 1; // bump
+
+// 主题注册表：全局计数 + 分页条目，轻客户端无需 getProgramAccounts 即可枚举主题
+pub const THEME_REGISTRY_PAGE_SIZE: usize = 32;
+pub const THEME_REGISTRY_SPACE: usize = 8 // total_themes
+    + 1; // bump
+pub const THEME_REGISTRY_PAGE_SPACE: usize = 4 // page_index
+    + (4 + THEME_REGISTRY_PAGE_SIZE * (32 + 32 + 8)) // entries (theme, token_mint, created_slot)
+    + 1; // bump
+
+// 主题退役分配池 (SOL 仍存于金库)
+pub const RETIREMENT_POOL_SPACE: usize = 32 // theme
+    + 32 // merkle_root
+    + 8 // total_claimable
+    + 8 // claimed_amount
+    + 8 // retired_at
+    + 1; // bump
+pub const RETIREMENT_CLAIM_SPACE: usize = 32 + 32 + 8; // theme + holder + amount
+
+// 主题代币空投活动 (代币存于活动 PDA 的 ATA)
+pub const AIRDROP_CAMPAIGN_SPACE: usize = 32 // theme
+    + 32 // creator
+    + 8 // campaign_id
+    + 32 // merkle_root
+    + 8 // total_amount
+    + 8 // claimed_amount
+    + 8 // expires_at
+    + 1 // reclaimed
+    + 1; // bump
+pub const AIRDROP_CLAIM_SPACE: usize = 32 + 32 + 8; // campaign + claimant + amount
+
+// 毕业迁移 LP 锁仓 (LP 代币存于该 PDA 的 ATA)
+pub const LP_LOCK_SPACE: usize = 32 // theme
+    + 32 // pool
+    + 32 // lp_mint
+    + 8 // amount
+    + 8 // locked_at
+    + 8 // unlock_at
+    + 1; // bump
+
+// 推荐人注册表：每个推荐人一个 PDA，推荐分成先存入 PDA 再由推荐人提取
+pub const REFERRAL_SPACE: usize = 32 // referrer
+    + 8 // fees_accrued
+    + 8 // total_earned
+    + 8 // referred_trades
+    + 1 // bump
+    + 16; // buffer
+
+// 主题代币质押池：质押代币存于池子 ATA，SOL 奖励存于池子 PDA
+pub const STAKE_POOL_SPACE: usize = 32 // theme
+    + 8 // total_staked
+    + 8 // total_unstaking
+    + 16 // acc_sol_per_share
+    + 16 // acc_token_per_share
+    + 8 // token_rewards_reserved
+    + 8 // vault_excess_synced
+    + 1 // bump
+    + 16; // buffer
+
+pub const STAKE_POSITION_SPACE: usize = 32 // theme
+    + 32 // owner
+    + 8 // amount
+    + 16 // sol_reward_debt
+    + 16 // token_reward_debt
+    + 8 // pending_sol
+    + 8 // pending_tokens
+    + 8 // unstaking_amount
+    + 8 // unstake_available_at
+    + 1 // bump
+    + 16; // buffer
+
+// -----------------------------------------------------------------------------
+// Pyth 价格 (USD 计价最小额)
+// -----------------------------------------------------------------------------
+
+// Pyth Receiver 程序 ID rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    12, 183, 250, 187, 82, 247, 166, 72, 187, 91, 49, 125, 154, 1, 139, 144, 87, 203, 2, 71,
+    116, 250, 254, 1, 230, 196, 223, 152, 204, 56, 88, 129,
+]);
+// Pyth SOL/USD feed id
+pub const PYTH_SOL_USD_FEED_ID: [u8; 32] = [
+    239, 13, 139, 111, 218, 44, 235, 164, 29, 161, 93, 64, 149, 209, 218, 57, 42, 13, 47, 142,
+    208, 198, 199, 188, 15, 76, 250, 200, 194, 128, 181, 109,
+];
+// sha256("account:PriceUpdateV2")[..8]
+pub const PYTH_PRICE_UPDATE_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+pub const PYTH_MAX_PRICE_AGE: i64 = 60; // 秒
+pub const MIN_STAKE_TWAP_WINDOW: i64 = 3600; // 最小质押换算使用 1 小时 TWAP
+
+/// 解析 Pyth PriceUpdateV2 账户 (仅接受完全验证的 SOL/USD 价格)，返回 (price, exponent)
+/// 布局：discriminator(8) + write_authority(32) + verification_level(1|2) + feed_id(32)
+///       + price(i64) + conf(u64) + exponent(i32) + publish_time(i64) + ...
+pub fn read_pyth_price(info: &AccountInfo, now: i64) -> Result<(i64, i32)> {
+    require!(
+        info.owner == &PYTH_RECEIVER_PROGRAM_ID,
+        ConsensusError::InvalidPriceFeed
+    );
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() >= 8 + 32 + 1 + 32 + 8 + 8 + 4 + 8
+            && data[..8] == PYTH_PRICE_UPDATE_DISCRIMINATOR,
+        ConsensusError::InvalidPriceFeed
+    );
+    // verification_level: 0 = Partial { num_signatures }，1 = Full
+    require!(data[40] == 1, ConsensusError::InvalidPriceFeed);
+    
+    let msg = &data[41..];
+    require!(msg[..32] == PYTH_SOL_USD_FEED_ID, ConsensusError::InvalidPriceFeed);
+    let price = i64::from_le_bytes(msg[32..40].try_into().unwrap());
+    let exponent = i32::from_le_bytes(msg[48..52].try_into().unwrap());
+    let publish_time = i64::from_le_bytes(msg[52..60].try_into().unwrap());
+    
+    require!(price > 0, ConsensusError::InvalidPriceFeed);
+    require!(
+        now.saturating_sub(publish_time) <= PYTH_MAX_PRICE_AGE,
+        ConsensusError::StalePrice
+    );
+    
+    Ok((price, exponent))
+}
+
+/// micro-USD 换算为 lamports：usd / (price * 10^exponent) SOL
+pub fn usd_to_lamports(usd_micro: u64, price: i64, exponent: i32) -> Result<u64> {
+    // lamports = usd_micro * 1e3 * 10^(-exponent) / price
+    let mut numerator = (usd_micro as u128)
+        .checked_mul(1_000)
+        .ok_or(ConsensusError::Overflow)?;
+    let mut denominator = price as u128;
+    if exponent < 0 {
+        numerator = numerator
+            .checked_mul(10u128.pow(exponent.unsigned_abs()))
+            .ok_or(ConsensusError::Overflow)?;
+    } else {
+        denominator = denominator
+            .checked_mul(10u128.pow(exponent as u32))
+            .ok_or(ConsensusError::Overflow)?;
+    }
+    
+    u64::try_from(numerator / denominator).map_err(|_| error!(ConsensusError::Overflow))
+}
 
 // -----------------------------------------------------------------------------
 // Bonding Curve Utilities
@@ -271,6 +1924,44 @@ pub fn calculate_buy_tokens(
     Ok(tokens_out as u64)
 }
 
+/// 计算买入精确数量代币所需的 SOL（含手续费，向上取整）
+/// 恒定乘积的逆运算：sol_in = sol_reserves * tokens_out / (token_reserves - tokens_out)
+pub fn calculate_buy_sol_for_tokens(
+    tokens_out: u64,
+    token_reserves: u64,
+    sol_reserves: u64,
+    fee_bps: u16,
+) -> Result<u64> {
+    if tokens_out == 0 || tokens_out >= token_reserves {
+        return err!(ConsensusError::InvalidAmount);
+    }
+    
+    // 扣费后需要进入储备的 SOL（向上取整，保证买到的数量不少于 tokens_out）
+    let numerator = (sol_reserves as u128)
+        .checked_mul(tokens_out as u128)
+        .ok_or(ConsensusError::Overflow)?;
+    let denominator = (token_reserves as u128)
+        .checked_sub(tokens_out as u128)
+        .ok_or(ConsensusError::Overflow)?;
+    let sol_after_fee = numerator
+        .checked_add(denominator - 1)
+        .ok_or(ConsensusError::Overflow)?
+        .checked_div(denominator)
+        .ok_or(ConsensusError::DivisionByZero)?;
+    
+    // 加回手续费：sol_in = sol_after_fee * BPS / (BPS - fee)，同样向上取整
+    let fee_denominator = (BPS_DENOMINATOR - fee_bps) as u128;
+    let sol_in = sol_after_fee
+        .checked_mul(BPS_DENOMINATOR as u128)
+        .ok_or(ConsensusError::Overflow)?
+        .checked_add(fee_denominator - 1)
+        .ok_or(ConsensusError::Overflow)?
+        .checked_div(fee_denominator)
+        .ok_or(ConsensusError::DivisionByZero)?;
+    
+    u64::try_from(sol_in).map_err(|_| error!(ConsensusError::Overflow))
+}
+
 /// 计算卖出代币获得的 SOL
 /// sol_out = sol_reserves * token_in / (token_reserves + token_in)
 pub fn calculate_sell_sol(
@@ -355,4 +2046,430 @@ pub enum ConsensusError {
     InvalidThemeMetadata,
     #[msg("Invalid token mint")]
     InvalidMint,
+    #[msg("Voter is not eligible for this gated contest")]
+    VoterNotEligible,
+    #[msg("Invalid sponsor profile")]
+    InvalidSponsorProfile,
+    #[msg("Escrow milestone not reached")]
+    MilestoneNotReached,
+    #[msg("Escrow milestone already released")]
+    MilestoneAlreadyReleased,
+    #[msg("Invalid series configuration")]
+    InvalidSeriesConfig,
+    #[msg("Next series episode is not due yet")]
+    SeriesEpisodeNotDue,
+    #[msg("An active idea with the same prompt already exists in this theme")]
+    DuplicatePrompt,
+    #[msg("Invalid payout curve")]
+    InvalidPayoutCurve,
+    #[msg("Invalid tie-break rule")]
+    InvalidTieBreakRule,
+    #[msg("State root epoch must increase by one")]
+    InvalidStateRootEpoch,
+    #[msg("Bonus award window has closed")]
+    BonusWindowClosed,
+    #[msg("Bonus award window is still open")]
+    BonusWindowOpen,
+    #[msg("Claim window has closed")]
+    ClaimWindowClosed,
+    #[msg("Claim window is still open")]
+    ClaimWindowOpen,
+    #[msg("Payout root is still in its challenge window")]
+    PayoutRootPending,
+    #[msg("Payout root has been challenged")]
+    PayoutRootChallenged,
+    #[msg("Payout root can no longer be replaced")]
+    PayoutRootLocked,
+    #[msg("Invalid merkle proof")]
+    InvalidMerkleProof,
+    #[msg("Invalid dispute period")]
+    InvalidDisputePeriod,
+    #[msg("Invalid RejectAll threshold")]
+    InvalidRejectThreshold,
+    #[msg("Invalid jackpot rollover share")]
+    InvalidRolloverBps,
+    #[msg("Invalid penalty burn share")]
+    InvalidPenaltyBurnBps,
+    #[msg("Winnings above the vesting threshold must be vested")]
+    WinningsMustVest,
+    #[msg("Nothing has vested yet")]
+    NothingVested,
+    #[msg("Jackpot accounts are required for this idea")]
+    JackpotAccountMissing,
+    #[msg("Dispute window has closed")]
+    DisputeWindowClosed,
+    #[msg("Settlement is still in its dispute window")]
+    DisputeWindowOpen,
+    #[msg("Settlement is under dispute")]
+    SettlementDisputed,
+    #[msg("Payout exceeds the posted total")]
+    PayoutExceedsTotal,
+    #[msg("Invalid reveal time")]
+    InvalidRevealTime,
+    #[msg("Image reveal time has not been reached")]
+    RevealNotReady,
+    #[msg("Revealed image does not match committed hash")]
+    RevealHashMismatch,
+    #[msg("Swap deadline has passed")]
+    SwapDeadlineExceeded,
+    #[msg("SOL reserves have not reached the migration threshold")]
+    MigrationThresholdNotReached,
+    #[msg("Invalid migration target")]
+    InvalidMigrationTarget,
+    #[msg("AMM pool accounts do not match the migrated pool")]
+    InvalidAmmPool,
+    #[msg("Price oracle has no observations yet")]
+    OracleNotReady,
+    #[msg("Invalid Pyth price feed")]
+    InvalidPriceFeed,
+    #[msg("Pyth price is too old")]
+    StalePrice,
+    #[msg("Invalid bonding curve phases")]
+    InvalidCurvePhases,
+    #[msg("No pending update")]
+    NoPendingUpdate,
+    #[msg("Timelock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Invalid authority")]
+    InvalidAuthority,
+    #[msg("Cannot refer yourself")]
+    SelfReferral,
+    #[msg("Referral fee split too high")]
+    ReferralFeeTooHigh,
+    #[msg("Launch limit account required during launch window")]
+    LaunchLimitRequired,
+    #[msg("Launch window buy limit exceeded")]
+    LaunchLimitExceeded,
+    #[msg("Invalid launch fee schedule")]
+    InvalidLaunchFeeSchedule,
+    #[msg("Trader state account required while swap cooldown is enabled")]
+    TraderStateRequired,
+    #[msg("Swap cooldown active")]
+    SwapCooldownActive,
+    #[msg("Per-slot volume limit exceeded")]
+    SlotVolumeLimitExceeded,
+    #[msg("Theme is paused")]
+    ThemePaused,
+    #[msg("Theme metadata updated too recently")]
+    MetadataUpdateTooSoon,
+    #[msg("Theme has migrated to an AMM pool; trade on the pool instead")]
+    ThemeMigrated,
+    #[msg("Retirement payout exceeds remaining theme SOL")]
+    RetirementExceedsReserves,
+    #[msg("wSOL payment requires wsol_mint and vault_wsol_account")]
+    InvalidWsolAccounts,
+    #[msg("Quote mint must be SOL or USDC")]
+    UnsupportedQuoteMint,
+    #[msg("Instruction does not match the theme's quote mint")]
+    QuoteMintMismatch,
+    #[msg("Stake fee share exceeds maximum")]
+    StakeFeeShareTooHigh,
+    #[msg("Unstake cooldown has not elapsed")]
+    UnstakeCooldownActive,
+    #[msg("LP tokens are still locked")]
+    LpLockActive,
+    #[msg("LP lock token account does not match the lock PDA")]
+    InvalidLpLockAccount,
+    #[msg("Airdrop campaign has expired")]
+    AirdropExpired,
+    #[msg("Airdrop campaign has not expired yet")]
+    AirdropNotExpired,
+    #[msg("Invalid buyback policy")]
+    InvalidBuybackPolicy,
+    #[msg("Protocol fee exceeds the allowed maximum")]
+    ProtocolFeeTooHigh,
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+    #[msg("Invalid protocol config account")]
+    InvalidProtocolConfig,
+    #[msg("Signer does not hold the required role")]
+    MissingRole,
+    #[msg("Invalid role")]
+    InvalidRole,
+    #[msg("Reputation account does not belong to the voter")]
+    InvalidReputation,
+    #[msg("Season has not ended yet")]
+    SeasonNotEnded,
+    #[msg("Season rewards claim window has closed")]
+    RewardsExpired,
+    #[msg("Season rewards claim window is still open")]
+    RewardsNotExpired,
+    #[msg("Invalid stake delegation")]
+    InvalidDelegation,
+    #[msg("Curator cut exceeds the maximum")]
+    DelegationCutTooHigh,
+    #[msg("Follow tip exceeds the maximum")]
+    FollowTipTooHigh,
+    #[msg("Auto-mirror is disabled for this follow")]
+    AutoMirrorDisabled,
+    #[msg("Invalid comment")]
+    InvalidComment,
+    #[msg("Only the initiator, or a winning reviewer after the priority window, can mint the winner NFT")]
+    WinnerNftNotAllowed,
+    #[msg("Participation badges are disabled for this theme")]
+    BadgesDisabled,
+    #[msg("Invalid badge merkle tree")]
+    InvalidBadgeTree,
+    #[msg("Badge voters must be passed in ascending order")]
+    BadgeOrderViolation,
+    #[msg("Edition revenue shares must sum to 10000 bps with the minimum voter share")]
+    InvalidRevenueSplit,
+    #[msg("Edition sale is not active")]
+    EditionSaleClosed,
+    #[msg("Edition sale is still running")]
+    EditionSaleActive,
+    #[msg("Edition sale is sold out")]
+    EditionSoldOut,
+    #[msg("Image URI does not match an approved storage prefix")]
+    ImageUriNotAllowed,
+    #[msg("Invalid image storage configuration")]
+    InvalidStorageConfig,
+    #[msg("Remix parent must be a completed idea with a winner in the same theme")]
+    InvalidRemixParent,
+    #[msg("Remix fee exceeds the allowed maximum")]
+    InvalidRemixFee,
+    #[msg("Remix pool accounts are required to settle a remix")]
+    RemixPoolMissing,
+    #[msg("Invalid collection configuration")]
+    InvalidCollection,
+    #[msg("Collection is closed to new ideas")]
+    CollectionClosed,
+    #[msg("Collection has not ended yet")]
+    CollectionNotEnded,
+    #[msg("Collection prize accounts are required to settle a collection idea")]
+    CollectionPrizeMissing,
+    #[msg("Gallery page does not hold the next gallery entry")]
+    InvalidGalleryPage,
+    #[msg("Winner NFT root can no longer be replaced")]
+    WinnerNftRootLocked,
+    #[msg("All winner NFTs in this drop have been claimed")]
+    WinnerNftDropExhausted,
+    #[msg("Account is already at the current version")]
+    AccountUpToDate,
+    #[msg("Account type cannot be migrated by this program")]
+    UnsupportedMigration,
+    #[msg("Account must be migrated to the current version first")]
+    AccountMigrationRequired,
+    #[msg("Votes must be passed in ascending voter order")]
+    MigrationOrderViolation,
+    #[msg("Prompt registry has no expired slot left for this theme")]
+    PromptRegistryFull,
+    #[msg("Corrected winner's forfeited stake cannot cover payouts already made at settlement")]
+    DisputeCorrectionUnderfunded,
+    #[msg("Vault only holds winnings that are still vesting")]
+    VestingOutstanding,
+    #[msg("Payout root has an unresolved challenge")]
+    PayoutChallengeOpen,
+}
+
+#[cfg(test)]
+mod settlement_tests {
+    use super::*;
+
+    /// 结算时转出金库的费用与销毁
+    fn settlement_outflow(split: &SettlementSplit) -> u64 {
+        split.fees.settler_reward
+            + split.fees.insurance_contribution
+            + split.fees.remix_royalty
+            + split.fees.collection_contribution
+            + split.fees.buyback_contribution
+            + split.penalty_burned
+    }
+
+    /// 所有评审按分账结果领取的总额
+    fn claims_outflow(split: &SettlementSplit, winners: &[u64], losers: &[u64], no_loss: bool) -> u64 {
+        let winning_stake: u64 = winners.iter().sum();
+        let voter_stake = winning_stake + losers.iter().sum::<u64>();
+        // 领取时按质押占比结清策展费与平台费 (折扣返还并入领取金额，不改变流出总额)
+        let claim_fees = |stake: u64| {
+            claim_fee_share(split.fees.curator_fee, stake, voter_stake).unwrap()
+                + claim_fee_share(split.fees.platform_fee, stake, voter_stake).unwrap()
+        };
+        let mut outflow = 0;
+        for &stake in winners {
+            let share = (split.penalty_pool as u128 * stake as u128 / winning_stake as u128) as u64;
+            outflow += stake + share + claim_fees(stake);
+        }
+        let loser_penalty_bps = if no_loss { 0 } else { PENALTY_BPS };
+        for &stake in losers {
+            outflow += loser_refund_amount(stake, loser_penalty_bps) + claim_fees(stake);
+        }
+        outflow
+    }
+
+    /// 按 settle_idea 的分账结算
+    fn settle(prize: u64, total_staked: u64, winning_stake: u64, no_loss: bool) -> SettlementSplit {
+        let fee_base = if no_loss { prize } else { total_staked };
+        let fees = SettlementFees::from_base(fee_base, CURATOR_FEE_BPS, MAX_PLATFORM_FEE_BPS, 1_000, 0).unwrap();
+        let forfeited = forfeited_stake(total_staked, prize, winning_stake, no_loss).unwrap();
+        settlement_split(fees, forfeited, MAX_PENALTY_BURN_BPS).unwrap()
+    }
+
+    /// 结算后让所有评审领取，返回 (金库全部流出, 金库余额)
+    fn settle_and_claim_all(prize: u64, winners: &[u64], losers: &[u64], no_loss: bool) -> (u64, u64) {
+        let winning_stake: u64 = winners.iter().sum();
+        let total_staked = prize + winning_stake + losers.iter().sum::<u64>();
+        let split = settle(prize, total_staked, winning_stake, no_loss);
+        let outflow = settlement_outflow(&split) + claims_outflow(&split, winners, losers, no_loss);
+        (outflow, total_staked)
+    }
+
+    #[test]
+    fn claims_never_exceed_vault() {
+        let cases: [(u64, &[u64], &[u64], bool); 6] = [
+            // 大部分质押押中：罚没部分远小于按全池计提的费用
+            (0, &[9_000_000, 7_000_001, 3_333_333], &[1_000_003], false),
+            // 全部押中：无罚没，费用缩减为 0，获胜者取回本金
+            (0, &[5_000_000, 5_000_000], &[], false),
+            // 大部分质押落败
+            (0, &[1_000_000], &[4_000_001, 3_000_007, 999_999, 1], false),
+            // 赞助奖池全部罚没
+            (50_000_000, &[2_000_000, 1_000_001], &[3_000_003, 7], false),
+            // 无损模式：仅赞助奖池参与分配
+            (10_000_000, &[2_000_000, 1_333_333], &[4_000_001], true),
+            (1, &[3, 5], &[7, 11, 13], false),
+        ];
+        for (prize, winners, losers, no_loss) in cases {
+            let (outflow, vault) = settle_and_claim_all(prize, winners, losers, no_loss);
+            assert!(outflow <= vault, "outflow {outflow} > vault {vault} for {winners:?} / {losers:?}");
+        }
+    }
+
+    #[test]
+    fn sponsor_prize_is_paid_out_in_full() {
+        let prize = 50_000_000;
+        assert_eq!(forfeited_stake(prize + 3_000_000, prize, 1_000_000, false).unwrap(), prize + 1_000_000);
+        assert_eq!(forfeited_stake(prize + 3_000_000, prize, 1_000_000, true).unwrap(), prize);
+
+        // 领取完毕后金库只剩取整误差 (每个领取人至多 3 个最小单位)，赞助奖池不会滞留到清扫
+        let (winners, losers) = ([2_000_000, 1_000_001], [3_000_003, 7]);
+        for no_loss in [false, true] {
+            let (outflow, vault) = settle_and_claim_all(prize, &winners, &losers, no_loss);
+            let dust = vault - outflow;
+            assert!(dust <= 3 * 4, "{dust} left in the vault (no_loss = {no_loss})");
+        }
+    }
+
+    #[test]
+    fn upheld_dispute_resettles_against_the_new_winner() {
+        // 原获胜图片与改判后的获胜图片质押不同
+        let prize = 20_000_000;
+        let overturned: &[u64] = &[6_000_000, 1_500_001];
+        let corrected: &[u64] = &[2_000_000, 333_333];
+        let others: &[u64] = &[900_007];
+        let (old_stake, new_stake) = (overturned.iter().sum::<u64>(), corrected.iter().sum::<u64>());
+        let total_staked = prize + old_stake + new_stake + others.iter().sum::<u64>();
+
+        for no_loss in [false, true] {
+            let settled = settle(prize, total_staked, old_stake, no_loss);
+            let reserved = SettlementSplit {
+                fees: SettlementFees {
+                    curator_fee: settled.fees.curator_fee,
+                    platform_fee: settled.fees.platform_fee,
+                    ..Default::default()
+                },
+                penalty_pool: settled.penalty_pool,
+                ..Default::default()
+            };
+            let resettled =
+                resettlement_split(total_staked, prize, no_loss, &reserved, old_stake, new_stake).unwrap();
+            // 无损模式下罚没部分只有赞助奖池，与获胜图片无关
+            assert_eq!(resettled.penalty_pool == settled.penalty_pool, no_loss);
+
+            let losers: Vec<u64> = overturned.iter().chain(others).copied().collect();
+            let outflow = settlement_outflow(&settled) + claims_outflow(&resettled, corrected, &losers, no_loss);
+            assert!(outflow <= total_staked, "outflow {outflow} > vault {total_staked} (no_loss = {no_loss})");
+            let dust = total_staked - outflow;
+            assert!(dust <= 3 * 5, "{dust} left in the vault (no_loss = {no_loss})");
+
+            // 维持原判时分账不变
+            let unchanged =
+                resettlement_split(total_staked, prize, no_loss, &reserved, old_stake, old_stake).unwrap();
+            assert_eq!(unchanged, reserved);
+        }
+    }
+
+    #[test]
+    fn resettlement_rejects_winner_that_cannot_cover_paid_out_fees() {
+        // 原获胜质押极小：结算时按大额罚没部分转出费用，改判给几乎全部质押后不足以覆盖
+        let (total_staked, old_stake, new_stake) = (10_000_000, 1, 9_999_000);
+        let settled = settle(0, total_staked, old_stake, false);
+        let reserved = SettlementSplit {
+            fees: SettlementFees {
+                curator_fee: settled.fees.curator_fee,
+                platform_fee: settled.fees.platform_fee,
+                ..Default::default()
+            },
+            penalty_pool: settled.penalty_pool,
+            ..Default::default()
+        };
+        assert_eq!(
+            resettlement_split(total_staked, 0, false, &reserved, old_stake, new_stake).unwrap_err(),
+            ConsensusError::DisputeCorrectionUnderfunded.into()
+        );
+    }
+
+    #[test]
+    fn fees_scale_down_to_forfeited_amount() {
+        let (fees, pool) = fit_to_forfeit([600, 300, 100], 500);
+        assert_eq!(fees, [300, 150, 50]);
+        assert_eq!(pool, 0);
+
+        let (fees, pool) = fit_to_forfeit([60, 30, 10], 500);
+        assert_eq!(fees, [60, 30, 10]);
+        assert_eq!(pool, 400);
+    }
+
+    #[test]
+    fn loser_refund_rounds_down() {
+        assert_eq!(loser_refund_amount(1_000_001, PENALTY_BPS), 500_000);
+        assert_eq!(loser_refund_amount(1_000_001, 0), 1_000_001);
+    }
+}
+
+#[cfg(test)]
+mod payout_tests {
+    use super::*;
+
+    fn share(pool: u64, curve: u8, exponent: u8, winners: &[(u64, u64)], i: usize) -> u128 {
+        let total: u128 = winners.iter().map(|&(s, w)| payout_unit(curve, exponent, s, w)).sum();
+        pool as u128 * payout_unit(curve, exponent, winners[i].0, winners[i].1) / total
+    }
+
+    #[test]
+    fn default_curve_is_weight_proportional() {
+        assert_eq!(DEFAULT_PAYOUT_CURVE, PAYOUT_CURVE_WEIGHT);
+        // 未写入分配曲线的旧账户字段均为 0，同样按投票权重分配
+        assert_eq!(payout_unit(0, 0, 700, 26), 26);
+        assert!(validate_payout_curve(DEFAULT_PAYOUT_CURVE, 1).is_ok());
+    }
+
+    #[test]
+    fn stake_curve_pays_winners_pro_rata() {
+        let winners = [(100, 10), (900, 30)];
+        assert_eq!(share(1_000, PAYOUT_CURVE_STAKE, 1, &winners, 0), 100);
+        assert_eq!(share(1_000, PAYOUT_CURVE_STAKE, 1, &winners, 1), 900);
+    }
+
+    #[test]
+    fn flat_curve_splits_per_head() {
+        let winners = [(100, 10), (900, 30)];
+        assert_eq!(share(1_000, PAYOUT_CURVE_FLAT, 1, &winners, 0), 500);
+        assert_eq!(share(1_000, PAYOUT_CURVE_FLAT, 1, &winners, 1), 500);
+    }
+
+    #[test]
+    fn weight_curve_exponent_favours_heavier_votes() {
+        let winners = [(100, 10), (900, 30)];
+        assert_eq!(share(1_000, PAYOUT_CURVE_WEIGHT, 1, &winners, 0), 250);
+        assert_eq!(share(1_000, PAYOUT_CURVE_WEIGHT, 1, &winners, 1), 750);
+        // 10^2 : 30^2 = 1 : 9
+        assert_eq!(share(1_000, PAYOUT_CURVE_WEIGHT, 2, &winners, 0), 100);
+        assert_eq!(share(1_000, PAYOUT_CURVE_WEIGHT, 2, &winners, 1), 900);
+        // 10^3 : 30^3 = 1 : 27
+        assert_eq!(share(1_000, PAYOUT_CURVE_WEIGHT, 3, &winners, 0), 35);
+        assert_eq!(share(1_000, PAYOUT_CURVE_WEIGHT, 3, &winners, 1), 964);
+        assert!(validate_payout_curve(PAYOUT_CURVE_WEIGHT, MAX_PAYOUT_EXPONENT).is_ok());
+        assert!(validate_payout_curve(PAYOUT_CURVE_WEIGHT, MAX_PAYOUT_EXPONENT + 1).is_err());
+    }
 }
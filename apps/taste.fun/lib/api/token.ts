@@ -13,8 +13,10 @@ import {
 } from '@solana/spl-token';
 import { AnchorProvider, Program, BN, web3, Idl } from '@coral-xyz/anchor';
 import tasteFunTokenIdl from '../idl/taste_fun_token.json';
+import tasteFunCoreIdl from '../idl/taste_fun_core.json';
 
 const TASTE_FUN_TOKEN_PROGRAM_ID = new PublicKey(tasteFunTokenIdl.address);
+const TASTE_FUN_CORE_PROGRAM_ID = new PublicKey(tasteFunCoreIdl.address);
 
 // Type alias for the program
 type TasteFunTokenProgram = Program<Idl>;
@@ -101,6 +103,16 @@ export function getThemeTokenMintPda(
   );
 }
 
+/**
+ * Get creator reserve vesting PDA
+ */
+export function getCreatorVestingPda(theme: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from('creator_vesting'), theme.toBuffer()],
+    TASTE_FUN_TOKEN_PROGRAM_ID
+  );
+}
+
 /**
  * Get trading configuration PDA
  */
@@ -111,6 +123,16 @@ export function getTradingConfigPda(): [PublicKey, number] {
   );
 }
 
+/**
+ * Get global protocol configuration PDA (owned by the core program, carries the pause flag)
+ */
+export function getProtocolConfigPda(): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from('protocol_config')],
+    TASTE_FUN_CORE_PROGRAM_ID
+  );
+}
+
 /**
  * Fetch a theme by its public key
  */
@@ -381,9 +403,12 @@ export async function mintInitialThemeTokens(
     true
   );
 
-  const creatorTokenAccount = await getAssociatedTokenAddress(
+  // Creator reserve is locked in the vesting PDA's ATA instead of the creator's wallet
+  const [creatorVesting] = getCreatorVestingPda(themePda);
+  const vestingTokenAccount = await getAssociatedTokenAddress(
     tokenMintPda,
-    wallet.publicKey
+    creatorVesting,
+    true
   );
 
   // Get fresh blockhash before sending
@@ -396,7 +421,8 @@ export async function mintInitialThemeTokens(
       vault: vaultPda,
       tokenMint: tokenMintPda,
       vaultTokenAccount,
-      creatorTokenAccount,
+      creatorVesting,
+      vestingTokenAccount,
       creator: wallet.publicKey,
       tokenProgram: TOKEN_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -500,6 +526,7 @@ export async function buyThemeTokens(
 
   const theme: any = await (program.account as any).theme.fetch(themePubkey);
   const [tradingConfigPda] = getTradingConfigPda();
+  const [protocolConfigPda] = getProtocolConfigPda();
   const [vaultPda] = getThemeVaultPda(theme.creator, theme.themeId);
 
   const vaultTokenAccount = await getAssociatedTokenAddress(
@@ -522,6 +549,7 @@ export async function buyThemeTokens(
       vaultTokenAccount,
       userTokenAccount,
       tradingConfig: tradingConfigPda,
+      protocolConfig: protocolConfigPda,
       user: wallet.publicKey,
       vaultSolAccount: vaultPda,
       tokenProgram: TOKEN_PROGRAM_ID,
@@ -552,6 +580,7 @@ export async function sellThemeTokens(
 
   const theme: any = await (program.account as any).theme.fetch(themePubkey);
   const [tradingConfigPda] = getTradingConfigPda();
+  const [protocolConfigPda] = getProtocolConfigPda();
   const [vaultPda] = getThemeVaultPda(theme.creator, theme.themeId);
 
   const vaultTokenAccount = await getAssociatedTokenAddress(
@@ -574,6 +603,7 @@ export async function sellThemeTokens(
       vaultTokenAccount,
       userTokenAccount,
       tradingConfig: tradingConfigPda,
+      protocolConfig: protocolConfigPda,
       user: wallet.publicKey,
       vaultSolAccount: vaultPda,
       tokenProgram: TOKEN_PROGRAM_ID,
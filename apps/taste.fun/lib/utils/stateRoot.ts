@@ -0,0 +1,163 @@
+/**
+ * State root verification utilities
+ *
+ * The settlement program periodically stores a merkle root of settled contests
+ * and reviewer balances in the `state_root` PDA. Mirrors on other chains can
+ * verify a single outcome against that root with a short proof instead of
+ * replaying the full account history over RPC.
+ *
+ * Leaf encoding and pair hashing mirror `taste_fun_shared::contest_leaf`,
+ * `balance_leaf` and `verify_merkle_proof`.
+ */
+import { Connection, PublicKey } from '@solana/web3.js';
+import { keccak_256 } from '@noble/hashes/sha3';
+import tasteFunSettlementIdl from '../idl/taste_fun_settlement.json';
+
+const SETTLEMENT_PROGRAM_ID = new PublicKey(tasteFunSettlementIdl.address);
+
+export const STATE_LEAF_CONTEST = 0;
+export const STATE_LEAF_BALANCE = 1;
+
+export interface ContestOutcome {
+  idea: PublicKey;
+  status: number; // IdeaStatus discriminant
+  winningImageIndex: number | null;
+  totalStaked: bigint;
+  penaltyPoolAmount: bigint;
+  winnerCount: bigint;
+}
+
+export interface StateRootAccount {
+  root: Uint8Array;
+  epoch: bigint;
+  leafCount: bigint;
+  slot: bigint;
+  postedAt: bigint;
+}
+
+/**
+ * Encode a u64 as 8 little-endian bytes
+ */
+function u64Le(value: bigint): Uint8Array {
+  const out = new Uint8Array(8);
+  new DataView(out.buffer).setBigUint64(0, value, true);
+  return out;
+}
+
+function concat(parts: Uint8Array[]): Uint8Array {
+  const out = new Uint8Array(parts.reduce((n, p) => n + p.length, 0));
+  let offset = 0;
+  for (const part of parts) {
+    out.set(part, offset);
+    offset += part.length;
+  }
+  return out;
+}
+
+function compareBytes(a: Uint8Array, b: Uint8Array): number {
+  for (let i = 0; i < a.length; i++) {
+    if (a[i] !== b[i]) return a[i] - b[i];
+  }
+  return 0;
+}
+
+/**
+ * Hash two sibling nodes in sorted order
+ */
+function hashPair(a: Uint8Array, b: Uint8Array): Uint8Array {
+  return compareBytes(a, b) <= 0 ? keccak_256(concat([a, b])) : keccak_256(concat([b, a]));
+}
+
+/**
+ * Leaf for a settled contest outcome
+ */
+export function contestLeaf(outcome: ContestOutcome): Uint8Array {
+  return keccak_256(
+    concat([
+      Uint8Array.of(STATE_LEAF_CONTEST),
+      outcome.idea.toBytes(),
+      Uint8Array.of(outcome.status, outcome.winningImageIndex ?? 255),
+      u64Le(outcome.totalStaked),
+      u64Le(outcome.penaltyPoolAmount),
+      u64Le(outcome.winnerCount),
+    ])
+  );
+}
+
+/**
+ * Leaf for a reviewer's settled balance in a contest
+ */
+export function balanceLeaf(idea: PublicKey, reviewer: PublicKey, amount: bigint): Uint8Array {
+  return keccak_256(
+    concat([Uint8Array.of(STATE_LEAF_BALANCE), idea.toBytes(), reviewer.toBytes(), u64Le(amount)])
+  );
+}
+
+/**
+ * Verify a merkle proof against a posted state root
+ */
+export function verifyMerkleProof(
+  proof: Uint8Array[],
+  root: Uint8Array,
+  leaf: Uint8Array
+): boolean {
+  const computed = proof.reduce((node, sibling) => hashPair(node, sibling), leaf);
+  return compareBytes(computed, root) === 0 && computed.length === root.length;
+}
+
+/**
+ * Build a tree from leaves (keeper side). An unpaired node is promoted as-is,
+ * so its proof simply has no sibling at that level.
+ */
+export function buildMerkleTree(leaves: Uint8Array[]): {
+  root: Uint8Array;
+  proofs: Uint8Array[][];
+} {
+  if (leaves.length === 0) {
+    return { root: new Uint8Array(32), proofs: [] };
+  }
+
+  const proofs: Uint8Array[][] = leaves.map(() => []);
+  let positions = leaves.map((_, i) => i);
+  let level = leaves;
+
+  while (level.length > 1) {
+    const next: Uint8Array[] = [];
+    for (let i = 0; i < level.length; i += 2) {
+      next.push(i + 1 < level.length ? hashPair(level[i], level[i + 1]) : level[i]);
+    }
+    positions = positions.map((pos, leafIndex) => {
+      const sibling = pos ^ 1;
+      if (sibling < level.length) {
+        proofs[leafIndex].push(level[sibling]);
+      }
+      return pos >> 1;
+    });
+    level = next;
+  }
+
+  return { root: level[0], proofs };
+}
+
+/**
+ * Fetch the latest posted state root
+ */
+export async function fetchStateRoot(connection: Connection): Promise<StateRootAccount | null> {
+  const [stateRootPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from('state_root')],
+    SETTLEMENT_PROGRAM_ID
+  );
+  const info = await connection.getAccountInfo(stateRootPda);
+  if (!info) return null;
+
+  // Skip the 8-byte anchor discriminator
+  const data = info.data.subarray(8);
+  const view = new DataView(data.buffer, data.byteOffset, data.byteLength);
+  return {
+    root: new Uint8Array(data.subarray(0, 32)),
+    epoch: view.getBigUint64(32, true),
+    leafCount: view.getBigUint64(40, true),
+    slot: view.getBigUint64(48, true),
+    postedAt: view.getBigInt64(56, true),
+  };
+}